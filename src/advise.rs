@@ -0,0 +1,87 @@
+//! Asks the solver for hints and slot values on a thread of its own, so working them out
+//! doesn't hold up the window.
+
+use druid::widget::Controller;
+use druid::{Env, Event, EventCtx, TimerToken, UpdateCtx, Widget};
+use std::{sync::Arc, time::Duration};
+
+use crate::core::advice::{Advisor, Position};
+use crate::InGameState;
+
+/// How often to look for the advisor's answer while waiting for one.
+const POLL: Duration = Duration::from_millis(30);
+
+/// Asks the advisor about each new position a hint or the slot values are wanted for, and puts
+/// its answers in `InGameState::advice`.
+pub struct Advise {
+    advisor: Advisor,
+    /// The position last asked about.
+    asked: Option<Position>,
+    timer: Option<TimerToken>,
+}
+
+impl Advise {
+    pub fn new() -> Self {
+        Self {
+            advisor: Advisor::start(),
+            asked: None,
+            timer: None,
+        }
+    }
+
+    /// Ask about the game as it stands, if advice is wanted and there isn't already advice for
+    /// it or a question about it.
+    fn ask(&mut self, ctx: &mut UpdateCtx, data: &InGameState) {
+        if !data.wants_hint() && !data.wants_placements() {
+            return;
+        }
+        let position = Position::of(&data.game);
+        let answered = data.advice.as_ref().map(|advice| &advice.position);
+        if position.is_none() || position.as_ref() == answered || position == self.asked {
+            return;
+        }
+        self.advisor.ask(data.game.clone());
+        self.asked = position;
+        if self.timer.is_none() {
+            self.timer = Some(ctx.request_timer(POLL));
+        }
+    }
+}
+
+impl<W: Widget<InGameState>> Controller<InGameState, W> for Advise {
+    fn event(
+        &mut self,
+        child: &mut W,
+        ctx: &mut EventCtx,
+        event: &Event,
+        data: &mut InGameState,
+        env: &Env,
+    ) {
+        match event {
+            Event::Timer(tok) if self.timer == Some(*tok) => {
+                self.timer = None;
+                if let Some(advice) = self.advisor.answers().last() {
+                    data.advice = Some(Arc::new(advice));
+                    data.sync_dice();
+                }
+                let answered = data.advice.as_ref().map(|advice| &advice.position);
+                if self.asked.is_some() && self.asked.as_ref() != answered {
+                    self.timer = Some(ctx.request_timer(POLL));
+                }
+            }
+            _ => child.event(ctx, event, data, env),
+        }
+    }
+
+    fn update(
+        &mut self,
+        child: &mut W,
+        ctx: &mut UpdateCtx,
+        old_data: &InGameState,
+        data: &InGameState,
+        env: &Env,
+    ) {
+        self.ask(ctx, data);
+        child.update(ctx, old_data, data, env)
+    }
+}
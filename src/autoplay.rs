@@ -0,0 +1,104 @@
+//! Plays the turns of computer players, one move at a time.
+
+use druid::widget::Controller;
+use druid::{Command, Env, Event, EventCtx, TimerToken, UpdateCtx, Widget};
+use std::{collections::HashMap, time::Duration};
+
+use crate::core::bot::{self, Bot, BotRegistry};
+use crate::{animation_time, window_bots, InGameState, PLAY_MOVE};
+
+/// How long a computer player waits before each move, so people can follow what it does.
+const THINKING_TIME: Duration = Duration::from_millis(700);
+/// How often a computer player that hasn't decided yet is asked again.
+const DECIDING_TIME: Duration = Duration::from_millis(500);
+
+/// Makes the moves for computer players through `PLAY_MOVE`, just like the buttons do for people,
+/// so their turns are shown the same way.
+pub struct AutoPlay {
+    timer: Option<TimerToken>,
+    registry: BotRegistry,
+    /// The bot playing for each computer player, by player index and bot name, made when first
+    /// needed so it can keep its own state through the game.
+    bots: HashMap<(usize, String), Box<dyn Bot>>,
+}
+
+impl AutoPlay {
+    pub fn new() -> Self {
+        Self {
+            timer: None,
+            registry: window_bots(),
+            bots: HashMap::new(),
+        }
+    }
+
+    /// Wait before the next move, if it is the turn of a computer player on this computer, no
+    /// move is pending and the game isn't paused. In a network game, the host plays the computer
+    /// players.
+    fn schedule(&mut self, ctx: &mut UpdateCtx, data: &InGameState, env: &Env) {
+        let player = data.current_player();
+        let waiting = data.game.any_rolling() || data.is_paused();
+        if self.timer.is_none() && player.local && player.bot.is_some() && !waiting {
+            self.timer = Some(ctx.request_timer(animation_time(THINKING_TIME, env)));
+        }
+    }
+}
+
+impl<W: Widget<InGameState>> Controller<InGameState, W> for AutoPlay {
+    fn event(
+        &mut self,
+        child: &mut W,
+        ctx: &mut EventCtx,
+        event: &Event,
+        data: &mut InGameState,
+        env: &Env,
+    ) {
+        match event {
+            Event::Timer(tok) if self.timer == Some(*tok) => {
+                self.timer = None;
+                // the move waits for the game to carry on, when it's scheduled again
+                if data.is_paused() {
+                    return;
+                }
+                let name = match &data.current_player().bot {
+                    Some(name) => name.clone(),
+                    None => return,
+                };
+                let registry = &self.registry;
+                let bot = self
+                    .bots
+                    .entry((data.game.player(), name.clone()))
+                    .or_insert_with(|| {
+                        registry
+                            .create(&name)
+                            .unwrap_or_else(|| panic!("no bot called {:?}", name))
+                    });
+                match bot::choose_move(&data.game, bot.as_mut()) {
+                    Some(mv) => ctx.submit_command(Command::new(PLAY_MOVE, mv), None),
+                    // the bot is still deciding
+                    None if !data.game.any_rolling() && !data.game.is_complete() => {
+                        self.timer = Some(ctx.request_timer(DECIDING_TIME));
+                    }
+                    None => (),
+                }
+                data.bot_status = bot.status();
+            }
+            _ => child.event(ctx, event, data, env),
+        }
+    }
+
+    fn update(
+        &mut self,
+        child: &mut W,
+        ctx: &mut UpdateCtx,
+        old_data: &InGameState,
+        data: &InGameState,
+        env: &Env,
+    ) {
+        // a timer from before a pause may never come, if the settings were opened meanwhile
+        if old_data.is_paused() && !data.is_paused() {
+            self.timer = None;
+        }
+        self.schedule(ctx, data, env);
+        child.update(ctx, old_data, data, env)
+    }
+}
@@ -0,0 +1,10 @@
+//! The subcommands of `yatzy` that run in a terminal, built without druid, for servers and
+//! scripts that have no window to open.
+
+use anyhow::anyhow;
+use yatzy::{cli, Result};
+
+fn main() -> Result {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    cli::run(&args).unwrap_or_else(|| Err(anyhow!("expected one of {}", cli::SUBCOMMANDS)))
+}
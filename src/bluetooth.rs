@@ -0,0 +1,90 @@
+//! Finding the smart dice on the table over Bluetooth, and passing on what they say.
+//!
+//! On Linux the adapter is used directly rather than through BlueZ, which needs the program to
+//! be run as root or given the `cap_net_admin` capability.
+
+use std::{thread, time::Duration};
+
+use anyhow::{anyhow, Context, Result};
+use btleplug::api::{Central, CharPropFlags, Peripheral, ValueNotification};
+#[cfg(target_os = "linux")]
+use btleplug::bluez::manager::Manager;
+#[cfg(target_os = "macos")]
+use btleplug::corebluetooth::manager::Manager;
+#[cfg(target_os = "windows")]
+use btleplug::winrtble::manager::Manager;
+use crossbeam_channel as channel;
+
+use crate::core::smart_dice::{DiceMessage, GODICE_NAME};
+
+/// How long to look for dice before connecting to those found.
+const SCAN_TIME: Duration = Duration::from_secs(5);
+
+/// What happened with the dice on the table.
+#[derive(Debug, Clone)]
+pub enum DiceEvent {
+    /// This many dice were found and connected to.
+    Connected(usize),
+    /// The dice couldn't be connected to, for this reason.
+    Failed(String),
+    /// The die with this number said something. The dice are numbered in the order they were
+    /// found, from 0.
+    Message { die: usize, message: DiceMessage },
+}
+
+/// Look for dice and connect to them on a thread of its own, sending what happens on `events`.
+/// The dice stay connected for as long as the program runs.
+pub fn connect(events: channel::Sender<DiceEvent>) {
+    thread::spawn(move || {
+        if let Err(e) = connect_dice(&events) {
+            let _ = events.send(DiceEvent::Failed(format!("{:#}", e)));
+        }
+    });
+}
+
+/// Connect to every GoDice nearby and pass on what they say, only returning if that can't be
+/// done, since the dice stay connected through the adapter for as long as it's kept.
+fn connect_dice(events: &channel::Sender<DiceEvent>) -> Result<()> {
+    let manager = Manager::new().context("starting Bluetooth")?;
+    let adapter = manager
+        .adapters()?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("there's no Bluetooth adapter"))?;
+    #[cfg(target_os = "linux")]
+    let adapter = adapter.connect()?;
+    adapter.start_scan()?;
+    thread::sleep(SCAN_TIME);
+    adapter.stop_scan()?;
+
+    let dice: Vec<_> = adapter
+        .peripherals()
+        .into_iter()
+        .filter(|peripheral| {
+            let name = peripheral.properties().local_name;
+            name.map_or(false, |name| name.starts_with(GODICE_NAME))
+        })
+        .collect();
+    if dice.is_empty() {
+        return Err(anyhow!("no dice were found, are they switched on?"));
+    }
+    for (die, peripheral) in dice.iter().enumerate() {
+        peripheral.connect()?;
+        // a die sends its messages on the one characteristic it notifies with
+        let said = peripheral
+            .discover_characteristics()?
+            .into_iter()
+            .find(|characteristic| characteristic.properties.contains(CharPropFlags::NOTIFY))
+            .ok_or_else(|| anyhow!("die {} doesn't send anything", die + 1))?;
+        let events = events.clone();
+        peripheral.on_notification(Box::new(move |notification: ValueNotification| {
+            let message = DiceMessage::from_godice(&notification.value);
+            let _ = events.send(DiceEvent::Message { die, message });
+        }));
+        peripheral.subscribe(&said)?;
+    }
+    let _ = events.send(DiceEvent::Connected(dice.len()));
+    loop {
+        thread::park();
+    }
+}
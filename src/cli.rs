@@ -0,0 +1,417 @@
+//! Subcommands that run without opening a window.
+
+use anyhow::{anyhow, bail, Context};
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::core::{
+    bot::Bot,
+    notation,
+    replay::Replay,
+    rules::RuleSet,
+    score::GameRng,
+    settings::{self, Settings},
+    sim, strategy,
+};
+use crate::net::{protocol, server, sync};
+use crate::{all_presets, bot_registry, Result};
+
+/// The width of each bar in the printed score distributions.
+const HISTOGRAM_WIDTH: u16 = 25;
+
+/// The names of the subcommands, for telling people what they could have typed.
+pub const SUBCOMMANDS: &str = "simulate, tournament, generate-strategy, notation, sync or --serve";
+
+/// Run the subcommand named by the first of `args`, the command line arguments after the
+/// program's name, or return `None` if it isn't one, for the window to be opened instead.
+pub fn run(args: &[String]) -> Option<Result> {
+    let rest = || args[1..].iter().cloned();
+    Some(match args.first()?.as_str() {
+        "simulate" => simulate(rest()),
+        "tournament" => tournament(rest()),
+        "generate-strategy" => generate_strategy(rest()),
+        "notation" => notation(rest()),
+        "sync" => sync(rest()),
+        "--serve" => serve(rest()),
+        _ => return None,
+    })
+}
+
+/// Options given as `--name value` or `--name=value`.
+pub struct Options(HashMap<String, String>);
+
+impl Options {
+    /// Parse the arguments after the subcommand, allowing only the given option names.
+    pub fn parse(mut args: impl Iterator<Item = String>, allowed: &[&str]) -> Result<Self> {
+        let mut options = HashMap::new();
+        while let Some(arg) = args.next() {
+            let (name, value) = match arg.find('=') {
+                Some(idx) if arg.starts_with("--") => {
+                    (arg[2..idx].to_string(), Some(arg[idx + 1..].to_string()))
+                }
+                _ if arg.starts_with("--") => (arg[2..].to_string(), None),
+                _ => bail!("unrecognised argument {:?}", arg),
+            };
+            if !allowed.contains(&name.as_str()) {
+                bail!("unrecognised option --{}", name);
+            }
+            let value = match value {
+                Some(value) => value,
+                None => args
+                    .next()
+                    .ok_or_else(|| anyhow!("--{} needs a value", name))?,
+            };
+            options.insert(name, value);
+        }
+        Ok(Self(options))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0.get(name).map(String::as_str)
+    }
+
+    pub fn required(&self, name: &str) -> Result<&str> {
+        self.get(name)
+            .ok_or_else(|| anyhow!("the --{} option is needed", name))
+    }
+
+    /// A number option, or `default` if it wasn't given.
+    pub fn number<T: std::str::FromStr>(&self, name: &str, default: T) -> Result<T> {
+        match self.get(name) {
+            Some(value) => value
+                .parse()
+                .map_err(|_| anyhow!("--{} must be a number, not {:?}", name, value)),
+            None => Ok(default),
+        }
+    }
+
+    /// The rules named by `--rules`, or Yatzy if it wasn't given.
+    pub fn rules(&self) -> Result<RuleSet> {
+        let name = match self.get("rules") {
+            Some(name) => name,
+            None => return Ok(RuleSet::yatzy()),
+        };
+        all_presets()
+            .iter()
+            .find(|rules| rules.name.eq_ignore_ascii_case(name))
+            .cloned()
+            .ok_or_else(|| anyhow!("there are no rules called {:?}", name))
+    }
+}
+
+/// Make the bot with the given name.
+pub fn create_bot(name: &str) -> Result<Box<dyn Bot>> {
+    let registry = bot_registry();
+    registry.create(name).with_context(|| {
+        let names: Vec<_> = registry.names().collect();
+        format!("there is no bot called {:?}, try one of {:?}", name, names)
+    })
+}
+
+/// The results of a match between two bots.
+struct MatchResult {
+    wins: [u64; 2],
+    draws: u64,
+    stats: [sim::ScoreStats; 2],
+}
+
+/// Play `games` games between two bots, taking turns to go first so neither gets the advantage.
+///
+/// Game `n` is rolled from `seed + n`, so the same seed gives the same dice to every match.
+fn play_match(bots: [&mut dyn Bot; 2], games: u64, rules: &RuleSet, seed: u64) -> MatchResult {
+    let [bot_a, bot_b] = bots;
+    let mut result = MatchResult {
+        wins: [0; 2],
+        draws: 0,
+        stats: [sim::ScoreStats::new(), sim::ScoreStats::new()],
+    };
+    for game_idx in 0..games {
+        let first = (game_idx % 2) as usize;
+        let order = [first, 1 - first];
+        let mut players: [&mut dyn Bot; 2] = if first == 0 {
+            [&mut *bot_a, &mut *bot_b]
+        } else {
+            [&mut *bot_b, &mut *bot_a]
+        };
+        let rng = GameRng::from_seed(seed.wrapping_add(game_idx));
+        let game = sim::play_game(rules.clone(), rng, &mut players);
+
+        let mut totals = [0; 2];
+        for (player, sheet) in game.sheets().iter().enumerate() {
+            totals[order[player]] = sheet.total();
+        }
+        result.stats[0].add(totals[0]);
+        result.stats[1].add(totals[1]);
+        match totals[0].cmp(&totals[1]) {
+            Ordering::Greater => result.wins[0] += 1,
+            Ordering::Less => result.wins[1] += 1,
+            Ordering::Equal => result.draws += 1,
+        }
+    }
+    result
+}
+
+/// `yatzy simulate --games N --bot-a X --bot-b Y [--rules R] [--seed S]`
+///
+/// Play bot A against bot B and print how often each wins and how their scores are spread.
+pub fn simulate(args: impl Iterator<Item = String>) -> Result {
+    let options = Options::parse(args, &["games", "bot-a", "bot-b", "rules", "seed"])?;
+    let games: u64 = options.number("games", 100)?;
+    let names = [options.required("bot-a")?, options.required("bot-b")?];
+    let rules = options.rules()?;
+    let seed: u64 = options.number("seed", 0)?;
+
+    let (mut bot_a, mut bot_b) = (create_bot(names[0])?, create_bot(names[1])?);
+    let result = play_match([bot_a.as_mut(), bot_b.as_mut()], games, &rules, seed);
+
+    println!("{} games of {}", games, rules.name);
+    let sides = names.iter().zip(&result.stats).zip(&result.wins);
+    for ((name, stats), wins) in sides {
+        println!();
+        println!(
+            "{}: won {} ({:.1}%), mean {:.1}, std dev {:.1}, min {}, max {}",
+            name,
+            wins,
+            percent(*wins, games),
+            stats.mean(),
+            stats.std_dev(),
+            stats.min().unwrap_or(0),
+            stats.max().unwrap_or(0),
+        );
+        print_histogram(stats);
+    }
+    println!();
+    println!(
+        "drawn: {} ({:.1}%)",
+        result.draws,
+        percent(result.draws, games)
+    );
+    Ok(())
+}
+
+/// A bot's results over a whole tournament.
+#[derive(Default)]
+struct Standing {
+    wins: u64,
+    draws: u64,
+    losses: u64,
+    stats: sim::ScoreStats,
+}
+
+impl Standing {
+    /// A point for a win and half for a draw.
+    fn points(&self) -> f64 {
+        self.wins as f64 + self.draws as f64 / 2.0
+    }
+}
+
+/// `yatzy tournament [--games N] [--rules R] [--seed S]`
+///
+/// Play every registered bot against every other, `N` games per pairing with the same dice for
+/// each pairing, and print a table ranked by points.
+pub fn tournament(args: impl Iterator<Item = String>) -> Result {
+    let options = Options::parse(args, &["games", "rules", "seed"])?;
+    let games: u64 = options.number("games", 100)?;
+    let rules = options.rules()?;
+    let seed: u64 = options.number("seed", 0)?;
+
+    let registry = bot_registry();
+    let names: Vec<&str> = registry.names().collect();
+    let mut standings: Vec<Standing> = names.iter().map(|_| Standing::default()).collect();
+    for a in 0..names.len() {
+        for b in a + 1..names.len() {
+            eprintln!("{} vs {}", names[a], names[b]);
+            let (mut bot_a, mut bot_b) = (create_bot(names[a])?, create_bot(names[b])?);
+            let result = play_match([bot_a.as_mut(), bot_b.as_mut()], games, &rules, seed);
+            for (side, idx) in [a, b].iter().enumerate() {
+                let standing = &mut standings[*idx];
+                standing.wins += result.wins[side];
+                standing.losses += result.wins[1 - side];
+                standing.draws += result.draws;
+                for score in result.stats[side].scores() {
+                    standing.stats.add(*score);
+                }
+            }
+        }
+    }
+
+    let mut ranked: Vec<(&str, Standing)> = names.into_iter().zip(standings).collect();
+    ranked.sort_by(|(_, a), (_, b)| {
+        b.points()
+            .partial_cmp(&a.points())
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| {
+                b.stats
+                    .mean()
+                    .partial_cmp(&a.stats.mean())
+                    .unwrap_or(Ordering::Equal)
+            })
+    });
+    println!(
+        "{} games per pairing of {}, seed {}",
+        games, rules.name, seed
+    );
+    println!();
+    println!(
+        "{:>4}  {:<20} {:>7} {:>6} {:>6} {:>6} {:>7} {:>7}",
+        "rank", "bot", "points", "won", "drawn", "lost", "mean", "dev"
+    );
+    for (rank, (name, standing)) in ranked.iter().enumerate() {
+        println!(
+            "{:>4}  {:<20} {:>7.1} {:>6} {:>6} {:>6} {:>7.1} {:>7.1}",
+            rank + 1,
+            name,
+            standing.points(),
+            standing.wins,
+            standing.draws,
+            standing.losses,
+            standing.stats.mean(),
+            standing.stats.std_dev(),
+        );
+    }
+    Ok(())
+}
+
+/// `yatzy generate-strategy [--out PATH]`
+///
+/// Work out the optimal strategy table for standard Yatzy and save it where the game looks for
+/// it, or to `PATH`. This takes a minute or so.
+pub fn generate_strategy(args: impl Iterator<Item = String>) -> Result {
+    let options = Options::parse(args, &["out"])?;
+    let path = match options.get("out") {
+        Some(path) => PathBuf::from(path),
+        None => strategy::default_path().context("there is no config directory, use --out")?,
+    };
+
+    let mut shown = None;
+    let table = strategy::StrategyTable::generate(|done, total| {
+        let percent = done * 100 / total;
+        if shown != Some(percent) {
+            eprint!("\r{}%", percent);
+            shown = Some(percent);
+        }
+    });
+    eprintln!();
+    table.save(&path)?;
+    println!("saved to {}", path.display());
+    println!(
+        "expected score with optimal play: {:.2}",
+        table.game_value()
+    );
+    Ok(())
+}
+
+/// `yatzy --serve [--port N] [--players N] [--rules R] [--password P] [--grace SECS]
+/// [--metrics-port M] [--webhook URL]`
+///
+/// Host a game over WebSocket without opening a window, starting once `N` players (2 by default)
+/// have joined and are ready. Players have to give `P` to join, if it's set. Players who drop out
+/// have `SECS` seconds to rejoin. With `M`, metrics for Prometheus are served on that port at
+/// `/metrics`, and with `URL`, it's sent a webhook as the game starts, passes to each player and
+/// ends.
+pub fn serve(args: impl Iterator<Item = String>) -> Result {
+    let options = Options::parse(
+        args,
+        &[
+            "port",
+            "players",
+            "rules",
+            "password",
+            "grace",
+            "metrics-port",
+            "webhook",
+        ],
+    )?;
+    let port = options.number("port", protocol::DEFAULT_PORT)?;
+    let players: usize = options.number("players", 2)?;
+    if players == 0 {
+        bail!("--players must be at least 1");
+    }
+    let grace_period = options.number("grace", protocol::DEFAULT_GRACE_PERIOD)?;
+    let metrics_port = match options.get("metrics-port") {
+        Some(_) => Some(options.number("metrics-port", 0)?),
+        None => None,
+    };
+    let config = server::Config {
+        port,
+        rules: options.rules()?,
+        players,
+        password: options.get("password"),
+        grace_period,
+        metrics_port,
+        webhooks: options
+            .get("webhook")
+            .map(str::to_string)
+            .into_iter()
+            .collect(),
+    };
+    server::run(config, &bot_registry())
+}
+
+/// `yatzy notation --replay PATH` or `yatzy notation --read PATH --out REPLAY`
+///
+/// Print a saved replay in the game notation, or read a game written in the notation and save it
+/// as a replay to watch.
+pub fn notation(args: impl Iterator<Item = String>) -> Result {
+    let options = Options::parse(args, &["replay", "read", "out"])?;
+    if let Some(path) = options.get("replay") {
+        let replay = Replay::load(Path::new(path))?;
+        print!("{}", notation::write(&replay));
+        return Ok(());
+    }
+    let path = options
+        .get("read")
+        .context("give a replay to print with --replay, or a game to read with --read")?;
+    let out = options.required("out")?;
+    let text = fs::read_to_string(path).with_context(|| format!("reading {}", path))?;
+    let replay =
+        notation::parse(&text, &all_presets()).with_context(|| format!("parsing {}", path))?;
+    replay.save(Path::new(out))?;
+    println!("saved to {}", out);
+    Ok(())
+}
+
+/// `yatzy sync`
+///
+/// Sync the profiles and saved games with the remote set up in the settings.
+pub fn sync(args: impl Iterator<Item = String>) -> Result {
+    Options::parse(args, &[])?;
+    let path = settings::default_path().context("there's no config directory for the settings")?;
+    let remote = Settings::load(&path)?.sync.with_context(|| {
+        format!(
+            "there's nowhere to sync to: add a [sync] table to {}",
+            path.display()
+        )
+    })?;
+    println!("{}", sync::sync_here(&remote)?);
+    Ok(())
+}
+
+/// Print a score distribution as rows of `#`s, scaled to fit in a terminal.
+pub fn print_histogram(stats: &sim::ScoreStats) {
+    const BAR_WIDTH: usize = 50;
+    let histogram = stats.histogram(HISTOGRAM_WIDTH);
+    let most = histogram.iter().map(|(_, count)| *count).max().unwrap_or(0);
+    for (start, count) in histogram {
+        let bar = (count * BAR_WIDTH).checked_div(most).unwrap_or(0);
+        println!(
+            "{:>4}-{:<4} {:>6} {}",
+            start,
+            start + HISTOGRAM_WIDTH - 1,
+            count,
+            "#".repeat(bar)
+        );
+    }
+}
+
+fn percent(count: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        count as f64 * 100.0 / total as f64
+    }
+}
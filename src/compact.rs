@@ -0,0 +1,64 @@
+//! Switches the game to a layout in one column when the window is too narrow for the usual one.
+
+use druid::{
+    BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx, PaintCtx,
+    Point, Rect, Size, UpdateCtx, Widget, WidgetPod,
+};
+
+use crate::COMPACT_WIDTH;
+
+/// Shows one of two layouts of the same thing: `compact` when it has less width than
+/// `COMPACT_WIDTH` in the `Env`, and `wide` otherwise. Both are kept up to date, so either can be
+/// shown as soon as the window is resized.
+pub struct Compact<T> {
+    wide: WidgetPod<T, Box<dyn Widget<T>>>,
+    compact: WidgetPod<T, Box<dyn Widget<T>>>,
+    /// Whether the compact layout was chosen when it was last laid out.
+    is_compact: bool,
+}
+
+impl<T: Data> Compact<T> {
+    pub fn new(wide: impl Widget<T> + 'static, compact: impl Widget<T> + 'static) -> Self {
+        Self {
+            wide: WidgetPod::new(Box::new(wide)),
+            compact: WidgetPod::new(Box::new(compact)),
+            is_compact: false,
+        }
+    }
+
+    fn current(&mut self) -> &mut WidgetPod<T, Box<dyn Widget<T>>> {
+        if self.is_compact {
+            &mut self.compact
+        } else {
+            &mut self.wide
+        }
+    }
+}
+
+impl<T: Data> Widget<T> for Compact<T> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        self.current().event(ctx, event, data, env)
+    }
+
+    fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &T, env: &Env) {
+        self.wide.lifecycle(ctx, event, data, env);
+        self.compact.lifecycle(ctx, event, data, env);
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: &T, data: &T, env: &Env) {
+        self.wide.update(ctx, data, env);
+        self.compact.update(ctx, data, env);
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, data: &T, env: &Env) -> Size {
+        self.is_compact = bc.max().width < env.get(COMPACT_WIDTH);
+        let current = self.current();
+        let size = current.layout(ctx, bc, data, env);
+        current.set_layout_rect(ctx, data, env, Rect::from_origin_size(Point::ORIGIN, size));
+        size
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, data: &T, env: &Env) {
+        self.current().paint(ctx, data, env)
+    }
+}
@@ -0,0 +1,209 @@
+//! The solver's advice for the turn being played, worked out on a thread of its own.
+//!
+//! Solving a turn, or valuing the open slots for a hand, can take a noticeable time with many
+//! dice or sides, so a window or server shouldn't wait for it. An `Advisor` is asked about the game as it stands, and answers when it
+//! has worked the advice out. Answers are kept, so asking about a position again is free.
+
+use std::{sync::mpsc, thread};
+
+use super::expectimax::{self, Solver, TurnValues};
+use super::game::Game;
+use super::rules::RuleSet;
+use super::scorecard::{ScoreSheet, Slot};
+use super::strategy;
+
+/// How many answers are kept, enough for undoing back through a turn.
+const KEPT_ANSWERS: usize = 16;
+
+/// Everything the advice depends on: the rules, whose turn it is and their sheet, the faces of
+/// the dice and the rolls left. Which dice are held doesn't matter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Position {
+    rules: RuleSet,
+    seed: u64,
+    player: usize,
+    sheet: ScoreSheet,
+    /// The faces showing, sorted.
+    hand: Vec<u8>,
+    rolls_left: u8,
+}
+
+impl Position {
+    /// Where the game has got to, or `None` if the dice haven't been rolled or are rolling.
+    pub fn of(game: &Game) -> Option<Self> {
+        if game.rolls() == 0 {
+            return None;
+        }
+        let mut hand: Vec<u8> = game.scores()?.iter().map(|die| die.0).collect();
+        hand.sort_unstable();
+        Some(Self {
+            rules: game.rules().clone(),
+            seed: game.seed(),
+            player: game.player(),
+            sheet: game.sheet().clone(),
+            hand,
+            rolls_left: game.rolls_left(),
+        })
+    }
+}
+
+/// The advice for a position.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Advice {
+    pub position: Position,
+    /// The faces to keep before the next roll, sorted, or `None` if there are no rolls left.
+    /// `expectimax::holds_for` turns them into holds for the dice.
+    pub kept: Option<Vec<u8>>,
+    /// After the last roll, how good each open slot is for the hand, as from
+    /// `expectimax::placement_values`.
+    pub placements: Option<Vec<(Slot, f64)>>,
+}
+
+impl Advice {
+    /// Work out the advice for the game as it stands, or `None` if the dice haven't landed. The
+    /// strategy table is used for the dice to keep when there is one for the rules, and the
+    /// solver otherwise. `turns` must only have been used with the same game.
+    pub fn new(game: &Game, turns: &mut TurnValues) -> Option<Self> {
+        let position = Position::of(game)?;
+        let kept = if game.can_roll() {
+            match strategy::for_rules(game.rules()) {
+                Some(table) => table.best_kept(game),
+                None => Solver::new(game.clone()).best_kept(),
+            }
+        } else {
+            None
+        };
+        let placements = expectimax::placement_values(game, turns);
+        Some(Self {
+            position,
+            kept,
+            placements,
+        })
+    }
+}
+
+/// Works out advice on its own thread, which stops when the advisor is dropped.
+pub struct Advisor {
+    questions: mpsc::Sender<Game>,
+    answers: mpsc::Receiver<Advice>,
+}
+
+impl Advisor {
+    /// Start the advisor's thread.
+    pub fn start() -> Self {
+        let (questions, asked) = mpsc::channel::<Game>();
+        let (answer, answers) = mpsc::channel();
+        thread::spawn(move || {
+            let mut kept: Vec<Advice> = Vec::new();
+            // the turn values for the rules and seed of the game last asked about
+            let mut turns = TurnValues::new();
+            let mut turns_for = None;
+            while let Ok(mut game) = asked.recv() {
+                // only the latest question is still worth answering
+                while let Ok(later) = asked.try_recv() {
+                    game = later;
+                }
+                let position = match Position::of(&game) {
+                    Some(position) => position,
+                    None => continue,
+                };
+                let this_game = Some((position.rules.clone(), position.seed));
+                if turns_for != this_game {
+                    turns = TurnValues::new();
+                    turns_for = this_game;
+                }
+                let advice = match kept.iter().find(|advice| advice.position == position) {
+                    Some(advice) => advice.clone(),
+                    None => match Advice::new(&game, &mut turns) {
+                        Some(advice) => {
+                            if kept.len() == KEPT_ANSWERS {
+                                kept.remove(0);
+                            }
+                            kept.push(advice.clone());
+                            advice
+                        }
+                        None => continue,
+                    },
+                };
+                if answer.send(advice).is_err() {
+                    break;
+                }
+            }
+        });
+        Self { questions, answers }
+    }
+
+    /// Ask for advice on the game as it stands. Questions asked while the advisor is still busy
+    /// are only answered for the latest game.
+    pub fn ask(&self, game: Game) {
+        // the thread only stops once this is dropped
+        let _ = self.questions.send(game);
+    }
+
+    /// The answers that have come in since this was last called, oldest first.
+    pub fn answers(&self) -> impl Iterator<Item = Advice> + '_ {
+        self.answers.try_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::event::GameEvent;
+    use crate::core::moves::Move;
+    use crate::core::score::{GameRng, Score};
+    use crate::core::scorecard::Category;
+    use std::time::Duration;
+
+    /// A game of Yatzy that has rolled each hand in turn.
+    fn rolled(hands: &[[u8; 5]]) -> Game {
+        let mut game = Game::new(RuleSet::yatzy(), GameRng::from_seed(7), 1);
+        for faces in hands {
+            game.play(Move::Roll).unwrap();
+            game.apply(GameEvent::Rolled(
+                faces.iter().map(|&face| Score(face)).collect(),
+            ));
+        }
+        game
+    }
+
+    #[test]
+    fn holds_dont_change_the_position() {
+        let mut game = rolled(&[[6, 6, 6, 2, 3]]);
+        let before = Position::of(&game);
+        game.play(Move::ToggleHold(0)).unwrap();
+        assert!(before.is_some());
+        assert_eq!(Position::of(&game), before);
+    }
+
+    #[test]
+    fn the_advisor_answers_with_the_solvers_advice() {
+        let game = rolled(&[[6, 6, 6, 2, 3]]);
+        let advisor = Advisor::start();
+        advisor.ask(game.clone());
+        let advice = advisor
+            .answers
+            .recv_timeout(Duration::from_secs(60))
+            .unwrap();
+        assert_eq!(
+            Some(advice.clone()),
+            Advice::new(&game, &mut TurnValues::new())
+        );
+        assert_eq!(advice.kept, Some(vec![6, 6, 6]));
+        assert_eq!(advice.placements, None);
+    }
+
+    #[test]
+    fn the_last_roll_is_advised_where_to_go() {
+        let game = rolled(&[[6, 6, 6, 2, 3], [6, 6, 6, 6, 2], [6, 6, 6, 6, 6]]);
+        let advice = Advice::new(&game, &mut TurnValues::new()).unwrap();
+        assert_eq!(advice.kept, None);
+        let placements = advice.placements.unwrap();
+        let best =
+            placements.iter().fold(
+                placements[0],
+                |best, value| if value.1 > best.1 { *value } else { best },
+            );
+        assert_eq!(best.0, Slot::new(Category::Yatzy));
+    }
+}
@@ -0,0 +1,113 @@
+//! The built-in computer players.
+//!
+//! Each is a `Bot`, registered by `register` under the name shown on the start screen.
+
+use super::bot::{Bot, BotRegistry};
+use super::expectimax::Solver;
+use super::game::Game;
+use super::montecarlo::MonteCarlo;
+use super::rules::RuleSet;
+use super::score::Score;
+use super::scorecard::Slot;
+use super::strategy;
+
+/// The points the easy bot is happy to stop rolling with.
+const EASY_GOOD_ENOUGH: u16 = 15;
+/// The bot that plays for someone who leaves a network game.
+pub const STAND_IN: &str = "Optimal";
+/// The bot that plays for them instead when `STAND_IN` would only play like it.
+pub const STAND_IN_FALLBACK: &str = "Hard";
+
+/// Add the built-in bots to a registry, from weakest to strongest.
+pub fn register(registry: &mut BotRegistry) {
+    registry.register("Easy", || Box::new(Easy));
+    registry.register("Medium", || Box::new(Medium));
+    registry.register("Hard", || Box::new(Hard));
+    registry.register("Monte Carlo", || Box::new(MonteCarloBot(MonteCarlo::new())));
+    registry.register("Optimal", || Box::new(Optimal));
+}
+
+/// The name of the bot to play for someone who leaves a network game of these rules:
+/// `STAND_IN` if there's a strategy table for it to play with, and `STAND_IN_FALLBACK` if there
+/// isn't or `STAND_IN` isn't in the registry.
+pub fn stand_in(rules: &RuleSet, registry: &BotRegistry) -> &'static str {
+    let registered = registry.names().any(|name| name == STAND_IN);
+    if registered && strategy::for_rules(rules).is_some() {
+        STAND_IN
+    } else {
+        STAND_IN_FALLBACK
+    }
+}
+
+/// Keeps a good enough hand, and otherwise rerolls everything.
+pub struct Easy;
+
+impl Bot for Easy {
+    fn choose_holds(&mut self, game: &Game) -> Vec<bool> {
+        let good = game
+            .best_category()
+            .is_some_and(|(_, points)| points >= EASY_GOOD_ENOUGH);
+        vec![good; game.dice().len()]
+    }
+}
+
+/// Holds the most common face and rerolls the rest.
+pub struct Medium;
+
+impl Bot for Medium {
+    fn choose_holds(&mut self, game: &Game) -> Vec<bool> {
+        let dice = game.scores().expect("dice are rolling");
+        let face = most_common_face(&dice);
+        dice.iter().map(|die| *die == face).collect()
+    }
+}
+
+/// Plays each turn for the best expected score, using the expectimax solver.
+pub struct Hard;
+
+impl Bot for Hard {
+    fn choose_holds(&mut self, game: &Game) -> Vec<bool> {
+        Solver::new(game.clone())
+            .best_holds()
+            .expect("dice are rolling")
+    }
+}
+
+/// Chooses holds by playing out the rest of the turn many times, see `montecarlo`.
+pub struct MonteCarloBot(pub MonteCarlo);
+
+impl Bot for MonteCarloBot {
+    fn choose_holds(&mut self, game: &Game) -> Vec<bool> {
+        self.0.best_holds(game).expect("dice are rolling")
+    }
+}
+
+/// Plays for the best final score over the whole game using the strategy table, when one has
+/// been generated for the rules, and otherwise plays like `Hard`.
+pub struct Optimal;
+
+impl Bot for Optimal {
+    fn choose_holds(&mut self, game: &Game) -> Vec<bool> {
+        match strategy::for_rules(game.rules()) {
+            Some(table) => table.best_holds(game).expect("dice are rolling"),
+            None => Hard.choose_holds(game),
+        }
+    }
+
+    fn choose_category(&mut self, game: &Game) -> Slot {
+        let best = strategy::for_rules(game.rules()).and_then(|table| table.best_slot(game));
+        match best {
+            Some(slot) => slot,
+            None => Hard.choose_category(game),
+        }
+    }
+}
+
+/// The face showing on the most dice, preferring higher faces on a tie.
+pub(super) fn most_common_face(dice: &[Score]) -> Score {
+    let count = |face: &Score| dice.iter().filter(|die| *die == face).count();
+    *dice
+        .iter()
+        .max_by(|a, b| count(a).cmp(&count(b)).then(a.0.cmp(&b.0)))
+        .expect("no dice")
+}
@@ -0,0 +1,308 @@
+//! Reviewing a finished game: each decision is replayed from the event log and compared with the
+//! best choice, like a chess engine's game review.
+//!
+//! For standard Yatzy with a generated strategy table, choices are valued by the expected final
+//! score of the whole game. Otherwise the expectimax solver is used, which only plays for the
+//! best score this turn and values a slot by what it could score later, so the review is a guide
+//! rather than the last word.
+
+use super::event::GameEvent;
+use super::expectimax::{self, Solver, TurnValues};
+use super::game::Game;
+use super::rules::RuleSet;
+use super::score::{GameRng, Score};
+use super::scorecard::Slot;
+use super::strategy::{self, StrategyTable};
+
+/// Points lost from which a decision is an inaccuracy.
+pub const INACCURACY: f64 = 0.5;
+/// Points lost from which a decision is a mistake.
+pub const MISTAKE: f64 = 2.0;
+/// Points lost from which a decision is a blunder.
+pub const BLUNDER: f64 = 6.0;
+
+/// How far a decision was from the best one.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Rating {
+    Best,
+    Inaccuracy,
+    Mistake,
+    Blunder,
+}
+
+impl Rating {
+    /// The rating for losing the given expected points.
+    pub fn for_loss(loss: f64) -> Self {
+        if loss >= BLUNDER {
+            Rating::Blunder
+        } else if loss >= MISTAKE {
+            Rating::Mistake
+        } else if loss >= INACCURACY {
+            Rating::Inaccuracy
+        } else {
+            Rating::Best
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Rating::Best => "best",
+            Rating::Inaccuracy => "inaccuracy",
+            Rating::Mistake => "mistake",
+            Rating::Blunder => "blunder",
+        }
+    }
+}
+
+/// Something a player chose to do.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Choice {
+    /// Keeping these dice and rerolling the rest. Keeping them all means stopping to score.
+    Keep(Vec<Score>),
+    /// Placing the dice in a slot.
+    Place(Slot),
+}
+
+/// One decision in a game, with the choice made and the best one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decision {
+    /// The player who made the decision.
+    pub player: usize,
+    /// Which of the player's turns it was in, from 1.
+    pub turn: usize,
+    /// The dice showing when it was made.
+    pub dice: Vec<Score>,
+    /// The rolls the player had left.
+    pub rolls_left: u8,
+    pub chosen: Choice,
+    /// The expected points from here with the choice made.
+    pub chosen_value: f64,
+    pub best: Choice,
+    /// The expected points from here with the best choice.
+    pub best_value: f64,
+}
+
+impl Decision {
+    /// The expected points given up by not making the best choice.
+    pub fn loss(&self) -> f64 {
+        (self.best_value - self.chosen_value).max(0.0)
+    }
+
+    pub fn rating(&self) -> Rating {
+        Rating::for_loss(self.loss())
+    }
+
+    /// What was chosen, and what would have been better if it wasn't the best.
+    pub fn describe(&self, rules: &RuleSet) -> String {
+        let chosen = describe_choice(&self.chosen, &self.dice, rules);
+        match self.rating() {
+            Rating::Best => chosen,
+            rating => format!(
+                "{}, better to {} ({}, {:.1} points)",
+                chosen,
+                describe_choice(&self.best, &self.dice, rules),
+                rating.name(),
+                -self.loss()
+            ),
+        }
+    }
+}
+
+/// The review of a whole game.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Analysis {
+    /// Every decision, in the order they were made.
+    pub decisions: Vec<Decision>,
+    /// Whether the strategy table was used, so values are for the whole game rather than a turn.
+    pub whole_game: bool,
+}
+
+impl Analysis {
+    /// The expected points a player gave up over the game.
+    pub fn loss(&self, player: usize) -> f64 {
+        self.for_player(player).map(Decision::loss).sum()
+    }
+
+    /// How many of a player's decisions had the given rating.
+    pub fn count(&self, player: usize, rating: Rating) -> usize {
+        self.for_player(player)
+            .filter(|decision| decision.rating() == rating)
+            .count()
+    }
+
+    fn for_player(&self, player: usize) -> impl Iterator<Item = &Decision> {
+        self.decisions
+            .iter()
+            .filter(move |decision| decision.player == player)
+    }
+}
+
+/// Replay a game's events and value every decision in it.
+///
+/// Holding dice and rolling again is one decision, and so is stopping before the last roll.
+/// Without a strategy table this runs the solver for every decision, which can take a few
+/// seconds.
+pub fn analyse(game: &Game) -> Analysis {
+    let table = strategy::for_rules(game.rules());
+    let rng = GameRng::from_seed(game.seed());
+    let mut replay = Game::new(game.rules().clone(), rng, game.players());
+    let mut turns = vec![1; game.players()];
+    let mut turn_values = TurnValues::new();
+    let mut decisions = Vec::new();
+    for event in game.events() {
+        match event {
+            GameEvent::RollStarted if replay.rolls() > 0 => {
+                let kept = replay
+                    .dice()
+                    .iter()
+                    .filter(|die| die.held)
+                    .filter_map(|die| die.value)
+                    .collect();
+                decisions.extend(keep_decision(&replay, kept, turns[replay.player()], table));
+            }
+            GameEvent::Scored(slot) | GameEvent::Scratched(slot) => {
+                let turn = turns[replay.player()];
+                if replay.can_roll() {
+                    let all = replay.scores().unwrap_or_default();
+                    decisions.extend(keep_decision(&replay, all, turn, table));
+                }
+                decisions.extend(place_decision(
+                    &replay,
+                    *slot,
+                    turn,
+                    table,
+                    &mut turn_values,
+                ));
+                turns[replay.player()] += 1;
+            }
+            _ => (),
+        }
+        replay.apply(event.clone());
+    }
+    Analysis {
+        decisions,
+        whole_game: table.is_some(),
+    }
+}
+
+/// The decision to keep some dice, made in the game as it is now.
+fn keep_decision(
+    game: &Game,
+    kept: Vec<Score>,
+    turn: usize,
+    table: Option<&StrategyTable>,
+) -> Option<Decision> {
+    let dice = game.scores()?;
+    let (best, chosen_value, best_value) = match table {
+        Some(table) => {
+            let holds = table.best_holds(game)?;
+            let best = held(&dice, &holds);
+            let value = |kept: &[Score]| {
+                if kept.len() == dice.len() {
+                    table
+                        .placement_value(game, table.best_slot(game)?)
+                        .map(f64::from)
+                } else {
+                    Some(f64::from(table.keep_value(game, kept)))
+                }
+            };
+            let (chosen_value, best_value) = (value(&kept)?, value(&best)?);
+            (best, chosen_value, best_value)
+        }
+        None => {
+            let mut solver = Solver::new(game.clone());
+            let best = held(&dice, &solver.best_holds()?);
+            let chosen_value = solver.value_of_keeping(&kept);
+            (best.clone(), chosen_value, solver.value_of_keeping(&best))
+        }
+    };
+    Some(Decision {
+        player: game.player(),
+        turn,
+        dice,
+        rolls_left: game.rolls_left(),
+        chosen: Choice::Keep(kept),
+        chosen_value,
+        best: Choice::Keep(best),
+        best_value,
+    })
+}
+
+/// The decision to place the dice in a slot, made in the game as it is now.
+fn place_decision(
+    game: &Game,
+    slot: Slot,
+    turn: usize,
+    table: Option<&StrategyTable>,
+    turn_values: &mut TurnValues,
+) -> Option<Decision> {
+    let dice = game.scores()?;
+    let values: Vec<(Slot, f64)> = match table {
+        Some(table) => game
+            .slots()
+            .filter(|slot| game.can_score(*slot))
+            .filter_map(|slot| Some((slot, f64::from(table.placement_value(game, slot)?))))
+            .collect(),
+        None => expectimax::slot_values(game, turn_values)?,
+    };
+    let chosen_value = values
+        .iter()
+        .find(|(open, _)| *open == slot)
+        .map(|(_, value)| *value)?;
+    let (best, best_value) = values
+        .iter()
+        .copied()
+        // the first of equally good slots, in scorecard order
+        .fold(
+            None,
+            |best: Option<(Slot, f64)>, (slot, value)| match best {
+                Some((_, best_value)) if best_value >= value => best,
+                _ => Some((slot, value)),
+            },
+        )?;
+    Some(Decision {
+        player: game.player(),
+        turn,
+        dice,
+        rolls_left: game.rolls_left(),
+        chosen: Choice::Place(slot),
+        chosen_value,
+        best: Choice::Place(best),
+        best_value,
+    })
+}
+
+fn held(dice: &[Score], holds: &[bool]) -> Vec<Score> {
+    dice.iter()
+        .zip(holds)
+        .filter(|(_, hold)| **hold)
+        .map(|(die, _)| *die)
+        .collect()
+}
+
+fn describe_choice(choice: &Choice, dice: &[Score], rules: &RuleSet) -> String {
+    match choice {
+        Choice::Keep(kept) if kept.len() == dice.len() => "stop and score".to_string(),
+        Choice::Keep(kept) if kept.is_empty() => "reroll everything".to_string(),
+        Choice::Keep(kept) => format!("keep {}", faces(kept)),
+        Choice::Place(slot) if rules.columns > 1 => {
+            format!(
+                "score {} (column {})",
+                slot.category.name(),
+                slot.column + 1
+            )
+        }
+        Choice::Place(slot) => format!("score {}", slot.category.name()),
+    }
+}
+
+/// Dice faces as `1 3 3`, lowest first.
+fn faces(dice: &[Score]) -> String {
+    let mut faces: Vec<u8> = dice.iter().map(|die| die.0).collect();
+    faces.sort_unstable();
+    faces
+        .iter()
+        .map(|face| face.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
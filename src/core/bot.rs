@@ -0,0 +1,142 @@
+//! The interface for computer players, and a registry to choose them from by name.
+//!
+//! To add a computer player, implement `Bot` and register it with a `BotRegistry`. The built-in
+//! players are in the `ai` module.
+
+use super::game::Game;
+use super::moves::Move;
+use super::scorecard::Slot;
+
+/// A computer player.
+///
+/// A bot is only asked for decisions: `choose_move` turns them into moves, so bots can't make
+/// moves that aren't allowed.
+pub trait Bot: Send {
+    /// Which of the current dice to hold before the next roll, in the same order as the game's
+    /// dice. Holding every die ends rolling for the turn.
+    ///
+    /// Only asked between rolls, when the dice aren't rolling.
+    fn choose_holds(&mut self, game: &Game) -> Vec<bool>;
+
+    /// Which slot to place the final hand in.
+    ///
+    /// If the slot isn't allowed, the best one is used instead. By default, this is the slot
+    /// scoring the most points right now.
+    fn choose_category(&mut self, game: &Game) -> Slot {
+        let (slot, _) = game.best_category().expect("no open slot to score in");
+        slot
+    }
+
+    /// Whether the bot has made up its mind about what to do next. `choose_move` asks again
+    /// later while it hasn't, so a bot can take its time, for example to wait for votes, without
+    /// holding up whoever asks. By default bots decide straight away.
+    fn ready(&mut self, _game: &Game) -> bool {
+        true
+    }
+
+    /// What the bot is waiting for while it isn't ready, to show the players.
+    fn status(&self) -> Option<String> {
+        None
+    }
+}
+
+/// The next move for a bot taking the current turn.
+///
+/// Returns `None` while the dice are rolling, while the bot isn't ready, or once the game is
+/// over.
+pub fn choose_move(game: &Game, bot: &mut dyn Bot) -> Option<Move> {
+    if game.any_rolling() || game.is_complete() {
+        return None;
+    }
+    if game.rolls() == 0 {
+        return Some(Move::Roll);
+    }
+    if !bot.ready(game) {
+        return None;
+    }
+    if game.can_roll() {
+        let holds = bot.choose_holds(game);
+        let change = game
+            .dice()
+            .iter()
+            .zip(&holds)
+            .position(|(die, hold)| die.held != *hold);
+        if let Some(idx) = change {
+            return Some(Move::ToggleHold(idx));
+        }
+        // keeping every die means the hand is as good as it's going to get
+        if holds.iter().any(|hold| !hold) {
+            return Some(Move::Roll);
+        }
+    }
+    let chosen = bot.choose_category(game);
+    let slot = if game.can_score(chosen) {
+        chosen
+    } else {
+        game.best_category()?.0
+    };
+    let dice = game.scores()?;
+    match game.sheet().points(slot, &dice, game.rules()) {
+        0 => Some(Move::Scratch(slot)),
+        _ => Some(Move::Score(slot)),
+    }
+}
+
+/// Makes a new bot.
+pub type BotFactory = Box<dyn Fn() -> Box<dyn Bot> + Send + Sync>;
+
+/// The bots that can be chosen, by name.
+pub struct BotRegistry {
+    bots: Vec<(String, BotFactory)>,
+}
+
+impl BotRegistry {
+    /// A registry with no bots.
+    pub fn new() -> Self {
+        Self { bots: Vec::new() }
+    }
+
+    /// A registry with the built-in bots.
+    pub fn builtin() -> Self {
+        let mut registry = Self::new();
+        super::ai::register(&mut registry);
+        registry
+    }
+
+    /// Add a bot, replacing any registered with the same name.
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        factory: impl Fn() -> Box<dyn Bot> + Send + Sync + 'static,
+    ) {
+        let name = name.into();
+        let factory: BotFactory = Box::new(factory);
+        match self
+            .bots
+            .iter_mut()
+            .find(|(registered, _)| *registered == name)
+        {
+            Some(entry) => entry.1 = factory,
+            None => self.bots.push((name, factory)),
+        }
+    }
+
+    /// The names of the registered bots, in the order they were registered.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.bots.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Make a new bot with the given name, if there is one.
+    pub fn create(&self, name: &str) -> Option<Box<dyn Bot>> {
+        self.bots
+            .iter()
+            .find(|(registered, _)| registered == name)
+            .map(|(_, factory)| factory())
+    }
+}
+
+impl Default for BotRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
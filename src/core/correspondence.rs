@@ -0,0 +1,160 @@
+//! Playing by file: once a player's turn is over, the whole game is saved to a small file that
+//! they send to whoever plays next, by email or in a chat. Opening the file carries on the game
+//! at that player's turn.
+//!
+//! The file holds the game's events but not its seed, so whoever opens it can't work out what
+//! the dice will roll: each copy rolls its turns with a generator of its own. The events come
+//! from someone else's computer, so they're checked as the game is rebuilt. Nothing stops a
+//! player opening the same file again to roll different dice, so it's for playing with people
+//! who won't.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use super::event::GameEvent;
+use super::game::Game;
+use super::moves::Move;
+use super::rules::RuleSet;
+use super::schema::{self, Schema};
+use super::score::GameRng;
+
+/// The extension of game files.
+pub const EXTENSION: &str = "yatzy";
+
+/// Game files go between computers, which might not have the same version of the game.
+const SCHEMA: Schema = Schema {
+    name: "game file",
+    migrations: &[schema::numbered],
+};
+
+/// Where game files are saved, in the user's documents directory.
+pub fn default_dir() -> Option<PathBuf> {
+    dirs::document_dir().map(|dir| dir.join("Yatzy"))
+}
+
+/// A game being played by file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameFile {
+    pub rules: RuleSet,
+    /// The players' names, in turn order.
+    pub players: Vec<String>,
+    /// The team of each player, in turn order, or nothing if they aren't playing in teams.
+    #[serde(default)]
+    pub teams: Vec<usize>,
+    /// Everything that has happened in the game so far.
+    pub events: Vec<GameEvent>,
+}
+
+impl GameFile {
+    /// The file for a game between the named players.
+    pub fn new(game: &Game, players: Vec<String>) -> Self {
+        Self {
+            rules: game.rules().clone(),
+            players,
+            teams: if game.has_teams() {
+                (0..game.players())
+                    .map(|player| game.team(player))
+                    .collect()
+            } else {
+                Vec::new()
+            },
+            events: game.events().to_vec(),
+        }
+    }
+
+    /// Read the game file at `path`.
+    pub fn load(path: &Path) -> Result<Self> {
+        let json =
+            fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        SCHEMA
+            .parse(&json)
+            .with_context(|| format!("parsing {}", path.display()))
+    }
+
+    /// Write the game file to `path`, replacing whatever was there.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+        }
+        let json = serde_json::to_string(&SCHEMA.write(self)?)?;
+        fs::write(path, json).with_context(|| format!("writing {}", path.display()))
+    }
+
+    /// A name to save the file under, from the players and how many turns have been played, for
+    /// example `Alice-Bob-turn-7.yatzy`.
+    pub fn file_name(&self) -> String {
+        let turns = self
+            .events
+            .iter()
+            .filter(|event| matches!(event, GameEvent::Scored(_) | GameEvent::Scratched(_)))
+            .count();
+        let names: Vec<String> = self
+            .players
+            .iter()
+            .map(|name| name.replace(|c: char| !c.is_alphanumeric(), "_"))
+            .collect();
+        format!("{}-turn-{}.{}", names.join("-"), turns + 1, EXTENSION)
+    }
+
+    /// Rebuild the game, so the next player can take their turn. Every event must be one that
+    /// could have happened when it did.
+    pub fn resume(&self) -> Result<Game> {
+        if self.players.is_empty() {
+            bail!("the game has no players");
+        }
+        let mut game = Game::new(
+            self.rules.clone(),
+            GameRng::from_entropy(),
+            self.players.len(),
+        );
+        if !self.teams.is_empty() {
+            if self.teams.len() != self.players.len() {
+                bail!(
+                    "the game has {} players but {} teams",
+                    self.players.len(),
+                    self.teams.len()
+                );
+            }
+            game.set_teams(self.teams.clone());
+        }
+        for (idx, event) in self.events.iter().enumerate() {
+            check(&game, event).with_context(|| format!("event {} of the game", idx + 1))?;
+            game.apply(event.clone());
+        }
+        if game.any_rolling() {
+            bail!("the game was saved while the dice were rolling");
+        }
+        Ok(game)
+    }
+}
+
+/// Check that an event could happen next in `game`, or say why not.
+pub fn check(game: &Game, event: &GameEvent) -> Result<()> {
+    let mv = match event {
+        GameEvent::RollStarted => Move::Roll,
+        GameEvent::HoldToggled(idx) => Move::ToggleHold(*idx),
+        GameEvent::Scored(slot) => Move::Score(*slot),
+        GameEvent::Scratched(slot) => Move::Scratch(*slot),
+        GameEvent::Rolled(values) => {
+            let rolling = game.dice().iter().filter(|die| die.value.is_none()).count();
+            let sides = game.rules().sides;
+            if values.len() != rolling {
+                bail!("{} dice landed but {} were rolling", values.len(), rolling);
+            }
+            if let Some(value) = values.iter().find(|value| value.0 == 0 || value.0 > sides) {
+                bail!(
+                    "a die landed on {}, but the dice have {} sides",
+                    value.0,
+                    sides
+                );
+            }
+            return Ok(());
+        }
+    };
+    game.validate(mv).map_err(|e| anyhow!("{}", e))
+}
@@ -0,0 +1,98 @@
+//! Dates, for showing when things happened and picking them out by day, all in UTC.
+
+/// The day of a time in seconds since the Unix epoch, like `2020-06-14`.
+pub fn format_day(secs: u64) -> String {
+    let (year, month, day) = civil_date(secs / 86_400);
+    format!("{}-{:02}-{:02}", year, month, day)
+}
+
+/// A time in seconds since the Unix epoch, like `2020-06-14 18:05 UTC`.
+pub fn format(secs: u64) -> String {
+    let (days, secs) = (secs / 86_400, secs % 86_400);
+    let (year, month, day) = civil_date(days);
+    format!(
+        "{}-{:02}-{:02} {:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        secs / 3600,
+        secs % 3600 / 60
+    )
+}
+
+/// A time in seconds since the Unix epoch in the short form of ISO 8601, like
+/// `20200614T180500Z`.
+pub fn timestamp(secs: u64) -> String {
+    let (days, secs) = (secs / 86_400, secs % 86_400);
+    let (year, month, day) = civil_date(days);
+    format!(
+        "{}{:02}{:02}T{:02}{:02}{:02}Z",
+        year,
+        month,
+        day,
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
+
+/// A length of time in seconds, like `4:05`, or `1:04:05` once it's over an hour.
+pub fn elapsed(secs: u64) -> String {
+    let (hours, minutes, secs) = (secs / 3600, secs % 3600 / 60, secs % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, secs)
+    } else {
+        format!("{}:{:02}", minutes, secs)
+    }
+}
+
+/// The start of a day written like `2020-06-14`, in seconds since the Unix epoch, or `None` if
+/// it isn't a day since then.
+pub fn parse_day(text: &str) -> Option<u64> {
+    let mut parts = text
+        .trim()
+        .splitn(3, '-')
+        .map(|part| part.parse::<u64>().ok());
+    let (year, month, day) = (parts.next()??, parts.next()??, parts.next()??);
+    if year < 1970 || month == 0 || month > 12 || day == 0 {
+        return None;
+    }
+    let days = days_from_civil(year, month, day);
+    // days past the end of the month end up in the next one
+    if civil_date(days) != (year, month, day) {
+        return None;
+    }
+    Some(days * 86_400)
+}
+
+/// The year, month and day of the day `days` after the Unix epoch, in the Gregorian calendar.
+fn civil_date(days: u64) -> (u64, u64, u64) {
+    // shift the epoch to 0000-03-01, so leap days fall at the end of each year
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// The number of days from the Unix epoch to the given day, the inverse of `civil_date`.
+fn days_from_civil(year: u64, month: u64, day: u64) -> u64 {
+    // years start in March, as in `civil_date`
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year / 400;
+    let year_of_era = year - era * 400;
+    let shifted_month = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * shifted_month + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
@@ -0,0 +1,25 @@
+//! The events that make up a game.
+//!
+//! Every change to a `Game` is an event applied by `Game::apply`, and the game keeps the full
+//! list of events in order. Replaying the events from the start rebuilds the same game, which is
+//! what undo, replays and network sync are built on.
+
+use serde::{Deserialize, Serialize};
+
+use super::score::Score;
+use super::scorecard::Slot;
+
+/// Something that happened in a game.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameEvent {
+    /// The dice that weren't held started rolling.
+    RollStarted,
+    /// The rolling dice landed on these values, in the order the dice are laid out.
+    Rolled(Vec<Score>),
+    /// The die at the given index was held or released.
+    HoldToggled(usize),
+    /// The dice were placed in a slot on the score sheet.
+    Scored(Slot),
+    /// A slot on the score sheet was filled with zero.
+    Scratched(Slot),
+}
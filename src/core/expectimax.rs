@@ -0,0 +1,279 @@
+//! An expectimax solver for the choices within a turn.
+//!
+//! The player's choices (which dice to keep) are maximised over, and the dice rolls are averaged
+//! over, all the way to the end of the turn, when the hand is placed in its best open slot. The
+//! order of the dice doesn't matter to the score, so hands are handled as sorted multisets and
+//! the value of each one is only worked out once.
+
+use std::collections::HashMap;
+
+use super::game::{Game, TurnPhase};
+use super::score::Score;
+use super::scorecard::{Category, ScoreSheet, Slot};
+
+/// Solves one turn of a game. Values are cached, so asking about several positions in the same
+/// turn is cheap.
+pub struct Solver {
+    /// A snapshot of the game, used to score finished hands.
+    game: Game,
+    /// Only score hands in this slot, rather than in the best open one.
+    target: Option<Slot>,
+    /// For each number of dice, every way they can land, with its probability.
+    outcomes: Vec<Vec<(Vec<u8>, f64)>>,
+    /// The expected score of a hand with some rolls left, before deciding what to keep.
+    hand_values: HashMap<(Vec<u8>, u8), f64>,
+    /// The expected score of rerolling everything but the kept dice, with some rolls left.
+    keep_values: HashMap<(Vec<u8>, u8), f64>,
+}
+
+impl Solver {
+    pub fn new(game: Game) -> Self {
+        Self::with_target(game, None)
+    }
+
+    /// A solver that scores every hand in the given slot.
+    pub fn for_slot(game: Game, slot: Slot) -> Self {
+        Self::with_target(game, Some(slot))
+    }
+
+    fn with_target(game: Game, target: Option<Slot>) -> Self {
+        Self {
+            game,
+            target,
+            outcomes: Vec::new(),
+            hand_values: HashMap::new(),
+            keep_values: HashMap::new(),
+        }
+    }
+
+    /// Which of the current dice to hold before the next roll, or `None` if the dice are
+    /// rolling. Holding every die means the hand should be scored as it is.
+    ///
+    /// Saved rolls aren't looked ahead through, beyond a normal turn's worth.
+    pub fn best_holds(&mut self) -> Option<Vec<bool>> {
+        let dice = self.game.scores()?;
+        let kept = self.best_kept()?;
+        Some(holds_for(&dice, &self.game, &kept))
+    }
+
+    /// The faces to keep from the current dice before the next roll, sorted, or `None` if the
+    /// dice are rolling. Keeping them all means the hand should be scored as it is.
+    pub fn best_kept(&mut self) -> Option<Vec<u8>> {
+        let dice = self.game.scores()?;
+        let rolls_left = self.game.rolls_left().min(self.game.rules().rolls);
+        let mut hand: Vec<u8> = dice.iter().map(|die| die.0).collect();
+        hand.sort_unstable();
+        Some(self.best_keep(&hand, rolls_left).0)
+    }
+
+    /// The expected score of keeping some of the current dice and rerolling the rest. Keeping
+    /// all of them means scoring the hand as it is.
+    pub fn value_of_keeping(&mut self, kept: &[Score]) -> f64 {
+        let dice = self.game.dice().len();
+        let rolls_left = self.game.rolls_left().min(self.game.rules().rolls);
+        let mut kept: Vec<u8> = kept.iter().map(|die| die.0).collect();
+        kept.sort_unstable();
+        if kept.len() == dice || rolls_left == 0 {
+            return self.score(&kept);
+        }
+        self.keep_value(&kept, dice - kept.len(), rolls_left)
+    }
+
+    /// The expected score of a whole turn, starting by rolling all the dice.
+    pub fn turn_value(&mut self) -> f64 {
+        let dice = self.game.dice().len();
+        let rolls = self.game.rules().rolls;
+        self.keep_value(&[], dice, rolls)
+    }
+
+    /// The expected score of a sorted hand, with the best choices from here on.
+    fn value(&mut self, hand: &[u8], rolls_left: u8) -> f64 {
+        if rolls_left == 0 {
+            return self.score(hand);
+        }
+        let key = (hand.to_vec(), rolls_left);
+        if let Some(value) = self.hand_values.get(&key) {
+            return *value;
+        }
+        let value = self.best_keep(hand, rolls_left).1;
+        self.hand_values.insert(key, value);
+        value
+    }
+
+    /// The best dice to keep from a sorted hand, and the expected score of keeping them.
+    fn best_keep(&mut self, hand: &[u8], rolls_left: u8) -> (Vec<u8>, f64) {
+        // keeping everything scores the hand now
+        let mut best = (hand.to_vec(), self.score(hand));
+        if rolls_left == 0 {
+            return best;
+        }
+        for kept in sub_multisets(hand) {
+            if kept.len() == hand.len() {
+                continue;
+            }
+            let value = self.keep_value(&kept, hand.len() - kept.len(), rolls_left);
+            if value > best.1 {
+                best = (kept, value);
+            }
+        }
+        best
+    }
+
+    /// The expected score of keeping some sorted dice and rerolling the rest.
+    fn keep_value(&mut self, kept: &[u8], rerolled: usize, rolls_left: u8) -> f64 {
+        let key = (kept.to_vec(), rolls_left);
+        if let Some(value) = self.keep_values.get(&key) {
+            return *value;
+        }
+        let outcomes = self.outcomes(rerolled).to_vec();
+        let mut value = 0.0;
+        for (outcome, probability) in outcomes {
+            let mut hand = kept.to_vec();
+            hand.extend(outcome);
+            hand.sort_unstable();
+            value += probability * self.value(&hand, rolls_left - 1);
+        }
+        self.keep_values.insert(key, value);
+        value
+    }
+
+    /// The points for placing a hand in its best open slot, or in the target slot.
+    fn score(&self, hand: &[u8]) -> f64 {
+        let dice: Vec<Score> = hand.iter().map(|face| Score(*face)).collect();
+        let points = match self.target {
+            Some(slot) => {
+                let points = self.game.sheet().points(slot, &dice, self.game.rules());
+                points * ScoreSheet::multiplier(slot.column)
+            }
+            None => self
+                .game
+                .best_slot_for(&dice)
+                .map_or(0, |(_, points)| points),
+        };
+        f64::from(points)
+    }
+
+    /// Every way `count` dice can land, as sorted multisets with their probabilities.
+    fn outcomes(&mut self, count: usize) -> &[(Vec<u8>, f64)] {
+        let sides = self.game.rules().sides;
+        while self.outcomes.len() <= count {
+            let dice = self.outcomes.len();
+            let mut counts = HashMap::new();
+            let total = u32::from(sides).pow(dice as u32);
+            for outcome in 0..total {
+                let mut rest = outcome;
+                let mut faces: Vec<u8> = (0..dice)
+                    .map(|_| {
+                        let face = (rest % u32::from(sides)) as u8 + 1;
+                        rest /= u32::from(sides);
+                        face
+                    })
+                    .collect();
+                faces.sort_unstable();
+                *counts.entry(faces).or_insert(0u32) += 1;
+            }
+            let outcomes = counts
+                .into_iter()
+                .map(|(faces, n)| (faces, f64::from(n) / f64::from(total)))
+                .collect();
+            self.outcomes.push(outcomes);
+        }
+        &self.outcomes[count]
+    }
+}
+
+/// Every distinct multiset that can be kept from a sorted hand, each sorted.
+pub(super) fn sub_multisets(hand: &[u8]) -> Vec<Vec<u8>> {
+    let mut subsets = vec![Vec::new()];
+    let mut idx = 0;
+    while idx < hand.len() {
+        // take 0..=n copies of each distinct face
+        let face = hand[idx];
+        let n = hand[idx..].iter().take_while(|die| **die == face).count();
+        let mut next = Vec::with_capacity(subsets.len() * (n + 1));
+        for subset in &subsets {
+            for copies in 0..=n {
+                let mut subset = subset.clone();
+                subset.resize(subset.len() + copies, face);
+                next.push(subset);
+            }
+        }
+        subsets = next;
+        idx += n;
+    }
+    subsets
+}
+
+/// Which dice to hold to keep the given faces, preferring dice that are already held so as few
+/// holds as possible change.
+pub fn holds_for(dice: &[Score], game: &Game, kept: &[u8]) -> Vec<bool> {
+    let mut wanted = kept.to_vec();
+    let mut holds = vec![false; dice.len()];
+    let mut order: Vec<usize> = (0..dice.len()).collect();
+    order.sort_by_key(|idx| !game.dice()[*idx].held);
+    for idx in order {
+        if let Some(pos) = wanted.iter().position(|face| *face == dice[idx].0) {
+            wanted.swap_remove(pos);
+            holds[idx] = true;
+        }
+    }
+    holds
+}
+
+/// The expected score of a turn played only for each slot, as `Solver::for_slot` works it out.
+///
+/// These don't depend on the hand, only on the rules and on whether a Yatzy in the slot's column
+/// would be a joker, so they're worked out once a game and kept. A `TurnValues` is only good for
+/// the game it was first used with.
+#[derive(Debug, Clone, Default)]
+pub struct TurnValues {
+    values: HashMap<(Slot, bool), f64>,
+}
+
+impl TurnValues {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The expected score of a turn in the game that only scores in `slot`.
+    pub fn for_slot(&mut self, game: &Game, slot: Slot) -> f64 {
+        let yatzy = Slot::in_column(slot.column, Category::Yatzy);
+        let joker = game.sheet().get(yatzy).is_some();
+        *self
+            .values
+            .entry((slot, joker))
+            .or_insert_with(|| Solver::for_slot(game.clone(), slot).turn_value())
+    }
+}
+
+/// How good it is to place the final hand of a turn in each open slot, or `None` before the
+/// last roll.
+///
+/// The value is the points scored now, less the points the slot would be expected to score if it
+/// were kept for a turn of its own, so a slot that is hard to fill is worth saving for a good
+/// hand. This ignores the upper-section bonus.
+pub fn placement_values(game: &Game, turns: &mut TurnValues) -> Option<Vec<(Slot, f64)>> {
+    if game.phase() != TurnPhase::Scoring {
+        return None;
+    }
+    slot_values(game, turns)
+}
+
+/// The same values as `placement_values`, for placing the current dice now even if there are
+/// rolls left. Returns `None` if the dice haven't been rolled or are rolling.
+pub fn slot_values(game: &Game, turns: &mut TurnValues) -> Option<Vec<(Slot, f64)>> {
+    if game.rolls() == 0 {
+        return None;
+    }
+    let dice = game.scores()?;
+    let values = game
+        .slots()
+        .filter(|slot| game.can_score(*slot))
+        .map(|slot| {
+            let points = game.sheet().points(slot, &dice, game.rules());
+            let points = f64::from(points * ScoreSheet::multiplier(slot.column));
+            (slot, points - turns.for_slot(game, slot))
+        })
+        .collect();
+    Some(values)
+}
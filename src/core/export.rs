@@ -0,0 +1,196 @@
+//! Exporting the results of a game for use elsewhere, such as in a spreadsheet or a chat.
+
+use std::{cmp::Reverse, fmt::Write, fs, path::Path};
+
+use anyhow::{Context, Result};
+
+use super::event::GameEvent;
+use super::game::Game;
+use super::rules::YatzyBonus;
+use super::score::{GameRng, Score};
+use super::scorecard::{ScoreSheet, Scorecard};
+
+/// The extension of exported results.
+pub const CSV_EXTENSION: &str = "csv";
+
+/// The score sheets of a game as a table, with a row for each category and total and a column
+/// for each of the named players, headings first. Categories that weren't filled are left empty.
+pub fn table(game: &Game, players: &[String]) -> Vec<Vec<String>> {
+    let rules = game.rules();
+    let header = std::iter::once("Category".to_string()).chain(players.iter().cloned());
+    let mut rows = vec![header.collect()];
+    for column in 0..rules.columns {
+        let cards: Vec<Option<&Scorecard>> = game
+            .sheets()
+            .iter()
+            .map(|sheet| sheet.column(column))
+            .collect();
+        // each heading says which column it's in, when there are several
+        let heading = |name: &str| {
+            if rules.columns > 1 {
+                format!("{} (x{})", name, ScoreSheet::multiplier(column))
+            } else {
+                name.to_string()
+            }
+        };
+        let row = |name: &str, value: &dyn Fn(&Scorecard) -> Option<u16>| {
+            let values = cards.iter().map(|card| card.and_then(value));
+            line(heading(name), values)
+        };
+        for &category in &rules.categories {
+            rows.push(row(category.name(), &|card| card.get(category)));
+        }
+        rows.push(row("Upper section", &|card| Some(card.upper_total())));
+        rows.push(row("Bonus", &|card| Some(card.bonus())));
+        if rules.yatzy_bonus == YatzyBonus::American {
+            rows.push(row("Yatzy bonus", &|card| Some(card.yatzy_bonus())));
+        }
+        if rules.columns > 1 {
+            rows.push(row("Total", &|card| Some(card.total())));
+        }
+    }
+    let totals = game.sheets().iter().map(|sheet| Some(sheet.total()));
+    rows.push(line("Total".to_string(), totals));
+    rows
+}
+
+/// The score sheets of a game as CSV, laid out like `table`.
+pub fn csv(game: &Game, players: &[String]) -> String {
+    let rows: Vec<String> = table(game, players)
+        .iter()
+        .map(|row| {
+            let fields: Vec<String> = row.iter().map(|value| field(value)).collect();
+            fields.join(",")
+        })
+        .collect();
+    let mut csv = rows.join("\r\n");
+    csv.push_str("\r\n");
+    csv
+}
+
+/// Write the results of a game to `path` as CSV, replacing whatever was there.
+pub fn save_csv(path: &Path, game: &Game, players: &[String]) -> Result<()> {
+    fs::write(path, csv(game, players)).with_context(|| format!("writing {}", path.display()))
+}
+
+/// The results of a game as a few lines to paste into a chat: the variant, then the named
+/// players from first to last with their totals and the dice of their best score. Teams come
+/// first in a team game.
+pub fn summary(game: &Game, players: &[String]) -> String {
+    let mut text = format!("\u{1f3b2} {}\n", game.rules().name);
+    if game.has_teams() {
+        let mut teams: Vec<usize> = (0..game.team_count()).collect();
+        teams.sort_by_key(|&team| Reverse(game.team_total(team)));
+        for (place, &team) in teams.iter().enumerate() {
+            let names: Vec<&str> = game
+                .team_members(team)
+                .map(|player| players[player].as_str())
+                .collect();
+            let total = game.team_total(team);
+            writeln!(text, "{} {}: {}", medal(place), names.join(" & "), total).unwrap();
+        }
+    }
+    let best = best_hands(game);
+    let mut order: Vec<usize> = (0..game.players()).collect();
+    order.sort_by_key(|&player| Reverse(game.sheets()[player].total()));
+    for (place, &player) in order.iter().enumerate() {
+        let total = game.sheets()[player].total();
+        let mut line = if game.has_teams() {
+            format!("{}: {}", players[player], total)
+        } else {
+            format!("{} {}: {}", medal(place), players[player], total)
+        };
+        if let Some(dice) = &best[player] {
+            let faces: Vec<String> = dice.iter().map(|die| die_emoji(*die)).collect();
+            write!(line, "  {}", faces.join("")).unwrap();
+        }
+        writeln!(text, "{}", line).unwrap();
+    }
+    text
+}
+
+/// The dice each player scored the most points with in a single slot, if they scored any.
+fn best_hands(game: &Game) -> Vec<Option<Vec<Score>>> {
+    let rng = GameRng::from_seed(game.seed());
+    let mut replay = Game::new(game.rules().clone(), rng, game.players());
+    let mut best: Vec<(u16, Option<Vec<Score>>)> = vec![(0, None); game.players()];
+    for event in game.events() {
+        let scored = match event {
+            GameEvent::Scored(slot) => Some((replay.player(), *slot, replay.scores())),
+            _ => None,
+        };
+        replay.apply(event.clone());
+        if let Some((player, slot, Some(dice))) = scored {
+            let points = replay.sheets()[player].get(slot).unwrap_or(0);
+            if points > best[player].0 {
+                best[player] = (points, Some(dice));
+            }
+        }
+    }
+    best.into_iter().map(|(_, dice)| dice).collect()
+}
+
+/// A medal for the first three places, and the place for the rest.
+fn medal(place: usize) -> String {
+    match place {
+        0 => "\u{1f947}".to_string(),
+        1 => "\u{1f948}".to_string(),
+        2 => "\u{1f949}".to_string(),
+        _ => format!("{}.", place + 1),
+    }
+}
+
+/// A die as the emoji for its face, from \u{2680} to \u{2685}, or as its number on dice with
+/// more faces than those.
+fn die_emoji(die: Score) -> String {
+    match die.0 {
+        face @ 1..=6 => std::char::from_u32(0x267f + u32::from(face))
+            .expect("the die faces are characters")
+            .to_string(),
+        face => format!("[{}]", face),
+    }
+}
+
+fn line(name: String, values: impl Iterator<Item = Option<u16>>) -> Vec<String> {
+    let values = values.map(|value| value.map(|v| v.to_string()).unwrap_or_default());
+    std::iter::once(name).chain(values).collect()
+}
+
+/// A field quoted if it needs to be, doubling any quotes in it.
+fn field(value: &str) -> String {
+    if value.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::moves::Move;
+    use crate::core::rules::RuleSet;
+    use crate::core::scorecard::{Category, Slot};
+
+    #[test]
+    fn the_summary_puts_the_best_first_with_their_best_dice() {
+        let mut game = Game::new(RuleSet::yatzy(), GameRng::from_seed(7), 2);
+        for (faces, category) in &[
+            ([1, 2, 3, 4, 5], Category::SmallStraight),
+            ([6, 6, 6, 6, 6], Category::Yatzy),
+        ] {
+            game.play(Move::Roll).unwrap();
+            game.apply(GameEvent::Rolled(
+                faces.iter().map(|&face| Score(face)).collect(),
+            ));
+            game.play(Move::Score(Slot::new(*category))).unwrap();
+        }
+        let players = vec!["Alice".to_string(), "Bob".to_string()];
+        assert_eq!(
+            summary(&game, &players),
+            "\u{1f3b2} Yatzy\n\
+             \u{1f947} Bob: 50  \u{2685}\u{2685}\u{2685}\u{2685}\u{2685}\n\
+             \u{1f948} Alice: 15  \u{2680}\u{2681}\u{2682}\u{2683}\u{2684}\n"
+        );
+    }
+}
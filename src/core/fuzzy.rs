@@ -0,0 +1,36 @@
+//! Fuzzy matching of what's been typed against a list of names, for the command palette.
+
+/// How well `query` matches `name`, or `None` if it doesn't. Every character of the query has to
+/// appear in the name in order, ignoring case, and characters found together or at the start of
+/// a word count for more.
+pub fn score(query: &str, name: &str) -> Option<u32> {
+    let name: Vec<char> = name.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut next = 0;
+    let mut last: Option<usize> = None;
+    for wanted in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = next + name[next..].iter().position(|&c| c == wanted)?;
+        score += 1;
+        if found > 0 && last == Some(found - 1) {
+            score += 4;
+        }
+        if found == 0 || !name[found - 1].is_alphanumeric() {
+            score += 8;
+        }
+        last = Some(found);
+        next = found + 1;
+    }
+    Some(score)
+}
+
+/// The positions of the names matching `query`, best first. Names that match as well as each
+/// other keep their order, and everything matches an empty query.
+pub fn rank<S: AsRef<str>>(query: &str, names: &[S]) -> Vec<usize> {
+    let mut matches: Vec<(usize, u32)> = names
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, name)| score(query, name.as_ref()).map(|score| (idx, score)))
+        .collect();
+    matches.sort_by(|(_, a), (_, b)| b.cmp(a));
+    matches.into_iter().map(|(idx, _)| idx).collect()
+}
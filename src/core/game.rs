@@ -0,0 +1,853 @@
+//! A game in progress: the dice, the turn structure and the score sheet.
+//!
+//! A game can be saved with serde and carried on later. It's saved as it stands, dice and all,
+//! with how far its generator had got, so the dice roll the same way once it's loaded.
+
+use std::convert::TryFrom;
+
+use serde::{Deserialize, Serialize};
+
+use super::event::GameEvent;
+use super::moves::{Move, MoveError};
+use super::rules::RuleSet;
+use super::score::{GameRng, Score};
+use super::scorecard::{ScoreSheet, Slot};
+
+/// Where the current player is within their turn.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TurnPhase {
+    /// The player may roll the dice, holding some between rolls.
+    Rolling,
+    /// The player has used all their rolls and must choose how to score them.
+    Scoring,
+}
+
+/// How many points fewer than the dice's best slot makes placing them somewhere else look like a
+/// slip.
+const DOUBTFUL_LOSS: u16 = 15;
+
+/// Why a move looks like it might have been made by mistake, from `Game::doubt`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Doubt {
+    /// The dice would score nothing in the slot.
+    Nothing,
+    /// The dice would score a lot more in another slot: the points here, and where they'd
+    /// score the most, with its points.
+    Worse {
+        points: u16,
+        best: Slot,
+        best_points: u16,
+    },
+    /// The slot would be scratched although the dice would score in another one.
+    Scratch { best: Slot, best_points: u16 },
+}
+
+/// A single die in the game.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Die {
+    /// The face showing, or `None` while the die is rolling.
+    pub value: Option<Score>,
+    /// Whether the die is held, meaning it will not be included in the next roll.
+    pub held: bool,
+}
+
+/// The state of a game, independent of how it is displayed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(into = "SavedGame", try_from = "SavedGame")]
+pub struct Game {
+    rules: RuleSet,
+    dice: Vec<Die>,
+    /// The number of rolls made so far this turn.
+    rolls: u8,
+    /// Each player's rolls left unused in earlier turns, for variants that let them be saved.
+    saved_rolls: Vec<u8>,
+    phase: TurnPhase,
+    /// Each player's score sheet, in turn order.
+    sheets: Vec<ScoreSheet>,
+    /// The player whose turn it is.
+    player: usize,
+    /// The team each player is on, in turn order. Without teams, everyone is on their own.
+    teams: Vec<usize>,
+    /// Where all of the game's dice rolls come from.
+    rng: GameRng,
+    /// Everything that has happened in the game, in order.
+    events: Vec<GameEvent>,
+    /// Events that have been undone, most recent last, ready to be redone.
+    undone: Vec<GameEvent>,
+}
+
+/// A game as it's saved: the game with its generator replaced by the seed and how many dice it
+/// has rolled, since the generator itself can't be saved.
+#[derive(Serialize, Deserialize)]
+struct SavedGame {
+    rules: RuleSet,
+    dice: Vec<Die>,
+    rolls: u8,
+    saved_rolls: Vec<u8>,
+    phase: TurnPhase,
+    sheets: Vec<ScoreSheet>,
+    player: usize,
+    teams: Vec<usize>,
+    seed: u64,
+    draws: u64,
+    events: Vec<GameEvent>,
+    undone: Vec<GameEvent>,
+}
+
+impl From<Game> for SavedGame {
+    fn from(game: Game) -> Self {
+        Self {
+            seed: game.rng.seed(),
+            draws: game.rng.draws(),
+            rules: game.rules,
+            dice: game.dice,
+            rolls: game.rolls,
+            saved_rolls: game.saved_rolls,
+            phase: game.phase,
+            sheets: game.sheets,
+            player: game.player,
+            teams: game.teams,
+            events: game.events,
+            undone: game.undone,
+        }
+    }
+}
+
+impl TryFrom<SavedGame> for Game {
+    type Error = String;
+
+    /// Load a saved game, checking its parts fit together since the file could have been
+    /// changed.
+    fn try_from(saved: SavedGame) -> Result<Self, Self::Error> {
+        let players = saved.sheets.len();
+        if players == 0 {
+            return Err("the game has no players".into());
+        }
+        if saved.saved_rolls.len() != players || saved.teams.len() != players {
+            return Err(format!(
+                "the game has {} score sheets but not as many players",
+                players
+            ));
+        }
+        if saved.player >= players {
+            return Err(format!(
+                "it's player {}'s turn, but there are {}",
+                saved.player + 1,
+                players
+            ));
+        }
+        if saved.dice.len() != saved.rules.dice {
+            return Err(format!(
+                "the game has {} dice but its rules play with {}",
+                saved.dice.len(),
+                saved.rules.dice
+            ));
+        }
+        if saved
+            .sheets
+            .iter()
+            .any(|sheet| sheet.columns().len() != saved.rules.columns)
+        {
+            return Err(format!(
+                "the rules have {} columns, but a score sheet doesn't",
+                saved.rules.columns
+            ));
+        }
+        for event in saved.events.iter().chain(&saved.undone) {
+            check_event(event, &saved.rules)?;
+        }
+        // every die the generator rolled landed in an event
+        let landed: usize = (saved.events.iter().chain(&saved.undone))
+            .map(|event| match event {
+                GameEvent::Rolled(values) => values.len(),
+                _ => 0,
+            })
+            .sum();
+        if saved.draws > landed as u64 {
+            return Err(format!(
+                "the dice were rolled {} times but only {} landed",
+                saved.draws, landed
+            ));
+        }
+        Ok(Self {
+            rng: GameRng::resume(saved.seed, saved.draws, saved.rules.sides),
+            rules: saved.rules,
+            dice: saved.dice,
+            rolls: saved.rolls,
+            saved_rolls: saved.saved_rolls,
+            phase: saved.phase,
+            sheets: saved.sheets,
+            player: saved.player,
+            teams: saved.teams,
+            events: saved.events,
+            undone: saved.undone,
+        })
+    }
+}
+
+/// Check an event from a saved game could happen under the rules: the dice and slots it names
+/// are in the game, and the dice landed on faces they have.
+fn check_event(event: &GameEvent, rules: &RuleSet) -> Result<(), String> {
+    match event {
+        GameEvent::RollStarted => Ok(()),
+        GameEvent::Rolled(values) => {
+            if values.len() > rules.dice {
+                return Err(format!(
+                    "{} dice landed, but the rules play with {}",
+                    values.len(),
+                    rules.dice
+                ));
+            }
+            match values
+                .iter()
+                .find(|value| value.0 == 0 || value.0 > rules.sides)
+            {
+                Some(value) => Err(format!(
+                    "a die landed on {}, but the dice have {} sides",
+                    value.0, rules.sides
+                )),
+                None => Ok(()),
+            }
+        }
+        GameEvent::HoldToggled(idx) if *idx >= rules.dice => Err(format!(
+            "die {} was held, but the rules play with {}",
+            idx + 1,
+            rules.dice
+        )),
+        GameEvent::HoldToggled(_) => Ok(()),
+        GameEvent::Scored(slot) | GameEvent::Scratched(slot) => {
+            if slot.column >= rules.columns {
+                Err(format!(
+                    "column {} was filled, but the rules have {}",
+                    slot.column + 1,
+                    rules.columns
+                ))
+            } else if !rules.has_category(slot.category) {
+                Err(format!(
+                    "{} was filled, but it isn't in the rules",
+                    slot.category.name()
+                ))
+            } else {
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Game {
+    /// Start a new game for the given number of players, rolling dice from `rng`.
+    pub fn new(rules: RuleSet, rng: GameRng, players: usize) -> Self {
+        assert!(players > 0, "a game needs at least one player");
+        let die = Die {
+            value: Some(Score(rules.sides)),
+            held: false,
+        };
+        Self {
+            dice: vec![die; rules.dice],
+            rolls: 0,
+            saved_rolls: vec![0; players],
+            phase: TurnPhase::Rolling,
+            sheets: vec![ScoreSheet::new(&rules); players],
+            player: 0,
+            teams: (0..players).collect(),
+            rules,
+            rng,
+            events: Vec::new(),
+            undone: Vec::new(),
+        }
+    }
+
+    /// Rebuild a game by applying the given events to a new game.
+    pub fn replay(rules: RuleSet, rng: GameRng, players: usize, events: &[GameEvent]) -> Self {
+        let mut game = Self::new(rules, rng, players);
+        for event in events {
+            game.apply(event.clone());
+        }
+        game
+    }
+
+    /// Everything that has happened in the game, in order.
+    pub fn events(&self) -> &[GameEvent] {
+        &self.events
+    }
+
+    /// The seed the game's dice are rolled from.
+    pub fn seed(&self) -> u64 {
+        self.rng.seed()
+    }
+
+    pub fn rules(&self) -> &RuleSet {
+        &self.rules
+    }
+
+    pub fn dice(&self) -> &[Die] {
+        &self.dice
+    }
+
+    /// The score sheet of the player whose turn it is.
+    pub fn sheet(&self) -> &ScoreSheet {
+        &self.sheets[self.player]
+    }
+
+    /// Every player's score sheet, in turn order.
+    pub fn sheets(&self) -> &[ScoreSheet] {
+        &self.sheets
+    }
+
+    /// The index of the player whose turn it is.
+    pub fn player(&self) -> usize {
+        self.player
+    }
+
+    pub fn players(&self) -> usize {
+        self.sheets.len()
+    }
+
+    /// Put the players in teams, given the team of each player in turn order and numbered from
+    /// 0. Teammates keep their own score sheets, and the team's score is their totals added
+    /// together.
+    pub fn set_teams(&mut self, teams: Vec<usize>) {
+        assert_eq!(teams.len(), self.players(), "every player needs a team");
+        self.teams = teams;
+    }
+
+    /// The team the given player is on.
+    pub fn team(&self, player: usize) -> usize {
+        self.teams[player]
+    }
+
+    pub fn team_count(&self) -> usize {
+        self.teams.iter().max().map_or(0, |team| team + 1)
+    }
+
+    /// Whether anyone is on a team with someone else.
+    pub fn has_teams(&self) -> bool {
+        self.team_count() < self.players()
+    }
+
+    /// The players on the given team, in turn order.
+    pub fn team_members(&self, team: usize) -> impl Iterator<Item = usize> + '_ {
+        (0..self.players()).filter(move |&player| self.teams[player] == team)
+    }
+
+    /// The score of the given team: its players' totals added together.
+    pub fn team_total(&self, team: usize) -> u16 {
+        self.team_members(team)
+            .map(|player| self.sheets[player].total())
+            .sum()
+    }
+
+    pub fn phase(&self) -> TurnPhase {
+        self.phase
+    }
+
+    /// The number of rolls made so far this turn.
+    pub fn rolls(&self) -> u8 {
+        self.rolls
+    }
+
+    /// How many rolls the player has left this turn, including any saved rolls.
+    pub fn rolls_left(&self) -> u8 {
+        self.rules.rolls.saturating_sub(self.rolls) + self.saved_rolls[self.player]
+    }
+
+    /// The round being played, counting from 1, where everyone takes a turn each round. Once
+    /// the game is over it's the last round.
+    pub fn round(&self) -> usize {
+        let played = self
+            .sheets
+            .iter()
+            .map(|sheet| {
+                self.slots()
+                    .filter(|slot| sheet.get(*slot).is_some())
+                    .count()
+            })
+            .min()
+            .unwrap_or(0);
+        (played + 1).min(self.rounds())
+    }
+
+    /// How many rounds the game lasts, one for each slot on a sheet.
+    pub fn rounds(&self) -> usize {
+        self.rules.categories.len() * self.rules.columns
+    }
+
+    /// Whether every player has filled every category, ending the game.
+    pub fn is_complete(&self) -> bool {
+        self.sheets
+            .iter()
+            .all(|sheet| sheet.is_complete(&self.rules))
+    }
+
+    pub fn any_rolling(&self) -> bool {
+        self.dice.iter().any(|die| die.value.is_none())
+    }
+
+    /// The values showing on the dice, or `None` if any of them are still rolling.
+    pub fn scores(&self) -> Option<Vec<Score>> {
+        self.dice.iter().map(|die| die.value).collect()
+    }
+
+    /// Whether the player is allowed to roll the dice right now.
+    pub fn can_roll(&self) -> bool {
+        self.validate(Move::Roll).is_ok()
+    }
+
+    /// Whether the player is allowed to hold or release dice right now.
+    pub fn can_hold(&self) -> bool {
+        self.validate(Move::ToggleHold(0)).is_ok()
+    }
+
+    /// Whether the current dice can be placed in the given slot.
+    pub fn can_score(&self, slot: Slot) -> bool {
+        self.validate(Move::Score(slot)).is_ok()
+    }
+
+    /// Every slot on the score sheet, column by column in scorecard order.
+    pub fn slots(&self) -> impl Iterator<Item = Slot> + '_ {
+        (0..self.rules.columns).flat_map(move |column| {
+            self.rules
+                .categories
+                .iter()
+                .map(move |category| Slot::in_column(column, *category))
+        })
+    }
+
+    /// The allowed slot where the current dice would score the most points (including the
+    /// column multiplier), with the points.
+    pub fn best_category(&self) -> Option<(Slot, u16)> {
+        self.best_slot_for(&self.scores()?)
+    }
+
+    /// The open slot on the current player's sheet where the given dice would score the most
+    /// points (including the column multiplier), with the points.
+    pub fn best_slot_for(&self, dice: &[Score]) -> Option<(Slot, u16)> {
+        let sheet = self.sheet();
+        self.slots()
+            .filter(|slot| sheet.check_fill(*slot, dice, &self.rules).is_ok())
+            .map(|slot| {
+                let points = sheet.points(slot, dice, &self.rules);
+                (slot, points * ScoreSheet::multiplier(slot.column))
+            })
+            // the first of equally good slots, in scorecard order
+            .fold(None, |best, (slot, points)| match best {
+                Some((_, best_points)) if best_points >= points => best,
+                _ => Some((slot, points)),
+            })
+    }
+
+    /// Why a move might be a slip worth asking about before it's made: placing the dice where
+    /// they score nothing or a lot less than they could, or scratching a slot when they'd
+    /// score somewhere. Points include the column multiplier. `None` if the move looks meant,
+    /// or isn't allowed anyway.
+    pub fn doubt(&self, mv: Move) -> Option<Doubt> {
+        self.validate(mv).ok()?;
+        let dice = self.scores()?;
+        let (best, best_points) = self.best_slot_for(&dice)?;
+        match mv {
+            Move::Score(slot) => {
+                let points = self.sheet().points(slot, &dice, &self.rules)
+                    * ScoreSheet::multiplier(slot.column);
+                if points == 0 {
+                    Some(Doubt::Nothing)
+                } else if best_points >= points + DOUBTFUL_LOSS {
+                    Some(Doubt::Worse {
+                        points,
+                        best,
+                        best_points,
+                    })
+                } else {
+                    None
+                }
+            }
+            Move::Scratch(_) if best_points > 0 => Some(Doubt::Scratch { best, best_points }),
+            _ => None,
+        }
+    }
+
+    /// The move to make for a player who has run out of time.
+    ///
+    /// If they haven't rolled yet the dice are rolled for them, otherwise the dice are placed in
+    /// the best category, or a category is scratched if they would score nothing anywhere.
+    /// Returns `None` while the dice are rolling.
+    pub fn timeout_move(&self) -> Option<Move> {
+        if self.any_rolling() {
+            return None;
+        }
+        if self.rolls == 0 {
+            return Some(Move::Roll);
+        }
+        match self.best_category()? {
+            (slot, 0) => Some(Move::Scratch(slot)),
+            (slot, _) => Some(Move::Score(slot)),
+        }
+    }
+
+    /// Check whether a move is allowed right now, or say why not.
+    ///
+    /// Dice can only be held between rolls, so not before the first roll or while rolling.
+    pub fn validate(&self, mv: Move) -> Result<(), MoveError> {
+        if self.any_rolling() {
+            return Err(MoveError::DiceRolling);
+        }
+        match mv {
+            Move::Roll => {
+                if self.phase != TurnPhase::Rolling || self.rolls_left() == 0 {
+                    return Err(MoveError::NoRollsLeft);
+                }
+            }
+            Move::ToggleHold(idx) => {
+                if self.rolls == 0 {
+                    return Err(MoveError::NotRolled);
+                }
+                if self.phase != TurnPhase::Rolling {
+                    return Err(MoveError::NoRollsLeft);
+                }
+                if idx >= self.dice.len() {
+                    return Err(MoveError::NoSuchDie(idx));
+                }
+            }
+            Move::Score(slot) | Move::Scratch(slot) => {
+                if self.rolls == 0 {
+                    return Err(MoveError::NotRolled);
+                }
+                if let Some(scores) = self.scores() {
+                    self.sheet().check_fill(slot, &scores, &self.rules)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Make a move, if it is allowed.
+    pub fn play(&mut self, mv: Move) -> Result<(), MoveError> {
+        self.validate(mv)?;
+        let event = match mv {
+            Move::Roll => GameEvent::RollStarted,
+            Move::ToggleHold(idx) => GameEvent::HoldToggled(idx),
+            Move::Score(slot) => GameEvent::Scored(slot),
+            Move::Scratch(slot) => GameEvent::Scratched(slot),
+        };
+        self.undone.clear();
+        self.apply(event);
+        Ok(())
+    }
+
+    /// Whether the last event can be taken back.
+    ///
+    /// Holding dice and choosing a category can be undone, but once the dice have been rolled
+    /// (including the first roll of the next turn) everything before is fixed.
+    pub fn can_undo(&self) -> bool {
+        matches!(
+            self.events.last(),
+            Some(GameEvent::HoldToggled(_))
+                | Some(GameEvent::Scored(_))
+                | Some(GameEvent::Scratched(_))
+        )
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.undone.is_empty()
+    }
+
+    /// Take back the last hold or category choice, if it can be undone.
+    pub fn undo(&mut self) {
+        if !self.can_undo() {
+            return;
+        }
+        let mut events = std::mem::take(&mut self.events);
+        let mut undone = std::mem::take(&mut self.undone);
+        undone.extend(events.pop());
+        // The rolls are in the events, so the generator isn't used while replaying and keeps
+        // its current state.
+        let teams = self.teams.clone();
+        *self = Game::replay(
+            self.rules.clone(),
+            self.rng.clone(),
+            self.players(),
+            &events,
+        );
+        self.teams = teams;
+        self.undone = undone;
+    }
+
+    /// Make the last undone hold or category choice again.
+    pub fn redo(&mut self) {
+        if let Some(event) = self.undone.pop() {
+            self.apply(event);
+        }
+    }
+
+    /// Land the rolling dice on new values from the game's generator.
+    pub fn stop_roll(&mut self) {
+        let sides = self.rules.sides;
+        let rolling = self.dice.iter().filter(|die| die.value.is_none()).count();
+        let values = (0..rolling).map(|_| self.rng.roll(sides)).collect();
+        self.land(values);
+    }
+
+    /// Land the rolling dice on `values`, one for each of them in the order they're laid out,
+    /// instead of on values from the generator. This is for dice rolled on the table.
+    pub fn land(&mut self, values: Vec<Score>) {
+        if !self.any_rolling() {
+            return;
+        }
+        let rolling = self.dice.iter().filter(|die| die.value.is_none()).count();
+        assert_eq!(values.len(), rolling, "every rolling die needs a value");
+        self.undone.clear();
+        self.apply(GameEvent::Rolled(values));
+    }
+
+    /// Apply an event to the game and add it to the log.
+    ///
+    /// Events are assumed to be valid: they come from `play` and `stop_roll`, or from the log of
+    /// a game that was played that way.
+    pub fn apply(&mut self, event: GameEvent) {
+        match &event {
+            GameEvent::RollStarted => {
+                if self.rolls >= self.rules.rolls {
+                    self.saved_rolls[self.player] -= 1;
+                }
+                self.rolls += 1;
+                for die in self.dice.iter_mut().filter(|die| !die.held) {
+                    die.value = None;
+                }
+            }
+            GameEvent::Rolled(values) => {
+                let rolling = self.dice.iter_mut().filter(|die| die.value.is_none());
+                for (die, value) in rolling.zip(values) {
+                    die.value = Some(*value);
+                }
+                if self.rolls_left() == 0 {
+                    self.phase = TurnPhase::Scoring;
+                }
+            }
+            GameEvent::HoldToggled(idx) => {
+                let die = &mut self.dice[*idx];
+                die.held = !die.held;
+            }
+            GameEvent::Scored(slot) => {
+                if let Some(scores) = self.scores() {
+                    self.sheets[self.player].fill(*slot, &scores, &self.rules);
+                }
+                self.next_turn();
+            }
+            GameEvent::Scratched(slot) => {
+                self.sheets[self.player].scratch(*slot, &self.rules);
+                self.next_turn();
+            }
+        }
+        self.events.push(event);
+    }
+
+    /// Pass the dice to the next player, ready for their first roll.
+    fn next_turn(&mut self) {
+        if self.rules.saved_rolls {
+            self.saved_rolls[self.player] += self.rules.rolls.saturating_sub(self.rolls);
+        }
+        self.player = (self.player + 1) % self.players();
+        self.rolls = 0;
+        self.phase = TurnPhase::Rolling;
+        for die in self.dice.iter_mut() {
+            die.held = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::scorecard::Category;
+
+    fn game(rules: RuleSet, players: usize) -> Game {
+        Game::new(rules, GameRng::from_seed(7), players)
+    }
+
+    /// Roll the dice that aren't held, landing them on `faces` rather than the generator's.
+    fn roll(game: &mut Game, faces: &[u8]) {
+        game.play(Move::Roll).unwrap();
+        game.apply(GameEvent::Rolled(
+            faces.iter().map(|&face| Score(face)).collect(),
+        ));
+    }
+
+    #[test]
+    fn a_turn_has_three_rolls() {
+        let mut game = game(RuleSet::yatzy(), 1);
+        assert_eq!(game.play(Move::ToggleHold(0)), Err(MoveError::NotRolled));
+        for _ in 0..3 {
+            roll(&mut game, &[1, 2, 3, 4, 5]);
+        }
+        assert_eq!(game.phase(), TurnPhase::Scoring);
+        assert_eq!(game.play(Move::Roll), Err(MoveError::NoRollsLeft));
+    }
+
+    #[test]
+    fn held_dice_keep_their_faces() {
+        let mut game = game(RuleSet::yatzy(), 1);
+        roll(&mut game, &[6, 6, 1, 2, 3]);
+        game.play(Move::ToggleHold(0)).unwrap();
+        game.play(Move::ToggleHold(1)).unwrap();
+        roll(&mut game, &[6, 6, 4]);
+        let faces: Vec<u8> = game.scores().unwrap().iter().map(|die| die.0).collect();
+        assert_eq!(faces, vec![6, 6, 6, 6, 4]);
+    }
+
+    #[test]
+    fn scoring_passes_the_dice_on() {
+        let mut game = game(RuleSet::yatzy(), 2);
+        roll(&mut game, &[1, 1, 1, 2, 3]);
+        let ones = Slot::new(Category::Ones);
+        game.play(Move::Score(ones)).unwrap();
+        assert_eq!(game.sheets()[0].get(ones), Some(3));
+        assert_eq!(game.player(), 1);
+        assert_eq!(game.rolls(), 0);
+        assert_eq!(game.play(Move::Score(ones)), Err(MoveError::NotRolled));
+    }
+
+    #[test]
+    fn forced_order_rejects_other_categories() {
+        let rules = RuleSet {
+            forced_order: true,
+            ..RuleSet::yatzy()
+        };
+        let mut game = game(rules, 1);
+        roll(&mut game, &[2, 2, 2, 4, 5]);
+        assert_eq!(
+            game.play(Move::Score(Slot::new(Category::Twos))),
+            Err(MoveError::OutOfOrder {
+                next: Category::Ones
+            })
+        );
+        assert_eq!(game.play(Move::Scratch(Slot::new(Category::Ones))), Ok(()));
+    }
+
+    #[test]
+    fn joker_is_checked_when_playing() {
+        let mut game = game(RuleSet::yahtzee(), 1);
+        roll(&mut game, &[3, 3, 3, 3, 3]);
+        game.play(Move::Score(Slot::new(Category::Yatzy))).unwrap();
+        roll(&mut game, &[3, 3, 3, 3, 3]);
+        assert_eq!(
+            game.play(Move::Score(Slot::new(Category::Chance))),
+            Err(MoveError::JokerUpper(Category::Threes))
+        );
+        game.play(Move::Score(Slot::new(Category::Threes))).unwrap();
+        assert_eq!(game.sheet().total(), 50 + 15 + 100);
+    }
+
+    #[test]
+    fn undo_and_redo_a_category() {
+        let mut game = game(RuleSet::yatzy(), 2);
+        roll(&mut game, &[5, 5, 5, 2, 3]);
+        let before = game.clone();
+        let fives = Slot::new(Category::Fives);
+        game.play(Move::Score(fives)).unwrap();
+        assert!(game.can_undo());
+        game.undo();
+        assert_eq!(game.player(), 0);
+        assert_eq!(game.sheets()[0].get(fives), None);
+        assert_eq!(game.dice(), before.dice());
+        assert!(game.can_redo());
+        game.redo();
+        assert_eq!(game.sheets()[0].get(fives), Some(15));
+        assert_eq!(game.player(), 1);
+    }
+
+    #[test]
+    fn undo_a_hold() {
+        let mut game = game(RuleSet::yatzy(), 1);
+        roll(&mut game, &[5, 5, 5, 2, 3]);
+        game.play(Move::ToggleHold(2)).unwrap();
+        game.undo();
+        assert!(!game.dice()[2].held);
+    }
+
+    #[test]
+    fn rolls_cannot_be_undone() {
+        let mut game = game(RuleSet::yatzy(), 1);
+        roll(&mut game, &[5, 5, 5, 2, 3]);
+        assert!(!game.can_undo());
+        let before = game.clone();
+        game.undo();
+        assert_eq!(game, before);
+    }
+
+    #[test]
+    fn a_new_move_clears_redo() {
+        let mut game = game(RuleSet::yatzy(), 1);
+        roll(&mut game, &[5, 5, 5, 2, 3]);
+        game.play(Move::ToggleHold(0)).unwrap();
+        game.undo();
+        game.play(Move::ToggleHold(1)).unwrap();
+        assert!(!game.can_redo());
+    }
+
+    #[test]
+    fn replaying_the_events_rebuilds_the_game() {
+        let mut game = game(RuleSet::yatzy(), 2);
+        roll(&mut game, &[4, 4, 1, 2, 6]);
+        game.play(Move::ToggleHold(0)).unwrap();
+        game.play(Move::ToggleHold(1)).unwrap();
+        roll(&mut game, &[4, 6, 6]);
+        game.play(Move::Score(Slot::new(Category::FullHouse)))
+            .unwrap();
+        roll(&mut game, &[1, 2, 3, 4, 5]);
+        let replayed = Game::replay(RuleSet::yatzy(), GameRng::from_seed(7), 2, game.events());
+        assert_eq!(replayed, game);
+        assert_eq!(replayed.sheets()[0].total(), 24);
+    }
+
+    #[test]
+    fn the_same_seed_rolls_the_same_dice() {
+        let mut first = game(RuleSet::yatzy(), 1);
+        let mut second = game(RuleSet::yatzy(), 1);
+        for game in [&mut first, &mut second].iter_mut() {
+            game.play(Move::Roll).unwrap();
+            game.stop_roll();
+        }
+        assert_eq!(first.scores(), second.scores());
+        assert!(first.scores().is_some());
+    }
+
+    #[test]
+    fn dice_can_land_where_they_were_rolled_on_the_table() {
+        let mut game = game(RuleSet::yatzy(), 1);
+        roll(&mut game, &[6, 6, 1, 2, 3]);
+        game.play(Move::ToggleHold(0)).unwrap();
+        game.play(Move::ToggleHold(1)).unwrap();
+        game.play(Move::Roll).unwrap();
+        game.land(vec![Score(6), Score(5), Score(4)]);
+        let faces: Vec<u8> = game.scores().unwrap().iter().map(|score| score.0).collect();
+        assert_eq!(faces, [6, 6, 6, 5, 4]);
+        assert_eq!(
+            game.events().last(),
+            Some(&GameEvent::Rolled(vec![Score(6), Score(5), Score(4)]))
+        );
+    }
+
+    #[test]
+    fn loading_checks_every_event() {
+        let mut game = game(RuleSet::yatzy(), 1);
+        roll(&mut game, &[1, 2, 3, 4, 5]);
+        game.play(Move::ToggleHold(0)).unwrap();
+        assert_eq!(
+            Game::try_from(SavedGame::from(game.clone())),
+            Ok(game.clone())
+        );
+        for event in &[
+            GameEvent::HoldToggled(5),
+            GameEvent::Scored(Slot::in_column(1, Category::Ones)),
+            GameEvent::Scratched(Slot::new(Category::Villa)),
+            GameEvent::Rolled(vec![Score(7)]),
+            GameEvent::Rolled(vec![Score(1); 6]),
+        ] {
+            let mut saved = SavedGame::from(game.clone());
+            saved.events.push(event.clone());
+            assert!(Game::try_from(saved).is_err(), "{:?}", event);
+            let mut saved = SavedGame::from(game.clone());
+            saved.undone.push(event.clone());
+            assert!(Game::try_from(saved).is_err(), "{:?}", event);
+        }
+    }
+}
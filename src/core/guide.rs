@@ -0,0 +1,119 @@
+//! How to play a variant, described from its rules for the "How to play" screen.
+
+use super::poker::PokerHand;
+use super::rules::{RuleSet, Scoring, YatzyBonus};
+use super::scorecard::Category;
+
+/// How to play a game with these rules, a short line each, with a line for each category.
+pub fn how_to_play(rules: &RuleSet) -> Vec<String> {
+    let mut lines = vec![
+        format!(
+            "Each turn, roll the {} dice up to {} times.",
+            rules.dice, rules.rolls
+        ),
+        "Between rolls, hold the dice you want to keep.".to_string(),
+        "Then place the dice in an empty category, or scratch one to score nothing.".to_string(),
+    ];
+    if rules.columns > 1 {
+        lines.push(format!(
+            "Each category is filled {} times, in the columns, and column N counts N times.",
+            rules.columns
+        ));
+    }
+    if rules.forced_order {
+        lines.push("The categories have to be filled in order, from the top.".to_string());
+    }
+    if rules.saved_rolls {
+        lines.push("Rolls left at the end of a turn are saved for later turns.".to_string());
+    }
+    lines.push(String::new());
+
+    for (category, key) in rules.shortcuts() {
+        lines.push(format!(
+            "{} ({}): {}",
+            category.name(),
+            key.to_ascii_uppercase(),
+            scoring(category, rules)
+        ));
+    }
+    lines.push(String::new());
+
+    let upper = rules.categories.iter().any(|category| category.is_upper());
+    if upper && rules.upper_bonus > 0 {
+        lines.push(format!(
+            "Scoring {} or more from Ones to Sixes earns a bonus of {}.",
+            rules.upper_bonus_threshold, rules.upper_bonus
+        ));
+    }
+    if rules.yatzy_bonus == YatzyBonus::American && rules.has_category(Category::Yatzy) {
+        if rules.extra_yatzy_bonus > 0 {
+            lines.push(format!(
+                "Each Yatzy after one scored in the Yatzy category earns {} more.",
+                rules.extra_yatzy_bonus
+            ));
+        }
+        lines.push(
+            "Once Yatzy is filled, another Yatzy can go in other categories as a joker.".into(),
+        );
+    }
+    lines.push("Whoever has the most points once every sheet is full wins.".to_string());
+    lines.push(String::new());
+
+    let dice_keys = rules.dice.min(9);
+    lines.push("Space or Enter rolls the dice.".to_string());
+    lines.push(format!(
+        "1 to {} hold the dice, counting along the tray.",
+        dice_keys
+    ));
+    lines.push(
+        "The letter after a category places the dice there, and with Shift scratches it.".into(),
+    );
+    lines
+}
+
+/// What a category scores, for example "the sum of the dice showing 3".
+pub fn scoring(category: Category, rules: &RuleSet) -> String {
+    if rules.scoring == Scoring::Poker {
+        return match PokerHand::for_category(category) {
+            Some(hand) => format!("{} for {}", hand.points(), hand.name().to_lowercase()),
+            None => "the points of the best poker hand the dice make".to_string(),
+        };
+    }
+    let american = rules.scoring == Scoring::American;
+    match category {
+        Category::Ones => "the sum of the dice showing 1".to_string(),
+        Category::Twos => "the sum of the dice showing 2".to_string(),
+        Category::Threes => "the sum of the dice showing 3".to_string(),
+        Category::Fours => "the sum of the dice showing 4".to_string(),
+        Category::Fives => "the sum of the dice showing 5".to_string(),
+        Category::Sixes => "the sum of the dice showing 6".to_string(),
+        Category::OnePair => "the sum of the highest pair".to_string(),
+        Category::TwoPairs => "the sum of two different pairs".to_string(),
+        Category::ThreePairs => "the sum of three different pairs".to_string(),
+        Category::ThreeOfAKind if american => {
+            "the sum of all the dice, if three show the same face".to_string()
+        }
+        Category::ThreeOfAKind => "the sum of three dice showing the same face".to_string(),
+        Category::FourOfAKind if american => {
+            "the sum of all the dice, if four show the same face".to_string()
+        }
+        Category::FourOfAKind => "the sum of four dice showing the same face".to_string(),
+        Category::FiveOfAKind => "the sum of five dice showing the same face".to_string(),
+        Category::SmallStraight if american => "30 for any run of four".to_string(),
+        Category::SmallStraight => "15 for 1-2-3-4-5".to_string(),
+        Category::LargeStraight if american => "40 for a run of five".to_string(),
+        Category::LargeStraight => "20 for 2-3-4-5-6".to_string(),
+        Category::FullStraight => "21 for 1-2-3-4-5-6".to_string(),
+        Category::FullHouse if american => {
+            "25 for three of one face and two of another".to_string()
+        }
+        Category::FullHouse => "the sum of three of one face and two of another".to_string(),
+        Category::Villa => "the sum of two different sets of three".to_string(),
+        Category::Tower => "the sum of four of one face and two of another".to_string(),
+        Category::Chance => "the sum of all the dice".to_string(),
+        Category::Yatzy => format!(
+            "{} for all the dice showing the same face",
+            rules.yatzy_score
+        ),
+    }
+}
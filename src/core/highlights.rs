@@ -0,0 +1,65 @@
+//! The notable moments of a finished game, picked out of its event log for the results.
+
+use super::event::GameEvent;
+use super::game::Game;
+use super::score::GameRng;
+use super::scorecard::{self, Slot};
+
+/// What stood out in a game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Highlights {
+    /// How many times each player scored with all the dice showing the same face, in turn order.
+    pub yatzys: Vec<usize>,
+    /// The most points scored in a single slot: the player, the slot and the points. The first
+    /// time it was scored wins a tie.
+    pub biggest: Option<(usize, Slot, u16)>,
+}
+
+impl Highlights {
+    /// Replay a game's events to find its highlights.
+    pub fn of(game: &Game) -> Self {
+        let rng = GameRng::from_seed(game.seed());
+        let mut replay = Game::new(game.rules().clone(), rng, game.players());
+        let mut yatzys = vec![0; game.players()];
+        let mut biggest: Option<(usize, Slot, u16)> = None;
+        for event in game.events() {
+            if let GameEvent::Scored(slot) = event {
+                let player = replay.player();
+                let dice = replay.scores().unwrap_or_default();
+                if !dice.is_empty() && scorecard::yatzy(&dice, 1) == 1 {
+                    yatzys[player] += 1;
+                }
+                replay.apply(event.clone());
+                let points = replay.sheets()[player].get(*slot).unwrap_or(0);
+                match biggest {
+                    Some((_, _, most)) if most >= points => (),
+                    _ => biggest = Some((player, *slot, points)),
+                }
+            } else {
+                replay.apply(event.clone());
+            }
+        }
+        Self { yatzys, biggest }
+    }
+
+    /// The player who scored the most Yatzys, and how many, unless nobody scored one. The
+    /// first player wins a tie.
+    pub fn most_yatzys(&self) -> Option<(usize, usize)> {
+        let (player, count) =
+            self.yatzys
+                .iter()
+                .enumerate()
+                .fold((0, 0), |(best, most), (player, &count)| {
+                    if count > most {
+                        (player, count)
+                    } else {
+                        (best, most)
+                    }
+                });
+        if count == 0 {
+            None
+        } else {
+            Some((player, count))
+        }
+    }
+}
@@ -0,0 +1,240 @@
+//! The history of finished games, kept in an SQLite database to look through and watch again.
+//!
+//! Each game is stored with its variant, when it finished and how long it took, its players and
+//! their totals, the points in every slot of their score sheets, and its replay. Games played on
+//! paper are kept with just their players' totals, and without a replay.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use rusqlite::{params, Connection};
+
+use super::date;
+use super::paper::PaperGame;
+use super::replay::{self, Replay};
+
+/// Where the history is kept, in the user's data directory.
+pub fn default_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("yatzy").join("history.sqlite3"))
+}
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS games (
+        id INTEGER PRIMARY KEY,
+        variant TEXT NOT NULL,
+        finished_at INTEGER NOT NULL,
+        duration INTEGER NOT NULL,
+        replay BLOB NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS players (
+        game INTEGER NOT NULL REFERENCES games (id),
+        seat INTEGER NOT NULL,
+        name TEXT NOT NULL,
+        total INTEGER NOT NULL,
+        PRIMARY KEY (game, seat)
+    );
+    CREATE TABLE IF NOT EXISTS scores (
+        game INTEGER NOT NULL REFERENCES games (id),
+        seat INTEGER NOT NULL,
+        sheet_column INTEGER NOT NULL,
+        category TEXT NOT NULL,
+        points INTEGER NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS players_by_name ON players (name COLLATE NOCASE);
+";
+
+/// A finished game in the history, as the history screen lists it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameRecord {
+    pub id: i64,
+    /// The name of the variant.
+    pub variant: String,
+    /// When the game finished, in seconds since the Unix epoch.
+    pub finished_at: u64,
+    /// How long the game took, in seconds.
+    pub duration: u64,
+    /// The players' names and totals, in turn order.
+    pub players: Vec<(String, u16)>,
+    /// Whether it was played on paper, so there's no replay of it.
+    pub on_paper: bool,
+}
+
+impl GameRecord {
+    /// When the game finished, like `2020-06-14 18:05 UTC`, or the day it was played on paper.
+    pub fn date(&self) -> String {
+        if self.on_paper {
+            date::format_day(self.finished_at)
+        } else {
+            date::format(self.finished_at)
+        }
+    }
+}
+
+/// The database of finished games.
+#[derive(Debug)]
+pub struct History {
+    conn: Connection,
+}
+
+impl History {
+    /// Open the history at `path`, creating it if there isn't one yet.
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+        }
+        let conn = Connection::open(path).with_context(|| format!("opening {}", path.display()))?;
+        conn.execute_batch(SCHEMA)
+            .with_context(|| format!("setting up {}", path.display()))?;
+        Ok(Self { conn })
+    }
+
+    /// Add the game in a replay, which took `duration` seconds.
+    pub fn record(&mut self, replay: &Replay, duration: u64) -> Result<()> {
+        let game = replay.game_at(replay.events.len());
+        let blob = serde_json::to_vec(&replay::SCHEMA.write(replay)?)?;
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "INSERT INTO games (variant, finished_at, duration, replay) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                replay.rules.name,
+                replay.finished_at as i64,
+                duration as i64,
+                blob
+            ],
+        )?;
+        let id = tx.last_insert_rowid();
+        for (seat, (name, sheet)) in replay.players.iter().zip(game.sheets()).enumerate() {
+            tx.execute(
+                "INSERT INTO players (game, seat, name, total) VALUES (?1, ?2, ?3, ?4)",
+                params![id, seat as i64, name, sheet.total() as i64],
+            )?;
+            for (column, card) in sheet.columns().iter().enumerate() {
+                for &category in &replay.rules.categories {
+                    if let Some(points) = card.get(category) {
+                        tx.execute(
+                            "INSERT INTO scores (game, seat, sheet_column, category, points) \
+                             VALUES (?1, ?2, ?3, ?4, ?5)",
+                            params![
+                                id,
+                                seat as i64,
+                                column as i64,
+                                category.name(),
+                                points as i64
+                            ],
+                        )?;
+                    }
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Add a game played on paper, unless it is already in the history from an earlier import.
+    /// It is kept as finishing at the start of the day it was played, and taking no time. Gives
+    /// whether it was added.
+    pub fn record_paper(&mut self, game: &PaperGame) -> Result<bool> {
+        if self.has_paper(game)? {
+            return Ok(false);
+        }
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "INSERT INTO games (variant, finished_at, duration, replay) VALUES (?1, ?2, 0, ?3)",
+            params![game.variant, game.played_on as i64, Vec::<u8>::new()],
+        )?;
+        let id = tx.last_insert_rowid();
+        for (seat, (name, total)) in game.players.iter().enumerate() {
+            tx.execute(
+                "INSERT INTO players (game, seat, name, total) VALUES (?1, ?2, ?3, ?4)",
+                params![id, seat as i64, name, *total as i64],
+            )?;
+        }
+        tx.commit()?;
+        Ok(true)
+    }
+
+    /// Whether a game played on paper is in the history already.
+    fn has_paper(&self, game: &PaperGame) -> Result<bool> {
+        let mut games = self.conn.prepare(
+            "SELECT id FROM games WHERE variant = ?1 AND finished_at = ?2 AND length(replay) = 0",
+        )?;
+        let mut players = self
+            .conn
+            .prepare("SELECT name, total FROM players WHERE game = ?1 ORDER BY seat")?;
+        let ids = games.query_map(params![game.variant, game.played_on as i64], |row| {
+            row.get::<_, i64>(0)
+        })?;
+        for id in ids {
+            let names = players.query_map(params![id?], |row| {
+                Ok((row.get(0)?, row.get::<_, i64>(1)? as u16))
+            })?;
+            if names.collect::<rusqlite::Result<Vec<_>>>()? == game.players {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// The games `player` played in, if one is given, that finished from `from` up to `until`
+    /// seconds since the Unix epoch, most recent first.
+    pub fn search(
+        &self,
+        player: Option<&str>,
+        from: Option<u64>,
+        until: Option<u64>,
+    ) -> Result<Vec<GameRecord>> {
+        let mut games = self.conn.prepare(
+            "SELECT id, variant, finished_at, duration, length(replay) = 0 FROM games
+             WHERE (?1 IS NULL OR id IN (SELECT game FROM players WHERE name = ?1 COLLATE NOCASE))
+               AND (?2 IS NULL OR finished_at >= ?2)
+               AND (?3 IS NULL OR finished_at < ?3)
+             ORDER BY finished_at DESC",
+        )?;
+        let mut players = self
+            .conn
+            .prepare("SELECT name, total FROM players WHERE game = ?1 ORDER BY seat")?;
+        let (from, until) = (
+            from.map(|from| from as i64),
+            until.map(|until| until as i64),
+        );
+        let found = games.query_map(params![player, from, until], |row| {
+            Ok(GameRecord {
+                id: row.get(0)?,
+                variant: row.get(1)?,
+                finished_at: row.get::<_, i64>(2)? as u64,
+                duration: row.get::<_, i64>(3)? as u64,
+                players: Vec::new(),
+                on_paper: row.get::<_, i64>(4)? != 0,
+            })
+        })?;
+        let mut records = Vec::new();
+        for record in found {
+            let mut record = record?;
+            let names = players.query_map(params![record.id], |row| {
+                Ok((row.get(0)?, row.get::<_, i64>(1)? as u16))
+            })?;
+            record.players = names.collect::<rusqlite::Result<_>>()?;
+            records.push(record);
+        }
+        Ok(records)
+    }
+
+    /// The replay of the game with the given id.
+    pub fn replay(&self, id: i64) -> Result<Replay> {
+        let blob: Vec<u8> = self.conn.query_row(
+            "SELECT replay FROM games WHERE id = ?1",
+            params![id],
+            |row| row.get(0),
+        )?;
+        if blob.is_empty() {
+            bail!("it was played on paper, so there's no replay of it");
+        }
+        serde_json::from_slice(&blob)
+            .map_err(Into::into)
+            .and_then(|value| replay::SCHEMA.read(value))
+            .context("reading the replay")
+    }
+}
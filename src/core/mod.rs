@@ -0,0 +1,40 @@
+//! The game rules, scoring and turn logic.
+//!
+//! Nothing in here depends on druid, so it can be used without the GUI, for example from tests,
+//! benchmarks, a command line interface or a server. The druid `Data` implementations for these
+//! types live in the crate's `data` module, built only with the `gui` feature.
+
+pub mod advice;
+pub mod ai;
+pub mod analysis;
+pub mod bot;
+pub mod correspondence;
+pub mod date;
+pub mod event;
+pub mod expectimax;
+pub mod export;
+pub mod fuzzy;
+pub mod game;
+pub mod guide;
+pub mod highlights;
+pub mod history;
+pub mod montecarlo;
+pub mod moves;
+pub mod notation;
+pub mod odds;
+pub mod paper;
+pub mod poker;
+pub mod presets;
+pub mod profiles;
+pub mod replay;
+pub mod roll_log;
+pub mod rules;
+pub mod save;
+pub mod schema;
+pub mod score;
+pub mod scorecard;
+pub mod settings;
+pub mod sim;
+pub mod skins;
+pub mod smart_dice;
+pub mod strategy;
@@ -0,0 +1,117 @@
+//! A Monte Carlo computer player, which judges each choice of dice to keep by playing out the
+//! rest of the turn many times with random rolls.
+//!
+//! It is much cheaper than the expectimax solver for many dice, at the cost of some accuracy,
+//! and the rollouts can be reused to test other playing policies.
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use super::ai;
+use super::game::Game;
+use super::score::Score;
+
+/// The number of times the rest of the turn is played out for each choice of dice to keep.
+pub const ROLLOUTS: usize = 1_000;
+
+/// Chooses which dice to keep before the next roll by simulation.
+pub struct MonteCarlo {
+    rng: StdRng,
+    rollouts: usize,
+}
+
+impl MonteCarlo {
+    /// Simulate with a random seed.
+    pub fn new() -> Self {
+        Self {
+            rng: StdRng::from_entropy(),
+            rollouts: ROLLOUTS,
+        }
+    }
+
+    /// Simulate from the given seed with the given number of rollouts per choice, so the
+    /// results can be repeated.
+    pub fn from_seed(seed: u64, rollouts: usize) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            rollouts,
+        }
+    }
+
+    /// Which of the current dice to hold before the next roll, or `None` if the dice are
+    /// rolling. Holding every die means the hand should be scored as it is.
+    pub fn best_holds(&mut self, game: &Game) -> Option<Vec<bool>> {
+        let dice = game.scores()?;
+        let rolls_left = game.rolls_left();
+        let score_now = f64::from(best_points(game, &dice));
+        let mut best = (vec![true; dice.len()], score_now);
+        // each bit of the pattern says whether the die at that index is held
+        for pattern in 0..(1u32 << dice.len()) - 1 {
+            let holds: Vec<bool> = (0..dice.len())
+                .map(|idx| pattern & (1 << idx) != 0)
+                .collect();
+            let kept: Vec<Score> = dice
+                .iter()
+                .zip(&holds)
+                .filter(|(_, hold)| **hold)
+                .map(|(die, _)| *die)
+                .collect();
+            let total: u64 = (0..self.rollouts)
+                .map(|_| {
+                    let points = rollout(game, &kept, rolls_left, keep_most_common, &mut self.rng);
+                    u64::from(points)
+                })
+                .sum();
+            let mean = total as f64 / self.rollouts as f64;
+            if mean > best.1 {
+                best = (holds, mean);
+            }
+        }
+        Some(best.0)
+    }
+}
+
+impl Default for MonteCarlo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Play out the rest of a turn from the kept dice, rerolling the others, and return the points
+/// for the final hand in its best open slot.
+///
+/// After each roll `policy` picks the dice to keep for the next one. Keeping them all ends the
+/// turn early. There must be at least one roll left.
+pub fn rollout(
+    game: &Game,
+    kept: &[Score],
+    rolls_left: u8,
+    policy: impl Fn(&Game, &[Score]) -> Vec<Score>,
+    rng: &mut impl Rng,
+) -> u16 {
+    let sides = game.rules().sides;
+    let dice = game.dice().len();
+    let mut hand = kept.to_vec();
+    for roll in 0..rolls_left {
+        while hand.len() < dice {
+            hand.push(Score::random_face(rng, sides));
+        }
+        if roll + 1 == rolls_left {
+            break;
+        }
+        hand = policy(game, &hand);
+        if hand.len() == dice {
+            break;
+        }
+    }
+    best_points(game, &hand)
+}
+
+/// A simple rollout policy: keep the dice showing the most common face.
+pub fn keep_most_common(_game: &Game, hand: &[Score]) -> Vec<Score> {
+    let face = ai::most_common_face(hand);
+    hand.iter().copied().filter(|die| *die == face).collect()
+}
+
+fn best_points(game: &Game, hand: &[Score]) -> u16 {
+    game.best_slot_for(hand).map_or(0, |(_, points)| points)
+}
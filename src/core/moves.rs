@@ -0,0 +1,83 @@
+//! The moves a player can make, and the reasons a move can be rejected.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::scorecard::{Category, Slot};
+
+/// Something the current player wants to do.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Move {
+    /// Roll all the dice that aren't held.
+    Roll,
+    /// Hold the die at the given index if it is free, or release it if it is held.
+    ToggleHold(usize),
+    /// Place the dice in a slot on the score sheet.
+    Score(Slot),
+    /// Enter a zero in a slot on the score sheet.
+    Scratch(Slot),
+}
+
+/// Why a move isn't allowed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MoveError {
+    /// The dice haven't been rolled yet this turn.
+    NotRolled,
+    /// The dice are still rolling.
+    DiceRolling,
+    /// All the rolls for this turn have been used.
+    NoRollsLeft,
+    /// There is no die at the given index.
+    NoSuchDie(usize),
+    /// There is no scorecard column with the given index.
+    NoSuchColumn(usize),
+    /// The category isn't on the scorecard for the variant being played.
+    NotInRules(Category),
+    /// The category has already been filled.
+    CategoryFilled(Category),
+    /// In Forced Yatzy, only the given category can be filled.
+    OutOfOrder { next: Category },
+    /// Under the joker rule, an extra Yatzy must go in the given upper-section category.
+    JokerUpper(Category),
+    /// Under the joker rule, an extra Yatzy must go in the lower section while it has space.
+    JokerLower,
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MoveError::NotRolled => write!(f, "roll the dice first"),
+            MoveError::DiceRolling => write!(f, "wait for the dice to stop rolling"),
+            MoveError::NoRollsLeft => write!(f, "there are no rolls left this turn"),
+            MoveError::NoSuchDie(idx) => write!(f, "there is no die number {}", idx + 1),
+            MoveError::NoSuchColumn(column) => write!(f, "there is no column {}", column + 1),
+            MoveError::NotInRules(category) => {
+                write!(
+                    f,
+                    "{} isn't on the scorecard in this variant",
+                    category.name()
+                )
+            }
+            MoveError::CategoryFilled(category) => {
+                write!(f, "{} has already been filled", category.name())
+            }
+            MoveError::OutOfOrder { next } => write!(
+                f,
+                "categories must be filled in order, {} is next",
+                next.name()
+            ),
+            MoveError::JokerUpper(category) => write!(
+                f,
+                "an extra Yatzy must go in {} while it is open",
+                category.name()
+            ),
+            MoveError::JokerLower => write!(
+                f,
+                "an extra Yatzy must go in the lower section while it has space"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MoveError {}
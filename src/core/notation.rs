@@ -0,0 +1,354 @@
+//! A short text notation for games, to log them, compare them and paste them into bug reports.
+//!
+//! A game is written as a few header lines, then its events with a turn to a line:
+//!
+//! ```text
+//! Variant: Yatzy
+//! Player: Alice
+//! Player: Bob
+//!
+//! 1. r13456 h1 r2256 =Chance
+//! 2. r66612 h1 h2 h3 r45 r66 =Sixes
+//! ```
+//!
+//! `r` rolls the dice that aren't held, followed by the faces they landed on in the order the
+//! dice are laid out. On dice with more than nine sides the faces are separated by dots, as in
+//! `r1.12.7`. `hN` holds or releases the Nth die. `=` scores the dice in a category and `-`
+//! scratches one, named as in `FullHouse`, with `/N` after it for the Nth scorecard column.
+//! Team games have a `Teams:` line with each player's team, such as `Teams: 0 1 0 1`. A
+//! `Totals:` line, such as `Totals: 16 30`, gives each player's total at the end, for checking
+//! against what the game adds up to.
+//!
+//! The numbers in front of the turns are only there for people and are skipped, as is
+//! everything after a `#`. The variant is only named, so games of house rules can only be read
+//! back where those rules are saved.
+
+use std::fmt::Write;
+
+use anyhow::{anyhow, bail, Context, Result};
+
+use super::event::GameEvent;
+use super::replay::Replay;
+use super::rules::RuleSet;
+use super::score::Score;
+use super::scorecard::{Category, Slot};
+
+/// The extension of files with a game written in the notation, which are plain text.
+pub const EXTENSION: &str = "txt";
+
+/// Write out a game in the notation.
+pub fn write(replay: &Replay) -> String {
+    let mut text = format!("Variant: {}\n", replay.rules.name);
+    for name in &replay.players {
+        writeln!(text, "Player: {}", name).unwrap();
+    }
+    if !replay.teams.is_empty() {
+        let teams: Vec<_> = replay.teams.iter().map(|team| team.to_string()).collect();
+        writeln!(text, "Teams: {}", teams.join(" ")).unwrap();
+    }
+    if !replay.totals.is_empty() {
+        let totals: Vec<_> = replay
+            .totals
+            .iter()
+            .map(|total| total.to_string())
+            .collect();
+        writeln!(text, "Totals: {}", totals.join(" ")).unwrap();
+    }
+
+    let mut turns = vec![Vec::new()];
+    let mut events = replay.events.iter().peekable();
+    while let Some(event) = events.next() {
+        let token = match event {
+            // the dice starting to roll and landing are written together
+            GameEvent::RollStarted => match events.peek() {
+                Some(GameEvent::Rolled(values)) => {
+                    events.next();
+                    format!("r{}", faces(values, &replay.rules))
+                }
+                _ => "r".to_string(),
+            },
+            GameEvent::Rolled(values) => format!("r{}", faces(values, &replay.rules)),
+            GameEvent::HoldToggled(idx) => format!("h{}", idx + 1),
+            GameEvent::Scored(slot) => format!("={}", slot_code(*slot, &replay.rules)),
+            GameEvent::Scratched(slot) => format!("-{}", slot_code(*slot, &replay.rules)),
+        };
+        let turn = turns
+            .last_mut()
+            .expect("there's always a turn being written");
+        turn.push(token);
+        if let GameEvent::Scored(_) | GameEvent::Scratched(_) = event {
+            turns.push(Vec::new());
+        }
+    }
+    text.push('\n');
+    for (idx, turn) in turns.iter().filter(|turn| !turn.is_empty()).enumerate() {
+        writeln!(text, "{}. {}", idx + 1, turn.join(" ")).unwrap();
+    }
+    text
+}
+
+/// Read a game written in the notation, in one of the given variants. The game is checked as
+/// it's read, so a game that couldn't have been played is an error.
+pub fn parse(text: &str, variants: &[RuleSet]) -> Result<Replay> {
+    let mut rules = None;
+    let mut players = Vec::new();
+    let mut teams = Vec::new();
+    let mut totals = Vec::new();
+    let mut events = Vec::new();
+    for (idx, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let parsed = if let Some(name) = header(line, "Variant") {
+            variants
+                .iter()
+                .find(|rules| rules.name.eq_ignore_ascii_case(name))
+                .map(|variant| rules = Some(variant.clone()))
+                .ok_or_else(|| anyhow!("there are no rules called {:?}", name))
+        } else if let Some(name) = header(line, "Player") {
+            players.push(name.to_string());
+            Ok(())
+        } else if let Some(list) = header(line, "Teams") {
+            let parsed: Result<Vec<usize>> = list
+                .split_whitespace()
+                .map(|team| team.parse().map_err(|_| anyhow!("{:?} isn't a team", team)))
+                .collect();
+            parsed.map(|parsed| teams = parsed)
+        } else if let Some(list) = header(line, "Totals") {
+            let parsed: Result<Vec<u16>> = list
+                .split_whitespace()
+                .map(|total| {
+                    total
+                        .parse()
+                        .map_err(|_| anyhow!("{:?} isn't a total", total))
+                })
+                .collect();
+            parsed.map(|parsed| totals = parsed)
+        } else {
+            let rules = rules
+                .as_ref()
+                .ok_or_else(|| anyhow!("the variant has to be given before the game"));
+            rules.and_then(|rules| {
+                for token in line.split_whitespace() {
+                    events.extend(event(token, rules)?);
+                }
+                Ok(())
+            })
+        };
+        parsed.with_context(|| format!("line {}", idx + 1))?;
+    }
+    let replay = Replay {
+        rules: rules.ok_or_else(|| anyhow!("the game doesn't say which variant it is"))?,
+        players,
+        teams,
+        events,
+        finished_at: 0,
+        totals,
+    };
+    replay.check()?;
+    Ok(replay)
+}
+
+/// The value of a `Name: value` header line, if the line is that header.
+fn header<'a>(line: &'a str, name: &str) -> Option<&'a str> {
+    let rest = line.strip_prefix(name)?;
+    rest.strip_prefix(':').map(str::trim)
+}
+
+/// The events written as one token.
+fn event(token: &str, rules: &RuleSet) -> Result<Vec<GameEvent>> {
+    // the turn numbers
+    if token.ends_with('.') && token[..token.len() - 1].chars().all(|c| c.is_ascii_digit()) {
+        return Ok(Vec::new());
+    }
+    let (kind, rest) = token.split_at(token.chars().next().map_or(0, char::len_utf8));
+    let events = match kind {
+        "r" if rest.is_empty() => vec![GameEvent::RollStarted],
+        "r" => vec![
+            GameEvent::RollStarted,
+            GameEvent::Rolled(parse_faces(rest, rules)?),
+        ],
+        "h" => {
+            let die: usize = rest
+                .parse()
+                .map_err(|_| anyhow!("{:?} isn't a die", rest))?;
+            if die == 0 {
+                bail!("the dice are numbered from 1");
+            }
+            vec![GameEvent::HoldToggled(die - 1)]
+        }
+        "=" => vec![GameEvent::Scored(parse_slot(rest)?)],
+        "-" => vec![GameEvent::Scratched(parse_slot(rest)?)],
+        _ => bail!("{:?} isn't a roll, a hold or a category", token),
+    };
+    Ok(events)
+}
+
+/// Faces as `13456`, or `1.12.7` when the dice have more than nine sides.
+fn faces(values: &[Score], rules: &RuleSet) -> String {
+    let faces: Vec<_> = values.iter().map(|value| value.0.to_string()).collect();
+    faces.join(if rules.sides > 9 { "." } else { "" })
+}
+
+fn parse_faces(faces: &str, rules: &RuleSet) -> Result<Vec<Score>> {
+    let parse = |face: &str| {
+        face.parse()
+            .map(Score)
+            .map_err(|_| anyhow!("{:?} isn't a face", face))
+    };
+    if rules.sides > 9 {
+        faces.split('.').map(parse).collect()
+    } else {
+        faces.chars().map(|face| parse(&face.to_string())).collect()
+    }
+}
+
+/// A slot as `FullHouse`, with its column as in `FullHouse/2` when there are several.
+fn slot_code(slot: Slot, rules: &RuleSet) -> String {
+    if rules.columns > 1 {
+        format!("{}/{}", category_code(slot.category), slot.column + 1)
+    } else {
+        category_code(slot.category)
+    }
+}
+
+fn parse_slot(code: &str) -> Result<Slot> {
+    let (name, column) = match code.find('/') {
+        Some(idx) => {
+            let column = &code[idx + 1..];
+            let column: usize = column
+                .parse()
+                .map_err(|_| anyhow!("{:?} isn't a column", column))?;
+            if column == 0 {
+                bail!("the columns are numbered from 1");
+            }
+            (&code[..idx], column - 1)
+        }
+        None => (code, 0),
+    };
+    let category = Category::ALL
+        .iter()
+        .find(|category| category_code(**category).eq_ignore_ascii_case(name))
+        .ok_or_else(|| anyhow!("there's no category called {:?}", name))?;
+    Ok(Slot::in_column(column, *category))
+}
+
+/// A category's name without spaces, as in `FullHouse`.
+fn category_code(category: Category) -> String {
+    format!("{:?}", category)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::replay::WrongTotal;
+
+    /// The game in the module docs.
+    const EXAMPLE: &str = "Variant: Yatzy
+Player: Alice
+Player: Bob
+
+1. r13456 h1 r2256 =Chance
+2. r66612 h1 h2 h3 r45 r66 =Sixes
+";
+
+    /// Read a game and check it's written back the same.
+    fn round_trip(text: &str, variants: &[RuleSet]) -> Replay {
+        let replay = parse(text, variants).unwrap();
+        assert_eq!(write(&replay), text);
+        assert_eq!(parse(&write(&replay), variants).unwrap(), replay);
+        replay
+    }
+
+    fn totals(replay: &Replay) -> Vec<u16> {
+        let game = replay.game_at(replay.events.len());
+        game.sheets().iter().map(|sheet| sheet.total()).collect()
+    }
+
+    #[test]
+    fn the_example_is_a_game() {
+        let replay = round_trip(EXAMPLE, &RuleSet::presets());
+        assert_eq!(replay.players, vec!["Alice", "Bob"]);
+        assert_eq!(totals(&replay), vec![16, 30]);
+    }
+
+    #[test]
+    fn columns_teams_and_scratches_read_back() {
+        let text = "Variant: Triple Yahtzee
+Player: Alice
+Player: Bob
+Teams: 0 0
+
+1. r11111 =Yatzy/3
+2. r12345 -Ones/2
+";
+        let replay = round_trip(text, &RuleSet::presets());
+        assert_eq!(replay.teams, vec![0, 0]);
+        assert_eq!(totals(&replay), vec![150, 0]);
+    }
+
+    #[test]
+    fn faces_past_nine_are_separated() {
+        let rules = RuleSet {
+            name: "Twelves".into(),
+            sides: 12,
+            ..RuleSet::yatzy()
+        };
+        let text = "Variant: Twelves
+Player: Alice
+
+1. r1.12.7.10.3 =Chance
+";
+        let replay = round_trip(text, &[rules]);
+        assert_eq!(totals(&replay), vec![33]);
+    }
+
+    #[test]
+    fn comments_and_turn_numbers_are_skipped() {
+        let text = "# a game from the docs
+Variant: yatzy
+Player: Alice # goes first
+Player: Bob
+
+r13456 h1 r2256 =chance
+2. r66612 h1 h2 h3 r45 r66 =Sixes
+";
+        let replay = parse(text, &RuleSet::presets()).unwrap();
+        assert_eq!(write(&replay), EXAMPLE);
+    }
+
+    #[test]
+    fn totals_are_checked_against_the_game() {
+        let text = EXAMPLE.replace("Player: Bob\n", "Player: Bob\nTotals: 16 30\n");
+        let replay = round_trip(&text, &RuleSet::presets());
+        assert_eq!(replay.wrong_totals(), vec![]);
+
+        let changed = text.replace("Totals: 16 30", "Totals: 16 99");
+        let replay = parse(&changed, &RuleSet::presets()).unwrap();
+        let wrong = WrongTotal {
+            player: 1,
+            claimed: 99,
+            actual: 30,
+        };
+        assert_eq!(replay.wrong_totals(), vec![wrong]);
+
+        let missing = text.replace("Totals: 16 30", "Totals: 16");
+        assert!(parse(&missing, &RuleSet::presets()).is_err());
+    }
+
+    #[test]
+    fn games_that_couldnt_be_played_are_errors() {
+        let presets = RuleSet::presets();
+        for text in &[
+            "Player: Alice\n\n1. r12345 =Chance\n",
+            "Variant: Yatzy\n\n1. r12345 =Chance\n",
+            "Variant: Yatzy\nPlayer: Alice\n\n1. r12345 =Villa\n",
+            "Variant: Yatzy\nPlayer: Alice\n\n1. h1\n",
+            "Variant: Yatzy\nPlayer: Alice\n\n1. r12345 r12345 r12345 r12345\n",
+            "Variant: Yatzy\nPlayer: Alice\n\n1. r12345 =Chance/2\n",
+        ] {
+            assert!(parse(text, &presets).is_err(), "{}", text);
+        }
+    }
+}
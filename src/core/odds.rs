@@ -0,0 +1,38 @@
+//! The chances of collecting more dice showing a face, by holding every die that lands on it.
+
+/// The chance of ending the turn with each number of dice showing a face, when `held` of them
+/// show it now and the rest are rolled again up to `rolls` times, keeping each one that lands on
+/// it. The chance of ending with `n` of them is at position `n`, for `n` up to `dice`.
+pub fn matching(held: usize, dice: usize, rolls: u8, sides: u8) -> Vec<f64> {
+    let mut chances = vec![0.0; dice + 1];
+    chances[held.min(dice)] = 1.0;
+    let hit = 1.0 / f64::from(sides.max(1));
+    for _ in 0..rolls {
+        let mut next = vec![0.0; dice + 1];
+        for (have, &chance) in chances.iter().enumerate() {
+            let rolled = dice - have;
+            for hits in 0..=rolled {
+                let ways = binomial(rolled, hits);
+                let odds = hit.powi(hits as i32) * (1.0 - hit).powi((rolled - hits) as i32);
+                next[have + hits] += chance * ways * odds;
+            }
+        }
+        chances = next;
+    }
+    chances
+}
+
+/// The chance of ending the turn with at least each number of dice showing a face, worked out
+/// the same way as `matching`.
+pub fn at_least(held: usize, dice: usize, rolls: u8, sides: u8) -> Vec<f64> {
+    let mut chances = matching(held, dice, rolls, sides);
+    for n in (0..dice).rev() {
+        chances[n] += chances[n + 1];
+    }
+    chances
+}
+
+/// The number of ways to choose `k` things out of `n`.
+fn binomial(n: usize, k: usize) -> f64 {
+    (0..k).fold(1.0, |ways, i| ways * (n - i) as f64 / (i + 1) as f64)
+}
@@ -0,0 +1,218 @@
+//! Games played on paper, read from CSV so they count in the history and statistics too.
+//!
+//! The CSV has a row of headings, then a row for each player in each game:
+//!
+//! ```text
+//! game,date,variant,player,total
+//! 1,2019-08-03,Yatzy,Alice,254
+//! 1,2019-08-03,Yatzy,Bob,198
+//! 2,2019-08-10,Yahtzee,Alice,231
+//! ```
+//!
+//! The rows with the same `game` are one game, with its players in the order of the rows, and
+//! they all give the same `date` and `variant`. The date is the day it was played, like
+//! `2019-08-03`. The headings can be in any order and any case, and other columns are left out,
+//! so a spreadsheet kept for scores can be exported as it is. Fields can be quoted, for names with
+//! commas in.
+
+use std::{fs, path::Path};
+
+use anyhow::{anyhow, bail, Context, Result};
+
+use super::date;
+
+/// The headings of the columns that have to be there.
+const HEADINGS: [&str; 5] = ["game", "date", "variant", "player", "total"];
+
+/// A game played on paper.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaperGame {
+    /// The name of the variant.
+    pub variant: String,
+    /// The day it was played, in seconds since the Unix epoch.
+    pub played_on: u64,
+    /// The players' names and totals, in the order they were written down.
+    pub players: Vec<(String, u16)>,
+}
+
+impl PaperGame {
+    /// Whether the player in `seat` won, with the highest total or a share of it.
+    pub fn won(&self, seat: usize) -> bool {
+        let best = self.players.iter().map(|(_, total)| *total).max();
+        Some(self.players[seat].1) == best
+    }
+}
+
+/// Read the games in a CSV file laid out as described above.
+pub fn load(path: &Path) -> Result<Vec<PaperGame>> {
+    let csv = fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    parse(&csv).with_context(|| format!("reading {}", path.display()))
+}
+
+/// Read the games in CSV laid out as described above, in the order they first appear.
+pub fn parse(csv: &str) -> Result<Vec<PaperGame>> {
+    let mut records = records(csv)?.into_iter();
+    let headings = match records.next() {
+        Some((_, headings)) => headings,
+        None => bail!("there's nothing in it"),
+    };
+    let mut columns = [0; 5];
+    for (column, heading) in columns.iter_mut().zip(&HEADINGS) {
+        *column = headings
+            .iter()
+            .position(|name| name.trim().eq_ignore_ascii_case(heading))
+            .ok_or_else(|| anyhow!("there's no {:?} column", heading))?;
+    }
+    let [id_column, date_column, variant_column, player_column, total_column] = columns;
+
+    // each game with the id it was given
+    let mut games: Vec<(String, PaperGame)> = Vec::new();
+    for (line, record) in records {
+        if record.iter().all(|field| field.trim().is_empty()) {
+            continue;
+        }
+        let field = |column: usize| record.get(column).map_or("", |field| field.trim());
+        let (id, day, variant, name) = (
+            field(id_column),
+            field(date_column),
+            field(variant_column),
+            field(player_column),
+        );
+        if id.is_empty() || variant.is_empty() || name.is_empty() {
+            bail!("line {}: the game, variant and player can't be blank", line);
+        }
+        let played_on = date::parse_day(day)
+            .ok_or_else(|| anyhow!("line {}: {:?} should be a day like 2019-08-03", line, day))?;
+        let total = field(total_column);
+        let total = total
+            .parse()
+            .map_err(|_| anyhow!("line {}: {:?} isn't a total", line, total))?;
+        match games.iter_mut().find(|(game_id, _)| game_id == id) {
+            Some((_, game)) => {
+                if game.variant != variant || game.played_on != played_on {
+                    bail!(
+                        "line {}: game {} was {} on {}, as written earlier",
+                        line,
+                        id,
+                        game.variant,
+                        date::format_day(game.played_on)
+                    );
+                }
+                game.players.push((name.to_string(), total));
+            }
+            None => games.push((
+                id.to_string(),
+                PaperGame {
+                    variant: variant.to_string(),
+                    played_on,
+                    players: vec![(name.to_string(), total)],
+                },
+            )),
+        }
+    }
+    Ok(games.into_iter().map(|(_, game)| game).collect())
+}
+
+/// The records of some CSV, each with the line it starts on. Quoted fields can have commas, line
+/// breaks and doubled quotes in them.
+fn records(csv: &str) -> Result<Vec<(usize, Vec<String>)>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let (mut line, mut start) = (1, 1);
+    let mut quoted = false;
+    let mut chars = csv.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted => {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    field.push('"');
+                } else {
+                    quoted = false;
+                }
+            }
+            '"' if field.is_empty() => quoted = true,
+            ',' if !quoted => record.push(std::mem::take(&mut field)),
+            '\r' if !quoted => (),
+            '\n' if !quoted => {
+                record.push(std::mem::take(&mut field));
+                records.push((start, std::mem::take(&mut record)));
+                line += 1;
+                start = line;
+            }
+            c => {
+                if c == '\n' {
+                    line += 1;
+                }
+                field.push(c);
+            }
+        }
+    }
+    if quoted {
+        bail!("line {}: a quote isn't closed", start);
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push((start, record));
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_of_a_game_are_put_together() {
+        let csv = "game,date,variant,player,total\r\n\
+                   1,2019-08-03,Yatzy,Alice,254\r\n\
+                   2,2019-08-10,Yahtzee,Alice,231\r\n\
+                   1,2019-08-03,Yatzy,Bob,198\r\n";
+        let games = parse(csv).unwrap();
+        assert_eq!(games.len(), 2);
+        assert_eq!(games[0].variant, "Yatzy");
+        assert_eq!(games[0].played_on, date::parse_day("2019-08-03").unwrap());
+        assert_eq!(
+            games[0].players,
+            vec![("Alice".to_string(), 254), ("Bob".to_string(), 198)]
+        );
+        assert!(games[0].won(0) && !games[0].won(1));
+        assert_eq!(games[1].players, vec![("Alice".to_string(), 231)]);
+    }
+
+    #[test]
+    fn columns_are_found_by_their_headings() {
+        let csv = "Player,Notes,Total,Variant,Date,Game\n\
+                   \"Smith, Jo\",\"said \"\"again!\"\"\",120,Yatzy,2020-01-01,a\n\
+                   \n\
+                   Sam,,120,Yatzy,2020-01-01,a";
+        let games = parse(csv).unwrap();
+        assert_eq!(
+            games[0].players,
+            vec![("Smith, Jo".to_string(), 120), ("Sam".to_string(), 120)]
+        );
+        // a tie is shared
+        assert!(games[0].won(0) && games[0].won(1));
+    }
+
+    #[test]
+    fn mistakes_say_where_they_are() {
+        let error = |csv: &str| format!("{:#}", parse(csv).unwrap_err());
+        assert!(error("game,date,variant,player\n").contains("\"total\""));
+        let header = "game,date,variant,player,total\n";
+        let bad_total = format!("{}1,2019-08-03,Yatzy,Alice,lots\n", header);
+        assert!(error(&bad_total).starts_with("line 2"));
+        let bad_day = format!(
+            "{}1,2019-08-03,Yatzy,Alice,1\n1,03/08/2019,Yatzy,Bob,2\n",
+            header
+        );
+        assert!(error(&bad_day).starts_with("line 3"));
+        let moved = format!(
+            "{}1,2019-08-03,Yatzy,Alice,1\n1,2019-08-04,Yatzy,Bob,2\n",
+            header
+        );
+        assert!(error(&moved).starts_with("line 3"));
+        assert!(error("\"game,date").contains("quote"));
+    }
+}
@@ -0,0 +1,137 @@
+//! Poker dice, where the faces are playing card ranks and the dice are scored as poker hands.
+//!
+//! The dice still roll 1 to 6, which stand for 9, 10, J, Q, K and A.
+
+use super::score::Score;
+use super::scorecard::Category;
+
+/// The card rank shown on a poker die.
+pub fn face_name(score: Score) -> &'static str {
+    match score.0 {
+        1 => "9",
+        2 => "10",
+        3 => "J",
+        4 => "Q",
+        5 => "K",
+        6 => "A",
+        _ => panic!("die score of {} is not a poker die face", score.0),
+    }
+}
+
+/// A poker hand, from worst to best.
+///
+/// There are no suits on poker dice, so no flushes, and an ace is always high.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PokerHand {
+    Bust,
+    OnePair,
+    TwoPairs,
+    ThreeOfAKind,
+    /// 9 to K.
+    LowStraight,
+    /// 10 to A.
+    HighStraight,
+    FullHouse,
+    FourOfAKind,
+    FiveOfAKind,
+}
+
+impl PokerHand {
+    /// The best hand made by the given dice.
+    pub fn of(dice: &[Score]) -> Self {
+        let mut counts = [0u8; 7];
+        for die in dice {
+            if let Some(count) = counts.get_mut(die.0 as usize) {
+                *count += 1;
+            }
+        }
+        let mut groups: Vec<u8> = counts.iter().copied().filter(|c| *c > 1).collect();
+        groups.sort_unstable_by(|a, b| b.cmp(a));
+        match groups.as_slice() {
+            [5, ..] => PokerHand::FiveOfAKind,
+            [4, ..] => PokerHand::FourOfAKind,
+            [3, 2, ..] => PokerHand::FullHouse,
+            [3, ..] => PokerHand::ThreeOfAKind,
+            [2, 2, ..] => PokerHand::TwoPairs,
+            [2, ..] => PokerHand::OnePair,
+            _ if counts[1..6].iter().all(|c| *c == 1) => PokerHand::LowStraight,
+            _ if counts[2..7].iter().all(|c| *c == 1) => PokerHand::HighStraight,
+            _ => PokerHand::Bust,
+        }
+    }
+
+    /// The hand scored by a category on the poker dice scorecard.
+    pub fn for_category(category: Category) -> Option<Self> {
+        match category {
+            Category::OnePair => Some(PokerHand::OnePair),
+            Category::TwoPairs => Some(PokerHand::TwoPairs),
+            Category::ThreeOfAKind => Some(PokerHand::ThreeOfAKind),
+            Category::SmallStraight => Some(PokerHand::LowStraight),
+            Category::LargeStraight => Some(PokerHand::HighStraight),
+            Category::FullHouse => Some(PokerHand::FullHouse),
+            Category::FourOfAKind => Some(PokerHand::FourOfAKind),
+            Category::FiveOfAKind => Some(PokerHand::FiveOfAKind),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PokerHand::Bust => "Bust",
+            PokerHand::OnePair => "One pair",
+            PokerHand::TwoPairs => "Two pairs",
+            PokerHand::ThreeOfAKind => "Three of a kind",
+            PokerHand::LowStraight => "Low straight",
+            PokerHand::HighStraight => "High straight",
+            PokerHand::FullHouse => "Full house",
+            PokerHand::FourOfAKind => "Four of a kind",
+            PokerHand::FiveOfAKind => "Five of a kind",
+        }
+    }
+
+    /// The points for scoring this hand.
+    pub fn points(self) -> u16 {
+        match self {
+            PokerHand::Bust => 0,
+            PokerHand::OnePair => 5,
+            PokerHand::TwoPairs => 10,
+            PokerHand::ThreeOfAKind => 15,
+            PokerHand::LowStraight => 20,
+            PokerHand::HighStraight => 25,
+            PokerHand::FullHouse => 30,
+            PokerHand::FourOfAKind => 40,
+            PokerHand::FiveOfAKind => 50,
+        }
+    }
+
+    /// Whether holding this hand means also holding `other`, for example a full house contains
+    /// three of a kind and a pair.
+    pub fn contains(self, other: PokerHand) -> bool {
+        use PokerHand::*;
+        match (self, other) {
+            _ if self == other => true,
+            (Bust, _) | (_, Bust) => false,
+            (LowStraight, _) | (HighStraight, _) | (_, LowStraight) | (_, HighStraight) => false,
+            (_, OnePair) => true,
+            (FullHouse, TwoPairs) | (FullHouse, ThreeOfAKind) => true,
+            (FourOfAKind, ThreeOfAKind) => true,
+            (FiveOfAKind, ThreeOfAKind) | (FiveOfAKind, FourOfAKind) => true,
+            _ => false,
+        }
+    }
+}
+
+/// How many points the given dice score in a category on the poker dice scorecard.
+///
+/// Each category scores a fixed value when the dice contain its hand, and chance scores the best
+/// hand made.
+pub fn score(category: Category, dice: &[Score]) -> u16 {
+    let hand = PokerHand::of(dice);
+    match category {
+        Category::Chance => hand.points(),
+        _ => match PokerHand::for_category(category) {
+            Some(wanted) if hand.contains(wanted) => wanted.points(),
+            _ => 0,
+        },
+    }
+}
@@ -0,0 +1,71 @@
+//! House rules: rule sets made by players, saved to disk as named presets.
+
+use std::{
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::rules::RuleSet;
+use super::schema::Schema;
+
+/// Where house rules are saved, in the user's config directory.
+pub fn default_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("yatzy").join("presets.json"))
+}
+
+const SCHEMA: Schema = Schema {
+    name: "house rules",
+    migrations: &[in_table],
+};
+
+/// Version 1 put the list of presets in a table, to have somewhere to keep the version.
+fn in_table(file: &mut Value) -> Result<()> {
+    *file = json!({ "presets": file.take() });
+    Ok(())
+}
+
+/// The presets as they're saved.
+#[derive(Serialize, Deserialize)]
+struct PresetsFile {
+    presets: Vec<RuleSet>,
+}
+
+/// Read the presets saved at `path`, or none if nothing has been saved yet.
+pub fn load(path: &Path) -> Result<Vec<RuleSet>> {
+    let json = match fs::read_to_string(path) {
+        Ok(json) => json,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("reading {}", path.display())),
+    };
+    SCHEMA
+        .parse(&json)
+        .map(|file: PresetsFile| file.presets)
+        .with_context(|| format!("parsing {}", path.display()))
+}
+
+/// Write presets to `path`, replacing whatever was saved there.
+pub fn save(path: &Path, presets: &[RuleSet]) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+    }
+    let file = PresetsFile {
+        presets: presets.to_vec(),
+    };
+    let json = serde_json::to_string_pretty(&SCHEMA.write(&file)?)?;
+    fs::write(path, json).with_context(|| format!("writing {}", path.display()))
+}
+
+/// Add a preset to those saved at `path`, replacing any saved preset with the same name.
+pub fn save_one(path: &Path, rules: &RuleSet) -> Result<()> {
+    let mut presets = load(path)?;
+    match presets.iter_mut().find(|saved| saved.name == rules.name) {
+        Some(saved) => *saved = rules.clone(),
+        None => presets.push(rules.clone()),
+    }
+    save(path, &presets)
+}
@@ -0,0 +1,144 @@
+//! Player profiles: each name that has played keeps how they've done across games, saved to
+//! disk so the starting screen can offer the names again.
+
+use std::{
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::schema::Schema;
+
+/// Where profiles are saved, in the user's config directory.
+pub fn default_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("yatzy").join("profiles.json"))
+}
+
+const SCHEMA: Schema = Schema {
+    name: "profiles",
+    migrations: &[in_table],
+};
+
+/// Version 1 put the list of profiles in a table, to have somewhere to keep the version.
+fn in_table(file: &mut Value) -> Result<()> {
+    *file = json!({ "profiles": file.take() });
+    Ok(())
+}
+
+/// The profiles as they're saved.
+#[derive(Serialize, Deserialize)]
+struct ProfilesFile {
+    profiles: Vec<Profile>,
+}
+
+/// How someone has done in the games they've finished.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    pub games: u32,
+    pub wins: u32,
+    /// Their totals from every game, added up.
+    pub points: u64,
+    /// Their highest total in a game.
+    pub best: u16,
+}
+
+impl Profile {
+    /// A profile for someone who hasn't finished a game yet.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            games: 0,
+            wins: 0,
+            points: 0,
+            best: 0,
+        }
+    }
+
+    /// Their average total, or 0 before they've finished a game.
+    pub fn average(&self) -> f64 {
+        if self.games == 0 {
+            0.0
+        } else {
+            self.points as f64 / f64::from(self.games)
+        }
+    }
+
+    /// Count a finished game in which they scored `total`, and whether they won it.
+    pub fn record(&mut self, total: u16, won: bool) {
+        self.games += 1;
+        if won {
+            self.wins += 1;
+        }
+        self.points += u64::from(total);
+        self.best = self.best.max(total);
+    }
+}
+
+/// Read the profiles saved at `path`, or none if nothing has been saved yet.
+pub fn load(path: &Path) -> Result<Vec<Profile>> {
+    let json = match fs::read_to_string(path) {
+        Ok(json) => json,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("reading {}", path.display())),
+    };
+    parse(&json).with_context(|| format!("parsing {}", path.display()))
+}
+
+/// Read profiles saved as JSON.
+pub fn parse(json: &str) -> Result<Vec<Profile>> {
+    SCHEMA.parse(json).map(|file: ProfilesFile| file.profiles)
+}
+
+/// Profiles as JSON, as they're saved.
+pub fn to_json(profiles: &[Profile]) -> Result<String> {
+    let file = ProfilesFile {
+        profiles: profiles.to_vec(),
+    };
+    Ok(serde_json::to_string_pretty(&SCHEMA.write(&file)?)?)
+}
+
+/// Write profiles to `path`, replacing whatever was saved there.
+pub fn save(path: &Path, profiles: &[Profile]) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+    }
+    let json = to_json(profiles)?;
+    fs::write(path, json).with_context(|| format!("writing {}", path.display()))
+}
+
+/// Two copies of the profiles put together. Where both have someone, the copy of their profile
+/// with more games counted in it is kept, since it has been changed more recently. The order of
+/// `ours` is kept, with anyone only in `theirs` after.
+pub fn merge(ours: &[Profile], theirs: &[Profile]) -> Vec<Profile> {
+    let mut merged = ours.to_vec();
+    for profile in theirs {
+        match merged.iter_mut().find(|ours| ours.name == profile.name) {
+            Some(ours) if ours.games < profile.games => *ours = profile.clone(),
+            Some(_) => (),
+            None => merged.push(profile.clone()),
+        }
+    }
+    merged
+}
+
+/// Count a finished game in the profiles saved at `path`, given each player's name, total and
+/// whether they won. Anyone without a profile gets one.
+pub fn record_game(path: &Path, results: &[(String, u16, bool)]) -> Result<()> {
+    let mut profiles = load(path)?;
+    for (name, total, won) in results {
+        let idx = match profiles.iter().position(|profile| profile.name == *name) {
+            Some(idx) => idx,
+            None => {
+                profiles.push(Profile::new(name));
+                profiles.len() - 1
+            }
+        };
+        profiles[idx].record(*total, *won);
+    }
+    save(path, &profiles)
+}
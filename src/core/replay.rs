@@ -0,0 +1,180 @@
+//! Replays: every roll and decision of a finished game, saved so it can be watched again.
+//!
+//! A replay is the game's event log with the players and rules it was played with. The rolls
+//! are in the events, so the game can be rebuilt exactly without the seed. One is saved
+//! automatically at the end of every game played on this computer.
+//!
+//! The players' totals are saved with it too. Anyone can write a replay, so they are checked
+//! against what the events add up to before it's watched, and a replay that gives the wrong ones
+//! has been changed since the game was played.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use super::correspondence;
+use super::event::GameEvent;
+use super::game::Game;
+use super::rules::RuleSet;
+use super::schema::{self, Schema};
+use super::score::GameRng;
+
+/// The extension of replay files.
+pub const EXTENSION: &str = "yatzyreplay";
+
+/// Replays are also kept in the history, so it reads them the same way.
+pub(super) const SCHEMA: Schema = Schema {
+    name: "replay",
+    migrations: &[schema::numbered],
+};
+
+/// Where replays are saved at the end of each game, in the user's documents directory.
+pub fn default_dir() -> Option<PathBuf> {
+    dirs::document_dir().map(|dir| dir.join("Yatzy").join("Replays"))
+}
+
+/// A finished game, to be watched again.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Replay {
+    pub rules: RuleSet,
+    /// The players' names, in turn order.
+    pub players: Vec<String>,
+    /// The team of each player, in turn order, or nothing if they didn't play in teams.
+    #[serde(default)]
+    pub teams: Vec<usize>,
+    /// Everything that happened in the game.
+    pub events: Vec<GameEvent>,
+    /// When the game finished, in seconds since the Unix epoch.
+    pub finished_at: u64,
+    /// Each player's total at the end, in turn order. Replays saved before the totals were kept
+    /// have none.
+    #[serde(default)]
+    pub totals: Vec<u16>,
+}
+
+/// A player whose total in a replay isn't what its events add up to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WrongTotal {
+    pub player: usize,
+    /// The total the replay gives.
+    pub claimed: u16,
+    /// The total the events add up to.
+    pub actual: u16,
+}
+
+impl Replay {
+    /// Record a game between the named players, as it is now.
+    pub fn new(game: &Game, players: Vec<String>) -> Self {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH);
+        Self {
+            rules: game.rules().clone(),
+            players,
+            teams: if game.has_teams() {
+                (0..game.players())
+                    .map(|player| game.team(player))
+                    .collect()
+            } else {
+                Vec::new()
+            },
+            events: game.events().to_vec(),
+            finished_at: now.map_or(0, |now| now.as_secs()),
+            totals: game.sheets().iter().map(|sheet| sheet.total()).collect(),
+        }
+    }
+
+    /// Read the replay at `path`. The events are checked, since the file could have come from
+    /// anywhere.
+    pub fn load(path: &Path) -> Result<Self> {
+        let json =
+            fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        let replay: Self = SCHEMA
+            .parse(&json)
+            .with_context(|| format!("parsing {}", path.display()))?;
+        replay.check()?;
+        Ok(replay)
+    }
+
+    /// Check the replay is of a game that could have been played, event by event.
+    pub fn check(&self) -> Result<()> {
+        if self.players.is_empty() {
+            bail!("the replay has no players");
+        }
+        if !self.teams.is_empty() && self.teams.len() != self.players.len() {
+            bail!(
+                "the replay has {} players but {} teams",
+                self.players.len(),
+                self.teams.len()
+            );
+        }
+        if !self.totals.is_empty() && self.totals.len() != self.players.len() {
+            bail!(
+                "the replay has {} players but {} totals",
+                self.players.len(),
+                self.totals.len()
+            );
+        }
+        let mut game = self.game_at(0);
+        for (idx, event) in self.events.iter().enumerate() {
+            correspondence::check(&game, event)
+                .with_context(|| format!("event {} of the replay", idx + 1))?;
+            game.apply(event.clone());
+        }
+        Ok(())
+    }
+
+    /// The players whose totals in the replay aren't what playing its events through gives
+    /// them. The events have to have been checked first.
+    pub fn wrong_totals(&self) -> Vec<WrongTotal> {
+        let game = self.game_at(self.events.len());
+        self.totals
+            .iter()
+            .zip(game.sheets())
+            .enumerate()
+            .filter(|(_, (&claimed, sheet))| claimed != sheet.total())
+            .map(|(player, (&claimed, sheet))| WrongTotal {
+                player,
+                claimed,
+                actual: sheet.total(),
+            })
+            .collect()
+    }
+
+    /// Write the replay to `path`, replacing whatever was there.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+        }
+        let json = serde_json::to_string(&SCHEMA.write(self)?)?;
+        fs::write(path, json).with_context(|| format!("writing {}", path.display()))
+    }
+
+    /// A name to save the replay under, from the players and when the game finished, for
+    /// example `Alice-Bob-1592157900.yatzyreplay`.
+    pub fn file_name(&self) -> String {
+        let names: Vec<String> = self
+            .players
+            .iter()
+            .map(|name| name.replace(|c: char| !c.is_alphanumeric(), "_"))
+            .collect();
+        format!("{}-{}.{}", names.join("-"), self.finished_at, EXTENSION)
+    }
+
+    /// The game as it was after its first `events` events.
+    pub fn game_at(&self, events: usize) -> Game {
+        // the rolls are all in the events, so the generator is never used
+        let rng = GameRng::from_seed(0);
+        let mut game = Game::new(self.rules.clone(), rng, self.players.len());
+        if !self.teams.is_empty() {
+            game.set_teams(self.teams.clone());
+        }
+        for event in &self.events[..events.min(self.events.len())] {
+            game.apply(event.clone());
+        }
+        game
+    }
+}
@@ -0,0 +1,38 @@
+//! Every roll of a game, picked out of its event log, to look back on what was rolled.
+
+use super::event::GameEvent;
+use super::game::Game;
+use super::score::{GameRng, Score};
+
+/// The dice as they landed after a roll.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RollRecord {
+    /// Whose roll it was.
+    pub player: usize,
+    /// Which roll of the turn it was, from 1.
+    pub roll: u8,
+    /// What every die showed after the roll, in the order they're laid out.
+    pub dice: Vec<Score>,
+    /// Which dice were held, and so kept from the roll before rather than rolled.
+    pub held: Vec<bool>,
+}
+
+/// Replay a game's events to list its rolls, in the order they were made.
+pub fn rolls(game: &Game) -> Vec<RollRecord> {
+    let rng = GameRng::from_seed(game.seed());
+    let mut replay = Game::new(game.rules().clone(), rng, game.players());
+    let mut rolls = Vec::new();
+    for event in game.events() {
+        replay.apply(event.clone());
+        if let GameEvent::Rolled(_) = event {
+            let dice = replay.dice();
+            rolls.push(RollRecord {
+                player: replay.player(),
+                roll: replay.rolls(),
+                dice: dice.iter().filter_map(|die| die.value).collect(),
+                held: dice.iter().map(|die| die.held).collect(),
+            });
+        }
+    }
+    rolls
+}
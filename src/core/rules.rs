@@ -0,0 +1,357 @@
+//! The rule sets for the different variants of the game.
+//!
+//! Everything that differs between variants - which categories are on the scorecard, how they
+//! are scored, and the bonuses - is decided by the active `RuleSet`.
+
+use serde::{Deserialize, Serialize};
+
+use super::poker;
+use super::score::Score;
+use super::scorecard::{self, Category};
+
+/// How the pattern categories (kinds, straights, full house) are scored.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Scoring {
+    /// Scandinavian Yatzy: kinds count only the matching dice, straights are fixed runs
+    /// (1-5 and 2-6), and a full house is the sum of the dice.
+    Scandinavian,
+    /// American Yahtzee: kinds count all dice, a small straight is any run of four, and
+    /// straights and full house score fixed values.
+    American,
+    /// Poker dice: each category is a poker hand scoring fixed points, see the `poker` module.
+    Poker,
+}
+
+/// What is drawn on the faces of the dice.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Faces {
+    /// Pips, or numbers for dice with more than six sides.
+    Pips,
+    /// Playing card ranks, from 9 to A.
+    Poker,
+}
+
+/// How Yatzys rolled after the Yatzy category has been filled are treated.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum YatzyBonus {
+    /// Scandinavian rules: further Yatzys are scored like any other hand.
+    Scandinavian,
+    /// American rules: each further Yatzy earns a bonus if the Yatzy category scored 50, and
+    /// the joker rule decides where it can be placed.
+    American,
+}
+
+/// The rules of a variant of the game.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RuleSet {
+    /// The name of the variant, shown on the start screen.
+    pub name: String,
+    /// The number of dice rolled.
+    pub dice: usize,
+    /// The number of faces on each die, numbered from 1.
+    pub sides: u8,
+    /// What is drawn on the faces of the dice.
+    pub faces: Faces,
+    /// The number of rolls a player gets each turn.
+    pub rolls: u8,
+    /// The categories on the scorecard, in order.
+    pub categories: Vec<Category>,
+    /// The number of scorecard columns each player fills.
+    pub columns: usize,
+    /// How the pattern categories are scored.
+    pub scoring: Scoring,
+    /// The upper-section subtotal needed to earn the bonus.
+    pub upper_bonus_threshold: u16,
+    /// Points awarded for reaching the upper-section bonus threshold.
+    pub upper_bonus: u16,
+    /// How extra Yatzys are treated.
+    pub yatzy_bonus: YatzyBonus,
+    /// Points awarded for each extra Yatzy, when `yatzy_bonus` is `American`.
+    pub extra_yatzy_bonus: u16,
+    /// Points for a Yatzy (all dice showing the same face).
+    pub yatzy_score: u16,
+    /// Whether rolls left unused at the end of a turn are saved for later turns.
+    pub saved_rolls: bool,
+    /// Whether categories must be filled in scorecard order (Forced Yatzy), rather than freely
+    /// chosen.
+    pub forced_order: bool,
+}
+
+impl RuleSet {
+    /// Scandinavian Yatzy, with pairs and fixed straights.
+    pub fn yatzy() -> Self {
+        Self {
+            name: "Yatzy".into(),
+            dice: 5,
+            sides: 6,
+            faces: Faces::Pips,
+            rolls: 3,
+            categories: vec![
+                Category::Ones,
+                Category::Twos,
+                Category::Threes,
+                Category::Fours,
+                Category::Fives,
+                Category::Sixes,
+                Category::OnePair,
+                Category::TwoPairs,
+                Category::ThreeOfAKind,
+                Category::FourOfAKind,
+                Category::SmallStraight,
+                Category::LargeStraight,
+                Category::FullHouse,
+                Category::Chance,
+                Category::Yatzy,
+            ],
+            columns: 1,
+            scoring: Scoring::Scandinavian,
+            upper_bonus_threshold: 63,
+            upper_bonus: 50,
+            yatzy_bonus: YatzyBonus::Scandinavian,
+            extra_yatzy_bonus: 0,
+            yatzy_score: 50,
+            saved_rolls: false,
+            forced_order: false,
+        }
+    }
+
+    /// American Yahtzee, with no pairs, run-based straights and the joker rule.
+    pub fn yahtzee() -> Self {
+        Self {
+            name: "Yahtzee".into(),
+            dice: 5,
+            sides: 6,
+            faces: Faces::Pips,
+            rolls: 3,
+            categories: vec![
+                Category::Ones,
+                Category::Twos,
+                Category::Threes,
+                Category::Fours,
+                Category::Fives,
+                Category::Sixes,
+                Category::ThreeOfAKind,
+                Category::FourOfAKind,
+                Category::FullHouse,
+                Category::SmallStraight,
+                Category::LargeStraight,
+                Category::Yatzy,
+                Category::Chance,
+            ],
+            columns: 1,
+            scoring: Scoring::American,
+            upper_bonus_threshold: 63,
+            upper_bonus: 35,
+            yatzy_bonus: YatzyBonus::American,
+            extra_yatzy_bonus: 100,
+            yatzy_score: 50,
+            saved_rolls: false,
+            forced_order: false,
+        }
+    }
+
+    /// Triple Yahtzee, where each player fills three Yahtzee columns worth one, two and three
+    /// times their points.
+    pub fn triple_yahtzee() -> Self {
+        Self {
+            name: "Triple Yahtzee".into(),
+            columns: 3,
+            ..Self::yahtzee()
+        }
+    }
+
+    /// German Kniffel, scored like Yahtzee but without the bonus for extra Kniffels.
+    ///
+    /// An extra Kniffel can still be played as a joker, following the same placement rules.
+    pub fn kniffel() -> Self {
+        Self {
+            name: "Kniffel".into(),
+            extra_yatzy_bonus: 0,
+            ..Self::yahtzee()
+        }
+    }
+
+    /// Maxi Yatzy, the Scandinavian variant with six dice, extra categories and saved rolls.
+    pub fn maxi_yatzy() -> Self {
+        Self {
+            name: "Maxi Yatzy".into(),
+            dice: 6,
+            sides: 6,
+            faces: Faces::Pips,
+            rolls: 3,
+            categories: vec![
+                Category::Ones,
+                Category::Twos,
+                Category::Threes,
+                Category::Fours,
+                Category::Fives,
+                Category::Sixes,
+                Category::OnePair,
+                Category::TwoPairs,
+                Category::ThreePairs,
+                Category::ThreeOfAKind,
+                Category::FourOfAKind,
+                Category::FiveOfAKind,
+                Category::SmallStraight,
+                Category::LargeStraight,
+                Category::FullStraight,
+                Category::FullHouse,
+                Category::Villa,
+                Category::Tower,
+                Category::Chance,
+                Category::Yatzy,
+            ],
+            columns: 1,
+            scoring: Scoring::Scandinavian,
+            upper_bonus_threshold: 84,
+            upper_bonus: 100,
+            yatzy_bonus: YatzyBonus::Scandinavian,
+            extra_yatzy_bonus: 0,
+            yatzy_score: 100,
+            saved_rolls: true,
+            forced_order: false,
+        }
+    }
+
+    /// Poker dice, where the dice show card ranks and each category is a poker hand.
+    pub fn poker_dice() -> Self {
+        Self {
+            name: "Poker dice".into(),
+            dice: 5,
+            sides: 6,
+            faces: Faces::Poker,
+            rolls: 3,
+            categories: vec![
+                Category::OnePair,
+                Category::TwoPairs,
+                Category::ThreeOfAKind,
+                Category::SmallStraight,
+                Category::LargeStraight,
+                Category::FullHouse,
+                Category::FourOfAKind,
+                Category::FiveOfAKind,
+                Category::Chance,
+            ],
+            columns: 1,
+            scoring: Scoring::Poker,
+            upper_bonus_threshold: 0,
+            upper_bonus: 0,
+            yatzy_bonus: YatzyBonus::Scandinavian,
+            extra_yatzy_bonus: 0,
+            yatzy_score: 50,
+            saved_rolls: false,
+            forced_order: false,
+        }
+    }
+
+    /// The built-in rule sets, for choosing between on the start screen.
+    pub fn presets() -> Vec<RuleSet> {
+        vec![
+            RuleSet::yatzy(),
+            RuleSet::yahtzee(),
+            RuleSet::triple_yahtzee(),
+            RuleSet::kniffel(),
+            RuleSet::maxi_yatzy(),
+            RuleSet::poker_dice(),
+        ]
+    }
+
+    /// Whether the given category is on the scorecard.
+    pub fn has_category(&self, category: Category) -> bool {
+        self.categories.contains(&category)
+    }
+
+    /// Add a category to the scorecard or take it off.
+    ///
+    /// An added category goes before the first category that comes after it in the standard
+    /// order, so the scorecard keeps the variant's own order.
+    pub fn set_category(&mut self, category: Category, included: bool) {
+        if !included {
+            self.categories.retain(|cat| *cat != category);
+        } else if !self.has_category(category) {
+            let order = |cat: &Category| Category::ALL.iter().position(|all| all == cat);
+            let idx = self
+                .categories
+                .iter()
+                .position(|cat| order(cat) > order(&category))
+                .unwrap_or(self.categories.len());
+            self.categories.insert(idx, category);
+        }
+    }
+
+    /// The letter key that picks each category on the scorecard, in scorecard order.
+    ///
+    /// Each category gets the first letter of its name that's still free, or the first free
+    /// letter of the alphabet if none of them are.
+    pub fn shortcuts(&self) -> Vec<(Category, char)> {
+        let mut taken: Vec<char> = Vec::new();
+        for category in &self.categories {
+            let name = category.name().to_ascii_lowercase();
+            let key = name
+                .chars()
+                .chain('a'..='z')
+                .find(|c| c.is_ascii_lowercase() && !taken.contains(c));
+            // there are fewer categories than letters, so one is always free
+            taken.push(key.unwrap_or('?'));
+        }
+        self.categories.iter().copied().zip(taken).collect()
+    }
+
+    /// The category picked with the given letter key, if any.
+    pub fn category_for_key(&self, key: char) -> Option<Category> {
+        let key = key.to_ascii_lowercase();
+        self.shortcuts()
+            .into_iter()
+            .find(|(_, shortcut)| *shortcut == key)
+            .map(|(category, _)| category)
+    }
+
+    /// How many points the given dice would score in a category.
+    pub fn score(&self, category: Category, dice: &[Score]) -> u16 {
+        match (self.scoring, category) {
+            (Scoring::Poker, _) => poker::score(category, dice),
+            (_, Category::Ones) => scorecard::upper(dice, 1),
+            (_, Category::Twos) => scorecard::upper(dice, 2),
+            (_, Category::Threes) => scorecard::upper(dice, 3),
+            (_, Category::Fours) => scorecard::upper(dice, 4),
+            (_, Category::Fives) => scorecard::upper(dice, 5),
+            (_, Category::Sixes) => scorecard::upper(dice, 6),
+            (_, Category::OnePair) => scorecard::one_pair(dice),
+            (_, Category::TwoPairs) => scorecard::two_pairs(dice),
+            (_, Category::ThreePairs) => scorecard::three_pairs(dice),
+            (_, Category::FiveOfAKind) => scorecard::five_of_a_kind(dice),
+            (_, Category::FullStraight) => scorecard::full_straight(dice),
+            (_, Category::Villa) => scorecard::villa(dice),
+            (_, Category::Tower) => scorecard::tower(dice),
+            (_, Category::Chance) => scorecard::chance(dice),
+            (_, Category::Yatzy) => scorecard::yatzy(dice, self.yatzy_score),
+            (Scoring::Scandinavian, Category::ThreeOfAKind) => scorecard::three_of_a_kind(dice),
+            (Scoring::Scandinavian, Category::FourOfAKind) => scorecard::four_of_a_kind(dice),
+            (Scoring::Scandinavian, Category::SmallStraight) => scorecard::small_straight(dice),
+            (Scoring::Scandinavian, Category::LargeStraight) => scorecard::large_straight(dice),
+            (Scoring::Scandinavian, Category::FullHouse) => scorecard::full_house(dice),
+            (Scoring::American, Category::ThreeOfAKind) => scorecard::kind_total(dice, 3),
+            (Scoring::American, Category::FourOfAKind) => scorecard::kind_total(dice, 4),
+            (Scoring::American, Category::SmallStraight) => scorecard::run(dice, 4, 30),
+            (Scoring::American, Category::LargeStraight) => scorecard::run(dice, 5, 40),
+            (Scoring::American, Category::FullHouse) => scorecard::fixed_full_house(dice, 25),
+        }
+    }
+
+    /// How many points a Yatzy played as a joker scores in a category.
+    ///
+    /// Categories that need a particular pattern of dice get their full value, the rest are
+    /// scored as normal.
+    pub fn joker_score(&self, category: Category, dice: &[Score]) -> u16 {
+        match (self.scoring, category) {
+            (Scoring::Scandinavian, Category::SmallStraight) => 15,
+            (Scoring::Scandinavian, Category::LargeStraight) => 20,
+            // a full house uses five dice, which all show the same face
+            (Scoring::Scandinavian, Category::FullHouse) => scorecard::five_of_a_kind(dice),
+            (Scoring::American, Category::SmallStraight) => 30,
+            (Scoring::American, Category::LargeStraight) => 40,
+            (Scoring::American, Category::FullHouse) => 25,
+            _ => self.score(category, dice),
+        }
+    }
+}
@@ -0,0 +1,198 @@
+//! Saving a game to carry on later, on the same computer.
+//!
+//! Unlike a game played by file, a saved game keeps everything, the seed included, so it carries
+//! on exactly where it stopped. Games are saved to a file chosen by the player, or to a named
+//! slot in the saves directory, which the load screen lists.
+
+use std::{
+    cmp::Reverse,
+    ffi::OsStr,
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use super::date;
+use super::game::Game;
+use super::schema::{self, Schema};
+
+/// The extension of saved games.
+pub const EXTENSION: &str = "yatzysave";
+
+const SCHEMA: Schema = Schema {
+    name: "saved game",
+    migrations: &[schema::numbered],
+};
+
+/// Where the named save slots are kept, in the user's config directory.
+pub fn slots_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("yatzy").join("saves"))
+}
+
+/// Someone playing in a saved game.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedPlayer {
+    pub name: String,
+    /// The bot playing for them, if it isn't a person.
+    pub bot: Option<String>,
+    /// The name of the skin on their dice. Games saved before there were skins have the classic
+    /// one.
+    #[serde(default)]
+    pub skin: String,
+}
+
+/// A game saved part way through.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SaveFile {
+    /// The players, in turn order.
+    pub players: Vec<SavedPlayer>,
+    pub game: Game,
+    /// The seconds allowed for each decision, or `None` for no time limit.
+    pub turn_time: Option<u32>,
+    /// When the game was saved, in seconds since the Unix epoch, or 0 if that isn't known.
+    #[serde(default)]
+    pub saved_at: u64,
+}
+
+impl SaveFile {
+    /// Save a game now.
+    pub fn new(players: Vec<SavedPlayer>, game: Game, turn_time: Option<u32>) -> Self {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH);
+        Self {
+            players,
+            game,
+            turn_time,
+            saved_at: now.map_or(0, |now| now.as_secs()),
+        }
+    }
+
+    /// Read the saved game at `path`.
+    pub fn load(path: &Path) -> Result<Self> {
+        let json =
+            fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        Self::parse(&json).with_context(|| format!("parsing {}", path.display()))
+    }
+
+    /// Read a game saved as JSON.
+    pub fn parse(json: &str) -> Result<Self> {
+        let file: Self = SCHEMA.parse(json)?;
+        if file.players.len() != file.game.players() {
+            bail!(
+                "the game has {} players but {} names",
+                file.game.players(),
+                file.players.len()
+            );
+        }
+        Ok(file)
+    }
+
+    /// Write the saved game to `path`, replacing whatever was there.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+        }
+        let json = serde_json::to_string(&SCHEMA.write(self)?)?;
+        fs::write(path, json).with_context(|| format!("writing {}", path.display()))
+    }
+}
+
+/// A game saved in a slot, as the load screen lists it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlotInfo {
+    /// The slot's name.
+    pub name: String,
+    pub players: Vec<String>,
+    /// The name of the variant.
+    pub variant: String,
+    /// When the game was saved, in seconds since the Unix epoch, or 0 if that isn't known.
+    pub saved_at: u64,
+    /// How many of the players' categories have been filled, and how many there are.
+    pub filled: usize,
+    pub categories: usize,
+}
+
+impl SlotInfo {
+    fn new(name: String, file: &SaveFile) -> Self {
+        let rules = file.game.rules();
+        let filled = file
+            .game
+            .sheets()
+            .iter()
+            .flat_map(|sheet| sheet.columns())
+            .map(|card| {
+                let categories = rules.categories.iter();
+                categories
+                    .filter(|&&category| card.is_filled(category))
+                    .count()
+            })
+            .sum();
+        Self {
+            name,
+            players: file
+                .players
+                .iter()
+                .map(|player| player.name.clone())
+                .collect(),
+            variant: rules.name.clone(),
+            saved_at: file.saved_at,
+            filled,
+            categories: file.game.players() * rules.columns * rules.categories.len(),
+        }
+    }
+
+    /// When the game was saved, like `2020-06-14 18:05 UTC`.
+    pub fn date(&self) -> String {
+        if self.saved_at == 0 {
+            return "at an unknown time".into();
+        }
+        date::format(self.saved_at)
+    }
+}
+
+/// The file a slot is saved in.
+pub fn slot_path(dir: &Path, name: &str) -> PathBuf {
+    let file_name: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    dir.join(format!("{}.{}", file_name, EXTENSION))
+}
+
+/// The games saved in slots in `dir`, most recent first. Files that can't be read are left out.
+pub fn list_slots(dir: &Path) -> Result<Vec<SlotInfo>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("reading {}", dir.display())),
+    };
+    let mut slots = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension() != Some(OsStr::new(EXTENSION)) {
+            continue;
+        }
+        let name = match path.file_stem() {
+            Some(name) => name.to_string_lossy().into_owned(),
+            None => continue,
+        };
+        if let Ok(file) = SaveFile::load(&path) {
+            slots.push(SlotInfo::new(name, &file));
+        }
+    }
+    slots.sort_by_key(|slot| Reverse(slot.saved_at));
+    Ok(slots)
+}
+
+/// Delete the game saved in the named slot in `dir`.
+pub fn delete_slot(dir: &Path, name: &str) -> Result<()> {
+    let path = slot_path(dir, name);
+    fs::remove_file(&path).with_context(|| format!("deleting {}", path.display()))
+}
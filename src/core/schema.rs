@@ -0,0 +1,82 @@
+//! Versions of the files the game saves, so files saved by older versions still load after the
+//! data in them changes.
+//!
+//! Each kind of file is saved with its version in a `version` field. Files saved before the
+//! versions were numbered are version 0. A file is brought up to date a version at a time while
+//! it's still plain data, before it's read into the game's types, so each change to a kind of
+//! file only needs a migration from the version before it. Files from a newer version of the
+//! game than this one are an error rather than being read wrongly.
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+/// The field a file's version is saved in.
+const VERSION_FIELD: &str = "version";
+
+/// Brings a file from one version up to the next.
+pub type Migration = fn(&mut Value) -> Result<()>;
+
+/// A kind of file, with every change that has been made to it.
+#[derive(Debug)]
+pub struct Schema {
+    /// What the files are called in errors, like `saved game`.
+    pub name: &'static str,
+    /// The migrations in order, the first bringing version 0 up to version 1. There's one for
+    /// each version, so adding one is what makes a new version.
+    pub migrations: &'static [Migration],
+}
+
+impl Schema {
+    /// The version files are saved as.
+    pub fn version(&self) -> u64 {
+        self.migrations.len() as u64
+    }
+
+    /// Read a file of this kind, bringing it up to date first if it's from an older version.
+    pub fn read<T: DeserializeOwned>(&self, mut value: Value) -> Result<T> {
+        let version = match value
+            .as_object_mut()
+            .and_then(|file| file.remove(VERSION_FIELD))
+        {
+            Some(version) => version
+                .as_u64()
+                .ok_or_else(|| anyhow!("the version of the {} isn't a number", self.name))?,
+            None => 0,
+        };
+        if version > self.version() {
+            bail!(
+                "the {} is version {}, from a newer version of the game that saves version {}",
+                self.name,
+                version,
+                self.version()
+            );
+        }
+        for (from, migrate) in self.migrations.iter().enumerate().skip(version as usize) {
+            migrate(&mut value)
+                .with_context(|| format!("updating the {} from version {}", self.name, from))?;
+        }
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// Read a file of this kind saved as JSON.
+    pub fn parse<T: DeserializeOwned>(&self, json: &str) -> Result<T> {
+        self.read(serde_json::from_str(json)?)
+    }
+
+    /// A file of this kind holding `data`, marked with the current version.
+    pub fn write<T: Serialize>(&self, data: &T) -> Result<Value> {
+        let mut value = serde_json::to_value(data)?;
+        match value.as_object_mut() {
+            Some(file) => file.insert(VERSION_FIELD.to_string(), self.version().into()),
+            None => bail!("a {} has to be a table to hold its version", self.name),
+        };
+        Ok(value)
+    }
+}
+
+/// The migration to version 1, for kinds of file that didn't change when the versions were
+/// first numbered.
+pub fn numbered(_file: &mut Value) -> Result<()> {
+    Ok(())
+}
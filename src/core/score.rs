@@ -0,0 +1,166 @@
+//! The value shown on a die, and rolling for new values.
+//!
+//! The random functions take the generator to use, so that game rolls can be drawn from the
+//! game's own seeded `GameRng` while purely cosmetic randomness can use `thread_rng()`.
+
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// The random number generator for a game's dice.
+///
+/// Two games started with the same seed and given the same actions roll the same dice. The seed
+/// is kept so it can be shown to the player and used to replay the game.
+#[derive(Debug, Clone)]
+pub struct GameRng {
+    seed: u64,
+    /// How many values have been drawn, so generators can be compared without comparing their
+    /// internal state.
+    draws: u64,
+    rng: StdRng,
+}
+
+impl GameRng {
+    /// Create a generator from the given seed.
+    pub fn from_seed(seed: u64) -> Self {
+        Self {
+            seed,
+            draws: 0,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Create a generator with a random seed.
+    pub fn from_entropy() -> Self {
+        Self::from_seed(thread_rng().gen())
+    }
+
+    /// Pick up where a generator left off, after it had rolled `draws` dice with `sides` sides
+    /// from `seed`.
+    pub fn resume(seed: u64, draws: u64, sides: u8) -> Self {
+        let mut rng = Self::from_seed(seed);
+        for _ in 0..draws {
+            rng.roll(sides);
+        }
+        rng
+    }
+
+    /// The seed this generator was created from.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// How many dice this generator has rolled.
+    pub fn draws(&self) -> u64 {
+        self.draws
+    }
+
+    /// Roll a die with the given number of sides.
+    pub fn roll(&mut self, sides: u8) -> Score {
+        self.draws += 1;
+        Score::random_face(&mut self.rng, sides)
+    }
+}
+
+impl PartialEq for GameRng {
+    fn eq(&self, other: &Self) -> bool {
+        self.seed == other.seed && self.draws == other.draws
+    }
+}
+
+/// A value representing a value, for example a die that has stopped moving.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Score(pub u8);
+
+impl Score {
+    /// Create a new score with the given value.
+    pub fn new(score: u8) -> Self {
+        Self(score)
+    }
+
+    /// Create a Score with a random value between 1 and 6, for a six-sided die.
+    pub fn random_die(rng: &mut impl Rng) -> Self {
+        Self::random_face(rng, 6)
+    }
+
+    /// Create a Score with a random value between 1 and `sides`, for a die with that many sides.
+    pub fn random_face(rng: &mut impl Rng, sides: u8) -> Self {
+        // counted in u16, as one past the sides doesn't fit in a u8 when there are 255
+        let n: u16 = rng.gen_range(1, u16::from(sides) + 1);
+        Self(n as u8)
+    }
+
+    /// Create a Score with a random value in the given range.
+    pub fn random(rng: &mut impl Rng, low: u8, hi: u8) -> Self {
+        let n: u8 = rng.gen_range(low, hi);
+        Self(n)
+    }
+
+    /// Create a Score with a random value between 1 and `sides`, that isn't the current value.
+    pub fn different_random_face(self, rng: &mut impl Rng, sides: u8) -> Self {
+        let old = self.0;
+        assert!(
+            1 <= old && old <= sides,
+            "the previous value {} must be a face of a die with {} sides",
+            old,
+            sides
+        );
+        // one face fewer to choose from, shifting those from the previous value up, so the
+        // highest is `sides` without going past it
+        let n: u8 = rng.gen_range(1, sides);
+        Self(if n >= old { n + 1 } else { n })
+    }
+
+    /// Create a Score with a random value in a range, that isn't the current value.
+    pub fn different_random(self, rng: &mut impl Rng, low: u8, hi: u8) -> Self {
+        let old = self.0;
+        assert!(
+            low <= old && old < hi,
+            "the previous value {} must be in the range [{}, {})",
+            old,
+            low,
+            hi
+        );
+        // Smaller range because we are going to shift numbers >= the previous value.
+        let n: u8 = rng.gen_range(low, hi - 1);
+        Self(if n >= old { n + 1 } else { n })
+    }
+}
+
+impl From<u8> for Score {
+    fn from(val: u8) -> Self {
+        Self(val)
+    }
+}
+
+impl From<Score> for u8 {
+    fn from(val: Score) -> Self {
+        val.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn faces_of_the_biggest_die_fit() {
+        let mut rng = GameRng::from_seed(3);
+        for _ in 0..100 {
+            assert!(rng.roll(255).0 >= 1);
+        }
+        let face = Score(255).different_random_face(&mut thread_rng(), 255);
+        assert!(face.0 >= 1 && face.0 < 255);
+    }
+
+    #[test]
+    fn a_different_face_is_different() {
+        let mut rng = thread_rng();
+        for old in 1..=6 {
+            for _ in 0..20 {
+                let face = Score(old).different_random_face(&mut rng, 6);
+                assert_ne!(face, Score(old));
+                assert!(face.0 >= 1 && face.0 <= 6);
+            }
+        }
+    }
+}
@@ -0,0 +1,787 @@
+//! The Yatzy scorecard and the scoring functions for each category.
+
+use serde::{Deserialize, Serialize};
+
+use super::moves::MoveError;
+use super::rules::{RuleSet, YatzyBonus};
+use super::score::Score;
+
+/// A category on the scorecard that a hand of dice can be placed in.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Category {
+    Ones,
+    Twos,
+    Threes,
+    Fours,
+    Fives,
+    Sixes,
+    OnePair,
+    TwoPairs,
+    ThreePairs,
+    ThreeOfAKind,
+    FourOfAKind,
+    FiveOfAKind,
+    SmallStraight,
+    LargeStraight,
+    FullStraight,
+    FullHouse,
+    Villa,
+    Tower,
+    Chance,
+    Yatzy,
+}
+
+impl Category {
+    /// Every category, in the order they appear on the scorecard.
+    pub const ALL: [Category; 20] = [
+        Category::Ones,
+        Category::Twos,
+        Category::Threes,
+        Category::Fours,
+        Category::Fives,
+        Category::Sixes,
+        Category::OnePair,
+        Category::TwoPairs,
+        Category::ThreePairs,
+        Category::ThreeOfAKind,
+        Category::FourOfAKind,
+        Category::FiveOfAKind,
+        Category::SmallStraight,
+        Category::LargeStraight,
+        Category::FullStraight,
+        Category::FullHouse,
+        Category::Villa,
+        Category::Tower,
+        Category::Chance,
+        Category::Yatzy,
+    ];
+
+    /// The name of the category as printed on the scorecard.
+    pub fn name(self) -> &'static str {
+        match self {
+            Category::Ones => "Ones",
+            Category::Twos => "Twos",
+            Category::Threes => "Threes",
+            Category::Fours => "Fours",
+            Category::Fives => "Fives",
+            Category::Sixes => "Sixes",
+            Category::OnePair => "One pair",
+            Category::TwoPairs => "Two pairs",
+            Category::ThreePairs => "Three pairs",
+            Category::ThreeOfAKind => "Three of a kind",
+            Category::FourOfAKind => "Four of a kind",
+            Category::FiveOfAKind => "Five of a kind",
+            Category::SmallStraight => "Small straight",
+            Category::LargeStraight => "Large straight",
+            Category::FullStraight => "Full straight",
+            Category::FullHouse => "Full house",
+            Category::Villa => "Villa",
+            Category::Tower => "Tower",
+            Category::Chance => "Chance",
+            Category::Yatzy => "Yatzy",
+        }
+    }
+
+    /// Whether this category is in the upper section (ones to sixes).
+    pub fn is_upper(self) -> bool {
+        matches!(
+            self,
+            Category::Ones
+                | Category::Twos
+                | Category::Threes
+                | Category::Fours
+                | Category::Fives
+                | Category::Sixes
+        )
+    }
+
+    /// The upper-section category for the given face, if there is one.
+    pub fn upper_for(face: u8) -> Option<Category> {
+        match face {
+            1 => Some(Category::Ones),
+            2 => Some(Category::Twos),
+            3 => Some(Category::Threes),
+            4 => Some(Category::Fours),
+            5 => Some(Category::Fives),
+            6 => Some(Category::Sixes),
+            _ => None,
+        }
+    }
+
+    /// The position of this category in `Category::ALL`.
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// A place on a score sheet: a category in one of its columns.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Slot {
+    /// The column, which is always 0 unless the variant has several columns.
+    pub column: usize,
+    pub category: Category,
+}
+
+impl Slot {
+    /// The given category in the first (usually only) column.
+    pub fn new(category: Category) -> Self {
+        Self {
+            column: 0,
+            category,
+        }
+    }
+
+    pub fn in_column(column: usize, category: Category) -> Self {
+        Self { column, category }
+    }
+}
+
+/// All of a player's scorecard columns.
+///
+/// Most variants have a single column. In Triple Yahtzee there are three, and the points in
+/// each are multiplied by one, two and three respectively.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoreSheet {
+    columns: Vec<Scorecard>,
+}
+
+impl ScoreSheet {
+    /// Create an empty score sheet with the columns for the given rules.
+    pub fn new(rules: &RuleSet) -> Self {
+        Self {
+            columns: vec![Scorecard::new(); rules.columns],
+        }
+    }
+
+    pub fn columns(&self) -> &[Scorecard] {
+        &self.columns
+    }
+
+    pub fn column(&self, column: usize) -> Option<&Scorecard> {
+        self.columns.get(column)
+    }
+
+    /// The points placed in the given slot, if it has been filled.
+    pub fn get(&self, slot: Slot) -> Option<u16> {
+        self.column(slot.column)?.get(slot.category)
+    }
+
+    /// How much the points in the given column are multiplied by in the total.
+    pub fn multiplier(column: usize) -> u16 {
+        column as u16 + 1
+    }
+
+    /// Whether every column has been filled.
+    pub fn is_complete(&self, rules: &RuleSet) -> bool {
+        self.columns.iter().all(|card| card.is_complete(rules))
+    }
+
+    /// The total of all columns, each multiplied by its multiplier.
+    pub fn total(&self) -> u16 {
+        self.columns
+            .iter()
+            .enumerate()
+            .map(|(column, card)| card.total() * Self::multiplier(column))
+            .sum()
+    }
+
+    /// Check the given dice may be placed in (or scratched from) a slot, or say why not.
+    pub fn check_fill(&self, slot: Slot, dice: &[Score], rules: &RuleSet) -> Result<(), MoveError> {
+        self.column(slot.column)
+            .ok_or(MoveError::NoSuchColumn(slot.column))?
+            .check_fill(slot.category, dice, rules)
+    }
+
+    /// Place the given dice in a slot, returning the points scored before the multiplier.
+    pub fn fill(&mut self, slot: Slot, dice: &[Score], rules: &RuleSet) -> u16 {
+        self.columns[slot.column].fill(slot.category, dice, rules)
+    }
+
+    /// The points the given dice would score in a slot, before the multiplier.
+    pub fn points(&self, slot: Slot, dice: &[Score], rules: &RuleSet) -> u16 {
+        self.columns[slot.column].points(slot.category, dice, rules)
+    }
+
+    /// Fill a slot with zero.
+    pub fn scratch(&mut self, slot: Slot, rules: &RuleSet) {
+        self.columns[slot.column].scratch(slot.category, rules)
+    }
+}
+
+/// A single scorecard column, recording the points placed in each category.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Scorecard {
+    /// The points in each category, or `None` if the category hasn't been filled yet.
+    ///
+    /// A category that has been scratched, or filled with dice that don't fit it, is `Some(0)`.
+    scores: [Option<u16>; 20],
+    /// The sum of the filled upper-section categories.
+    upper_total: u16,
+    /// The upper-section bonus, once earned.
+    bonus: u16,
+    /// The bonus points earned for extra Yatzys.
+    yatzy_bonus: u16,
+    /// The sum of all filled categories plus the bonuses.
+    total: u16,
+}
+
+impl Scorecard {
+    /// Create an empty scorecard.
+    pub fn new() -> Self {
+        Self {
+            scores: [None; 20],
+            upper_total: 0,
+            bonus: 0,
+            yatzy_bonus: 0,
+            total: 0,
+        }
+    }
+
+    /// The points placed in the given category, if it has been filled.
+    pub fn get(&self, category: Category) -> Option<u16> {
+        self.scores[category.index()]
+    }
+
+    pub fn is_filled(&self, category: Category) -> bool {
+        self.get(category).is_some()
+    }
+
+    /// Whether every category in the rule set has been filled.
+    pub fn is_complete(&self, rules: &RuleSet) -> bool {
+        rules.categories.iter().all(|cat| self.is_filled(*cat))
+    }
+
+    /// The first unfilled category in scorecard order, which is the only one that can be
+    /// filled when playing Forced Yatzy.
+    pub fn next_category(&self, rules: &RuleSet) -> Option<Category> {
+        rules
+            .categories
+            .iter()
+            .copied()
+            .find(|cat| !self.is_filled(*cat))
+    }
+
+    /// Whether the dice are a Yatzy that must be played under the joker rule.
+    fn is_joker(&self, dice: &[Score], rules: &RuleSet) -> bool {
+        rules.yatzy_bonus == YatzyBonus::American
+            && yatzy(dice, rules.yatzy_score) > 0
+            && self.is_filled(Category::Yatzy)
+    }
+
+    /// Check the given dice may be placed in (or scratched from) a category, or say why not.
+    ///
+    /// In Forced Yatzy only the next category in scorecard order can be filled.
+    ///
+    /// Under the joker rule an extra Yatzy must go in its upper-section category if that is
+    /// open, otherwise in an open lower-section category, and only in the upper section if the
+    /// lower section is full.
+    pub fn check_fill(
+        &self,
+        category: Category,
+        dice: &[Score],
+        rules: &RuleSet,
+    ) -> Result<(), MoveError> {
+        if !rules.has_category(category) {
+            return Err(MoveError::NotInRules(category));
+        }
+        if self.is_filled(category) {
+            return Err(MoveError::CategoryFilled(category));
+        }
+        if rules.forced_order {
+            return match self.next_category(rules) {
+                Some(next) if next != category => Err(MoveError::OutOfOrder { next }),
+                _ => Ok(()),
+            };
+        }
+        if !self.is_joker(dice, rules) {
+            return Ok(());
+        }
+        if let Some(upper) = Category::upper_for(dice[0].0) {
+            if rules.has_category(upper) && !self.is_filled(upper) {
+                return if category == upper {
+                    Ok(())
+                } else {
+                    Err(MoveError::JokerUpper(upper))
+                };
+            }
+        }
+        let lower_open = rules
+            .categories
+            .iter()
+            .any(|cat| !cat.is_upper() && !self.is_filled(*cat));
+        if lower_open && category.is_upper() {
+            return Err(MoveError::JokerLower);
+        }
+        Ok(())
+    }
+
+    /// Place the given dice in a category, returning the points scored.
+    ///
+    /// Panics if the category has already been filled.
+    pub fn fill(&mut self, category: Category, dice: &[Score], rules: &RuleSet) -> u16 {
+        assert!(
+            !self.is_filled(category),
+            "category {} has already been filled",
+            category.name()
+        );
+        if self.is_joker(dice, rules) && self.get(Category::Yatzy) == Some(rules.yatzy_score) {
+            self.yatzy_bonus += rules.extra_yatzy_bonus;
+        }
+        let score = self.points(category, dice, rules);
+        self.set(category, score, rules);
+        score
+    }
+
+    /// The points the given dice would score in a category, taking the joker rule into account.
+    ///
+    /// Doesn't include any extra Yatzy bonus.
+    pub fn points(&self, category: Category, dice: &[Score], rules: &RuleSet) -> u16 {
+        if self.is_joker(dice, rules) {
+            rules.joker_score(category, dice)
+        } else {
+            rules.score(category, dice)
+        }
+    }
+
+    /// Deliberately fill a category with zero, for when the dice don't fit anywhere useful.
+    ///
+    /// Panics if the category has already been filled.
+    pub fn scratch(&mut self, category: Category, rules: &RuleSet) {
+        assert!(
+            !self.is_filled(category),
+            "category {} has already been filled",
+            category.name()
+        );
+        self.set(category, 0, rules);
+    }
+
+    fn set(&mut self, category: Category, score: u16, rules: &RuleSet) {
+        self.scores[category.index()] = Some(score);
+        self.update_totals(rules);
+    }
+
+    /// The sum of the filled upper-section categories, not including the bonus.
+    pub fn upper_total(&self) -> u16 {
+        self.upper_total
+    }
+
+    /// The upper-section bonus, or 0 if the upper total hasn't reached the threshold.
+    pub fn bonus(&self) -> u16 {
+        self.bonus
+    }
+
+    /// How many more upper-section points are needed for the bonus, or 0 if it has been earned.
+    pub fn points_to_bonus(&self, rules: &RuleSet) -> u16 {
+        rules.upper_bonus_threshold.saturating_sub(self.upper_total)
+    }
+
+    /// The bonus points earned for extra Yatzys, under American bonus rules.
+    pub fn yatzy_bonus(&self) -> u16 {
+        self.yatzy_bonus
+    }
+
+    /// The grand total of all filled categories, including the bonuses.
+    pub fn total(&self) -> u16 {
+        self.total
+    }
+
+    /// Recalculate the derived totals after a category has been filled.
+    fn update_totals(&mut self, rules: &RuleSet) {
+        let card = &*self;
+        let filled = |upper: bool| {
+            Category::ALL
+                .iter()
+                .filter(move |cat| cat.is_upper() == upper)
+                .filter_map(move |cat| card.get(*cat))
+                .sum::<u16>()
+        };
+        let (upper_total, lower_total) = (filled(true), filled(false));
+        self.upper_total = upper_total;
+        self.bonus = if upper_total >= rules.upper_bonus_threshold {
+            rules.upper_bonus
+        } else {
+            0
+        };
+        self.total = upper_total + self.bonus + lower_total + self.yatzy_bonus;
+    }
+}
+
+impl Default for Scorecard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Count how many dice show each face. Index 0 is unused, so `counts[n]` is the number of `n`s.
+///
+/// There is always an entry for at least the faces of a six-sided die.
+fn counts(dice: &[Score]) -> Vec<u8> {
+    let highest = dice.iter().map(|die| die.0).max().unwrap_or(0).max(6);
+    let mut counts = vec![0; highest as usize + 1];
+    for die in dice {
+        counts[die.0 as usize] += 1;
+    }
+    counts
+}
+
+/// The faces that appear at least `n` times, highest first.
+fn faces_with_at_least(dice: &[Score], n: u8) -> impl Iterator<Item = u16> {
+    let counts = counts(dice);
+    (1..counts.len())
+        .rev()
+        .filter(move |&face| counts[face] >= n)
+        .map(|face| face as u16)
+}
+
+/// The sum of all dice.
+fn sum(dice: &[Score]) -> u16 {
+    dice.iter().map(|die| die.0 as u16).sum()
+}
+
+/// The sum of the dice showing `face`, for the upper section.
+pub fn upper(dice: &[Score], face: u8) -> u16 {
+    dice.iter()
+        .filter(|die| die.0 == face)
+        .map(|die| die.0 as u16)
+        .sum()
+}
+
+/// The sum of the highest pair, or 0 if there is no pair.
+pub fn one_pair(dice: &[Score]) -> u16 {
+    pairs(dice, 1)
+}
+
+/// The sum of two different pairs, or 0 if there aren't two.
+pub fn two_pairs(dice: &[Score]) -> u16 {
+    pairs(dice, 2)
+}
+
+/// The sum of three different pairs, or 0 if there aren't three.
+pub fn three_pairs(dice: &[Score]) -> u16 {
+    pairs(dice, 3)
+}
+
+/// The sum of the `n` highest different pairs, or 0 if there aren't `n`.
+fn pairs(dice: &[Score], n: usize) -> u16 {
+    let pairs: Vec<u16> = faces_with_at_least(dice, 2).take(n).collect();
+    if pairs.len() == n {
+        pairs.iter().map(|face| face * 2).sum()
+    } else {
+        0
+    }
+}
+
+/// The sum of `n` dice showing the highest face that at least `n` dice show, or 0.
+fn of_a_kind(dice: &[Score], n: u8) -> u16 {
+    faces_with_at_least(dice, n)
+        .next()
+        .map(|face| face * n as u16)
+        .unwrap_or(0)
+}
+
+/// The sum of three dice showing the same face, or 0.
+pub fn three_of_a_kind(dice: &[Score]) -> u16 {
+    of_a_kind(dice, 3)
+}
+
+/// The sum of four dice showing the same face, or 0.
+pub fn four_of_a_kind(dice: &[Score]) -> u16 {
+    of_a_kind(dice, 4)
+}
+
+/// The sum of five dice showing the same face, or 0.
+pub fn five_of_a_kind(dice: &[Score]) -> u16 {
+    of_a_kind(dice, 5)
+}
+
+/// The sum of `first` dice of one face and `second` dice of a different face, choosing the
+/// highest faces possible, or 0.
+fn groups(dice: &[Score], first: u8, second: u8) -> u16 {
+    let counts = counts(dice);
+    for big in faces_with_at_least(dice, first) {
+        let small = (1..counts.len())
+            .rev()
+            .find(|&face| face as u16 != big && counts[face] >= second);
+        if let Some(small) = small {
+            return big * first as u16 + small as u16 * second as u16;
+        }
+    }
+    0
+}
+
+/// 15 points for 1-2-3-4-5, otherwise 0.
+pub fn small_straight(dice: &[Score]) -> u16 {
+    let counts = counts(dice);
+    if (1..=5).all(|face| counts[face] >= 1) {
+        15
+    } else {
+        0
+    }
+}
+
+/// 20 points for 2-3-4-5-6, otherwise 0.
+pub fn large_straight(dice: &[Score]) -> u16 {
+    let counts = counts(dice);
+    if (2..=6).all(|face| counts[face] >= 1) {
+        20
+    } else {
+        0
+    }
+}
+
+/// 21 points for 1-2-3-4-5-6, otherwise 0.
+pub fn full_straight(dice: &[Score]) -> u16 {
+    let counts = counts(dice);
+    if (1..=6).all(|face| counts[face] >= 1) {
+        21
+    } else {
+        0
+    }
+}
+
+/// The sum of three of one face and two of another, otherwise 0.
+pub fn full_house(dice: &[Score]) -> u16 {
+    groups(dice, 3, 2)
+}
+
+/// The sum of two different sets of three, otherwise 0.
+pub fn villa(dice: &[Score]) -> u16 {
+    groups(dice, 3, 3)
+}
+
+/// The sum of four of one face and two of another, otherwise 0.
+pub fn tower(dice: &[Score]) -> u16 {
+    groups(dice, 4, 2)
+}
+
+/// The sum of all dice.
+pub fn chance(dice: &[Score]) -> u16 {
+    sum(dice)
+}
+
+/// `points` if all dice show the same face, otherwise 0.
+pub fn yatzy(dice: &[Score], points: u16) -> u16 {
+    match dice.split_first() {
+        Some((first, rest)) if rest.iter().all(|die| die == first) => points,
+        _ => 0,
+    }
+}
+
+/// The sum of all dice if at least `n` show the same face, otherwise 0.
+pub fn kind_total(dice: &[Score], n: u8) -> u16 {
+    if faces_with_at_least(dice, n).next().is_some() {
+        sum(dice)
+    } else {
+        0
+    }
+}
+
+/// `points` if the dice contain a run of at least `len` consecutive faces, otherwise 0.
+pub fn run(dice: &[Score], len: usize, points: u16) -> u16 {
+    let counts = counts(dice);
+    let mut longest = 0;
+    let mut current = 0;
+    for &count in &counts[1..] {
+        if count > 0 {
+            current += 1;
+            longest = longest.max(current);
+        } else {
+            current = 0;
+        }
+    }
+    if longest >= len {
+        points
+    } else {
+        0
+    }
+}
+
+/// `points` for three of one face and two of another, otherwise 0.
+pub fn fixed_full_house(dice: &[Score], points: u16) -> u16 {
+    if full_house(dice) > 0 {
+        points
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dice(faces: &[u8]) -> Vec<Score> {
+        faces.iter().map(|&face| Score(face)).collect()
+    }
+
+    #[test]
+    fn upper_counts_only_the_face() {
+        assert_eq!(upper(&dice(&[1, 3, 3, 5, 3]), 3), 9);
+        assert_eq!(upper(&dice(&[1, 2, 4, 5, 6]), 3), 0);
+    }
+
+    #[test]
+    fn pairs_take_the_highest_different_faces() {
+        assert_eq!(one_pair(&dice(&[2, 2, 5, 5, 6])), 10);
+        assert_eq!(two_pairs(&dice(&[2, 2, 5, 5, 6])), 14);
+        assert_eq!(two_pairs(&dice(&[2, 2, 2, 2, 6])), 0);
+        assert_eq!(three_pairs(&dice(&[1, 1, 2, 2, 3, 3])), 12);
+        assert_eq!(one_pair(&dice(&[1, 2, 3, 4, 5])), 0);
+    }
+
+    #[test]
+    fn of_a_kind_scores_only_the_matching_dice() {
+        assert_eq!(three_of_a_kind(&dice(&[4, 4, 4, 4, 2])), 12);
+        assert_eq!(four_of_a_kind(&dice(&[4, 4, 4, 4, 2])), 16);
+        assert_eq!(four_of_a_kind(&dice(&[4, 4, 4, 2, 2])), 0);
+        assert_eq!(five_of_a_kind(&dice(&[3, 3, 3, 3, 3, 1])), 15);
+    }
+
+    #[test]
+    fn straights_need_every_face() {
+        assert_eq!(small_straight(&dice(&[3, 1, 2, 5, 4])), 15);
+        assert_eq!(small_straight(&dice(&[2, 3, 4, 5, 6])), 0);
+        assert_eq!(large_straight(&dice(&[2, 3, 4, 5, 6])), 20);
+        assert_eq!(full_straight(&dice(&[1, 2, 3, 4, 5, 6])), 21);
+        assert_eq!(full_straight(&dice(&[1, 2, 3, 4, 5, 5])), 0);
+    }
+
+    #[test]
+    fn groups_need_different_faces() {
+        assert_eq!(full_house(&dice(&[2, 2, 3, 3, 3])), 13);
+        assert_eq!(full_house(&dice(&[3, 3, 3, 3, 3])), 0);
+        assert_eq!(villa(&dice(&[2, 2, 2, 5, 5, 5])), 21);
+        assert_eq!(tower(&dice(&[6, 6, 6, 6, 1, 1])), 26);
+    }
+
+    #[test]
+    fn chance_and_yatzy() {
+        assert_eq!(chance(&dice(&[1, 2, 3, 4, 6])), 16);
+        assert_eq!(yatzy(&dice(&[5, 5, 5, 5, 5]), 50), 50);
+        assert_eq!(yatzy(&dice(&[5, 5, 5, 5, 4]), 50), 0);
+    }
+
+    #[test]
+    fn american_patterns() {
+        assert_eq!(kind_total(&dice(&[2, 2, 2, 5, 6]), 3), 17);
+        assert_eq!(kind_total(&dice(&[2, 2, 3, 5, 6]), 3), 0);
+        assert_eq!(run(&dice(&[1, 2, 3, 4, 6]), 4, 30), 30);
+        assert_eq!(run(&dice(&[1, 2, 3, 5, 6]), 4, 30), 0);
+        assert_eq!(fixed_full_house(&dice(&[6, 6, 1, 1, 1]), 25), 25);
+    }
+
+    #[test]
+    fn upper_bonus_at_the_threshold() {
+        let rules = RuleSet::yatzy();
+        let mut card = Scorecard::new();
+        let uppers = [
+            Category::Ones,
+            Category::Twos,
+            Category::Threes,
+            Category::Fours,
+            Category::Fives,
+        ];
+        for (face, category) in (1..).zip(&uppers) {
+            let other = face % 6 + 1;
+            card.fill(*category, &dice(&[face, face, face, other, other]), &rules);
+        }
+        assert_eq!(card.bonus(), 0);
+        assert_eq!(card.points_to_bonus(&rules), 18);
+        card.fill(Category::Sixes, &dice(&[6, 6, 6, 1, 1]), &rules);
+        assert_eq!(card.upper_total(), 63);
+        assert_eq!(card.bonus(), 50);
+        assert_eq!(card.total(), 113);
+    }
+
+    #[test]
+    fn filled_and_missing_categories_cannot_be_filled() {
+        let rules = RuleSet::yatzy();
+        let mut card = Scorecard::new();
+        let hand = dice(&[1, 2, 3, 4, 5]);
+        assert_eq!(
+            card.check_fill(Category::Villa, &hand, &rules),
+            Err(MoveError::NotInRules(Category::Villa))
+        );
+        card.scratch(Category::Chance, &rules);
+        assert_eq!(card.get(Category::Chance), Some(0));
+        assert_eq!(
+            card.check_fill(Category::Chance, &hand, &rules),
+            Err(MoveError::CategoryFilled(Category::Chance))
+        );
+    }
+
+    #[test]
+    fn forced_order_allows_only_the_next_category() {
+        let rules = RuleSet {
+            forced_order: true,
+            ..RuleSet::yatzy()
+        };
+        let mut card = Scorecard::new();
+        let hand = dice(&[2, 2, 2, 4, 5]);
+        assert_eq!(
+            card.check_fill(Category::Twos, &hand, &rules),
+            Err(MoveError::OutOfOrder {
+                next: Category::Ones
+            })
+        );
+        assert_eq!(card.check_fill(Category::Ones, &hand, &rules), Ok(()));
+        card.fill(Category::Ones, &hand, &rules);
+        assert_eq!(card.check_fill(Category::Twos, &hand, &rules), Ok(()));
+    }
+
+    #[test]
+    fn joker_goes_in_its_upper_category_first() {
+        let rules = RuleSet::yahtzee();
+        let mut card = Scorecard::new();
+        let fours = dice(&[4, 4, 4, 4, 4]);
+        assert_eq!(card.fill(Category::Yatzy, &fours, &rules), 50);
+        assert_eq!(
+            card.check_fill(Category::Chance, &fours, &rules),
+            Err(MoveError::JokerUpper(Category::Fours))
+        );
+        assert_eq!(card.check_fill(Category::Fours, &fours, &rules), Ok(()));
+        assert_eq!(card.fill(Category::Fours, &fours, &rules), 20);
+        assert_eq!(card.yatzy_bonus(), 100);
+    }
+
+    #[test]
+    fn joker_fills_the_lower_section_before_the_upper() {
+        let rules = RuleSet::yahtzee();
+        let mut card = Scorecard::new();
+        let fours = dice(&[4, 4, 4, 4, 4]);
+        card.fill(Category::Yatzy, &fours, &rules);
+        card.fill(Category::Fours, &dice(&[4, 4, 1, 2, 3]), &rules);
+        assert_eq!(
+            card.check_fill(Category::Ones, &fours, &rules),
+            Err(MoveError::JokerLower)
+        );
+        assert_eq!(card.check_fill(Category::FullHouse, &fours, &rules), Ok(()));
+        assert_eq!(card.points(Category::FullHouse, &fours, &rules), 25);
+        assert_eq!(card.points(Category::LargeStraight, &fours, &rules), 40);
+    }
+
+    #[test]
+    fn no_extra_bonus_after_scratching_yatzy() {
+        let rules = RuleSet::yahtzee();
+        let mut card = Scorecard::new();
+        card.scratch(Category::Yatzy, &rules);
+        card.fill(Category::Fours, &dice(&[4, 4, 4, 4, 4]), &rules);
+        assert_eq!(card.yatzy_bonus(), 0);
+    }
+
+    #[test]
+    fn the_service_definition_numbers_the_categories_the_same() {
+        let proto = include_str!("../../proto/yatzy.proto");
+        for (idx, category) in Category::ALL.iter().enumerate() {
+            // `FullHouse` is `FULL_HOUSE` there
+            let mut name = String::new();
+            for c in format!("{:?}", category).chars() {
+                if c.is_uppercase() && !name.is_empty() {
+                    name.push('_');
+                }
+                name.push(c.to_ascii_uppercase());
+            }
+            let line = format!("  {} = {};", name, idx + 1);
+            assert!(proto.contains(&line), "{:?} isn't {:?}", category, line);
+        }
+    }
+}
@@ -0,0 +1,182 @@
+//! The player's settings, kept in a TOML file in the config directory so they can be edited by
+//! hand too.
+
+use std::{
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use super::schema::{self, Schema};
+
+/// Where the settings are saved, in the user's config directory.
+pub fn default_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("yatzy").join("settings.toml"))
+}
+
+const SCHEMA: Schema = Schema {
+    name: "settings",
+    migrations: &[schema::numbered],
+};
+
+/// The colors the game is drawn in. The dark theme is druid's own.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    Light,
+    Dark,
+}
+
+/// The colors that stand out in the game, such as the border of a held die, picked to be told
+/// apart with each kind of color blindness.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorPalette {
+    Standard,
+    /// For red-green color blindness from weak green cones.
+    Deuteranopia,
+    /// For red-green color blindness from weak red cones.
+    Protanopia,
+    /// For blue-yellow color blindness.
+    Tritanopia,
+}
+
+/// How the game is laid out in the window.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Layout {
+    /// In one column when the window is narrow, and with the panels beside the game otherwise.
+    Automatic,
+    Wide,
+    /// In one column, with the dice above the scorecard and the panels under them.
+    Compact,
+}
+
+/// Where the main window was and how big it was.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WindowGeometry {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+/// Where to keep a copy of the profiles and saved games online, to share them between
+/// computers.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum SyncSettings {
+    /// A folder on a WebDAV server, such as Nextcloud, given by its URL.
+    WebDav {
+        url: String,
+        user: String,
+        password: String,
+    },
+    /// A bucket in an S3-compatible store, with the files named starting with `prefix`.
+    S3 {
+        /// Where the store is, like `https://s3.eu-west-1.amazonaws.com`.
+        endpoint: String,
+        region: String,
+        bucket: String,
+        #[serde(default)]
+        prefix: String,
+        access_key: String,
+        secret_key: String,
+    },
+}
+
+/// Everything the player can set. Settings missing from the file keep their defaults, so files
+/// written by older versions still load.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub theme: Theme,
+    pub colors: ColorPalette,
+    pub layout: Layout,
+    /// Whether to play sounds.
+    pub sounds: bool,
+    /// The name of the variant chosen when the game opens.
+    pub default_variant: String,
+    /// How fast the dice roll and computer players move, where 1 is normal speed.
+    pub animation_speed: f64,
+    /// The names of the people who played last, filled in when the game opens.
+    pub player_names: Vec<String>,
+    /// Addresses to send a webhook to when a game played by file starts, passes to someone, or
+    /// ends.
+    pub webhooks: Vec<String>,
+    /// The Twitch channel whose chat can play, by voting on each decision.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub twitch_channel: Option<String>,
+    /// The main window as it was when it was last closed, or `None` to open it at the default
+    /// size.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub window: Option<WindowGeometry>,
+    /// Where to sync the profiles and saved games, or `None` to keep them on this computer.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sync: Option<SyncSettings>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            theme: Theme::Dark,
+            colors: ColorPalette::Standard,
+            layout: Layout::Automatic,
+            sounds: true,
+            default_variant: "Yatzy".into(),
+            animation_speed: 1.0,
+            player_names: Vec::new(),
+            webhooks: Vec::new(),
+            twitch_channel: None,
+            window: None,
+            sync: None,
+        }
+    }
+}
+
+impl Settings {
+    /// Read the settings saved at `path`, or the defaults if nothing has been saved yet.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e).with_context(|| format!("reading {}", path.display())),
+        };
+        toml::from_str(&text)
+            .map_err(Into::into)
+            .and_then(|value| SCHEMA.read(value))
+            .with_context(|| format!("parsing {}", path.display()))
+    }
+
+    /// Write the settings to `path`, replacing whatever was saved there.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+        }
+        // TOML needs the tables after the other settings, which its own values know to do
+        let text = toml::to_string_pretty(&toml::Value::try_from(SCHEMA.write(self)?)?)?;
+        fs::write(path, text).with_context(|| format!("writing {}", path.display()))
+    }
+
+    /// The animation speed, limited to something that can be watched and won't stop it.
+    pub fn speed(&self) -> f64 {
+        match self.animation_speed {
+            speed if speed < 0.25 => 0.25,
+            speed if speed > 4.0 => 4.0,
+            speed if speed.is_finite() => speed,
+            _ => 1.0,
+        }
+    }
+
+    /// The saved window, if it could be opened again. A window can't be opened with no room
+    /// inside it, or anywhere that isn't a number.
+    pub fn window(&self) -> Option<WindowGeometry> {
+        self.window.filter(|window| {
+            let sides = [window.x, window.y, window.width, window.height];
+            sides.iter().all(|side| side.is_finite()) && window.width > 0.0 && window.height > 0.0
+        })
+    }
+}
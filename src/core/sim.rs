@@ -0,0 +1,96 @@
+//! Playing whole games between bots without a user interface, and summarising the results.
+
+use super::bot::{self, Bot};
+use super::game::Game;
+use super::rules::RuleSet;
+use super::score::GameRng;
+
+/// Play a game to the end with a bot for each player, in turn order, and return the finished
+/// game.
+pub fn play_game(rules: RuleSet, rng: GameRng, bots: &mut [&mut dyn Bot]) -> Game {
+    let mut game = Game::new(rules, rng, bots.len());
+    while !game.is_complete() {
+        if game.any_rolling() {
+            game.stop_roll();
+            continue;
+        }
+        let bot = &mut *bots[game.player()];
+        let mv = bot::choose_move(&game, bot).expect("a bot didn't choose a move");
+        game.play(mv).expect("bot moves are always allowed");
+    }
+    game
+}
+
+/// A summary of a set of scores.
+#[derive(Debug, Clone, Default)]
+pub struct ScoreStats {
+    scores: Vec<u16>,
+}
+
+impl ScoreStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, score: u16) {
+        self.scores.push(score);
+    }
+
+    pub fn scores(&self) -> &[u16] {
+        &self.scores
+    }
+
+    pub fn count(&self) -> usize {
+        self.scores.len()
+    }
+
+    pub fn mean(&self) -> f64 {
+        if self.scores.is_empty() {
+            return 0.0;
+        }
+        let total: u64 = self.scores.iter().map(|score| u64::from(*score)).sum();
+        total as f64 / self.scores.len() as f64
+    }
+
+    pub fn std_dev(&self) -> f64 {
+        if self.scores.is_empty() {
+            return 0.0;
+        }
+        let mean = self.mean();
+        let variance = self
+            .scores
+            .iter()
+            .map(|score| (f64::from(*score) - mean).powi(2))
+            .sum::<f64>()
+            / self.scores.len() as f64;
+        variance.sqrt()
+    }
+
+    pub fn min(&self) -> Option<u16> {
+        self.scores.iter().copied().min()
+    }
+
+    pub fn max(&self) -> Option<u16> {
+        self.scores.iter().copied().max()
+    }
+
+    /// How many scores fall in each range of `width` points, as (start of range, count), for
+    /// every range from the lowest score to the highest.
+    pub fn histogram(&self, width: u16) -> Vec<(u16, usize)> {
+        let (min, max) = match (self.min(), self.max()) {
+            (Some(min), Some(max)) => (min / width * width, max),
+            _ => return Vec::new(),
+        };
+        (min..=max)
+            .step_by(width as usize)
+            .map(|start| {
+                let count = self
+                    .scores
+                    .iter()
+                    .filter(|score| (start..start.saturating_add(width)).contains(*score))
+                    .count();
+                (start, count)
+            })
+            .collect()
+    }
+}
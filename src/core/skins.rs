@@ -0,0 +1,90 @@
+//! The looks the dice can be given, chosen for each player.
+//!
+//! A skin is only colors and the shape of the pips, so the `Die` widget can draw any of them.
+
+/// A color as red, green and blue.
+pub type Rgb = (u8, u8, u8);
+
+/// The shape of the pips on a die.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PipStyle {
+    /// Filled circles.
+    Round,
+    /// Filled squares.
+    Square,
+    /// Circles drawn as rings, as though lit from the inside.
+    Ring,
+}
+
+/// The colors of a die with a skin.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SkinColors {
+    pub body: Rgb,
+    pub pips: Rgb,
+    /// The border around a die that isn't held. A held die's is always the theme's accent.
+    pub border: Rgb,
+}
+
+/// How a die looks.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Skin {
+    pub name: &'static str,
+    /// The colors of the die, or `None` to follow the theme.
+    pub colors: Option<SkinColors>,
+    pub pips: PipStyle,
+}
+
+/// Dice in the colors of the theme, which everyone has unless they choose another.
+pub const CLASSIC: Skin = Skin {
+    name: "Classic",
+    colors: None,
+    pips: PipStyle::Round,
+};
+
+/// All the skins to choose from, in the order they're gone through.
+pub const SKINS: [Skin; 4] = [
+    CLASSIC,
+    Skin {
+        name: "Wooden",
+        colors: Some(SkinColors {
+            body: (0xc8, 0x9b, 0x62),
+            pips: (0x4a, 0x2c, 0x12),
+            border: (0x7a, 0x4e, 0x26),
+        }),
+        pips: PipStyle::Round,
+    },
+    Skin {
+        name: "Casino red",
+        colors: Some(SkinColors {
+            body: (0xb0, 0x14, 0x24),
+            pips: (0xff, 0xff, 0xff),
+            border: (0xe8, 0xe8, 0xe8),
+        }),
+        pips: PipStyle::Square,
+    },
+    Skin {
+        name: "Neon",
+        colors: Some(SkinColors {
+            body: (0x10, 0x10, 0x18),
+            pips: (0x39, 0xff, 0x14),
+            border: (0xff, 0x2a, 0xd4),
+        }),
+        pips: PipStyle::Ring,
+    },
+];
+
+/// The skin called `name`, or the classic one if there isn't one, such as for a player saved
+/// before there were skins.
+pub fn by_name(name: &str) -> Skin {
+    SKINS
+        .iter()
+        .find(|skin| skin.name == name)
+        .copied()
+        .unwrap_or(CLASSIC)
+}
+
+/// The skin after the one called `name`, going back to the first after the last.
+pub fn next(name: &str) -> Skin {
+    let current = SKINS.iter().position(|skin| skin.name == name).unwrap_or(0);
+    SKINS[(current + 1) % SKINS.len()]
+}
@@ -0,0 +1,152 @@
+//! Rolling real dice: smart dice on the table say where they landed, and the game's dice land
+//! there too, instead of where the generator would have them.
+//!
+//! Only GoDice are understood so far. Each sends a message as it starts rolling and another once
+//! it's still, with which way up it is as an acceleration from `x`, `y` and `z`. Die `n` on the
+//! table is die `n` in the game: the dice held on screen are put to one side and the others
+//! rolled, and the roll lands once every one of those has landed.
+
+use super::game::Game;
+use super::score::Score;
+
+/// What GoDice are called when they advertise, followed by their colour and a number.
+pub const GODICE_NAME: &str = "GoDice";
+
+/// GoDice only come with six sides.
+pub const SIDES: u8 = 6;
+
+/// Which way gravity pulls on a GoDice for each face to be up, in the die's own axes.
+const FACE_VECTORS: [(u8, [i32; 3]); 6] = [
+    (1, [-64, 0, 0]),
+    (2, [0, 0, 64]),
+    (3, [0, 64, 0]),
+    (4, [0, -64, 0]),
+    (5, [0, 0, -64]),
+    (6, [64, 0, 0]),
+];
+
+/// Something a die on the table said.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiceMessage {
+    /// It has started rolling.
+    Rolling,
+    /// It has come to rest on this face.
+    Landed(Score),
+    /// Something else, like its battery or colour.
+    Other,
+}
+
+impl DiceMessage {
+    /// Read a message sent by a GoDice. It has landed once it's stable, even if it was moved or
+    /// tilted to get there.
+    pub fn from_godice(bytes: &[u8]) -> Self {
+        let vector = match bytes {
+            [b'R'] => return DiceMessage::Rolling,
+            [b'S', x, y, z] | [b'F' | b'M' | b'T', b'S', x, y, z] => [*x, *y, *z],
+            _ => return DiceMessage::Other,
+        };
+        let vector = vector.map(|axis| axis as i8 as i32);
+        let (face, _) = FACE_VECTORS
+            .iter()
+            .min_by_key(|(_, face)| {
+                face.iter()
+                    .zip(&vector)
+                    .map(|(a, b)| (a - b) * (a - b))
+                    .sum::<i32>()
+            })
+            .expect("there are faces");
+        DiceMessage::Landed(Score(*face))
+    }
+}
+
+/// Where each die on the table has landed since the game last took its faces.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Table {
+    landed: Vec<Option<Score>>,
+}
+
+impl Table {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Note what die `die` said.
+    pub fn receive(&mut self, die: usize, message: DiceMessage) {
+        if die >= self.landed.len() {
+            self.landed.resize(die + 1, None);
+        }
+        match message {
+            DiceMessage::Rolling => self.landed[die] = None,
+            DiceMessage::Landed(face) => self.landed[die] = Some(face),
+            DiceMessage::Other => (),
+        }
+    }
+
+    /// The faces for the dice rolling in `game`, once each of them has landed on the table,
+    /// forgetting them so they aren't used again for the next roll.
+    pub fn take_faces(&mut self, game: &Game) -> Option<Vec<Score>> {
+        let rolling: Vec<usize> = game
+            .dice()
+            .iter()
+            .enumerate()
+            .filter(|(_, die)| die.value.is_none())
+            .map(|(idx, _)| idx)
+            .collect();
+        if rolling.is_empty() {
+            return None;
+        }
+        let faces = rolling
+            .iter()
+            .map(|&idx| self.landed.get(idx).copied().flatten())
+            .collect::<Option<Vec<_>>>()?;
+        for idx in rolling {
+            self.landed[idx] = None;
+        }
+        Some(faces)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{moves::Move, rules::RuleSet, score::GameRng};
+
+    #[test]
+    fn godice_say_which_way_up_they_are() {
+        assert_eq!(DiceMessage::from_godice(b"R"), DiceMessage::Rolling);
+        assert_eq!(
+            DiceMessage::from_godice(&[b'S', 60, 3, 250]),
+            DiceMessage::Landed(Score(6))
+        );
+        // -62 as a byte
+        assert_eq!(
+            DiceMessage::from_godice(&[b'M', b'S', 2, 5, 194]),
+            DiceMessage::Landed(Score(5))
+        );
+        assert_eq!(DiceMessage::from_godice(b"Bat\x50"), DiceMessage::Other);
+    }
+
+    #[test]
+    fn a_roll_lands_once_every_rolled_die_has() {
+        let mut game = Game::new(RuleSet::yatzy(), GameRng::from_seed(1), 1);
+        let mut table = Table::new();
+        game.play(Move::Roll).unwrap();
+        for die in 0..5 {
+            assert_eq!(table.take_faces(&game), None);
+            table.receive(die, DiceMessage::Landed(Score(die as u8 + 1)));
+        }
+        let faces = table.take_faces(&game).unwrap();
+        game.land(faces);
+        assert_eq!(game.scores().unwrap()[4], Score(5));
+
+        // the faces from before aren't used again
+        game.play(Move::ToggleHold(0)).unwrap();
+        game.play(Move::Roll).unwrap();
+        assert_eq!(table.take_faces(&game), None);
+        for die in 1..5 {
+            table.receive(die, DiceMessage::Rolling);
+            table.receive(die, DiceMessage::Landed(Score(6)));
+        }
+        assert_eq!(table.take_faces(&game), Some(vec![Score(6); 4]));
+    }
+}
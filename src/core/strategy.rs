@@ -0,0 +1,483 @@
+//! The optimal strategy for a whole game of standard Yatzy, as a precomputed table.
+//!
+//! The expectimax solver only plays for the best score this turn. Playing a whole game perfectly
+//! needs the expected final score from every state the scorecard can be in between turns: which
+//! categories are filled, and how far the upper section is towards its bonus. Working that out
+//! takes a while, so `generate` does it once (see the `generate-strategy` subcommand) and the
+//! table is saved to disk. During a game, `global` loads it when first needed, and each decision
+//! is then a single turn's worth of expectimax on top of the table.
+
+use std::{
+    collections::HashMap,
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use once_cell::sync::OnceCell;
+
+use super::expectimax::holds_for;
+use super::game::Game;
+use super::rules::RuleSet;
+use super::score::Score;
+use super::scorecard::{Scorecard, Slot};
+
+/// The first bytes of a strategy file.
+const MAGIC: &[u8; 4] = b"YZST";
+/// The version of the strategy file layout, changed whenever old files can't be read.
+const VERSION: u8 = 1;
+/// The number of categories in standard Yatzy, one bit each in a state's mask.
+const CATEGORIES: usize = 15;
+/// Upper totals at or above the bonus threshold are all the same state.
+const UPPER_STATES: usize = 64;
+/// The number of states the table has a value for.
+const STATES: usize = (1 << CATEGORIES) * UPPER_STATES;
+
+/// The expected final score from every state between turns, for standard Yatzy.
+pub struct StrategyTable {
+    rules: RuleSet,
+    dice: DiceTables,
+    /// By `mask * UPPER_STATES + upper`, the points still to come from that state with optimal
+    /// play, including any upper bonus not yet won.
+    values: Vec<f32>,
+}
+
+static GLOBAL: OnceCell<Option<StrategyTable>> = OnceCell::new();
+
+/// Where the strategy table is saved, in the user's config directory.
+pub fn default_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("yatzy").join("strategy.bin"))
+}
+
+/// The table saved at `default_path`, loaded the first time it's asked for, or `None` if it
+/// hasn't been generated.
+///
+/// A table that can't be read is reported once on stderr, and then treated as missing.
+pub fn global() -> Option<&'static StrategyTable> {
+    GLOBAL
+        .get_or_init(|| {
+            let path = default_path()?;
+            StrategyTable::load(&path).unwrap_or_else(|e| {
+                eprintln!("couldn't load the strategy table: {:#}", e);
+                None
+            })
+        })
+        .as_ref()
+}
+
+/// The global table, if there is one and it was made for these rules.
+pub fn for_rules(rules: &RuleSet) -> Option<&'static StrategyTable> {
+    global().filter(|table| table.applies_to(rules))
+}
+
+impl StrategyTable {
+    /// Work out the whole table. This takes a while, so `progress` is called with the number of
+    /// scorecard masks done so far and the number there are.
+    ///
+    /// Filling a category only ever sets a bit, so every state's successors have larger masks
+    /// and are already done when the masks are worked through from the top.
+    pub fn generate(mut progress: impl FnMut(usize, usize)) -> Self {
+        let rules = RuleSet::yatzy();
+        let mut table = Self {
+            dice: DiceTables::new(&rules),
+            rules,
+            values: vec![0.0; STATES],
+        };
+        let reachable = reachable_upper_totals();
+        let full = (1 << CATEGORIES) - 1;
+        for mask in (0..full).rev() {
+            for (upper, &can_reach) in reachable[mask & 0b11_1111].iter().enumerate() {
+                if can_reach {
+                    table.values[index(mask, upper)] = table.turn_value(mask, upper);
+                }
+            }
+            progress(full - mask, full);
+        }
+        table
+    }
+
+    /// Read the table saved at `path`, or `None` if there isn't one.
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e).with_context(|| format!("reading {}", path.display())),
+        };
+        let header = MAGIC.len() + 1;
+        if bytes.len() < header || &bytes[..MAGIC.len()] != MAGIC {
+            bail!("{} isn't a strategy table", path.display());
+        }
+        if bytes[MAGIC.len()] != VERSION {
+            bail!(
+                "{} is from a different version, generate it again",
+                path.display()
+            );
+        }
+        if bytes.len() != header + STATES * 4 {
+            bail!("{} is the wrong size", path.display());
+        }
+        let values = bytes[header..]
+            .chunks_exact(4)
+            .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect();
+        let rules = RuleSet::yatzy();
+        Ok(Some(Self {
+            dice: DiceTables::new(&rules),
+            rules,
+            values,
+        }))
+    }
+
+    /// Write the table to `path`, replacing whatever was saved there.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+        }
+        let mut bytes = Vec::with_capacity(MAGIC.len() + 1 + self.values.len() * 4);
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        for value in &self.values {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        fs::write(path, bytes).with_context(|| format!("writing {}", path.display()))
+    }
+
+    /// Whether the table was made for these rules. Only standard Yatzy has a table, and the
+    /// name doesn't matter, so house rules that are really the same game can use it too.
+    pub fn applies_to(&self, rules: &RuleSet) -> bool {
+        let table = &self.rules;
+        rules.dice == table.dice
+            && rules.sides == table.sides
+            && rules.rolls == table.rolls
+            && rules.categories == table.categories
+            && rules.columns == table.columns
+            && rules.scoring == table.scoring
+            && rules.upper_bonus_threshold == table.upper_bonus_threshold
+            && rules.upper_bonus == table.upper_bonus
+            && rules.yatzy_bonus == table.yatzy_bonus
+            && rules.extra_yatzy_bonus == table.extra_yatzy_bonus
+            && rules.yatzy_score == table.yatzy_score
+            && !rules.saved_rolls
+            && !rules.forced_order
+    }
+
+    /// The expected final score of a new game with optimal play.
+    pub fn game_value(&self) -> f32 {
+        self.values[index(0, 0)]
+    }
+
+    /// The expected final score of the current player's game with optimal play from the start
+    /// of their next turn, counting the points they already have.
+    pub fn value(&self, game: &Game) -> f32 {
+        let card = &game.sheet().columns()[0];
+        let (mask, upper) = state(card, &self.rules);
+        f32::from(card.total()) + self.values[index(mask, upper)]
+    }
+
+    /// Which of the current dice to hold before the next roll for the best final score, or
+    /// `None` if the dice are rolling. Holding every die means the hand should be scored now.
+    pub fn best_holds(&self, game: &Game) -> Option<Vec<bool>> {
+        let dice = game.scores()?;
+        let kept = self.best_kept(game)?;
+        Some(holds_for(&dice, game, &kept))
+    }
+
+    /// The faces to keep from the current dice before the next roll for the best final score,
+    /// sorted, or `None` if the dice are rolling.
+    pub fn best_kept(&self, game: &Game) -> Option<Vec<u8>> {
+        let dice = game.scores()?;
+        Some(self.dice.keeps[self.best_keep(game, &dice)].clone())
+    }
+
+    /// The slot to place the current dice in for the best final score, or `None` if the dice
+    /// are rolling or every slot is filled.
+    pub fn best_slot(&self, game: &Game) -> Option<Slot> {
+        let dice = game.scores()?;
+        let hand = self.dice.hand(&dice);
+        let (mask, upper) = state(&game.sheet().columns()[0], &self.rules);
+        let (category, _) = self.best_placement(hand, mask, upper)?;
+        Some(Slot::new(self.rules.categories[category]))
+    }
+
+    /// The expected final points from keeping the given faces and rerolling the rest, with the
+    /// game's rolls left, not counting points already scored.
+    pub fn keep_value(&self, game: &Game, kept: &[Score]) -> f32 {
+        let (mask, upper) = state(&game.sheet().columns()[0], &self.rules);
+        let values = self.hand_values(mask, upper, self.rolls_left(game).saturating_sub(1));
+        let mut faces: Vec<u8> = kept.iter().map(|die| die.0).collect();
+        faces.sort_unstable();
+        self.dice.keep_value(self.dice.keep_index[&faces], &values)
+    }
+
+    /// The expected final points from placing the current dice in a slot, not counting points
+    /// already scored, or `None` if the dice are rolling.
+    pub fn placement_value(&self, game: &Game, slot: Slot) -> Option<f32> {
+        let dice = game.scores()?;
+        let hand = self.dice.hand(&dice);
+        let category = self
+            .rules
+            .categories
+            .iter()
+            .position(|category| *category == slot.category)?;
+        let (mask, upper) = state(&game.sheet().columns()[0], &self.rules);
+        Some(self.place(hand, category, mask, upper))
+    }
+
+    /// The best dice to keep from the current hand, as an index into the keeps.
+    fn best_keep(&self, game: &Game, dice: &[Score]) -> usize {
+        let hand = self.dice.hand(dice);
+        let rolls_left = self.rolls_left(game);
+        if rolls_left == 0 {
+            return self.dice.hand_keep[hand];
+        }
+        let (mask, upper) = state(&game.sheet().columns()[0], &self.rules);
+        let values = self.hand_values(mask, upper, rolls_left - 1);
+        self.dice.best_keep(hand, &values).0
+    }
+
+    fn rolls_left(&self, game: &Game) -> u8 {
+        game.rolls_left().min(self.rules.rolls)
+    }
+
+    /// The expected points still to come from a state, at the start of a turn.
+    fn turn_value(&self, mask: usize, upper: usize) -> f32 {
+        let values = self.hand_values(mask, upper, self.rules.rolls - 1);
+        self.dice
+            .keep_value(self.dice.keep_index[&Vec::new()], &values)
+    }
+
+    /// The expected points still to come from each hand in a state, with some rolls left.
+    fn hand_values(&self, mask: usize, upper: usize, rolls_left: u8) -> Vec<f32> {
+        let mut values: Vec<f32> = (0..self.dice.hands.len())
+            .map(|hand| {
+                self.best_placement(hand, mask, upper)
+                    .map_or(0.0, |(_, v)| v)
+            })
+            .collect();
+        for _ in 0..rolls_left {
+            values = self.dice.reroll(&values);
+        }
+        values
+    }
+
+    /// The best open category for a hand, and the points still to come after placing it there.
+    fn best_placement(&self, hand: usize, mask: usize, upper: usize) -> Option<(usize, f32)> {
+        (0..CATEGORIES)
+            .filter(|category| mask & (1 << category) == 0)
+            .map(|category| (category, self.place(hand, category, mask, upper)))
+            .max_by(|(_, a), (_, b)| a.partial_cmp(b).expect("values are never NaN"))
+    }
+
+    /// The points now, plus any bonus, plus the points to come, for placing a hand in a category.
+    fn place(&self, hand: usize, category: usize, mask: usize, upper: usize) -> f32 {
+        let points = self.dice.points[hand][category];
+        let mut next_upper = upper;
+        let mut bonus = 0;
+        if self.rules.categories[category].is_upper() {
+            let threshold = usize::from(self.rules.upper_bonus_threshold);
+            next_upper = (upper + usize::from(points)).min(threshold);
+            if upper < threshold && next_upper == threshold {
+                bonus = self.rules.upper_bonus;
+            }
+        }
+        let next = index(mask | (1 << category), next_upper);
+        f32::from(points + bonus) + self.values[next]
+    }
+}
+
+fn index(mask: usize, upper: usize) -> usize {
+    mask * UPPER_STATES + upper
+}
+
+/// A scorecard's state: the mask of filled categories, and the upper total up to the threshold.
+fn state(card: &Scorecard, rules: &RuleSet) -> (usize, usize) {
+    let mask = rules
+        .categories
+        .iter()
+        .enumerate()
+        .filter(|(_, category)| card.is_filled(**category))
+        .fold(0, |mask, (bit, _)| mask | (1 << bit));
+    let upper = card.upper_total().min(rules.upper_bonus_threshold);
+    (mask, usize::from(upper))
+}
+
+/// For each mask of filled upper categories, which upper totals can be reached. Every other
+/// state is skipped when generating, which saves a lot of time.
+///
+/// This relies on the six upper categories coming first, from ones to sixes.
+fn reachable_upper_totals() -> Vec<[bool; UPPER_STATES]> {
+    let mut reachable = vec![[false; UPPER_STATES]; 1 << 6];
+    reachable[0][0] = true;
+    for mask in 1..reachable.len() {
+        let bit = mask.trailing_zeros() as usize;
+        let face = bit + 1;
+        let before = reachable[mask & !(1 << bit)];
+        for (total, _) in before.iter().enumerate().filter(|(_, reached)| **reached) {
+            for count in 0..=5 {
+                reachable[mask][(total + face * count).min(UPPER_STATES - 1)] = true;
+            }
+        }
+    }
+    reachable
+}
+
+/// Every hand of dice and every set of dice that can be kept, as indices, with the chances of
+/// rolling from one to the other, so a turn can be solved without any hashing.
+struct DiceTables {
+    /// Every sorted hand.
+    hands: Vec<Vec<u8>>,
+    hand_index: HashMap<Vec<u8>, usize>,
+    /// Every sorted set of dice that can be kept, from none to all of them.
+    keeps: Vec<Vec<u8>>,
+    keep_index: HashMap<Vec<u8>, usize>,
+    /// For each keep, the hands rerolling the rest can give, with their probabilities.
+    rolls: Vec<Vec<(usize, f32)>>,
+    /// For each hand, the distinct keeps taken from it.
+    hand_keeps: Vec<Vec<usize>>,
+    /// For each hand, the keep holding all of it.
+    hand_keep: Vec<usize>,
+    /// For each hand, the points it scores in each category.
+    points: Vec<Vec<u16>>,
+}
+
+impl DiceTables {
+    fn new(rules: &RuleSet) -> Self {
+        let keeps: Vec<Vec<u8>> = (0..=rules.dice)
+            .flat_map(|count| multisets(count, rules.sides))
+            .collect();
+        let keep_index: HashMap<_, _> = keeps
+            .iter()
+            .enumerate()
+            .map(|(idx, keep)| (keep.clone(), idx))
+            .collect();
+        let hands = multisets(rules.dice, rules.sides);
+        let hand_index: HashMap<_, _> = hands
+            .iter()
+            .enumerate()
+            .map(|(idx, hand)| (hand.clone(), idx))
+            .collect();
+
+        let rolls = keeps
+            .iter()
+            .map(|keep| {
+                let rerolled = rules.dice - keep.len();
+                let total = u32::from(rules.sides).pow(rerolled as u32);
+                let mut counts: HashMap<usize, u32> = HashMap::new();
+                for outcome in 0..total {
+                    let mut rest = outcome;
+                    let mut hand = keep.clone();
+                    for _ in 0..rerolled {
+                        hand.push((rest % u32::from(rules.sides)) as u8 + 1);
+                        rest /= u32::from(rules.sides);
+                    }
+                    hand.sort_unstable();
+                    *counts.entry(hand_index[&hand]).or_insert(0) += 1;
+                }
+                counts
+                    .into_iter()
+                    .map(|(hand, n)| (hand, n as f32 / total as f32))
+                    .collect()
+            })
+            .collect();
+        let hand_keeps = hands
+            .iter()
+            .map(|hand| {
+                let mut subs: Vec<usize> = super::expectimax::sub_multisets(hand)
+                    .iter()
+                    .map(|keep| keep_index[keep])
+                    .collect();
+                subs.sort_unstable();
+                subs.dedup();
+                subs
+            })
+            .collect();
+        let hand_keep = hands.iter().map(|hand| keep_index[hand]).collect();
+        let points = hands
+            .iter()
+            .map(|hand| {
+                let dice: Vec<Score> = hand.iter().map(|face| Score(*face)).collect();
+                rules
+                    .categories
+                    .iter()
+                    .map(|category| rules.score(*category, &dice))
+                    .collect()
+            })
+            .collect();
+
+        Self {
+            hands,
+            hand_index,
+            keeps,
+            keep_index,
+            rolls,
+            hand_keeps,
+            hand_keep,
+            points,
+        }
+    }
+
+    fn hand(&self, dice: &[Score]) -> usize {
+        let mut hand: Vec<u8> = dice.iter().map(|die| die.0).collect();
+        hand.sort_unstable();
+        self.hand_index[&hand]
+    }
+
+    /// The expected value of rerolling everything but a keep, given the value of each hand after
+    /// the roll.
+    fn keep_value(&self, keep: usize, values: &[f32]) -> f32 {
+        self.rolls[keep]
+            .iter()
+            .map(|(hand, probability)| probability * values[*hand])
+            .sum()
+    }
+
+    /// The best keep from a hand, and its value, given the value of each hand after the roll.
+    fn best_keep(&self, hand: usize, values: &[f32]) -> (usize, f32) {
+        let all = self.hand_keep[hand];
+        // keeping everything is tried first, so it wins when nothing is better
+        let mut best = (all, values[hand]);
+        for keep in &self.hand_keeps[hand] {
+            let value = self.keep_value(*keep, values);
+            if value > best.1 {
+                best = (*keep, value);
+            }
+        }
+        best
+    }
+
+    /// The value of each hand with one more roll left, given the values with one fewer.
+    fn reroll(&self, values: &[f32]) -> Vec<f32> {
+        let keep_values: Vec<f32> = (0..self.keeps.len())
+            .map(|keep| self.keep_value(keep, values))
+            .collect();
+        self.hand_keeps
+            .iter()
+            .map(|keeps| {
+                keeps
+                    .iter()
+                    .map(|keep| keep_values[*keep])
+                    .fold(f32::NEG_INFINITY, f32::max)
+            })
+            .collect()
+    }
+}
+
+/// Every sorted multiset of `count` faces from 1 to `sides`.
+fn multisets(count: usize, sides: u8) -> Vec<Vec<u8>> {
+    let mut sets = vec![Vec::new()];
+    for _ in 0..count {
+        sets = sets
+            .into_iter()
+            .flat_map(|set: Vec<u8>| {
+                let lowest = set.last().copied().unwrap_or(1);
+                (lowest..=sides).map(move |face| {
+                    let mut set = set.clone();
+                    set.push(face);
+                    set
+                })
+            })
+            .collect();
+    }
+    sets
+}
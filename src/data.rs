@@ -0,0 +1,114 @@
+//! Druid `Data` implementations for the core and network types, which don't depend on druid
+//! themselves.
+
+use druid::Data;
+
+use crate::core::{
+    game::Game,
+    highlights::Highlights,
+    history::GameRecord,
+    moves::Move,
+    rules::{Faces, RuleSet},
+    save::SlotInfo,
+    score::Score,
+    scorecard::{ScoreSheet, Scorecard},
+    settings::{ColorPalette, Layout, Settings, Theme},
+    skins::Skin,
+};
+use crate::net::discovery::LanGame;
+
+impl Data for Score {
+    fn same(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+impl Data for Scorecard {
+    fn same(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+impl Data for ScoreSheet {
+    fn same(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+impl Data for Skin {
+    fn same(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+impl Data for Faces {
+    fn same(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+impl Data for Move {
+    fn same(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+impl Data for RuleSet {
+    fn same(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+impl Data for Game {
+    fn same(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+impl Data for Highlights {
+    fn same(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+impl Data for GameRecord {
+    fn same(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+impl Data for SlotInfo {
+    fn same(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+impl Data for LanGame {
+    fn same(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+impl Data for Theme {
+    fn same(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+impl Data for ColorPalette {
+    fn same(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+impl Data for Layout {
+    fn same(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+impl Data for Settings {
+    fn same(&self, other: &Self) -> bool {
+        self == other
+    }
+}
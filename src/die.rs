@@ -2,90 +2,76 @@
 
 use anyhow::Error;
 use crossbeam_channel as channel;
+use druid::kurbo::{Circle, RoundedRect};
+use druid::piet::{FontBuilder, Text, TextLayout, TextLayoutBuilder};
 use druid::widget::{Align, Flex, Label, TextBox};
 use druid::{
-    AppDelegate, AppLauncher, BoxConstraints, Color, Command, Data, DelegateCtx, Env, Event,
-    EventCtx, LayoutCtx, Lens, LifeCycle, LifeCycleCtx, LocalizedString, PaintCtx, Rect,
-    RenderContext, Selector, Size, Target, TimerToken, UpdateCtx, Widget, WidgetExt, WindowDesc,
-    WindowId,
+    theme, Affine, AppDelegate, AppLauncher, BoxConstraints, Color, Command, Data, DelegateCtx,
+    Env, Event, EventCtx, Key, LayoutCtx, Lens, LifeCycle, LifeCycleCtx, LocalizedString, PaintCtx,
+    Point, Rect, RenderContext, Selector, Size, Target, TimerToken, UpdateCtx, Vec2, Widget,
+    WidgetExt, WindowDesc, WindowId,
 };
 use rand::prelude::*;
 use std::{convert::TryFrom, thread, time::Duration};
 
-const ROLL_RATE: Duration = Duration::from_millis(100);
-
-/// A value representing a value, for example a die that has stopped moving.
-#[derive(Debug, Copy, Clone, PartialEq, Data)]
-pub struct Score(pub u8);
-
-impl Score {
-    /// Create a new score with the given value.
-    pub fn new(score: u8) -> Self {
-        Self(score)
-    }
-
-    /// Locations of the dice points, used in painting. Only supports 0-6.
-    fn points(self) -> &'static [(f64, f64)] {
-        match self.0 {
-            0 => &[],
-            1 => &[(4.0, 4.0)],
-            2 => &[(4.0, 3.0), (4.0, 5.0)],
-            3 => &[(4.0, 2.0), (4.0, 4.0), (4.0, 6.0)],
-            4 => &[(2.0, 2.0), (2.0, 6.0), (6.0, 2.0), (6.0, 6.0)],
-            5 => &[(2.0, 2.0), (2.0, 6.0), (6.0, 2.0), (6.0, 6.0), (4.0, 4.0)],
-            6 => &[
-                (2.0, 2.0),
-                (2.0, 4.0),
-                (2.0, 6.0),
-                (6.0, 2.0),
-                (6.0, 4.0),
-                (6.0, 6.0),
-            ],
-            _ => panic!("die score of {} not supported when drawing points", self.0),
-        }
-    }
-
-    /// Create a Score with a random value between 1 and 6, for a six-sided die.
-    pub fn random_die() -> Self {
-        Self::random(1, 7)
-    }
-
-    /// Create a Score with a random value in the given range.
-    pub fn random(low: u8, hi: u8) -> Self {
-        // maybe todo: SmallRng
-        let mut rng = thread_rng();
-        let n: u8 = rng.gen_range(low, hi);
-        Self(n)
-    }
-
-    /// Create a Score with a random value between 1 and 6, that isn't the current value.
-    pub fn different_random_die(self) -> Self {
-        self.different_random(1, 7)
-    }
+use crate::core::{
+    poker,
+    rules::Faces,
+    score::Score,
+    skins::{self, PipStyle, Rgb, Skin},
+};
+use crate::theme::{player_color, ACCENT_COLOR, DIE_COLOR, DIM_PIP_COLOR, PIP_COLOR};
+use crate::{animation_time, DICE_PER_ROW, LABEL_SPACING};
 
-    /// Create a Score with a random value in a range, that isn't the current value.
-    pub fn different_random(self, low: u8, hi: u8) -> Self {
-        let old = self.0;
-        assert!(
-            low <= old && old <= hi,
-            "the previous value {} must be in the range [{}, {}]"
-        );
-        let mut rng = thread_rng();
-        // Smaller range because we are going to shift numbers >= the previous value.
-        let n: u8 = rng.gen_range(low, hi - 1);
-        Self(if n >= old { n + 1 } else { n })
-    }
-}
+/// How round the corners of the dice are.
+pub const CORNER_RADIUS: Key<f64> = Key::new("yatzy.die.corner-radius");
+/// The width of the border around the dice.
+pub const BORDER_WIDTH: Key<f64> = Key::new("yatzy.die.border-width");
+/// The color of the border around a die that isn't held.
+pub const BORDER_COLOR: Key<Color> = Key::new("yatzy.die.border-color");
 
-impl From<u8> for Score {
-    fn from(val: u8) -> Self {
-        Self(val)
-    }
+const ROLL_RATE: Duration = Duration::from_millis(100);
+/// The size of a die when there's no telling how much room there is. Dice are drawn on a grid of
+/// 9 by 9, so their sizes are kept to multiples of 9 for the pips to line up with the pixels.
+const SIZE: f64 = 9.0 * 4.0;
+/// The smallest a die is shrunk to, which can still be seen and clicked.
+const MIN_SIZE: f64 = 9.0 * 3.0;
+/// The biggest a die grows to in a wide window.
+const MAX_SIZE: f64 = 9.0 * 8.0;
+/// The radius of a pip, in squares of the grid the die is drawn on.
+const PIP_RADIUS: f64 = 0.65;
+/// How far the mouse has to move with the button down for a click on a die to be a drag.
+const DRAG_DISTANCE: f64 = 6.0;
+
+/// Locations of the dice points, used in painting, or `None` for a score above 6, which is
+/// drawn as a number instead.
+fn points(score: Score) -> Option<&'static [(f64, f64)]> {
+    Some(match score.0 {
+        0 => &[],
+        1 => &[(4.0, 4.0)],
+        2 => &[(4.0, 3.0), (4.0, 5.0)],
+        3 => &[(4.0, 2.0), (4.0, 4.0), (4.0, 6.0)],
+        4 => &[(2.0, 2.0), (2.0, 6.0), (6.0, 2.0), (6.0, 6.0)],
+        5 => &[(2.0, 2.0), (2.0, 6.0), (6.0, 2.0), (6.0, 6.0), (4.0, 4.0)],
+        6 => &[
+            (2.0, 2.0),
+            (2.0, 4.0),
+            (2.0, 6.0),
+            (6.0, 2.0),
+            (6.0, 4.0),
+            (6.0, 6.0),
+        ],
+        _ => return None,
+    })
 }
 
-impl From<Score> for u8 {
-    fn from(val: Score) -> Self {
-        val.0
+/// What is written on a face that isn't drawn with pips.
+fn face_text(faces: Faces, sides: u8, score: Score) -> String {
+    match (faces, score.0) {
+        (Faces::Poker, _) => poker::face_name(score).to_string(),
+        // a 6 and a 9 look the same upside down, so they're marked like on real dice
+        (Faces::Pips, 6) | (Faces::Pips, 9) if sides >= 9 => format!("{}.", score.0),
+        (Faces::Pips, value) => value.to_string(),
     }
 }
 
@@ -118,16 +104,47 @@ pub struct DieData {
     ///
     /// Not bright can be used to indicate that the die is not selected, for example for re-rolls.
     pub bright: bool,
+    /// Whether the die is held, meaning it will not be included in the next roll.
+    held: bool,
+    /// Whether the game is paused, which stops a rolling die on the face it's showing.
+    paused: bool,
+    /// How many places along the tray the die has just been dragged, to be moved there. The
+    /// dice in the rows below count on from the end of the row above.
+    moved_by: i32,
+    /// The number of faces on the die.
+    sides: u8,
+    /// What is drawn on the faces.
+    faces: Faces,
+    /// How the die looks.
+    skin: Skin,
+    /// The seat of the player rolling it, whose color its border is, or `None` when there's no
+    /// one else to tell them apart from.
+    player: Option<usize>,
 }
 
 impl DieData {
-    pub fn new(value: u8) -> Self {
+    pub fn new(value: u8, sides: u8, faces: Faces) -> Self {
         Self {
             state: DieState::new(value),
             bright: true,
+            held: false,
+            paused: false,
+            moved_by: 0,
+            sides,
+            faces,
+            skin: skins::CLASSIC,
+            player: None,
         }
     }
 
+    pub fn sides(&self) -> u8 {
+        self.sides
+    }
+
+    pub fn faces(&self) -> Faces {
+        self.faces
+    }
+
     pub fn is_rolling(&self) -> bool {
         self.state.is_rolling()
     }
@@ -157,18 +174,69 @@ impl DieData {
         self.bright = bright;
         self
     }
+
+    pub fn is_held(&self) -> bool {
+        self.held
+    }
+
+    pub fn set_held(&mut self, held: bool) -> &mut Self {
+        self.held = held;
+        self
+    }
+
+    pub fn toggle_held(&mut self) -> &mut Self {
+        self.held = !self.held;
+        self
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn set_paused(&mut self, paused: bool) -> &mut Self {
+        self.paused = paused;
+        self
+    }
+
+    pub fn skin(&self) -> Skin {
+        self.skin
+    }
+
+    pub fn set_skin(&mut self, skin: Skin) -> &mut Self {
+        self.skin = skin;
+        self
+    }
+
+    pub fn player(&self) -> Option<usize> {
+        self.player
+    }
+
+    pub fn set_player(&mut self, player: Option<usize>) -> &mut Self {
+        self.player = player;
+        self
+    }
+
+    pub fn moved_by(&self) -> i32 {
+        self.moved_by
+    }
 }
 
 pub struct Die {
     rolling_timer: Option<TimerToken>,
     rolling_score: Score,
+    /// Where the mouse went down on the die, while the button is held.
+    pressed: Option<Point>,
+    /// How far the die has been dragged from its place, to draw it under the mouse.
+    dragged: Vec2,
 }
 
 impl Die {
     pub fn new() -> Self {
         Self {
             rolling_timer: None,
-            rolling_score: Score::random_die(),
+            rolling_score: Score::random_die(&mut thread_rng()),
+            pressed: None,
+            dragged: Vec2::ZERO,
         }
     }
 
@@ -181,15 +249,51 @@ impl Die {
 }
 
 impl Widget<DieData> for Die {
-    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut DieData, _env: &Env) {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut DieData, env: &Env) {
         match event {
             Event::Timer(tok) if self.rolling_timer.map(|t| t == *tok).unwrap_or(false) => {
-                if data.is_rolling() {
-                    self.rolling_score = self.rolling_score.different_random_die();
-                    self.rolling_timer = Some(ctx.request_timer(ROLL_RATE));
+                self.rolling_timer = None;
+                if data.is_rolling() && !data.is_paused() {
+                    // the rolling animation is cosmetic, so doesn't use the game's generator
+                    self.rolling_score = self
+                        .rolling_score
+                        .different_random_face(&mut thread_rng(), data.sides());
+                    self.rolling_timer = Some(ctx.request_timer(animation_time(ROLL_RATE, env)));
                 }
                 ctx.request_paint();
             }
+            Event::MouseDown(mouse) if !data.is_rolling() => {
+                self.pressed = Some(mouse.pos);
+                ctx.set_active(true);
+                ctx.set_handled();
+            }
+            Event::MouseMove(mouse) if ctx.is_active() => {
+                if let Some(pressed) = self.pressed {
+                    self.dragged = mouse.pos - pressed;
+                    ctx.request_paint();
+                }
+            }
+            Event::MouseUp(mouse) if ctx.is_active() => {
+                ctx.set_active(false);
+                let pressed = match self.pressed.take() {
+                    Some(pressed) => pressed,
+                    None => return,
+                };
+                self.dragged = Vec2::ZERO;
+                let moved = mouse.pos - pressed;
+                if moved.hypot() < DRAG_DISTANCE {
+                    // clicking holds or releases the die, so it's left out of the next roll or not
+                    data.toggle_held();
+                } else {
+                    // dragging it moves it to wherever it was dropped in the tray
+                    let size = ctx.size();
+                    let across = (moved.x / (size.width + LABEL_SPACING)).round() as i32;
+                    let down = (moved.y / (size.height + LABEL_SPACING)).round() as i32;
+                    data.moved_by = across + down * DICE_PER_ROW as i32;
+                }
+                ctx.set_handled();
+                ctx.request_paint();
+            }
             _ => (),
         }
     }
@@ -203,11 +307,16 @@ impl Widget<DieData> for Die {
     ) {
     }
 
-    fn update(&mut self, ctx: &mut UpdateCtx, old_data: &DieData, data: &DieData, _env: &Env) {
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: &DieData, data: &DieData, env: &Env) {
+        if old_data.is_paused() != data.is_paused() {
+            // a tick from before the pause may never come, so the roll starts again afterwards
+            self.rolling_timer = None;
+        }
         match (data.is_rolling(), old_data.is_rolling()) {
             (true, false) => {
-                // Setup the rolling effect.
-                self.rolling_timer = Some(ctx.request_timer(ROLL_RATE));
+                // Setup the rolling effect, starting from a face this die actually has.
+                self.rolling_score = Score::random_face(&mut thread_rng(), data.sides());
+                self.rolling_timer = Some(ctx.request_timer(animation_time(ROLL_RATE, env)));
             }
             (false, true) => {
                 // Stop rolling effect on next tick (don't redraw yet).
@@ -217,7 +326,10 @@ impl Widget<DieData> for Die {
                 ctx.request_paint();
             }
             (true, true) => {
-                // Do nothing (continue to roll)
+                // Continue to roll, starting again if the game was paused
+                if self.rolling_timer.is_none() && !data.is_paused() {
+                    self.rolling_timer = Some(ctx.request_timer(animation_time(ROLL_RATE, env)));
+                }
             }
         }
     }
@@ -229,35 +341,123 @@ impl Widget<DieData> for Die {
         _data: &DieData,
         _env: &Env,
     ) -> Size {
-        const SIZE: f64 = 9.0 * 4.0;
-        bc.constrain((SIZE, SIZE))
+        // as wide as there's room for, for example a share of the tray
+        let width = bc.max().width;
+        let size = if width.is_finite() {
+            ((width / 9.0).floor() * 9.0).max(MIN_SIZE).min(MAX_SIZE)
+        } else {
+            SIZE
+        };
+        bc.constrain((size, size))
     }
 
     fn paint(&mut self, ctx: &mut PaintCtx, data: &DieData, env: &Env) {
+        if self.dragged != Vec2::ZERO {
+            ctx.transform(Affine::translate(self.dragged));
+        }
         let score = self.score(data);
         let size = ctx.size();
         let bg = Rect::ZERO.with_size(size);
         let x_unit = size.width / 9.0;
         let y_unit = size.height / 9.0;
 
-        let square = |xy: (f64, f64)| {
+        // each pip is centered in a square of the grid, and a little wider than it
+        let radius = PIP_RADIUS * x_unit.min(y_unit);
+        let center = |xy: (f64, f64)| {
             let (x, y) = xy;
-            Rect::new(
-                x * x_unit,
-                y * y_unit,
-                (x + 1.0) * x_unit,
-                (y + 1.0) * y_unit,
-            )
+            Point::new((x + 0.5) * x_unit, (y + 0.5) * y_unit)
         };
 
-        let white_b = ctx.solid_brush(Color::WHITE);
-        let black_b = ctx.solid_brush(Color::BLACK);
+        // a skin with its own colors draws over the theme's, other than the accent of a held die
+        let rgb = |(r, g, b): Rgb| Color::rgb8(r, g, b);
+        let (body_color, pip_color, dim_pip_color, border_color) = match data.skin().colors {
+            Some(colors) => (
+                rgb(colors.body),
+                rgb(colors.pips),
+                rgb(colors.pips).with_alpha(0.35),
+                rgb(colors.border),
+            ),
+            None => (
+                env.get(DIE_COLOR),
+                env.get(PIP_COLOR),
+                env.get(DIM_PIP_COLOR),
+                env.get(BORDER_COLOR),
+            ),
+        };
+        let body_b = ctx.solid_brush(body_color);
+        // in a game with others, whose roll it is shows in the border rather than the skin's
+        let border_b = match (data.is_held(), data.player()) {
+            (true, _) => ctx.solid_brush(env.get(ACCENT_COLOR)),
+            (false, Some(player)) => ctx.solid_brush(player_color(player)),
+            (false, None) => ctx.solid_brush(border_color),
+        };
+        let face_b = if data.bright() {
+            ctx.solid_brush(pip_color)
+        } else {
+            ctx.solid_brush(dim_pip_color)
+        };
 
-        // border & background
-        ctx.fill(bg, &white_b);
-        ctx.fill(bg.inset((-x_unit, -y_unit)), &black_b);
-        for pt in score.points() {
-            ctx.fill(square(*pt), &white_b);
+        // the border is stroked along the middle of its width, so it's kept inside the die. A held
+        // die's is twice as thick, to be told apart without seeing its color.
+        let border = if data.is_held() {
+            env.get(BORDER_WIDTH) * 2.0
+        } else {
+            env.get(BORDER_WIDTH)
+        };
+        let body = RoundedRect::from_rect(bg.inset(-border / 2.0), env.get(CORNER_RADIUS));
+        ctx.fill(body, &body_b);
+        ctx.stroke(body, &border_b, border);
+        // dice with more than six sides are all numbered, rather than some having pips
+        let pips = match data.faces() {
+            Faces::Pips if data.sides() <= 6 => points(score),
+            _ => None,
+        };
+        match pips {
+            Some(pips) => {
+                for pt in pips {
+                    let center = center(*pt);
+                    match data.skin().pips {
+                        PipStyle::Round => ctx.fill(Circle::new(center, radius), &face_b),
+                        PipStyle::Square => {
+                            let corner = Vec2::new(radius, radius);
+                            ctx.fill(Rect::from_points(center - corner, center + corner), &face_b)
+                        }
+                        // the ring is kept inside the circle the other pips fill
+                        PipStyle::Ring => {
+                            let width = radius * 0.4;
+                            let ring = Circle::new(center, radius - width / 2.0);
+                            ctx.stroke(ring, &face_b, width)
+                        }
+                    }
+                }
+            }
+            None => {
+                // draw the number or card rank in the middle of the face, smaller when it's
+                // too long to fit
+                let text = face_text(data.faces(), data.sides(), score);
+                let font_size = if text.len() > 2 {
+                    y_unit * 3.0
+                } else {
+                    y_unit * 4.0
+                };
+                let font_name = env.get(theme::FONT_NAME);
+                let font = ctx
+                    .text()
+                    .new_font_by_name(font_name, font_size)
+                    .build()
+                    .unwrap();
+                let layout = ctx
+                    .text()
+                    .new_text_layout(&font, &text, std::f64::INFINITY)
+                    .build()
+                    .unwrap();
+                // text is drawn from its baseline
+                let origin = Point::new(
+                    (size.width - layout.width()) / 2.0,
+                    (size.height + font_size * 0.75) / 2.0,
+                );
+                ctx.draw_text(&layout, origin, &face_b);
+            }
         }
     }
 }
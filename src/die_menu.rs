@@ -0,0 +1,73 @@
+//! A menu of things to do with a die, opened by right-clicking it.
+
+use druid::widget::Controller;
+use druid::{
+    Command, ContextMenu, Env, Event, EventCtx, LocalizedString, MenuDesc, MenuItem, MouseButton,
+    Widget,
+};
+
+use crate::core::moves::Move;
+use crate::{AppState, InGameState, HOLD_MATCHING, PLAY_MOVE, SHOW_ODDS};
+
+/// Opens the menu for the die at `place` in the tray when it's right-clicked. A left click is
+/// left to the die, to hold it or drag it.
+pub struct DieMenu {
+    place: usize,
+}
+
+impl DieMenu {
+    pub fn new(place: usize) -> Self {
+        Self { place }
+    }
+}
+
+impl<W: Widget<InGameState>> Controller<InGameState, W> for DieMenu {
+    fn event(
+        &mut self,
+        child: &mut W,
+        ctx: &mut EventCtx,
+        event: &Event,
+        data: &mut InGameState,
+        env: &Env,
+    ) {
+        match event {
+            Event::MouseDown(mouse) if mouse.button == MouseButton::Right => {
+                let idx = data.dice_order[self.place];
+                let die = &data.game.dice()[idx];
+                let hold = if die.held {
+                    LocalizedString::new("yatzy-die-release").with_placeholder("Release")
+                } else {
+                    LocalizedString::new("yatzy-die-hold").with_placeholder("Hold")
+                };
+                // only a die that has landed can be held, or has a face to match
+                let landed = die.value.is_some();
+                let can_hold = landed && data.game.can_hold();
+                let menu = MenuDesc::<AppState>::empty()
+                    .append(
+                        MenuItem::new(hold, Command::new(PLAY_MOVE, Move::ToggleHold(idx)))
+                            .disabled_if(|| !can_hold),
+                    )
+                    .append(
+                        MenuItem::new(
+                            LocalizedString::new("yatzy-die-hold-matching")
+                                .with_placeholder("Hold all matching"),
+                            Command::new(HOLD_MATCHING, idx),
+                        )
+                        .disabled_if(|| !can_hold),
+                    )
+                    .append_separator()
+                    .append(
+                        MenuItem::new(
+                            LocalizedString::new("yatzy-die-odds")
+                                .with_placeholder("Show probabilities"),
+                            Command::new(SHOW_ODDS, idx),
+                        )
+                        .disabled_if(|| !landed),
+                    );
+                ctx.show_context_menu(ContextMenu::new(menu, mouse.window_pos));
+                ctx.set_handled();
+            }
+            _ => child.event(ctx, event, data, env),
+        }
+    }
+}
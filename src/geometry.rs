@@ -0,0 +1,58 @@
+//! Remembers where the main window is, so it opens in the same place next time.
+
+use druid::widget::Controller;
+use druid::{Env, Event, EventCtx, Point, Size, Widget};
+
+use crate::core::settings::{self, Settings, WindowGeometry};
+
+/// Keeps track of the window's size and position, and saves them in the settings when the
+/// window closes and its widgets are dropped.
+pub struct RememberGeometry {
+    position: Option<Point>,
+    size: Option<Size>,
+}
+
+impl RememberGeometry {
+    pub fn new() -> Self {
+        Self {
+            position: None,
+            size: None,
+        }
+    }
+}
+
+impl<T, W: Widget<T>> Controller<T, W> for RememberGeometry {
+    fn event(&mut self, child: &mut W, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        if let Event::WindowSize(size) = event {
+            self.size = Some(*size);
+        }
+        // moving the window isn't an event, so its position is read whenever anything happens
+        self.position = Some(ctx.window().get_position());
+        child.event(ctx, event, data, env)
+    }
+}
+
+impl Drop for RememberGeometry {
+    fn drop(&mut self) {
+        let (position, size) = match (self.position, self.size) {
+            (Some(position), Some(size)) => (position, size),
+            _ => return,
+        };
+        let path = match settings::default_path() {
+            Some(path) => path,
+            None => return,
+        };
+        let saved = Settings::load(&path).and_then(|mut settings| {
+            settings.window = Some(WindowGeometry {
+                x: position.x,
+                y: position.y,
+                width: size.width,
+                height: size.height,
+            });
+            settings.save(&path)
+        });
+        if let Err(e) = saved {
+            eprintln!("warning: couldn't save where the window is: {:#}", e);
+        }
+    }
+}
@@ -0,0 +1,45 @@
+//! Pages of text, for how to play and about the game.
+
+use druid::widget::{Align, Button, Flex, Label, List, Scroll};
+use druid::{Data, Env, Lens, Widget, WidgetExt};
+use std::sync::Arc;
+
+use crate::{YatzyState, CLOSE_HELP, VERTICAL_WIDGET_SPACING};
+
+/// A page of text, for how to play or about the game.
+#[derive(Debug, Clone, Data, Lens)]
+pub(crate) struct HelpState {
+    /// The screen to go back to.
+    pub(crate) back: Arc<YatzyState>,
+    pub(crate) title: String,
+    pub(crate) lines: Arc<Vec<String>>,
+}
+
+impl HelpState {
+    pub(crate) fn new(title: impl Into<String>, lines: Vec<String>, back: YatzyState) -> Self {
+        Self {
+            back: Arc::new(back),
+            title: title.into(),
+            lines: Arc::new(lines),
+        }
+    }
+}
+
+/// A page of text with a way back, for how to play or about the game.
+pub(crate) fn build_help() -> impl Widget<HelpState> {
+    let title = Label::new(|data: &HelpState, _env: &Env| data.title.clone());
+    let lines =
+        List::new(|| Label::new(|line: &String, _env: &Env| line.clone())).lens(HelpState::lines);
+    let back_btn = Button::new("Back").on_click(|ctx, _data: &mut HelpState, _env| {
+        ctx.submit_command(CLOSE_HELP, None);
+    });
+
+    let layout = Flex::column()
+        .with_child(title)
+        .with_spacer(VERTICAL_WIDGET_SPACING)
+        .with_flex_child(Scroll::new(lines).vertical(), 1.0)
+        .with_spacer(VERTICAL_WIDGET_SPACING)
+        .with_child(back_btn);
+
+    Align::centered(layout)
+}
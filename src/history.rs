@@ -0,0 +1,292 @@
+//! How everyone has done, and the games finished on this computer to watch again.
+
+use druid::widget::{Align, Button, Either, Flex, Label, List, Scroll, SizedBox, TextBox};
+use druid::{commands, Command, Data, Env, FileDialogOptions, FileSpec, Lens, Widget, WidgetExt};
+use std::{cmp::Ordering, path::Path, sync::Arc};
+
+use crate::core::{
+    date, export,
+    history::{self, GameRecord, History},
+    paper,
+    profiles::{self, Profile},
+};
+use crate::menu::MenuState;
+use crate::{
+    all_profiles, Result, YatzyState, CLOSE_HISTORY, CLOSE_STATISTICS, ERROR_COLOR, LABEL_SPACING,
+    OPEN_HISTORY, TEXT_BOX_WIDTH, VERTICAL_WIDGET_SPACING, WATCH_HISTORY_GAME,
+};
+
+/// How everyone who has finished a game on this computer has done.
+#[derive(Debug, Clone, Data, Lens)]
+pub(crate) struct StatsState {
+    /// The main menu to go back to.
+    pub(crate) menu: MenuState,
+    /// Everyone's profile, the best average first.
+    pub(crate) profiles: Arc<Vec<Profile>>,
+    /// How importing games played on paper went.
+    pub(crate) status: String,
+}
+
+impl StatsState {
+    pub(crate) fn new(menu: MenuState) -> Self {
+        let mut state = Self {
+            menu,
+            profiles: Arc::new(Vec::new()),
+            status: String::new(),
+        };
+        state.load_profiles();
+        state
+    }
+
+    /// Read everyone's profile again.
+    pub(crate) fn load_profiles(&mut self) {
+        let mut profiles = (*all_profiles()).clone();
+        profiles.sort_by(|a, b| {
+            b.average()
+                .partial_cmp(&a.average())
+                .unwrap_or(Ordering::Equal)
+        });
+        self.profiles = Arc::new(profiles);
+    }
+
+    /// Add the games played on paper in a CSV file to the history, and count them in the
+    /// profiles of the people who played them. Games imported before are left out.
+    pub(crate) fn import(&mut self, path: &Path) {
+        let imported = paper::load(path).and_then(|games| {
+            let history_path = history::default_path()
+                .ok_or_else(|| anyhow::anyhow!("there's no data directory for the history"))?;
+            let mut history = History::open(&history_path)?;
+            let mut added = 0;
+            for game in &games {
+                if !history.record_paper(game)? {
+                    continue;
+                }
+                added += 1;
+                if let Some(profiles_path) = profiles::default_path() {
+                    let results: Vec<_> = game
+                        .players
+                        .iter()
+                        .enumerate()
+                        .map(|(seat, (name, total))| (name.clone(), *total, game.won(seat)))
+                        .collect();
+                    profiles::record_game(&profiles_path, &results)?;
+                }
+            }
+            Ok((added, games.len()))
+        });
+        self.status = match imported {
+            Ok((added, found)) if added == found => format!("Imported {} games.", added),
+            Ok((added, found)) => format!(
+                "Imported {} games. The other {} were imported before.",
+                added,
+                found - added
+            ),
+            Err(e) => format!("Couldn't import the games: {:#}", e),
+        };
+        self.load_profiles();
+    }
+}
+
+/// The games finished on this computer, to look through and watch again.
+#[derive(Debug, Clone, Data, Lens)]
+pub(crate) struct HistoryState {
+    /// The screen to go back to.
+    pub(crate) back: Arc<YatzyState>,
+    /// Only list the games this person played in, unless it's blank.
+    pub(crate) player: String,
+    /// Only list the games finished from this day on, like 2020-06-14, unless it's blank.
+    pub(crate) from: String,
+    /// Only list the games finished up to the end of this day, unless it's blank.
+    pub(crate) to: String,
+    /// The games found, most recent first.
+    pub(crate) games: Arc<Vec<GameRecord>>,
+    /// Why the games couldn't be listed, or that none were found.
+    pub(crate) status: String,
+}
+
+impl HistoryState {
+    pub(crate) fn new(back: Arc<YatzyState>) -> Self {
+        let mut state = Self {
+            back,
+            player: String::new(),
+            from: String::new(),
+            to: String::new(),
+            games: Arc::new(Vec::new()),
+            status: String::new(),
+        };
+        state.search();
+        state
+    }
+
+    /// List the games matching the filters.
+    pub(crate) fn search(&mut self) {
+        fn day(text: &str) -> Result<Option<u64>> {
+            if text.trim().is_empty() {
+                return Ok(None);
+            }
+            date::parse_day(text)
+                .map(Some)
+                .ok_or_else(|| anyhow::anyhow!("{:?} should be a day like 2020-06-14", text))
+        }
+        let player = Some(self.player.trim()).filter(|player| !player.is_empty());
+        let found = day(&self.from).and_then(|from| {
+            let until = day(&self.to)?.map(|to| to + 86_400);
+            let path = history::default_path()
+                .ok_or_else(|| anyhow::anyhow!("there's no data directory for the history"))?;
+            History::open(&path)?.search(player, from, until)
+        });
+        match found {
+            Ok(games) => {
+                self.status = if games.is_empty() {
+                    "No games found".into()
+                } else {
+                    String::new()
+                };
+                self.games = Arc::new(games);
+            }
+            Err(e) => self.status = format!("Couldn't search the history: {:#}", e),
+        }
+    }
+}
+
+pub(crate) fn build_statistics() -> impl Widget<StatsState> {
+    let profiles = List::new(|| {
+        Label::new(|profile: &Profile, _env: &Env| {
+            let percent = if profile.games == 0 {
+                0
+            } else {
+                profile.wins * 100 / profile.games
+            };
+            format!(
+                "{}: {} games, {} wins ({}%), average {:.1}, best {}",
+                profile.name,
+                profile.games,
+                profile.wins,
+                percent,
+                profile.average(),
+                profile.best
+            )
+        })
+    })
+    .lens(StatsState::profiles);
+    let empty = Label::new(|data: &StatsState, _env: &Env| {
+        if data.profiles.is_empty() {
+            "Nobody has finished a game yet".to_string()
+        } else {
+            String::new()
+        }
+    });
+    let status = Label::new(|data: &StatsState, _env: &Env| data.status.clone());
+    let history_btn = Button::new("History").on_click(|ctx, _data: &mut StatsState, _env| {
+        ctx.submit_command(OPEN_HISTORY, None);
+    });
+    // games played on paper, laid out as `core::paper` describes
+    let import_btn =
+        Button::new("Import paper games").on_click(|ctx, _data: &mut StatsState, _env| {
+            let scores = FileSpec::new("CSV", &[export::CSV_EXTENSION]);
+            let options = FileDialogOptions::new().allowed_types(vec![scores]);
+            ctx.submit_command(Command::new(commands::SHOW_OPEN_PANEL, options), None);
+        });
+    let back_btn = Button::new("Back").on_click(|ctx, _data: &mut StatsState, _env| {
+        ctx.submit_command(CLOSE_STATISTICS, None);
+    });
+
+    let layout = Flex::column()
+        .with_flex_child(Scroll::new(profiles).vertical(), 1.0)
+        .with_child(empty)
+        .with_spacer(VERTICAL_WIDGET_SPACING)
+        .with_child(status)
+        .with_spacer(VERTICAL_WIDGET_SPACING)
+        .with_child(
+            Flex::row()
+                .with_child(history_btn)
+                .with_spacer(LABEL_SPACING)
+                .with_child(import_btn)
+                .with_spacer(LABEL_SPACING)
+                .with_child(back_btn),
+        );
+
+    Align::centered(layout)
+}
+
+pub(crate) fn build_history() -> impl Widget<HistoryState> {
+    // who played and when, to narrow the games down
+    let filters = Flex::row()
+        .with_child(
+            TextBox::new()
+                .with_placeholder("player")
+                .fix_width(TEXT_BOX_WIDTH)
+                .lens(HistoryState::player),
+        )
+        .with_spacer(LABEL_SPACING)
+        .with_child(
+            TextBox::new()
+                .with_placeholder("from, e.g. 2020-06-01")
+                .fix_width(TEXT_BOX_WIDTH)
+                .lens(HistoryState::from),
+        )
+        .with_spacer(LABEL_SPACING)
+        .with_child(
+            TextBox::new()
+                .with_placeholder("to, e.g. 2020-06-30")
+                .fix_width(TEXT_BOX_WIDTH)
+                .lens(HistoryState::to),
+        )
+        .with_spacer(LABEL_SPACING)
+        .with_child(
+            Button::new("Search").on_click(|_ctx, data: &mut HistoryState, _env| data.search()),
+        );
+    // a line for each game, with a button to watch it again
+    let games = List::new(|| {
+        let description = Label::new(|game: &GameRecord, _env: &Env| {
+            let players: Vec<_> = game
+                .players
+                .iter()
+                .map(|(name, total)| format!("{} {}", name, total))
+                .collect();
+            if game.on_paper {
+                format!(
+                    "{}, {}: {}, on paper",
+                    game.date(),
+                    game.variant,
+                    players.join(", ")
+                )
+            } else {
+                format!(
+                    "{}, {}: {}, {} minutes",
+                    game.date(),
+                    game.variant,
+                    players.join(", "),
+                    (game.duration + 30) / 60
+                )
+            }
+        });
+        Flex::row()
+            .with_child(description)
+            .with_spacer(LABEL_SPACING)
+            .with_child(Either::new(
+                |game: &GameRecord, _env| game.on_paper,
+                SizedBox::empty(),
+                Button::new("Watch").on_click(|ctx, game: &mut GameRecord, _env| {
+                    ctx.submit_command(Command::new(WATCH_HISTORY_GAME, game.id), None);
+                }),
+            ))
+    })
+    .lens(HistoryState::games);
+    let status = Label::new(|data: &HistoryState, _env: &Env| data.status.clone())
+        .with_text_color(ERROR_COLOR);
+    let back_btn = Button::new("Back").on_click(|ctx, _data: &mut HistoryState, _env| {
+        ctx.submit_command(CLOSE_HISTORY, None);
+    });
+
+    let layout = Flex::column()
+        .with_child(filters)
+        .with_spacer(VERTICAL_WIDGET_SPACING)
+        .with_flex_child(Scroll::new(games).vertical(), 1.0)
+        .with_spacer(VERTICAL_WIDGET_SPACING)
+        .with_child(status)
+        .with_spacer(VERTICAL_WIDGET_SPACING)
+        .with_child(back_btn);
+
+    Align::centered(layout)
+}
@@ -0,0 +1,40 @@
+//! Yatzy without the window: the rules, scoring and turn logic in `core`, network play in `net`
+//! and the subcommands that run in a terminal in `cli`.
+//!
+//! Nothing here needs druid, so the `yatzy-cli` binary, the headless server and the tests build
+//! without it. The `gui` feature, on by default, adds the druid `Data` implementations the window
+//! needs for these types.
+
+use anyhow::Error;
+use std::sync::Arc;
+
+pub mod cli;
+pub mod core;
+#[cfg(feature = "gui")]
+mod data;
+pub mod net;
+
+use crate::core::{bot::BotRegistry, presets, rules::RuleSet};
+
+pub type Result<T = (), E = Error> = std::result::Result<T, E>;
+
+/// The bots that can be played against.
+///
+/// Register your own implementations of `Bot` here to play against them.
+pub fn bot_registry() -> BotRegistry {
+    BotRegistry::builtin()
+}
+
+/// The built-in variants, followed by the house rules saved by players.
+///
+/// House rules that can't be read are left out, with a warning.
+pub fn all_presets() -> Arc<Vec<RuleSet>> {
+    let mut all = RuleSet::presets();
+    if let Some(path) = presets::default_path() {
+        match presets::load(&path) {
+            Ok(saved) => all.extend(saved),
+            Err(err) => eprintln!("warning: couldn't load house rules: {:#}", err),
+        }
+    }
+    Arc::new(all)
+}
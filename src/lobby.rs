@@ -0,0 +1,191 @@
+//! The lobby, where players wait for a network game to start.
+
+use druid::widget::{Align, Button, Either, Flex, Label, List, SizedBox, ViewSwitcher};
+use druid::{Data, Env, Lens, Widget, WidgetExt};
+use std::sync::Arc;
+
+use crate::core::{ai, rules::RuleSet};
+use crate::net::{discovery::Advertisement, protocol::LobbyPlayer, ConnectionId, Session};
+use crate::qr::QrImage;
+use crate::{
+    bot_registry, build_kick_buttons, joined_names, StartingState, LABEL_SPACING, LEAVE_LOBBY,
+    START_GAME, VERTICAL_WIDGET_SPACING,
+};
+
+/// Waiting for players to join a network game before the host starts it.
+#[derive(Debug, Clone, Data, Lens)]
+pub(crate) struct LobbyState {
+    /// The starting screen to go back to. The host starts the game with its settings.
+    pub(crate) starting: StartingState,
+    pub(crate) session: Arc<Session>,
+    /// Whether this computer is the host, which chooses the variant and starts the game.
+    pub(crate) hosting: bool,
+    /// Everyone who has joined, and whether they're ready.
+    pub(crate) players: Arc<Vec<String>>,
+    /// Everyone who has joined when hosting, for turning them out.
+    pub(crate) joined: Arc<Vec<(ConnectionId, String)>>,
+    /// The name of the variant to be played.
+    pub(crate) variant: String,
+    /// Whether the person here is ready to play, when joining someone else's game.
+    pub(crate) ready: bool,
+    pub(crate) status: String,
+    /// The listing of the game on the local network, when hosting.
+    pub(crate) advertisement: Option<Arc<Advertisement>>,
+    /// The address others on the network can join at, when hosting, shown as a QR code.
+    pub(crate) join_address: Option<String>,
+}
+
+impl LobbyState {
+    pub(crate) fn new(starting: StartingState, session: Session, status: String) -> Self {
+        Self {
+            hosting: matches!(session, Session::Host(_)),
+            variant: starting.rules.name.clone(),
+            starting,
+            session: Arc::new(session),
+            players: Arc::new(Vec::new()),
+            joined: Arc::new(Vec::new()),
+            ready: false,
+            status,
+            advertisement: None,
+            join_address: None,
+        }
+    }
+
+    /// A line in the list of players.
+    pub(crate) fn describe(player: &LobbyPlayer) -> String {
+        if player.ready {
+            format!("{} (ready)", player.name)
+        } else {
+            player.name.clone()
+        }
+    }
+
+    /// Catch up with who has joined the game being hosted.
+    pub(crate) fn update_players(&mut self) {
+        if let Session::Host(host) = &*self.session {
+            let players = host.joined();
+            self.players = Arc::new(players.iter().map(|(_, p)| Self::describe(p)).collect());
+        }
+        self.joined = joined_names(&self.session);
+    }
+
+    /// Choose the variant to play, and show it to everyone who joined.
+    pub(crate) fn choose_rules(&mut self, rules: &RuleSet) {
+        if let Session::Host(host) = &*self.session {
+            self.starting.rules = rules.clone();
+            self.variant = rules.name.clone();
+            host.set_variant(&rules.name);
+        }
+    }
+
+    /// Tell the host the person here is ready to play, or no longer ready.
+    pub(crate) fn toggle_ready(&mut self) {
+        if let Session::Client(client) = &*self.session {
+            match client.set_ready(!self.ready) {
+                Ok(()) => self.ready = !self.ready,
+                Err(e) => self.status = format!("{:#}", e),
+            }
+        }
+    }
+}
+
+pub(crate) fn build_lobby() -> impl Widget<LobbyState> {
+    let status = Label::new(|data: &LobbyState, _env: &Env| data.status.clone());
+    let variant = Label::new(|data: &LobbyState, _env: &Env| format!("Variant: {}", data.variant));
+    // the host's computer plays for anyone who leaves
+    let stand_in = Label::new(|data: &LobbyState, _env: &Env| {
+        if data.hosting {
+            let bot = ai::stand_in(&data.starting.rules, &bot_registry());
+            format!("If someone leaves, the {} bot plays for them", bot)
+        } else {
+            String::new()
+        }
+    });
+
+    // everyone who has joined, each on a line
+    let players = Flex::column()
+        .with_child(Either::new(
+            |data: &LobbyState, _env| data.players.is_empty(),
+            Label::new("Nobody has joined yet"),
+            Label::new("Joined:"),
+        ))
+        .with_spacer(LABEL_SPACING)
+        .with_child(
+            List::new(|| Label::new(|name: &String, _env: &Env| name.clone()))
+                .lens(LobbyState::players),
+        );
+
+    // the host chooses the variant and starts the game, the others say when they're ready
+    let choose_rules =
+        ViewSwitcher::new(
+            |data: &LobbyState, _env| data.starting.presets.clone(),
+            |presets, _data, _env| {
+                let mut choices = Flex::row();
+                for rules in presets.iter().cloned() {
+                    choices.add_child(Button::new(rules.name.clone()).on_click(
+                        move |_ctx, data: &mut LobbyState, _env| data.choose_rules(&rules),
+                    ));
+                    choices.add_spacer(LABEL_SPACING);
+                }
+                Box::new(choices)
+            },
+        );
+    let start_game_btn =
+        Button::new("Start game!").on_click(|ctx, _data: &mut LobbyState, _env| {
+            ctx.submit_command(START_GAME, None);
+        });
+    let host_controls = Flex::column()
+        .with_child(build_kick_buttons().lens(LobbyState::joined))
+        .with_spacer(VERTICAL_WIDGET_SPACING)
+        .with_child(choose_rules)
+        .with_spacer(VERTICAL_WIDGET_SPACING)
+        .with_child(start_game_btn);
+    let ready_btn = Button::new(|data: &LobbyState, _env: &Env| {
+        if data.ready {
+            "Not ready".to_string()
+        } else {
+            "Ready".to_string()
+        }
+    })
+    .on_click(|_ctx, data: &mut LobbyState, _env| data.toggle_ready());
+    let controls = Either::new(
+        |data: &LobbyState, _env| data.hosting,
+        host_controls,
+        ready_btn,
+    );
+
+    // a code for phones to scan, with the address for typing in
+    let join_code = ViewSwitcher::new(
+        |data: &LobbyState, _env| data.join_address.clone(),
+        |address, _data, _env| match (address, address.as_deref().and_then(QrImage::new)) {
+            (Some(address), Some(qr)) => Box::new(
+                Flex::column()
+                    .with_child(Label::new(format!("Scan to join: {}", address)))
+                    .with_spacer(LABEL_SPACING)
+                    .with_child(qr),
+            ),
+            _ => Box::new(SizedBox::empty()),
+        },
+    );
+
+    let leave_btn = Button::new("Leave").on_click(|ctx, _data: &mut LobbyState, _env| {
+        ctx.submit_command(LEAVE_LOBBY, None);
+    });
+
+    let layout = Flex::column()
+        .with_child(status)
+        .with_spacer(VERTICAL_WIDGET_SPACING)
+        .with_child(variant)
+        .with_spacer(VERTICAL_WIDGET_SPACING)
+        .with_child(stand_in)
+        .with_spacer(VERTICAL_WIDGET_SPACING)
+        .with_child(players)
+        .with_spacer(VERTICAL_WIDGET_SPACING)
+        .with_child(join_code)
+        .with_spacer(VERTICAL_WIDGET_SPACING)
+        .with_child(controls)
+        .with_spacer(VERTICAL_WIDGET_SPACING)
+        .with_child(leave_btn);
+
+    Align::centered(layout)
+}
@@ -1,181 +1,4324 @@
-use anyhow::Error;
+use anyhow::Context;
 use crossbeam_channel as channel;
-use druid::widget::{Align, Button, Flex, Label, TextBox};
+use druid::widget::{
+    Align, Button, Checkbox, CrossAxisAlignment, Either, EnvScope, Flex, Label, List, RadioGroup,
+    Scroll, SizedBox, TextBox, ViewSwitcher,
+};
 use druid::{
-    lens::Field, AppDelegate, AppLauncher, BoxConstraints, Color, Command, Data, DelegateCtx, Env,
-    Event, EventCtx, LayoutCtx, Lens, LifeCycle, LifeCycleCtx, LocalizedString, PaintCtx, Rect,
-    RenderContext, Selector, Size, Target, TimerToken, UpdateCtx, Widget, WidgetExt, WindowDesc,
-    WindowId,
+    commands, AppDelegate, AppLauncher, Application, BoxConstraints, Color, Command, Data,
+    DelegateCtx, Env, Event, EventCtx, FileDialogOptions, FileSpec, HotKey, Key, KeyCode, KeyEvent,
+    LayoutCtx, Lens, LensExt, LifeCycle, LifeCycleCtx, LocalizedString, PaintCtx, Point, Rect,
+    RenderContext, Selector, Size, SysMods, Target, TimerToken, UpdateCtx, Widget, WidgetExt,
+    WindowDesc, WindowId,
 };
 use match_derive::Matcher;
 use rand::prelude::*;
-use std::{convert::TryFrom, thread, time::Duration};
+use std::{
+    collections::HashMap,
+    convert::TryFrom,
+    ffi::OsStr,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use yatzy::{all_presets, bot_registry, cli, core, net, Result};
 
+mod advise;
+mod autoplay;
+mod bluetooth;
+mod compact;
 mod die;
+mod die_menu;
+mod geometry;
+mod help;
+mod history;
+mod lobby;
+mod menu;
+mod notify;
+mod pause;
+mod playback;
+mod qr;
+mod replay_view;
+mod review;
+mod roll;
+mod saves;
+mod scorecard;
+mod settings_view;
+mod shortcuts;
+mod snapshot;
+mod theme;
+mod timer;
+mod toast;
 
-use die::{Die, DieData, Score};
-
-type Result<T = (), E = Error> = std::result::Result<T, E>;
+use crate::core::{
+    advice::{Advice, Position},
+    ai,
+    bot::BotRegistry,
+    correspondence::{self, GameFile},
+    date,
+    event::GameEvent,
+    expectimax, export, fuzzy,
+    game::{Doubt, Game, TurnPhase},
+    guide,
+    highlights::Highlights,
+    history::History,
+    moves::Move,
+    notation, odds,
+    poker::{self, PokerHand},
+    presets,
+    profiles::{self, Profile},
+    replay::{self, Replay},
+    roll_log,
+    rules::{Faces, RuleSet, YatzyBonus},
+    save::{self, SaveFile, SavedPlayer},
+    score::GameRng,
+    scorecard::{Category, ScoreSheet, Slot},
+    settings::{self, Layout, Settings},
+    skins,
+    smart_dice::{self, DiceMessage, Table},
+};
+use advise::Advise;
+use autoplay::AutoPlay;
+use bluetooth::DiceEvent;
+use compact::Compact;
+use die::{Die, DieData};
+use die_menu::DieMenu;
+use geometry::RememberGeometry;
+use help::{build_help, HelpState};
+use history::{build_history, build_statistics, HistoryState, StatsState};
+use lobby::{build_lobby, LobbyState};
+use menu::{build_menu, MenuState};
+use net::{
+    discovery::{self, Advertisement, Browser, LanGame},
+    protocol::{self, Succession},
+    sync, twitch, webhook, Client, ConnectionId, Host, NetEvent, Session, Transport,
+};
+use pause::Pausable;
+use replay_view::{build_replay, ReplayState};
+use review::{build_review, ReviewState};
+use roll::LandDice;
+use saves::{build_load_screen, LoadState};
+use scorecard::Scorecard;
+use settings_view::{build_settings, SettingsState};
+use shortcuts::Shortcuts;
+use timer::TurnTimer;
+use toast::ExpireToasts;
 
 const VERTICAL_WIDGET_SPACING: f64 = 20.0;
 const LABEL_SPACING: f64 = 4.0;
 const TEXT_BOX_WIDTH: f64 = 200.0;
-const WINDOW_TITLE: LocalizedString<YatzyState> = LocalizedString::new("Yatzy!");
-const ROLL: Selector<()> = Selector::new("die.roll");
-const STOP_ROLL: Selector<Score> = Selector::new("die.stop-roll");
+/// The width of the category names on the results screen.
+const RESULTS_NAME_WIDTH: f64 = 140.0;
+/// The width of each player's points on the results screen.
+const RESULTS_COLUMN_WIDTH: f64 = 90.0;
+/// The most people who can play at once, taking turns at the same computer.
+const MAX_PLAYERS: usize = 6;
+/// The team of each player in a team game, two against two with teammates taking turns in
+/// between the other team's.
+const TEAMS: [usize; 4] = [0, 1, 0, 1];
+const ERROR_COLOR: Color = Color::rgb8(0xff, 0x60, 0x60);
+/// The space around the name on each player's card.
+const CARD_PADDING: f64 = 4.0;
+/// The most dice laid out in a row, with any more in rows under them.
+const DICE_PER_ROW: usize = 6;
+/// The most actions listed in the command palette at once.
+const PALETTE_LENGTH: usize = 8;
+/// Text standing in for a button that can't be pressed yet.
+const DISABLED_COLOR: Color = Color::grey8(0x80);
+const WINDOW_TITLE: LocalizedString<AppState> = LocalizedString::new("Yatzy!");
+const SCORECARD_TITLE: LocalizedString<AppState> = LocalizedString::new("Yatzy! scorecard");
+/// The size of the scorecard's own window when it's popped out.
+const SCORECARD_WINDOW_SIZE: Size = Size::new(400.0, 500.0);
+/// The size of the main window the first time it's opened.
+const WINDOW_SIZE: Size = Size::new(600.0, 400.0);
+/// How fast the dice roll and computer players move, from the settings, where 1 is normal.
+const ANIMATION_SPEED: Key<f64> = Key::new("yatzy.animation-speed");
+/// Whether to play sounds, as set in the settings.
+const SOUNDS: Key<bool> = Key::new("yatzy.sounds");
+/// The width under which the game is laid out in one column, from the layout in the settings.
+const COMPACT_WIDTH: Key<f64> = Key::new("yatzy.compact-width");
+/// The width of the game under which the automatic layout is compact.
+const COMPACT_WINDOW_WIDTH: f64 = 480.0;
+/// Make a move in the game in progress.
+const PLAY_MOVE: Selector<Move> = Selector::new("play-move");
+const STOP_ROLL: Selector<()> = Selector::new("die.stop-roll");
+const UNDO: Selector<()> = Selector::new("undo");
+const REDO: Selector<()> = Selector::new("redo");
+/// Open or close the pause menu in the game in progress.
+const TOGGLE_PAUSE: Selector<()> = Selector::new("toggle-pause");
+/// Move the scorecard into a window of its own, to put it on another screen.
+const DETACH_SCORECARD: Selector<()> = Selector::new("detach-scorecard");
+/// Close the scorecard's own window, putting it back beside the game.
+const ATTACH_SCORECARD: Selector<()> = Selector::new("attach-scorecard");
+/// Hold every die showing the same face as the die at this index in the game.
+const HOLD_MATCHING: Selector<usize> = Selector::new("hold-matching");
+/// Show the chances of collecting more of the face the die at this index in the game shows.
+const SHOW_ODDS: Selector<usize> = Selector::new("show-odds");
+/// Do something picked in the command palette.
+const RUN_PALETTE: Selector<PaletteAction> = Selector::new("run-palette");
 const START_GAME: Selector<()> = Selector::new("start-game");
+const NEW_GAME: Selector<()> = Selector::new("new-game");
+/// Play the finished game again, with the same players and rules.
+const REMATCH: Selector<()> = Selector::new("rematch");
+/// Open the house rules editor from the starting screen.
+const EDIT_RULES: Selector<()> = Selector::new("edit-rules");
+/// Save the house rules being edited as a preset.
+const SAVE_RULES: Selector<()> = Selector::new("save-rules");
+/// Leave the house rules editor without saving.
+const CANCEL_RULES: Selector<()> = Selector::new("cancel-rules");
+/// Review the decisions made in the finished game.
+const REVIEW_GAME: Selector<()> = Selector::new("review-game");
+/// Go back from the game review to the results.
+const CLOSE_REVIEW: Selector<()> = Selector::new("close-review");
+/// Host a network game for others to join.
+const HOST_GAME: Selector<()> = Selector::new("host-game");
+/// Join a network game hosted on another computer.
+const JOIN_GAME: Selector<()> = Selector::new("join-game");
+/// Something happened in a network game.
+const NET_EVENT: Selector<NetEvent> = Selector::new("net-event");
+/// Look for smart dice to roll on the table.
+const CONNECT_DICE: Selector<()> = Selector::new("connect-dice");
+/// Something happened with the smart dice on the table.
+const DICE_EVENT: Selector<DiceEvent> = Selector::new("dice-event");
+/// Join the network game found on the local network at the given address.
+const JOIN_LAN_GAME: Selector<String> = Selector::new("join-lan-game");
+/// Leave the lobby of a network game for the starting screen.
+const LEAVE_LOBBY: Selector<()> = Selector::new("leave-lobby");
+/// Open the list of saved games.
+const OPEN_SAVES: Selector<()> = Selector::new("open-saves");
+/// Go back to the starting screen from the saved games.
+const CLOSE_SAVES: Selector<()> = Selector::new("close-saves");
+/// Carry on the game saved in the named slot.
+const LOAD_SLOT: Selector<String> = Selector::new("load-slot");
+/// Delete the game saved in the named slot.
+const DELETE_SLOT: Selector<String> = Selector::new("delete-slot");
+/// Turn a player out of the network game being hosted, banning them if set.
+const KICK_PLAYER: Selector<(ConnectionId, bool)> = Selector::new("kick-player");
+/// Open the history of finished games.
+const OPEN_HISTORY: Selector<()> = Selector::new("open-history");
+/// Go back from the history to where it was opened from.
+const CLOSE_HISTORY: Selector<()> = Selector::new("close-history");
+/// Watch the game in the history with the given id.
+const WATCH_HISTORY_GAME: Selector<i64> = Selector::new("watch-history-game");
+/// Watch the game written in the notation on the clipboard, from the starting screen.
+const WATCH_PASTED_GAME: Selector<()> = Selector::new("watch-pasted-game");
+/// Watch the replay of the finished game.
+const WATCH_REPLAY: Selector<()> = Selector::new("watch-replay");
+/// Stop watching a replay, going back to where it was opened from.
+const CLOSE_REPLAY: Selector<()> = Selector::new("close-replay");
+/// Go back to the main menu from the starting screen, the results or a game.
+const OPEN_MENU: Selector<()> = Selector::new("open-menu");
+/// Carry on the game saved most recently, from the main menu.
+const CONTINUE_GAME: Selector<()> = Selector::new("continue-game");
+/// Show how everyone has done, from the main menu.
+const OPEN_STATISTICS: Selector<()> = Selector::new("open-statistics");
+/// Go back to the main menu from the statistics.
+const CLOSE_STATISTICS: Selector<()> = Selector::new("close-statistics");
+/// Open the settings, from the main menu or a paused game.
+const OPEN_SETTINGS: Selector<()> = Selector::new("open-settings");
+/// Save the settings being changed, and go back to where they were opened from.
+const SAVE_SETTINGS: Selector<()> = Selector::new("save-settings");
+/// Leave the settings without saving.
+const CLOSE_SETTINGS: Selector<()> = Selector::new("close-settings");
+/// Show how to play the variant chosen on the main menu or being played.
+const OPEN_HOW_TO_PLAY: Selector<()> = Selector::new("open-how-to-play");
+/// Show the version of the game and who made it.
+const OPEN_ABOUT: Selector<()> = Selector::new("open-about");
+/// Go back from how to play or the about screen to where it was opened from.
+const CLOSE_HELP: Selector<()> = Selector::new("close-help");
+/// Show a message for a moment at the top of the window, after any already waiting.
+const SHOW_TOAST: Selector<String> = Selector::new("show-toast");
+
+/// Everything in the window: the screen it's showing, and the settings it's drawn with.
+#[derive(Debug, Clone, Data, Lens)]
+struct AppState {
+    screen: YatzyState,
+    /// The settings as they were last saved from the settings screen, which the `Env` is set
+    /// from.
+    settings: Settings,
+    /// The messages waiting to be shown in a toast, the one being shown first.
+    toasts: Arc<Vec<String>>,
+}
+
+impl AppState {
+    /// Save the settings being changed, using them from now on, and go back to where they were
+    /// opened from. If they can't be saved, the settings stay open showing why.
+    fn save_settings(&mut self) {
+        let state = match &mut self.screen {
+            YatzyState::Settings(state) => state,
+            _ => panic!("saving the settings when they aren't open"),
+        };
+        let settings = state.settings();
+        let saved = settings::default_path()
+            .ok_or_else(|| anyhow::anyhow!("there's no config directory for the settings"))
+            .and_then(|path| settings.save(&path));
+        if let Err(e) = saved {
+            state.status = format!("Couldn't save the settings: {:#}", e);
+            return;
+        }
+        let mut back = (*state.back).clone();
+        // a game set up from the menu starts from the new defaults
+        if let YatzyState::Menu(menu) = &mut back {
+            menu.starting.use_settings(&settings);
+        }
+        self.screen = back;
+        self.settings = settings;
+    }
+}
 
 #[derive(Debug, Clone, Data, Matcher)]
 #[matcher(matcher_name = Yatzy)]
 enum YatzyState {
+    Menu(MenuState),
     Starting(StartingState),
+    Lobby(LobbyState),
+    EditingRules(RulesEditorState),
     InGame(InGameState),
+    Finished(FinishedState),
+    Reviewing(ReviewState),
+    Loading(LoadState),
+    Watching(ReplayState),
+    History(HistoryState),
+    Statistics(StatsState),
+    Settings(SettingsState),
+    Help(HelpState),
 }
 
 impl YatzyState {
     fn start_game(&mut self) {
-        let d = DieData::new(6);
         match self {
-            YatzyState::Starting(state) => {
-                *self = YatzyState::InGame(InGameState {
-                    player_name: state.player_name.clone(),
-                    dice: [d, d, d, d, d],
-                })
+            YatzyState::Starting(state) => *self = YatzyState::InGame(state.start(None)),
+            YatzyState::Lobby(lobby) => {
+                let host = match &*lobby.session {
+                    Session::Host(host) => host,
+                    Session::Client(_) => return,
+                };
+                let joined = host.joined();
+                if let Some((_, player)) = joined.iter().find(|(_, player)| !player.ready) {
+                    lobby.status = format!("Waiting for {} to be ready", player.name);
+                    return;
+                }
+                let game = lobby.starting.start(Some(lobby.session.clone()));
+                *self = YatzyState::InGame(game)
             }
-            YatzyState::InGame(state) => panic!("starting a new game when already in game"),
+            _ => panic!("starting a new game when not on the starting screen"),
         }
     }
-}
 
-#[derive(Debug, Clone, Data, Lens)]
-struct StartingState {
-    player_name: String,
-}
+    /// Host a network game, and wait in the lobby for others to join.
+    fn host(&mut self, events: channel::Sender<NetEvent>) {
+        if let YatzyState::Starting(state) = self {
+            if let Some(host) = state.host(events) {
+                host.set_password(state.password());
+                let mut status = match host.fingerprint() {
+                    Some(fingerprint) => format!(
+                        "Hosting on port {}, encrypted. Others can join at tls:// and this \
+                         computer's address, and should see the fingerprint {}.",
+                        host.port(),
+                        fingerprint
+                    ),
+                    None => format!(
+                        "Hosting on port {}. Others can join at this computer's address.",
+                        host.port()
+                    ),
+                };
+                let encrypted = host.fingerprint().is_some();
+                let advertisement =
+                    match Advertisement::start(state.network_name(), host.port(), encrypted) {
+                        Ok(advertisement) => Some(Arc::new(advertisement)),
+                        Err(e) => {
+                            status.push_str(&format!(" It isn't listed on the network: {:#}", e));
+                            None
+                        }
+                    };
+                let join_address = discovery::local_ipv4().ok().map(|ip| {
+                    let scheme = if encrypted { "tls://" } else { "" };
+                    format!("{}{}:{}", scheme, ip, host.port())
+                });
+                let mut lobby = LobbyState::new(state.clone(), Session::Host(host), status);
+                lobby.advertisement = advertisement;
+                lobby.join_address = join_address;
+                *self = YatzyState::Lobby(lobby);
+            }
+        }
+    }
 
-#[derive(Debug, Clone, Data, Lens)]
-struct InGameState {
-    player_name: String,
-    dice: [DieData; 5],
-}
+    /// Join a network game, and wait in the lobby for the host to start it.
+    fn join(&mut self, events: channel::Sender<NetEvent>) {
+        if let YatzyState::Starting(state) = self {
+            if let Some((client, address)) = state.join(events) {
+                let mut status = format!("Joined {}. Waiting for the host to start.", address);
+                if let Some(fingerprint) = client.fingerprint() {
+                    status.push_str(&format!(
+                        " The game is encrypted, the host's fingerprint is {}.",
+                        fingerprint
+                    ));
+                }
+                let lobby = LobbyState::new(state.clone(), Session::Client(client), status);
+                *self = YatzyState::Lobby(lobby);
+            }
+        }
+    }
 
-pub fn main() -> Result {
-    // describe the main window
-    let main_window = WindowDesc::new(|| {
-        YatzyState::matcher()
-            .starting(build_starting())
-            .in_game(build_in_game())
-    })
-    .title(WINDOW_TITLE)
-    .window_size((400.0, 400.0));
+    /// Leave the lobby of a network game, closing it if this computer is the host.
+    fn leave_lobby(&mut self) {
+        match self {
+            YatzyState::Lobby(lobby) => *self = YatzyState::Starting(lobby.starting.clone()),
+            _ => panic!("leaving the lobby when not in it"),
+        }
+    }
 
-    // create the initial app state
-    let initial_state = YatzyState::Starting(StartingState {
-        player_name: "".into(),
-    });
+    /// Turn a player out of the network game being hosted, in the lobby or once it has started.
+    fn kick(&mut self, connection: ConnectionId, ban: bool) {
+        let session = match self {
+            YatzyState::Lobby(lobby) => Some(&*lobby.session),
+            YatzyState::InGame(state) => state.session.as_deref(),
+            _ => None,
+        };
+        if let Some(Session::Host(host)) = session {
+            host.kick(connection, ban);
+        }
+    }
 
-    // setup die rolling periodically
-    let launcher = AppLauncher::with_window(main_window);
-    let sink = launcher.get_external_handle();
-    thread::spawn(move || loop {
-        thread::sleep(Duration::from_millis(1_000));
-        sink.submit_command(ROLL, (), None).unwrap();
-        thread::sleep(Duration::from_millis(1_000));
-        sink.submit_command(STOP_ROLL, Score::random_die(), None)
-            .unwrap();
-    });
+    /// Carry on the game saved at `path`, where it stopped.
+    fn load_game(&mut self, path: &Path) {
+        if let YatzyState::Loading(state) = self {
+            match SaveFile::load(path) {
+                Ok(file) => {
+                    *self = YatzyState::InGame(InGameState::from_save(file));
+                    self.finish_game_if_complete();
+                }
+                Err(e) => state.status = format!("Couldn't load the game: {:#}", e),
+            }
+        }
+    }
 
-    // start the application
-    launcher.delegate(Delegate).launch(initial_state)?;
-    Ok(())
-}
+    /// Open the list of games saved in slots, from the starting screen.
+    fn open_saves(&mut self) {
+        match self {
+            YatzyState::Starting(_) => {
+                *self = YatzyState::Loading(LoadState::new(Arc::new(self.clone())))
+            }
+            _ => panic!("opening the saved games when not on the starting screen"),
+        }
+    }
 
-struct Delegate;
+    /// Go back from the saved games to where they were opened from.
+    fn close_saves(&mut self) {
+        match self {
+            YatzyState::Loading(state) => *self = (*state.back).clone(),
+            _ => panic!("closing the saved games when they aren't open"),
+        }
+    }
 
-impl AppDelegate<YatzyState> for Delegate {
-    fn command(
-        &mut self,
-        ctx: &mut DelegateCtx,
-        target: Target,
-        cmd: &Command,
-        data: &mut YatzyState,
-        env: &Env,
-    ) -> bool {
-        if cmd.is(ROLL) {
-            if let YatzyState::InGame(data) = data {
-                data.dice[0].set_rolling();
+    /// Carry on the game saved most recently, from the main menu. If it can't be loaded, the
+    /// saved games are left open showing why.
+    fn continue_game(&mut self) {
+        let name = match self {
+            YatzyState::Menu(menu) => match &menu.last_save {
+                Some(name) => name.clone(),
+                None => return,
+            },
+            _ => panic!("continuing a game when not on the main menu"),
+        };
+        *self = YatzyState::Loading(LoadState::new(Arc::new(self.clone())));
+        self.load_slot(&name);
+    }
+
+    /// Carry on the game saved in the named slot.
+    fn load_slot(&mut self, name: &str) {
+        if let Some(dir) = save::slots_dir() {
+            self.load_game(&save::slot_path(&dir, name));
+        }
+    }
+
+    /// Delete the game saved in the named slot, and list the rest.
+    fn delete_slot(&mut self, name: &str) {
+        if let YatzyState::Loading(state) = self {
+            let deleted = save::slots_dir()
+                .ok_or_else(|| anyhow::anyhow!("there's no config directory for saves"))
+                .and_then(|dir| save::delete_slot(&dir, name));
+            state.refresh();
+            if let Err(e) = deleted {
+                state.status = format!("Couldn't delete the game: {:#}", e);
             }
-            false
-        } else if cmd.is(START_GAME) {
-            data.start_game();
-            false
-        } else if let Some(score) = cmd.get(STOP_ROLL) {
-            if let YatzyState::InGame(data) = data {
-                data.dice[0].set_value(*score);
+        }
+    }
+
+    /// Carry on a game played by file from the file at `path`, at the turn of whoever plays
+    /// next.
+    fn open_game_file(&mut self, path: &Path) {
+        if let YatzyState::Starting(state) = self {
+            let opened = GameFile::load(path).and_then(|file| Ok((file.resume()?, file.players)));
+            match opened {
+                Ok((game, names)) => {
+                    let players = names
+                        .into_iter()
+                        .enumerate()
+                        .map(|(idx, name)| Player {
+                            name,
+                            bot: None,
+                            local: idx == game.player(),
+                            skin: skins::CLASSIC.name.to_string(),
+                        })
+                        .collect();
+                    let mut game = InGameState::from_game(players, game, state.turn_time());
+                    game.by_file = true;
+                    game.notified = game.game.events().len();
+                    *self = YatzyState::InGame(game);
+                    self.finish_game_if_complete();
+                }
+                Err(e) => state.file_status = format!("Couldn't open the game: {:#}", e),
             }
-            false
-        } else {
-            true
         }
     }
-}
 
-fn build_starting() -> impl Widget<StartingState> {
-    // a label that will determine its text based on the current app data.
-    let label = Label::new("Player name:");
+    /// Watch the replay saved at `path`, from the starting screen.
+    fn watch_replay(&mut self, path: &Path) {
+        if let YatzyState::Starting(state) = self {
+            let replay = if path.extension() == Some(OsStr::new(notation::EXTENSION)) {
+                fs::read_to_string(path)
+                    .with_context(|| format!("reading {}", path.display()))
+                    .and_then(|text| notation::parse(&text, &state.presets))
+            } else {
+                Replay::load(path)
+            };
+            match replay {
+                Ok(replay) => *self = YatzyState::Watching(ReplayState::new(replay, self.clone())),
+                Err(e) => state.file_status = format!("Couldn't open the replay: {:#}", e),
+            }
+        }
+    }
 
-    // a textbox that modifies `name`.
-    let textbox = TextBox::new()
-        .with_placeholder("e.g. Joe Bloggs")
-        .fix_width(TEXT_BOX_WIDTH)
-        .lens(StartingState::player_name);
+    /// Watch the game written in the notation on the clipboard, from the starting screen.
+    fn watch_pasted_game(&mut self) {
+        if let YatzyState::Starting(state) = self {
+            let replay = Application::global()
+                .clipboard()
+                .get_string()
+                .ok_or_else(|| anyhow::anyhow!("there's no text on the clipboard"))
+                .and_then(|text| notation::parse(&text, &state.presets));
+            match replay {
+                Ok(replay) => *self = YatzyState::Watching(ReplayState::new(replay, self.clone())),
+                Err(e) => state.file_status = format!("Couldn't read the game: {:#}", e),
+            }
+        }
+    }
 
-    let start_game_btn =
-        Button::new("Start game!").on_click(|ctx, _data: &mut StartingState, _env| {
-            ctx.submit_command(START_GAME, None);
+    /// Count the games played on paper in a CSV file in the history and statistics, from the
+    /// statistics.
+    fn import_paper_games(&mut self, path: &Path) {
+        if let YatzyState::Statistics(state) = self {
+            state.import(path);
+        }
+    }
+
+    /// Act on something that happened in a network game.
+    fn net_event(&mut self, event: NetEvent, events: channel::Sender<NetEvent>) {
+        match self {
+            YatzyState::Lobby(lobby) => match event {
+                NetEvent::Joined { .. } | NetEvent::Left { .. } | NetEvent::Ready { .. } => {
+                    lobby.update_players()
+                }
+                NetEvent::Lobby { players, variant } => {
+                    lobby.players = Arc::new(players.iter().map(LobbyState::describe).collect());
+                    lobby.variant = variant;
+                }
+                NetEvent::Started {
+                    rules,
+                    players,
+                    seat,
+                    turn_time,
+                } => {
+                    let players = players
+                        .into_iter()
+                        .enumerate()
+                        .map(|(idx, info)| Player {
+                            name: info.name,
+                            bot: info.bot,
+                            local: idx == seat,
+                            skin: skins::CLASSIC.name.to_string(),
+                        })
+                        .collect();
+                    // this copy never rolls, the dice all come from the host
+                    let rng = GameRng::from_seed(0);
+                    let mut game = InGameState::new(players, rules, rng, turn_time);
+                    game.session = Some(lobby.session.clone());
+                    *self = YatzyState::InGame(game);
+                }
+                NetEvent::Disconnected(reason) => {
+                    let mut starting = lobby.starting.clone();
+                    starting.network_status = format!("Left the game: {}", reason);
+                    *self = YatzyState::Starting(starting);
+                }
+                _ => (),
+            },
+            YatzyState::InGame(state) => {
+                match event {
+                    NetEvent::Move { seat, mv } => state.play_remote(seat, mv),
+                    NetEvent::Event(event) => state.apply_event(event),
+                    NetEvent::Left { connection, name } => state.player_left(connection, &name),
+                    NetEvent::Chat { from, text } => state.receive_chat(&from, &text),
+                    NetEvent::Dropped { name, .. } => state.toast(format!(
+                        "{} lost their connection, their seat is kept for them to rejoin",
+                        name
+                    )),
+                    NetEvent::Rejoined { name, .. } => state.toast(format!("{} is back", name)),
+                    NetEvent::Reconnecting(reason) => {
+                        state.connection = Connection::Reconnecting;
+                        state.error = Some(format!(
+                            "Lost the connection to the host ({}), rejoining...",
+                            reason
+                        ));
+                    }
+                    NetEvent::Resumed(events) => {
+                        state.connection = Connection::Connected;
+                        state.resume(&events)
+                    }
+                    NetEvent::Promoted {
+                        succession,
+                        grace_period,
+                        encrypted,
+                    } if !state.game.is_complete() => {
+                        state.take_over(&succession, grace_period, encrypted, events)
+                    }
+                    NetEvent::Disconnected(reason) => {
+                        state.connection = Connection::Disconnected;
+                        state.error = Some(format!("Lost the connection to the host: {}", reason));
+                    }
+                    _ => (),
+                }
+                if let Some(session) = &state.session {
+                    state.joined = joined_names(session);
+                }
+                self.finish_game_if_complete();
+            }
+            _ => (),
+        }
+    }
+
+    /// Move to the results screen if the game in progress is over.
+    fn finish_game_if_complete(&mut self) {
+        if let YatzyState::InGame(state) = self {
+            if state.game.is_complete() {
+                let mut finished = FinishedState {
+                    note: if state.by_file {
+                        state.error.clone().unwrap_or_default()
+                    } else {
+                        String::new()
+                    },
+                    players: state.players.clone(),
+                    sheets: Arc::new(state.game.sheets().to_vec()),
+                    rules: state.game.rules().clone(),
+                    turn_time: state.turn_time,
+                    highlights: Arc::new(Highlights::of(&state.game)),
+                    game: state.game.clone(),
+                    toasts: state.toasts.clone(),
+                };
+                // a game that was already over when it was opened has been counted before
+                if state.played {
+                    let mut notes = vec![finished.note.clone()];
+                    if let Err(e) = finished.record_profiles() {
+                        notes.push(format!("Couldn't update the profiles: {:#}", e));
+                    }
+                    notes.push(match finished.save_replay() {
+                        Ok(path) => format!("The replay is saved in {}.", path.display()),
+                        Err(e) => format!("Couldn't save the replay: {:#}", e),
+                    });
+                    if let Err(e) = finished.record_history(now().saturating_sub(state.started_at))
+                    {
+                        notes.push(format!("Couldn't add the game to the history: {:#}", e));
+                    }
+                    sync_in_background();
+                    notes.retain(|note| !note.is_empty());
+                    finished.note = notes.join(" ");
+                }
+                *self = YatzyState::Finished(finished);
+            }
+        }
+    }
+
+    /// Go to the starting screen to set up a game, from the main menu, a game or after one,
+    /// keeping the players' names.
+    fn new_game(&mut self) {
+        match self {
+            YatzyState::Menu(menu) => *self = YatzyState::Starting(menu.starting.clone()),
+            YatzyState::Finished(state) => *self = YatzyState::Starting(state.starting()),
+            // the game being played is left without saving, as when quitting to the menu
+            YatzyState::InGame(state) => {
+                let starting = StartingState::after(&state.players, &state.game, state.turn_time);
+                *self = YatzyState::Starting(starting)
+            }
+            _ => panic!("new game requested when not on the main menu, results or game"),
+        }
+    }
+
+    /// Play the finished game again, with the same players and rules.
+    fn rematch(&mut self) {
+        match self {
+            YatzyState::Finished(state) => *self = YatzyState::InGame(state.starting().start(None)),
+            _ => panic!("rematch requested before the game finished"),
+        }
+    }
+
+    /// Open the house rules editor, starting from the variant chosen on the starting screen.
+    fn edit_rules(&mut self) {
+        match self {
+            YatzyState::Starting(state) => {
+                *self = YatzyState::EditingRules(RulesEditorState::new(state.clone()))
+            }
+            _ => panic!("editing rules when not on the starting screen"),
+        }
+    }
+
+    /// Save the house rules being edited, and go back to the starting screen with them chosen.
+    ///
+    /// If the rules aren't valid or can't be saved, the editor stays open showing why.
+    fn save_rules(&mut self) {
+        let state = match self {
+            YatzyState::EditingRules(state) => state,
+            _ => panic!("saving rules when not in the rules editor"),
+        };
+        let result = state.rules().and_then(|rules| {
+            let path = presets::default_path()
+                .ok_or_else(|| "there is nowhere to save presets on this system".to_string())?;
+            presets::save_one(&path, &rules).map_err(|err| format!("{:#}", err))?;
+            Ok(rules)
         });
+        match result {
+            Ok(rules) => {
+                let mut starting = state.starting.clone();
+                starting.presets = all_presets();
+                starting.rules = rules;
+                *self = YatzyState::Starting(starting);
+            }
+            Err(err) => state.error = Some(err),
+        }
+    }
 
-    // arrange the two widgets vertically, with some padding
-    let layout = Flex::column()
-        .with_child(
-            Flex::row()
-                .with_child(label)
-                .with_spacer(LABEL_SPACING)
-                .with_child(textbox),
-        )
-        .with_spacer(VERTICAL_WIDGET_SPACING)
-        .with_child(start_game_btn);
+    /// Leave the house rules editor without saving.
+    fn cancel_rules(&mut self) {
+        match self {
+            YatzyState::EditingRules(state) => *self = YatzyState::Starting(state.starting.clone()),
+            _ => panic!("leaving the rules editor when it isn't open"),
+        }
+    }
 
-    // center the two widgets in the available space
-    Align::centered(layout)
+    /// Open the review of the finished game.
+    fn review_game(&mut self) {
+        match self {
+            YatzyState::Finished(state) => *self = YatzyState::Reviewing(ReviewState::new(state)),
+            _ => panic!("reviewing a game that hasn't finished"),
+        }
+    }
+
+    /// Go back from the review to the results.
+    fn close_review(&mut self) {
+        match self {
+            YatzyState::Reviewing(state) => *self = YatzyState::Finished(state.finished.clone()),
+            _ => panic!("closing the game review when it isn't open"),
+        }
+    }
+
+    /// Watch the replay of the finished game, from the start.
+    fn watch_game(&mut self) {
+        match self {
+            YatzyState::Finished(state) => {
+                let replay = state.replay();
+                *self = YatzyState::Watching(ReplayState::new(replay, self.clone()))
+            }
+            _ => panic!("watching a game that hasn't finished"),
+        }
+    }
+
+    /// Open the history of finished games, from the starting screen or the statistics.
+    fn open_history(&mut self) {
+        match self {
+            YatzyState::Starting(_) | YatzyState::Statistics(_) => {
+                *self = YatzyState::History(HistoryState::new(Arc::new(self.clone())))
+            }
+            _ => panic!("opening the history when not on the starting screen or the statistics"),
+        }
+    }
+
+    /// Go back from the history to where it was opened from.
+    fn close_history(&mut self) {
+        match self {
+            YatzyState::History(state) => *self = (*state.back).clone(),
+            _ => panic!("closing the history when it isn't open"),
+        }
+    }
+
+    /// Watch a game from the history, coming back to the history afterwards.
+    fn watch_history_game(&mut self, id: i64) {
+        if let YatzyState::History(state) = self {
+            let replay = core::history::default_path()
+                .ok_or_else(|| anyhow::anyhow!("there's no data directory for the history"))
+                .and_then(|path| History::open(&path)?.replay(id));
+            match replay {
+                Ok(replay) => *self = YatzyState::Watching(ReplayState::new(replay, self.clone())),
+                Err(e) => state.status = format!("Couldn't open the game: {:#}", e),
+            }
+        }
+    }
+
+    /// Stop watching a replay, going back to where it was opened from.
+    fn close_replay(&mut self) {
+        match self {
+            YatzyState::Watching(state) => *self = (*state.back).clone(),
+            _ => panic!("closing a replay when one isn't open"),
+        }
+    }
+
+    /// Go back to the main menu from the starting screen, keeping what was set up on it, or
+    /// from the results or a game, keeping the players. A game that's left isn't saved.
+    fn open_menu(&mut self) {
+        match self {
+            YatzyState::Starting(state) => *self = YatzyState::Menu(MenuState::new(state.clone())),
+            YatzyState::Finished(state) => {
+                *self = YatzyState::Menu(MenuState::new(state.starting()))
+            }
+            YatzyState::InGame(state) => {
+                let starting = StartingState::after(&state.players, &state.game, state.turn_time);
+                *self = YatzyState::Menu(MenuState::new(starting))
+            }
+            _ => panic!("opening the main menu when not on the starting screen, results or game"),
+        }
+    }
+
+    /// Show how everyone has done, from the main menu.
+    fn open_statistics(&mut self) {
+        match self {
+            YatzyState::Menu(menu) => *self = YatzyState::Statistics(StatsState::new(menu.clone())),
+            _ => panic!("opening the statistics when not on the main menu"),
+        }
+    }
+
+    /// Go back to the main menu from the statistics.
+    fn close_statistics(&mut self) {
+        match self {
+            YatzyState::Statistics(state) => *self = YatzyState::Menu(state.menu.clone()),
+            _ => panic!("closing the statistics when they aren't open"),
+        }
+    }
+
+    /// Open the settings, from the main menu or a paused game, which carries on paused once
+    /// they're closed.
+    fn open_settings(&mut self) {
+        match self {
+            YatzyState::Menu(_) | YatzyState::InGame(_) => {
+                *self = YatzyState::Settings(SettingsState::new(Arc::new(self.clone())))
+            }
+            _ => panic!("opening the settings when not on the main menu or in a game"),
+        }
+    }
+
+    /// Leave the settings without saving them.
+    fn close_settings(&mut self) {
+        match self {
+            YatzyState::Settings(state) => *self = (*state.back).clone(),
+            _ => panic!("closing the settings when they aren't open"),
+        }
+    }
+
+    /// Show how to play the variant chosen for a new game on the main menu, or the one being
+    /// played in a paused game.
+    fn open_how_to_play(&mut self) {
+        let rules = match self {
+            YatzyState::Menu(menu) => menu.starting.rules.clone(),
+            YatzyState::InGame(state) => state.game.rules().clone(),
+            _ => panic!("showing how to play when not on the main menu or in a game"),
+        };
+        let title = format!("How to play {}", rules.name);
+        let help = HelpState::new(title, guide::how_to_play(&rules), self.clone());
+        *self = YatzyState::Help(help);
+    }
+
+    /// Show the version of the game and who made it, from the main menu or a paused game.
+    fn open_about(&mut self) {
+        match self {
+            YatzyState::Menu(_) | YatzyState::InGame(_) => {
+                let lines = vec![
+                    format!("Version {}", env!("CARGO_PKG_VERSION")),
+                    format!("By {}", env!("CARGO_PKG_AUTHORS").replace(':', ", ")),
+                    "Made with druid.".to_string(),
+                ];
+                *self = YatzyState::Help(HelpState::new("About Yatzy!", lines, self.clone()));
+            }
+            _ => panic!("showing the about screen when not on the main menu or in a game"),
+        }
+    }
+
+    /// Go back from how to play or the about screen.
+    fn close_help(&mut self) {
+        match self {
+            YatzyState::Help(state) => *self = (*state.back).clone(),
+            _ => panic!("closing the help when it isn't open"),
+        }
+    }
+
+    /// The toasts the game has left to be shown, taking them so they're only shown once. The
+    /// results keep those from the game's last moves.
+    fn take_toasts(&mut self) -> Vec<String> {
+        let toasts = match self {
+            YatzyState::InGame(state) => &mut state.toasts,
+            YatzyState::Finished(state) => &mut state.toasts,
+            _ => return Vec::new(),
+        };
+        if toasts.is_empty() {
+            return Vec::new();
+        }
+        std::mem::replace(toasts, Arc::new(Vec::new())).to_vec()
+    }
 }
 
-fn build_in_game() -> impl Widget<InGameState> {
-    // a label that will determine its text based on the current app data.
-    let player_name =
-        Label::new(|data: &InGameState, _env: &Env| format!("Player: {}", data.player_name));
+/// A place in a game being set up, for a person or a computer player.
+#[derive(Debug, Clone, PartialEq, Data, Lens)]
+struct Seat {
+    /// The name as typed in. Left blank, one is filled in when the game starts.
+    name: String,
+    /// The name of the bot playing in this seat, or `None` for a person.
+    bot: Option<String>,
+    /// The name of the skin on the dice of whoever plays in this seat.
+    skin: String,
+}
+
+impl Seat {
+    fn person(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            bot: None,
+            skin: skins::CLASSIC.name.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Data, Lens)]
+struct StartingState {
+    /// Everyone playing here, in turn order.
+    players: Arc<Vec<Seat>>,
+    /// The variants to choose from: the built-in ones followed by any saved house rules.
+    presets: Arc<Vec<RuleSet>>,
+    /// The variant to play.
+    rules: RuleSet,
+    /// Whether to play Forced Yatzy, filling categories in order.
+    forced_order: bool,
+    /// Whether to play two against two, when there are four players and none of them join
+    /// over the network.
+    teams: bool,
+    /// The seed for the dice, as typed in. Left blank for a random game.
+    seed: String,
+    /// The seconds allowed for each decision, as typed in. Left blank for no time limit.
+    turn_time: String,
+    /// The seconds to keep the seat of a player whose connection drops when hosting, as typed
+    /// in. Left blank for the default.
+    grace_period: String,
+    /// The port to host a network game on, or the address of one to join, as typed in.
+    address: String,
+    /// Whether to encrypt the network game being hosted.
+    encrypted: bool,
+    /// The password to join the network game, as typed in. Left blank for none.
+    password: String,
+    /// Why a network game couldn't be hosted or joined, or why it ended.
+    network_status: String,
+    /// The games being hosted on the local network.
+    lan_games: Arc<Vec<LanGame>>,
+    /// Whether to play by file, each person at their own computer.
+    by_file: bool,
+    /// Why a game file couldn't be opened.
+    file_status: String,
+    /// Everyone who has played on this computer, to choose from instead of typing names.
+    profiles: Arc<Vec<Profile>>,
+    /// Whether the profiles to choose from are showing.
+    profiles_open: bool,
+}
+
+impl StartingState {
+    /// Add a seat to the end of the players, unless there are already as many as can play.
+    fn add_player(&mut self, seat: Seat) {
+        if self.players.len() < MAX_PLAYERS {
+            Arc::make_mut(&mut self.players).push(seat);
+        }
+    }
+
+    /// Put the name of a profile in the first blank seat for a person, or add a person with it.
+    /// Nothing changes if they're already playing.
+    fn choose_profile(&mut self, name: &str) {
+        self.profiles_open = false;
+        if self.players.iter().any(|seat| seat.name.trim() == name) {
+            return;
+        }
+        let players = Arc::make_mut(&mut self.players);
+        let blank = players
+            .iter_mut()
+            .find(|seat| seat.bot.is_none() && seat.name.trim().is_empty());
+        match blank {
+            Some(blank) => blank.name = name.to_string(),
+            None if players.len() < MAX_PLAYERS => players.push(Seat::person(name)),
+            None => (),
+        }
+    }
+
+    /// Remove a player, always leaving at least one.
+    fn remove_player(&mut self, idx: usize) {
+        if self.players.len() > 1 {
+            Arc::make_mut(&mut self.players).remove(idx);
+        }
+    }
+
+    /// Swap a player with the one before them, so they take their turn earlier.
+    fn move_up(&mut self, idx: usize) {
+        if idx > 0 && idx < self.players.len() {
+            Arc::make_mut(&mut self.players).swap(idx - 1, idx);
+        }
+    }
+
+    /// Change who plays in a seat to the next of a person and each of the bots.
+    fn next_bot(&mut self, idx: usize) {
+        let registry = window_bots();
+        let choices: Vec<_> = std::iter::once(None)
+            .chain(registry.names().map(|name| Some(name.to_string())))
+            .collect();
+        let seat = &mut Arc::make_mut(&mut self.players)[idx];
+        let current = choices.iter().position(|bot| *bot == seat.bot).unwrap_or(0);
+        seat.bot = choices[(current + 1) % choices.len()].clone();
+    }
+
+    /// Give the seat at `idx` the next of the skins for its dice.
+    fn next_skin(&mut self, idx: usize) {
+        let seat = &mut Arc::make_mut(&mut self.players)[idx];
+        seat.skin = skins::next(&seat.skin).name.to_string();
+    }
 
-    macro_rules! die_lens {
-        ($idx:expr) => {
-            Field::new::<InGameState, _>(|s| &s.dice[$idx], |s| &mut s.dice[$idx])
+    /// Host a network game on the port typed in, or the default one, encrypted if chosen.
+    fn host(&mut self, events: channel::Sender<NetEvent>) -> Option<Host> {
+        let port = match self.address.trim() {
+            "" => protocol::DEFAULT_PORT,
+            port => match port.parse() {
+                Ok(port) => port,
+                Err(_) => {
+                    self.network_status = format!("{:?} isn't a port number", port);
+                    return None;
+                }
+            },
+        };
+        let transport = if self.encrypted {
+            Transport::Tls
+        } else {
+            Transport::Tcp
         };
+        Host::start(port, transport, &self.rules.name, events)
+            .map_err(|e| self.network_status = format!("Couldn't host: {:#}", e))
+            .ok()
     }
-    let dice = Flex::row()
-        .with_child(Die::new().lens(die_lens!(0)))
-        .with_spacer(LABEL_SPACING)
-        .with_child(Die::new().lens(die_lens!(1)))
-        .with_spacer(LABEL_SPACING)
-        .with_child(Die::new().lens(die_lens!(2)))
-        .with_spacer(LABEL_SPACING)
-        .with_child(Die::new().lens(die_lens!(3)))
-        .with_spacer(LABEL_SPACING)
-        .with_child(Die::new().lens(die_lens!(4)));
 
-    // arrange the two widgets vertically, with some padding
-    let layout = Flex::column()
-        .with_child(player_name)
-        .with_spacer(VERTICAL_WIDGET_SPACING)
-        .with_child(dice);
+    /// Join the network game at the address typed in, as the first player. Also gives the
+    /// address joined, with the port filled in.
+    fn join(&mut self, events: channel::Sender<NetEvent>) -> Option<(Client, String)> {
+        let address = protocol::with_default_port(self.address.trim());
+        match Client::connect(&address, self.network_name(), self.password(), events) {
+            Ok(client) => Some((client, address)),
+            Err(e) => {
+                self.network_status = format!("Couldn't join: {:#}", e);
+                None
+            }
+        }
+    }
 
-    // center the two widgets in the available space
-    Align::centered(layout)
+    /// The name to host or join network games under: the first person's.
+    fn network_name(&self) -> &str {
+        let person = self.players.iter().find(|seat| seat.bot.is_none());
+        match person.map_or("", |seat| seat.name.trim()) {
+            "" => "Player",
+            name => name,
+        }
+    }
+
+    /// The game set up on this screen.
+    ///
+    /// When hosting, the people who joined take their turns after those here, and are sent the
+    /// game. When playing by file, the first person plays here and the computer players are
+    /// left out.
+    fn start(&self, session: Option<Arc<Session>>) -> InGameState {
+        if let Err(e) = self.remember() {
+            eprintln!("warning: couldn't save the settings: {:#}", e);
+        }
+        let mut rules = self.rules.clone();
+        rules.forced_order = self.forced_order;
+        let rng = match self.seed() {
+            Some(seed) => GameRng::from_seed(seed),
+            None => GameRng::from_entropy(),
+        };
+        let seats = self
+            .players
+            .iter()
+            .filter(|seat| !self.by_file || seat.bot.is_none());
+        let mut players: Vec<Player> = seats
+            .enumerate()
+            .map(|(idx, seat)| Player {
+                name: match (seat.name.trim(), &seat.bot) {
+                    ("", Some(bot)) => format!("Computer ({})", bot),
+                    ("", None) => format!("Player {}", idx + 1),
+                    (name, _) => name.to_string(),
+                },
+                bot: seat.bot.clone(),
+                local: !self.by_file || idx == 0,
+                skin: seat.skin.clone(),
+            })
+            .collect();
+        let mut seats = HashMap::new();
+        if let Some(Session::Host(host)) = session.as_deref() {
+            for (connection, joined) in host.joined() {
+                seats.insert(connection, players.len());
+                players.push(Player {
+                    name: joined.name,
+                    bot: None,
+                    local: false,
+                    skin: skins::CLASSIC.name.to_string(),
+                });
+            }
+        }
+        if let Some(Session::Host(host)) = session.as_deref() {
+            let info: Vec<_> = players.iter().map(Player::info).collect();
+            let (turn_time, grace_period) = (self.turn_time(), self.grace_period());
+            host.start_game(&rules, &info, seats, turn_time, grace_period);
+        }
+        let mut game = InGameState::new(players, rules, rng, self.turn_time());
+        if self.teams && game.players.len() == TEAMS.len() && session.is_none() {
+            game.game.set_teams(TEAMS.to_vec());
+        }
+        if let Some(session) = &session {
+            game.joined = joined_names(session);
+        }
+        game.session = session;
+        game.by_file = self.by_file;
+        game
+    }
+
+    /// The starting screen for another game after one with these players, with the same rules
+    /// and everyone who played on this computer in the same seats.
+    fn after(players: &[Player], game: &Game, turn_time: Option<u32>) -> Self {
+        let mut rules = game.rules().clone();
+        let forced_order = std::mem::replace(&mut rules.forced_order, false);
+        let seats = players
+            .iter()
+            .filter(|player| player.local)
+            .map(|player| Seat {
+                name: player.name.clone(),
+                bot: player.bot.clone(),
+                skin: player.skin.clone(),
+            });
+        Self {
+            players: Arc::new(seats.collect()),
+            presets: all_presets(),
+            rules,
+            forced_order,
+            teams: game.has_teams(),
+            seed: String::new(),
+            turn_time: turn_time.map(|t| t.to_string()).unwrap_or_default(),
+            grace_period: String::new(),
+            address: String::new(),
+            encrypted: false,
+            password: String::new(),
+            network_status: String::new(),
+            lan_games: Arc::new(Vec::new()),
+            by_file: false,
+            file_status: String::new(),
+            profiles: all_profiles(),
+            profiles_open: false,
+        }
+    }
+
+    /// Fill in the players' names and choose the variant as the settings have them.
+    fn use_settings(&mut self, settings: &Settings) {
+        if let Some(rules) = self
+            .presets
+            .iter()
+            .find(|rules| rules.name == settings.default_variant)
+        {
+            self.rules = rules.clone();
+        }
+        let mut players: Vec<_> = settings
+            .player_names
+            .iter()
+            .map(|name| Seat::person(name))
+            .collect();
+        players.truncate(MAX_PLAYERS);
+        if players.is_empty() {
+            players.push(Seat::person(""));
+        }
+        self.players = Arc::new(players);
+    }
+
+    /// Save the names of the people playing and the variant in the settings, to fill in next
+    /// time.
+    fn remember(&self) -> Result {
+        let path = match settings::default_path() {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let mut settings = Settings::load(&path)?;
+        settings.player_names = self
+            .players
+            .iter()
+            .filter(|seat| seat.bot.is_none())
+            .map(|seat| seat.name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .collect();
+        settings.default_variant = self.rules.name.clone();
+        settings.save(&path)
+    }
+
+    /// The seed to use for the game, or `None` if the player didn't enter a valid one.
+    fn seed(&self) -> Option<u64> {
+        self.seed.trim().parse().ok()
+    }
+
+    /// The seconds allowed for each decision, or `None` for no time limit.
+    fn turn_time(&self) -> Option<u32> {
+        self.turn_time.trim().parse().ok().filter(|&t| t > 0)
+    }
+
+    /// The password for the network game being hosted or joined, if one was typed in.
+    fn password(&self) -> Option<&str> {
+        Some(self.password.trim()).filter(|password| !password.is_empty())
+    }
+
+    /// The seconds a dropped player has to rejoin a game being hosted.
+    fn grace_period(&self) -> u32 {
+        self.grace_period
+            .trim()
+            .parse()
+            .unwrap_or(protocol::DEFAULT_GRACE_PERIOD)
+    }
+}
+
+/// The house rules editor, where players make their own variants.
+#[derive(Debug, Clone, Data, Lens)]
+struct RulesEditorState {
+    /// The starting screen to go back to.
+    starting: StartingState,
+    /// The rules being edited. The number fields below are kept as typed, and only checked when
+    /// the rules are saved.
+    rules: RuleSet,
+    name: String,
+    upper_bonus_threshold: String,
+    upper_bonus: String,
+    rolls: String,
+    /// Why the rules couldn't be saved, if they couldn't.
+    error: Option<String>,
+}
+
+impl RulesEditorState {
+    fn new(starting: StartingState) -> Self {
+        let rules = starting.rules.clone();
+        Self {
+            name: format!("House {}", rules.name),
+            upper_bonus_threshold: rules.upper_bonus_threshold.to_string(),
+            upper_bonus: rules.upper_bonus.to_string(),
+            rolls: rules.rolls.to_string(),
+            rules,
+            starting,
+            error: None,
+        }
+    }
+
+    /// The rules as edited, or why they aren't valid.
+    fn rules(&self) -> Result<RuleSet, String> {
+        fn number<T: std::str::FromStr>(value: &str, what: &str) -> Result<T, String> {
+            value
+                .trim()
+                .parse()
+                .map_err(|_| format!("the {} must be a number, not {:?}", what, value))
+        }
+
+        let name = self.name.trim();
+        if name.is_empty() {
+            return Err("the rules need a name".into());
+        }
+        if RuleSet::presets().iter().any(|preset| preset.name == name) {
+            return Err(format!("{} is the name of a built-in variant", name));
+        }
+        if self.rules.categories.is_empty() {
+            return Err("choose at least one category".into());
+        }
+        let mut rules = self.rules.clone();
+        rules.name = name.to_string();
+        rules.upper_bonus_threshold = number(&self.upper_bonus_threshold, "bonus threshold")?;
+        rules.upper_bonus = number(&self.upper_bonus, "bonus")?;
+        rules.rolls = number(&self.rolls, "number of rolls")?;
+        if rules.rolls == 0 {
+            return Err("there must be at least one roll each turn".into());
+        }
+        Ok(rules)
+    }
+}
+
+/// Someone taking part in a game.
+#[derive(Debug, Clone, Data, Lens)]
+struct Player {
+    name: String,
+    /// The name of the bot playing for this player, or `None` for a person.
+    bot: Option<String>,
+    /// Whether the player's moves are made on this computer, rather than on another in a
+    /// network game.
+    local: bool,
+    /// The name of the skin on the dice during the player's turns.
+    skin: String,
+}
+
+impl Player {
+    fn info(&self) -> protocol::PlayerInfo {
+        protocol::PlayerInfo {
+            name: self.name.clone(),
+            bot: self.bot.clone(),
+        }
+    }
+}
+
+/// How the connection to the host of a game that was joined is doing.
+#[derive(Debug, Copy, Clone, PartialEq, Data)]
+enum Connection {
+    Connected,
+    Reconnecting,
+    Disconnected,
+}
+
+/// Something the command palette can do in a game.
+#[derive(Debug, Copy, Clone, PartialEq, Data)]
+enum PaletteAction {
+    Play(Move),
+    Undo,
+    Redo,
+    Save,
+    Pause,
+    NewGame,
+}
+
+/// An action in the command palette, as it's listed.
+#[derive(Debug, Clone, Data, Lens)]
+struct PaletteEntry {
+    name: String,
+    action: PaletteAction,
+    /// Whether it's the one Enter would pick.
+    selected: bool,
+}
+
+/// The command palette: what's been typed, and the actions it matches, best first.
+#[derive(Debug, Clone, Default, Data, Lens)]
+struct Palette {
+    open: bool,
+    query: String,
+    matches: Arc<Vec<PaletteEntry>>,
+    /// The position in `matches` of the action Enter would pick.
+    selected: usize,
+}
+
+#[derive(Debug, Clone, Data, Lens)]
+struct InGameState {
+    /// The players, in turn order.
+    players: Arc<Vec<Player>>,
+    game: Game,
+    /// The dice as displayed, kept in step with the dice in `game`.
+    dice: Arc<Vec<DieData>>,
+    /// Which of the game's dice is shown in each place in the tray, as the players have
+    /// rearranged them. The order is only for show, so it doesn't change the game.
+    dice_order: Arc<Vec<usize>>,
+    /// The player whose score sheet is shown, picked with the tabs above it.
+    shown_sheet: usize,
+    /// Whose turn it was when the sheet was last picked, so the current player's sheet is
+    /// shown again when the turn passes.
+    shown_turn: usize,
+    /// Why the last move was rejected, if it was.
+    error: Option<String>,
+    /// The seconds allowed for each decision, or `None` for no time limit.
+    turn_time: Option<u32>,
+    /// The seconds left for the current decision, when there is a time limit.
+    time_left: u32,
+    /// Whether to show which dice the solver would hold, by dimming the others.
+    hints: bool,
+    /// The solver's last advice, from the advisor thread. It may be for an earlier position.
+    advice: Option<Arc<Advice>>,
+    /// After the last roll of a person's turn, how good each open slot would be for the hand,
+    /// once the advisor has said.
+    placement_values: Option<Arc<Vec<(Slot, f64)>>>,
+    /// The network game this is part of, if it is one.
+    session: Option<Arc<Session>>,
+    /// How many of the game's events have been sent to the players who joined, when hosting.
+    published: usize,
+    /// What has been said in a network game, a line each.
+    chat: Arc<Vec<String>>,
+    /// The message being typed.
+    chat_draft: String,
+    chat_open: bool,
+    /// Whether the list of the game's rolls is showing.
+    rolls_open: bool,
+    /// A line for each roll made so far, kept up to date while the list is showing.
+    roll_log: Arc<Vec<String>>,
+    /// How many messages have arrived while the chat was closed.
+    unread_chat: usize,
+    /// Whether the game is played by file, so it's saved whenever the turn passes to someone
+    /// at another computer.
+    by_file: bool,
+    /// How many of the game's events the webhooks have been sent notices about, when it's
+    /// played by file.
+    notified: usize,
+    /// Where the smart dice on the table have landed, once they're being rolled instead of the
+    /// dice on screen.
+    table: Option<Arc<Table>>,
+    /// The players still connected to the game being hosted, for turning them out.
+    joined: Arc<Vec<(ConnectionId, String)>>,
+    /// The name of the slot to save the game in, as typed in.
+    save_name: String,
+    /// Whether saving would replace another game, and is waiting to be confirmed.
+    overwriting: bool,
+    /// A move clicked on the scorecard that looked like a slip, waiting to be confirmed.
+    confirming: Option<Move>,
+    /// The chances of collecting more of a face, asked for from a die's menu, until the next
+    /// move.
+    odds: Option<String>,
+    /// What the computer player whose turn it is is waiting for, such as chat to vote.
+    bot_status: Option<String>,
+    /// Whether the pause menu is open.
+    paused: bool,
+    palette: Palette,
+    /// Whether the scorecard is in a window of its own, kept up to date by the delegate.
+    scorecard_detached: bool,
+    /// Messages for toasts, for the delegate to send on with `SHOW_TOAST`.
+    toasts: Arc<Vec<String>>,
+    /// Whether anything has been played since the game was shown, so a game that was already
+    /// over when it was opened isn't counted in the profiles again.
+    played: bool,
+    /// When the game was shown, in seconds since the Unix epoch, to tell how long it took.
+    started_at: u64,
+    /// The seconds since the game was shown, kept up to date by the turn timer's ticks.
+    elapsed: u64,
+    connection: Connection,
+}
+
+impl InGameState {
+    fn new(players: Vec<Player>, rules: RuleSet, rng: GameRng, turn_time: Option<u32>) -> Self {
+        let game = Game::new(rules, rng, players.len());
+        Self::from_game(players, game, turn_time)
+    }
+
+    /// Carry on a saved game, with everyone playing at this computer.
+    fn from_save(file: SaveFile) -> Self {
+        let players = file
+            .players
+            .into_iter()
+            .map(|player| Player {
+                name: player.name,
+                bot: player.bot,
+                local: true,
+                skin: player.skin,
+            })
+            .collect();
+        Self::from_game(players, file.game, file.turn_time)
+    }
+
+    /// Show a game that has already started.
+    fn from_game(players: Vec<Player>, game: Game, turn_time: Option<u32>) -> Self {
+        let sides = game.rules().sides;
+        let dice = vec![DieData::new(sides, sides, game.rules().faces); game.dice().len()];
+        let dice_order = (0..dice.len()).collect();
+        let player = game.player();
+        let mut state = Self {
+            players: Arc::new(players),
+            game,
+            dice: Arc::new(dice),
+            dice_order: Arc::new(dice_order),
+            shown_sheet: player,
+            shown_turn: player,
+            error: None,
+            turn_time,
+            time_left: turn_time.unwrap_or(0),
+            hints: false,
+            advice: None,
+            placement_values: None,
+            session: None,
+            published: 0,
+            chat: Arc::new(Vec::new()),
+            chat_draft: String::new(),
+            chat_open: false,
+            rolls_open: false,
+            roll_log: Arc::new(Vec::new()),
+            unread_chat: 0,
+            by_file: false,
+            notified: 0,
+            table: None,
+            joined: Arc::new(Vec::new()),
+            save_name: String::new(),
+            overwriting: false,
+            confirming: None,
+            odds: None,
+            bot_status: None,
+            paused: false,
+            palette: Palette::default(),
+            scorecard_detached: false,
+            toasts: Arc::new(Vec::new()),
+            played: false,
+            started_at: now(),
+            elapsed: 0,
+            connection: Connection::Connected,
+        };
+        state.sync_dice();
+        state
+    }
+
+    /// Make a move from this computer, keeping the reason if it was rejected.
+    ///
+    /// In a game that was joined, the move is sent to the host, which sends back what happened.
+    fn play(&mut self, mv: Move) {
+        let player = self.current_player();
+        if !player.local {
+            self.error = Some(format!("It's {}'s turn", player.name));
+            return;
+        }
+        if let Some(Session::Client(client)) = self.session.as_deref() {
+            let result = self.game.validate(mv).map_err(|err| err.to_string());
+            let result = result.and_then(|_| client.send_move(mv).map_err(|e| format!("{:#}", e)));
+            self.error = result.err();
+            return;
+        }
+        self.apply_move(mv);
+    }
+
+    /// Make a move sent by a player who joined the game being hosted, if it's their turn.
+    fn play_remote(&mut self, seat: usize, mv: Move) {
+        if seat == self.game.player() && !self.current_player().local {
+            self.apply_move(mv);
+            self.notify_turn(seat);
+        }
+    }
+
+    /// Ask before making a move that looks like a slip, such as placing the dice where they
+    /// score nothing, returning whether it's waiting to be confirmed with `confirm_move`.
+    fn doubt(&mut self, mv: Move) -> bool {
+        if !self.current_player().local {
+            return false;
+        }
+        let rules = self.game.rules();
+        let reason = match (mv, self.game.doubt(mv)) {
+            (Move::Score(slot), Some(Doubt::Nothing)) => format!(
+                "{} would score nothing. Place the dice there anyway?",
+                slot_name(slot, rules)
+            ),
+            (
+                Move::Score(slot),
+                Some(Doubt::Worse {
+                    points,
+                    best,
+                    best_points,
+                }),
+            ) => format!(
+                "{} would score {}, but {} would score {}. Place the dice there anyway?",
+                slot_name(slot, rules),
+                points,
+                slot_name(best, rules),
+                best_points
+            ),
+            (Move::Scratch(slot), Some(Doubt::Scratch { best, best_points })) => format!(
+                "Scratch {}? The dice would score {} in {}.",
+                slot_name(slot, rules),
+                best_points,
+                slot_name(best, rules)
+            ),
+            _ => return false,
+        };
+        self.error = Some(reason);
+        self.confirming = Some(mv);
+        true
+    }
+
+    /// Take the move that was waiting to be confirmed, to be played with `PLAY_MOVE` so the game
+    /// finishes if it was the last one.
+    fn confirm_move(&mut self) -> Option<Move> {
+        let mv = self.confirming.take()?;
+        self.error = None;
+        Some(mv)
+    }
+
+    /// Forget the move that was waiting to be confirmed.
+    fn cancel_move(&mut self) {
+        self.confirming = None;
+        self.error = None;
+    }
+
+    /// Hold every die showing the same face as the die at `idx` in the game, and that one too.
+    fn hold_matching(&mut self, idx: usize) {
+        let face = match self.game.dice()[idx].value {
+            Some(face) => face,
+            None => return,
+        };
+        let unheld: Vec<usize> = (0..self.game.dice().len())
+            .filter(|&other| {
+                let die = &self.game.dice()[other];
+                die.value == Some(face) && !die.held
+            })
+            .collect();
+        for other in unheld {
+            self.play(Move::ToggleHold(other));
+            if self.error.is_some() {
+                break;
+            }
+        }
+    }
+
+    /// Work out the chances of collecting more of the face the die at `idx` in the game shows,
+    /// by holding every die showing it for the rest of the turn.
+    fn show_odds(&mut self, idx: usize) {
+        let face = match self.game.dice()[idx].value {
+            Some(face) => face,
+            None => return,
+        };
+        let rules = self.game.rules();
+        let dice = self.game.dice().len();
+        let held = self
+            .game
+            .dice()
+            .iter()
+            .filter(|die| die.value == Some(face))
+            .count();
+        let rolls = self.game.rolls_left();
+        let name = match rules.faces {
+            Faces::Pips => format!("{}s", face.0),
+            Faces::Poker => poker::face_name(face).to_string(),
+        };
+        if rolls == 0 || held == dice {
+            self.odds = Some(format!("Holding every {}: {} of them", name, held));
+            return;
+        }
+        let chances = odds::at_least(held, dice, rolls, rules.sides);
+        let odds = ((held + 1)..=dice)
+            .map(|n| format!("{} or more {:.0}%", n, chances[n] * 100.0))
+            .collect::<Vec<_>>()
+            .join(", ");
+        self.odds = Some(format!(
+            "Holding every {} with {} rolls left: {}",
+            name, rolls, odds
+        ));
+    }
+
+    /// The move a key plays, if it's a shortcut: Space or Enter rolls, the numbers hold the
+    /// dice counting along the tray, and a category's letter places the dice there, or scratches
+    /// it with Shift held. With several columns it's the first one the move can be made in.
+    fn shortcut(&self, key: &KeyEvent) -> Option<Move> {
+        if key.mods.ctrl || key.mods.alt || key.mods.meta || key.is_repeat {
+            return None;
+        }
+        match key.key_code {
+            KeyCode::Space | KeyCode::Return | KeyCode::NumpadEnter => return Some(Move::Roll),
+            _ => (),
+        }
+        let pressed = key.unmod_text()?.chars().next()?;
+        if let Some(digit) = pressed.to_digit(10) {
+            let place = (digit as usize).checked_sub(1)?;
+            return self.dice_order.get(place).map(|&idx| Move::ToggleHold(idx));
+        }
+        let rules = self.game.rules();
+        let category = rules.category_for_key(pressed)?;
+        let slots = (0..rules.columns).map(|column| Slot::in_column(column, category));
+        let moves: Vec<Move> = if key.mods.shift {
+            slots.map(Move::Scratch).collect()
+        } else {
+            slots.map(Move::Score).collect()
+        };
+        // when it can't be made anywhere, making it in the first column says why
+        moves
+            .iter()
+            .copied()
+            .find(|mv| self.game.validate(*mv).is_ok())
+            .or_else(|| moves.first().copied())
+    }
+
+    /// Everything that can be done in the game at the moment, named for the command palette.
+    fn palette_actions(&self) -> Vec<(String, PaletteAction)> {
+        let rules = self.game.rules();
+        let mut actions = Vec::new();
+        if self.game.can_roll() {
+            actions.push(("Roll the dice".to_string(), PaletteAction::Play(Move::Roll)));
+        }
+        if self.game.can_hold() {
+            for (place, &idx) in self.dice_order.iter().enumerate() {
+                let name = if self.game.dice()[idx].held {
+                    format!("Let go of die {}", place + 1)
+                } else {
+                    format!("Hold die {}", place + 1)
+                };
+                actions.push((name, PaletteAction::Play(Move::ToggleHold(idx))));
+            }
+        }
+        let dice = self.game.scores().unwrap_or_default();
+        for slot in self.game.slots() {
+            if self.game.can_score(slot) {
+                let points = self.game.sheet().points(slot, &dice, rules)
+                    * ScoreSheet::multiplier(slot.column);
+                let name = format!("Score {} ({})", slot_name(slot, rules), points);
+                actions.push((name, PaletteAction::Play(Move::Score(slot))));
+            }
+        }
+        for slot in self.game.slots() {
+            if self.game.validate(Move::Scratch(slot)).is_ok() {
+                let name = format!("Scratch {}", slot_name(slot, rules));
+                actions.push((name, PaletteAction::Play(Move::Scratch(slot))));
+            }
+        }
+        if self.session.is_none() {
+            if self.game.can_undo() {
+                actions.push(("Undo".to_string(), PaletteAction::Undo));
+            }
+            if self.game.can_redo() {
+                actions.push(("Redo".to_string(), PaletteAction::Redo));
+            }
+            actions.push(("Save the game".to_string(), PaletteAction::Save));
+        }
+        actions.push(("Pause".to_string(), PaletteAction::Pause));
+        actions.push((
+            "New game, leaving this one".to_string(),
+            PaletteAction::NewGame,
+        ));
+        actions
+    }
+
+    /// Open the command palette, or close it if it's open.
+    fn toggle_palette(&mut self) {
+        if self.palette.open {
+            self.palette = Palette::default();
+        } else {
+            self.palette.open = true;
+            self.sync_palette();
+        }
+    }
+
+    /// Match the actions that can be done against what's been typed in the command palette.
+    fn sync_palette(&mut self) {
+        let actions = self.palette_actions();
+        let names: Vec<&str> = actions.iter().map(|(name, _)| name.as_str()).collect();
+        let ranked = fuzzy::rank(&self.palette.query, &names);
+        self.palette.selected = 0;
+        self.palette.matches = Arc::new(
+            ranked
+                .into_iter()
+                .take(PALETTE_LENGTH)
+                .enumerate()
+                .map(|(place, idx)| PaletteEntry {
+                    name: actions[idx].0.clone(),
+                    action: actions[idx].1,
+                    selected: place == 0,
+                })
+                .collect(),
+        );
+    }
+
+    /// Move which action Enter would pick, up or down the command palette.
+    fn move_palette_selection(&mut self, by: isize) {
+        let count = self.palette.matches.len() as isize;
+        if count == 0 {
+            return;
+        }
+        let selected = (self.palette.selected as isize + by).rem_euclid(count) as usize;
+        self.palette.selected = selected;
+        for (place, entry) in Arc::make_mut(&mut self.palette.matches)
+            .iter_mut()
+            .enumerate()
+        {
+            entry.selected = place == selected;
+        }
+    }
+
+    /// Type into the command palette while it's open, returning the command to send for an
+    /// action picked with Enter.
+    fn palette_key(&mut self, key: &KeyEvent) -> Option<Command> {
+        match key.key_code {
+            KeyCode::Escape => self.toggle_palette(),
+            KeyCode::Return | KeyCode::NumpadEnter => {
+                let action = self.palette.matches.get(self.palette.selected)?.action;
+                return self.run_palette(action);
+            }
+            KeyCode::ArrowUp => self.move_palette_selection(-1),
+            KeyCode::ArrowDown => self.move_palette_selection(1),
+            KeyCode::Backspace => {
+                self.palette.query.pop();
+                self.sync_palette();
+            }
+            _ if key.mods.ctrl || key.mods.alt || key.mods.meta => (),
+            _ => {
+                let text = key.text().unwrap_or_default();
+                if !text.is_empty() && !text.chars().any(char::is_control) {
+                    self.palette.query.push_str(text);
+                    self.sync_palette();
+                }
+            }
+        }
+        None
+    }
+
+    /// Do an action picked in the command palette, closing it. Moves are checked for slips
+    /// first, and are left to be played with the command returned.
+    fn run_palette(&mut self, action: PaletteAction) -> Option<Command> {
+        self.palette = Palette::default();
+        match action {
+            PaletteAction::Play(mv @ Move::Score(_))
+            | PaletteAction::Play(mv @ Move::Scratch(_))
+                if self.doubt(mv) =>
+            {
+                None
+            }
+            PaletteAction::Play(mv) => Some(Command::new(PLAY_MOVE, mv)),
+            PaletteAction::Undo => {
+                self.undo();
+                None
+            }
+            PaletteAction::Redo => {
+                self.redo();
+                None
+            }
+            PaletteAction::Save => {
+                self.save_slot(false);
+                None
+            }
+            PaletteAction::Pause => Some(Command::new(TOGGLE_PAUSE, ())),
+            PaletteAction::NewGame => Some(Command::new(NEW_GAME, ())),
+        }
+    }
+
+    fn apply_move(&mut self, mv: Move) {
+        self.confirming = None;
+        self.odds = None;
+        let (player, bonuses) = (self.game.player(), self.bonuses(self.game.player()));
+        let result = self.game.play(mv);
+        if result.is_ok() {
+            self.played = true;
+            self.reset_timer();
+            self.announce_bonus(player, bonuses);
+        }
+        self.error = result.err().map(|err| err.to_string());
+        self.publish();
+        self.sync_dice();
+        let passed = self.game.is_complete() || !self.current_player().local;
+        if result.is_ok() && self.by_file && passed {
+            self.save_game_file();
+        }
+    }
+
+    /// Show a message for a moment in a toast.
+    fn toast(&mut self, text: impl Into<String>) {
+        Arc::make_mut(&mut self.toasts).push(text.into());
+    }
+
+    /// How many upper section bonuses a player has earned, one for each column.
+    fn bonuses(&self, player: usize) -> usize {
+        self.game.sheets()[player]
+            .columns()
+            .iter()
+            .filter(|card| card.bonus() > 0)
+            .count()
+    }
+
+    /// Let everyone know if a player has just earned an upper section bonus, having had
+    /// `before` of them.
+    fn announce_bonus(&mut self, player: usize, before: usize) {
+        if self.bonuses(player) <= before {
+            return;
+        }
+        if self.players.len() == 1 {
+            self.toast("Bonus achieved!");
+        } else {
+            let name = self.players[player].name.clone();
+            self.toast(format!("Bonus achieved by {}!", name));
+        }
+    }
+
+    /// Save a game played by file, for the person to send it to whoever plays next, and let the
+    /// webhooks know.
+    fn save_game_file(&mut self) {
+        let names: Vec<String> = self
+            .players
+            .iter()
+            .map(|player| player.name.clone())
+            .collect();
+        let file = GameFile::new(&self.game, names.clone());
+        let saved = correspondence::default_dir()
+            .ok_or_else(|| anyhow::anyhow!("there's no documents directory to save it in"))
+            .and_then(|dir| {
+                let path = dir.join(file.file_name());
+                file.save(&path).map(|_| path)
+            });
+        if saved.is_ok() {
+            let notices = webhook::notices(&self.game, &names, self.notified);
+            webhook::send(&load_settings().webhooks, notices);
+            self.notified = self.game.events().len();
+        }
+        self.error = Some(match saved {
+            Ok(path) if self.game.is_complete() => format!(
+                "Saved the finished game to {}. Send it to the others to see the scores.",
+                path.display()
+            ),
+            Ok(path) => format!(
+                "Saved the game to {}. Send it to {} to take their turn.",
+                path.display(),
+                self.current_player().name
+            ),
+            Err(e) => format!("Couldn't save the game: {:#}", e),
+        });
+    }
+
+    /// Save the game to `path`, to carry on later. Network games can't be saved, since the
+    /// others would have to come back too.
+    fn save_to(&mut self, path: &Path) {
+        if self.session.is_some() {
+            self.error = Some("Network games can't be saved".into());
+            return;
+        }
+        if self.game.any_rolling() {
+            self.error = Some("Wait for the dice to land before saving".into());
+            return;
+        }
+        let players = self
+            .players
+            .iter()
+            .map(|player| SavedPlayer {
+                name: player.name.clone(),
+                bot: player.bot.clone(),
+                skin: player.skin.clone(),
+            })
+            .collect();
+        let file = SaveFile::new(players, self.game.clone(), self.turn_time);
+        match file.save(path) {
+            Ok(()) => {
+                self.error = None;
+                self.toast(format!("Saved the game to {}", path.display()));
+            }
+            Err(e) => self.error = Some(format!("Couldn't save the game: {:#}", e)),
+        }
+    }
+
+    /// Save the game in the slot named in the save box, or one named after the players if it's
+    /// blank. Saving over another game has to be confirmed with `overwrite`.
+    fn save_slot(&mut self, overwrite: bool) {
+        let dir = match save::slots_dir() {
+            Some(dir) => dir,
+            None => {
+                self.error = Some("There's no config directory to save games in".into());
+                return;
+            }
+        };
+        let name = match self.save_name.trim() {
+            "" => {
+                let names: Vec<_> = self.players.iter().map(|p| p.name.as_str()).collect();
+                names.join(" vs ")
+            }
+            name => name.to_string(),
+        };
+        let path = save::slot_path(&dir, &name);
+        if path.exists() && !overwrite {
+            self.overwriting = true;
+            self.error = Some(format!("There's already a game saved as {}", name));
+            return;
+        }
+        self.overwriting = false;
+        self.save_to(&path);
+    }
+
+    /// Keep the game saved in the slot rather than saving over it.
+    fn cancel_overwrite(&mut self) {
+        self.overwriting = false;
+        self.error = None;
+    }
+
+    /// Apply an event sent by the host of the game that was joined. An event that couldn't
+    /// happen next means the host can't be trusted, so the game is left.
+    fn apply_event(&mut self, event: GameEvent) {
+        if let Err(e) = correspondence::check(&self.game, &event) {
+            self.leave_host(&format!("{:#}", e));
+            return;
+        }
+        self.confirming = None;
+        self.odds = None;
+        let (player, bonuses) = (self.game.player(), self.bonuses(self.game.player()));
+        self.game.apply(event);
+        self.announce_bonus(player, bonuses);
+        self.notify_turn(player);
+        self.played = true;
+        self.reset_timer();
+        self.sync_dice();
+    }
+
+    /// Catch up with the host after rejoining, from all the game's events so far.
+    fn resume(&mut self, events: &[GameEvent]) {
+        let rules = self.game.rules().clone();
+        let rng = GameRng::from_seed(self.game.seed());
+        let mut game = Game::new(rules, rng, self.players.len());
+        for (idx, event) in events.iter().enumerate() {
+            if let Err(e) = correspondence::check(&game, event) {
+                self.leave_host(&format!("event {}: {:#}", idx + 1, e));
+                return;
+            }
+            game.apply(event.clone());
+        }
+        self.game = game;
+        self.error = None;
+        self.confirming = None;
+        self.odds = None;
+        self.reset_timer();
+        self.sync_dice();
+    }
+
+    /// Stop listening to the host of the game that was joined, which sent something that
+    /// couldn't have happened.
+    fn leave_host(&mut self, why: &str) {
+        if let Some(Session::Client(client)) = self.session.as_deref() {
+            client.close();
+        }
+        self.connection = Connection::Disconnected;
+        self.error = Some(format!(
+            "Left the game, as the host sent a move that can't be played: {}",
+            why
+        ));
+    }
+
+    /// Host the game that was joined, now its host has left and this player is next in line.
+    /// The computer plays for the bots the old host was running, and the other players' seats
+    /// are kept for them to rejoin.
+    fn take_over(
+        &mut self,
+        succession: &Succession,
+        grace_period: u32,
+        encrypted: bool,
+        events: channel::Sender<NetEvent>,
+    ) {
+        let players = Arc::make_mut(&mut self.players);
+        for player in players.iter_mut().filter(|player| player.bot.is_some()) {
+            player.local = true;
+        }
+        let kept = players
+            .iter()
+            .enumerate()
+            .filter(|(_, player)| !player.local)
+            .map(|(seat, player)| (seat, player.name.clone()))
+            .collect();
+        let transport = if encrypted {
+            Transport::Tls
+        } else {
+            Transport::Tcp
+        };
+        let log = self.game.events().to_vec();
+        let host = match Host::take_over(transport, succession, kept, log, grace_period, events) {
+            Ok(host) => host,
+            Err(e) => {
+                self.error = Some(format!(
+                    "The host left, and hosting instead failed: {:#}",
+                    e
+                ));
+                return;
+            }
+        };
+        // the copy that joined never rolled, so this one rolls from here on
+        let rules = self.game.rules().clone();
+        let rng = GameRng::from_entropy();
+        self.game = Game::replay(rules, rng, self.players.len(), self.game.events());
+        self.session = Some(Arc::new(Session::Host(host)));
+        self.published = self.game.events().len();
+        self.error = Some(format!(
+            "The host left, so this computer is hosting the game now on port {}",
+            succession.port
+        ));
+        self.reset_timer();
+        self.sync_dice();
+    }
+
+    /// Send the events the players who joined haven't seen yet, when hosting.
+    fn publish(&mut self) {
+        if let Some(Session::Host(host)) = self.session.as_deref() {
+            for event in &self.game.events()[self.published..] {
+                host.broadcast(event);
+            }
+        }
+        self.published = self.game.events().len();
+    }
+
+    /// Let the computer take over from someone who left the game being hosted.
+    fn player_left(&mut self, connection: ConnectionId, name: &str) {
+        let seat = match self.session.as_deref() {
+            Some(Session::Host(host)) => host.seat(connection),
+            _ => None,
+        };
+        if let Some(seat) = seat {
+            let bot = ai::stand_in(self.game.rules(), &bot_registry());
+            let player = &mut Arc::make_mut(&mut self.players)[seat];
+            player.bot = Some(bot.to_string());
+            player.local = true;
+            self.toast(format!(
+                "{} left, the {} bot is playing for them",
+                name, bot
+            ));
+        }
+    }
+
+    /// Send the message being typed to everyone in the network game.
+    fn send_chat(&mut self) {
+        let text = self.chat_draft.trim().to_string();
+        if text.is_empty() {
+            return;
+        }
+        match self.session.as_deref() {
+            // the host passes the message on itself, and doesn't hear it back
+            Some(Session::Host(host)) => {
+                let from = self
+                    .players
+                    .iter()
+                    .find(|player| player.local && player.bot.is_none())
+                    .map_or("Host", |player| player.name.as_str())
+                    .to_string();
+                host.chat(&from, &text);
+                self.receive_chat(&from, &text);
+            }
+            Some(Session::Client(client)) => {
+                if let Err(e) = client.send_chat(&text) {
+                    self.error = Some(format!("{:#}", e));
+                    return;
+                }
+            }
+            None => return,
+        }
+        self.chat_draft.clear();
+    }
+
+    fn receive_chat(&mut self, from: &str, text: &str) {
+        Arc::make_mut(&mut self.chat).push(format!("{}: {}", from, text));
+        if !self.chat_open {
+            self.unread_chat += 1;
+        }
+    }
+
+    fn set_rolls_open(&mut self, open: bool) {
+        self.rolls_open = open;
+        self.sync_roll_log();
+    }
+
+    /// Describe each roll made so far, if the list is showing, with the held dice in brackets,
+    /// for example "Alice, roll 2: 3 [5] [5] 2 6".
+    fn sync_roll_log(&mut self) {
+        if !self.rolls_open {
+            return;
+        }
+        let faces = self.game.rules().faces;
+        let log: Vec<_> = roll_log::rolls(&self.game)
+            .iter()
+            .map(|roll| {
+                let dice: Vec<_> = roll
+                    .dice
+                    .iter()
+                    .zip(&roll.held)
+                    .map(|(&value, &held)| {
+                        let face = match faces {
+                            Faces::Poker => poker::face_name(value).to_string(),
+                            Faces::Pips => value.0.to_string(),
+                        };
+                        if held {
+                            format!("[{}]", face)
+                        } else {
+                            face
+                        }
+                    })
+                    .collect();
+                let name = &self.players[roll.player].name;
+                format!("{}, roll {}: {}", name, roll.roll, dice.join(" "))
+            })
+            .collect();
+        // only a new roll changes the list, so it isn't rebuilt for every other move
+        if *self.roll_log != log {
+            self.roll_log = Arc::new(log);
+        }
+    }
+
+    fn set_chat_open(&mut self, open: bool) {
+        self.chat_open = open;
+        if open {
+            self.unread_chat = 0;
+        }
+    }
+
+    /// Let the person at this computer know if it has become their turn, after `before` played
+    /// from another computer.
+    fn notify_turn(&self, before: usize) {
+        let passed = self.game.player() != before && !self.players[before].local;
+        if passed && !self.game.is_complete() && self.local_person_playing() {
+            notify::your_turn(&self.current_player().name, &self.game.rules().name);
+        }
+    }
+
+    /// Whether it's the turn of a person at this computer.
+    fn local_person_playing(&self) -> bool {
+        let player = self.current_player();
+        player.local && player.bot.is_none()
+    }
+
+    /// The player whose turn it is.
+    fn current_player(&self) -> &Player {
+        &self.players[self.game.player()]
+    }
+
+    /// Whether the sheet being shown is the current player's, so the dice can be placed in it.
+    fn showing_current_sheet(&self) -> bool {
+        self.shown_sheet == self.game.player()
+    }
+
+    /// Whether the timers and the dice have stopped for the pause menu. A network game carries
+    /// on for the others while it's open, so it isn't stopped.
+    fn is_paused(&self) -> bool {
+        self.paused && self.session.is_none()
+    }
+
+    /// Open the pause menu, or close it and carry on.
+    fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        self.sync_dice();
+    }
+
+    /// Restart the countdown for the next decision.
+    fn reset_timer(&mut self) {
+        self.time_left = self.turn_time.unwrap_or(0);
+    }
+
+    /// Take back the last move, unless it was made by a computer player.
+    fn undo(&mut self) {
+        if self.session.is_some() {
+            self.error = Some("Moves can't be taken back in a network game".into());
+            return;
+        }
+        let mut game = self.game.clone();
+        game.undo();
+        if self.players[game.player()].bot.is_none() {
+            self.game = game;
+        }
+        self.error = None;
+        self.sync_dice();
+    }
+
+    fn redo(&mut self) {
+        if self.session.is_some() {
+            return;
+        }
+        self.game.redo();
+        self.error = None;
+        self.sync_dice();
+    }
+
+    /// Land the rolling dice. In a game that was joined, the host rolls them instead.
+    fn stop_roll(&mut self) {
+        if let Some(Session::Client(_)) = self.session.as_deref() {
+            return;
+        }
+        self.game.stop_roll();
+        self.landed();
+    }
+
+    /// Whether the dice are rolled on the table, with smart dice, rather than on screen.
+    fn rolling_on_table(&self) -> bool {
+        self.table.is_some()
+    }
+
+    /// Land the dice on screen until the smart dice are rolled again, for when one of them has
+    /// stopped answering.
+    fn roll_on_screen(&mut self) {
+        self.table = None;
+    }
+
+    /// Roll the dice on screen as the smart dice are rolled, and land them where those land.
+    /// Die `n` on the table is die `n` on screen, so the ones held stay to one side.
+    fn dice_event(&mut self, event: DiceEvent) {
+        let (die, message) = match event {
+            DiceEvent::Connected(dice) => {
+                self.toast(format!("Found {} smart dice, roll them on the table", dice));
+                return;
+            }
+            DiceEvent::Failed(why) => {
+                self.error = Some(format!("Couldn't connect to the smart dice: {}", why));
+                return;
+            }
+            DiceEvent::Message { die, message } => (die, message),
+        };
+        let joined = matches!(self.session.as_deref(), Some(Session::Client(_)));
+        let held = self.game.dice().get(die).map_or(true, |die| die.held);
+        if joined || held || self.game.rules().sides != smart_dice::SIDES {
+            return;
+        }
+        Arc::make_mut(self.table.get_or_insert_with(Default::default)).receive(die, message);
+        if message == DiceMessage::Rolling && !self.game.any_rolling() {
+            if !self.local_person_playing() || !self.game.can_roll() {
+                return;
+            }
+            self.play(Move::Roll);
+        }
+        let faces = match &mut self.table {
+            Some(table) => Arc::make_mut(table).take_faces(&self.game),
+            None => None,
+        };
+        if let Some(faces) = faces {
+            self.game.land(faces);
+            self.landed();
+        }
+    }
+
+    /// Catch up with the dice having landed.
+    fn landed(&mut self) {
+        self.reset_timer();
+        self.publish();
+        self.sync_dice();
+    }
+
+    /// Move the die in a place in the tray along by some places, as far as the ends.
+    fn move_die(&mut self, place: usize, by: i32) {
+        let last = self.dice_order.len() as i32 - 1;
+        let to = (place as i32 + by).max(0).min(last) as usize;
+        let order = Arc::make_mut(&mut self.dice_order);
+        let idx = order.remove(place);
+        order.insert(to, idx);
+        self.sync_dice();
+    }
+
+    fn set_hints(&mut self, hints: bool) {
+        self.hints = hints;
+        self.sync_dice();
+    }
+
+    /// Whether the dice to hold should be shown, because hints are on and it's a person's turn
+    /// to choose.
+    fn wants_hint(&self) -> bool {
+        let choosing = self.game.rolls() > 0 && self.game.can_roll();
+        self.hints && choosing && self.local_person_playing()
+    }
+
+    /// Whether to show how good each open slot is for the hand, because it's after the last
+    /// roll of a person's turn.
+    fn wants_placements(&self) -> bool {
+        self.game.phase() == TurnPhase::Scoring && self.local_person_playing()
+    }
+
+    /// The advisor's advice for the dice showing, once it has worked it out.
+    fn current_advice(&self) -> Option<&Advice> {
+        let advice = self.advice.as_deref()?;
+        if Some(&advice.position) == Position::of(&self.game).as_ref() {
+            Some(advice)
+        } else {
+            None
+        }
+    }
+
+    /// The dice to hold before the next roll, if a hint is wanted and the advisor has worked
+    /// it out for the dice showing.
+    fn hint(&self) -> Option<Vec<bool>> {
+        if !self.wants_hint() {
+            return None;
+        }
+        let kept = self.current_advice()?.kept.as_ref()?;
+        Some(expectimax::holds_for(
+            &self.game.scores()?,
+            &self.game,
+            kept,
+        ))
+    }
+
+    fn sync_dice(&mut self) {
+        // each turn starts on the sheet of the player taking it
+        if self.shown_turn != self.game.player() {
+            self.shown_turn = self.game.player();
+            self.shown_sheet = self.shown_turn;
+        }
+        // the values only change when the hand does, which is once a turn
+        if !self.wants_placements() {
+            self.placement_values = None;
+        } else if self.placement_values.is_none() {
+            self.placement_values = self
+                .current_advice()
+                .and_then(|advice| advice.placements.clone())
+                .map(Arc::new);
+        }
+        self.sync_roll_log();
+        // what can be done changes with the game, like holding dice once they've landed
+        if self.palette.open {
+            self.sync_palette();
+        }
+        let hint = self.hint();
+        let paused = self.is_paused();
+        // the dice are in the skin and color of whoever's turn it is
+        let skin = skins::by_name(&self.current_player().skin);
+        let player = if self.players.len() > 1 {
+            Some(self.game.player())
+        } else {
+            None
+        };
+        let dice = Arc::make_mut(&mut self.dice);
+        for (data, &idx) in dice.iter_mut().zip(self.dice_order.iter()) {
+            let die = &self.game.dice()[idx];
+            match die.value {
+                Some(value) => data.set_value(value),
+                None => data.set_rolling(),
+            };
+            data.set_held(die.held);
+            data.set_bright(hint.as_ref().map_or(true, |holds| holds[idx]));
+            data.set_paused(paused);
+            data.set_skin(skin);
+            data.set_player(player);
+        }
+    }
+}
+
+/// The final results of a completed game.
+#[derive(Debug, Clone, Data, Lens)]
+struct FinishedState {
+    /// Anything to tell the players about how the game ended, such as where it was saved.
+    note: String,
+    players: Arc<Vec<Player>>,
+    /// Each player's score sheet, in the same order as `players`.
+    sheets: Arc<Vec<ScoreSheet>>,
+    rules: RuleSet,
+    /// The time limit the game was played with, to offer again for the next game.
+    turn_time: Option<u32>,
+    /// What stood out in the game.
+    highlights: Arc<Highlights>,
+    /// The finished game, to review.
+    game: Game,
+    /// The toasts still to send on from the game's last moves.
+    toasts: Arc<Vec<String>>,
+}
+
+impl FinishedState {
+    /// The starting screen for another game, with the same rules and everyone who played on
+    /// this computer in the same seats.
+    fn starting(&self) -> StartingState {
+        StartingState::after(&self.players, &self.game, self.turn_time)
+    }
+
+    /// The index of the player with the highest total. The first player wins a tie.
+    ///
+    /// In a team game, this is the best player on the winning team.
+    fn winner(&self) -> usize {
+        let team = self.winning_team();
+        let totals = self.sheets.iter().map(|sheet| sheet.total()).enumerate();
+        totals
+            .filter(|(idx, _)| self.game.team(*idx) == team)
+            .fold(None, |best, (idx, total)| match best {
+                Some((_, best_total)) if best_total >= total => best,
+                _ => Some((idx, total)),
+            })
+            .map(|(idx, _)| idx)
+            .expect("a game needs at least one player")
+    }
+
+    /// Count the game in the profiles of the people who played it on this computer.
+    fn record_profiles(&self) -> Result {
+        let path = match profiles::default_path() {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let team = self.winning_team();
+        let results: Vec<_> = self
+            .players
+            .iter()
+            .zip(self.sheets.iter())
+            .enumerate()
+            .filter(|(_, (player, _))| player.local && player.bot.is_none())
+            .map(|(idx, (player, sheet))| {
+                let won = self.game.team(idx) == team;
+                (player.name.clone(), sheet.total(), won)
+            })
+            .collect();
+        if results.is_empty() {
+            return Ok(());
+        }
+        profiles::record_game(&path, &results)
+    }
+
+    /// The players' names, in turn order.
+    fn names(&self) -> Vec<String> {
+        self.players
+            .iter()
+            .map(|player| player.name.clone())
+            .collect()
+    }
+
+    /// Put the results on the clipboard as text, for pasting into a chat.
+    fn copy_results(&mut self) {
+        let summary = export::summary(&self.game, &self.names());
+        Application::global().clipboard().put_string(summary);
+        self.note = "The results are on the clipboard.".to_string();
+    }
+
+    /// The replay of the game.
+    fn replay(&self) -> Replay {
+        Replay::new(&self.game, self.names())
+    }
+
+    /// Write everyone's score sheet to `path`, as a picture if it's a PNG and as CSV otherwise,
+    /// saying where in the note.
+    fn export_results(&mut self, path: &Path) {
+        let saved = if path.extension() == Some(OsStr::new(snapshot::EXTENSION)) {
+            snapshot::save_png(path, &self.game, &self.names())
+        } else {
+            export::save_csv(path, &self.game, &self.names())
+        };
+        self.note = match saved {
+            Ok(()) => format!("The results are saved in {}.", path.display()),
+            Err(e) => format!("Couldn't export the results: {:#}", e),
+        };
+    }
+
+    /// Add the game, which took `duration` seconds, to the history.
+    fn record_history(&self, duration: u64) -> Result {
+        let path = core::history::default_path()
+            .ok_or_else(|| anyhow::anyhow!("there's no data directory to keep it in"))?;
+        History::open(&path)?.record(&self.replay(), duration)
+    }
+
+    /// Save the game's replay in the replays directory, giving where it was saved.
+    fn save_replay(&self) -> Result<PathBuf> {
+        let replay = self.replay();
+        let dir = replay::default_dir()
+            .ok_or_else(|| anyhow::anyhow!("there's no documents directory to save it in"))?;
+        let path = dir.join(replay.file_name());
+        replay.save(&path)?;
+        Ok(path)
+    }
+
+    /// The team with the highest score. The first team wins a tie.
+    fn winning_team(&self) -> usize {
+        let totals = (0..self.game.team_count()).map(|team| (team, self.game.team_total(team)));
+        totals
+            .fold(None, |best, (team, total)| match best {
+                Some((_, best_total)) if best_total >= total => best,
+                _ => Some((team, total)),
+            })
+            .map(|(team, _)| team)
+            .expect("a game needs at least one team")
+    }
+}
+
+/// A slot's category, with its column when the sheets have more than one.
+fn slot_name(slot: Slot, rules: &RuleSet) -> String {
+    if rules.columns > 1 {
+        format!("{} (column {})", slot.category.name(), slot.column + 1)
+    } else {
+        slot.category.name().to_string()
+    }
+}
+
+pub fn main() -> Result {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(result) = cli::run(&args) {
+        return result;
+    }
+    let seed = parse_seed(args.into_iter())?;
+    let settings = load_settings();
+
+    // describe the main window, where it was last time if it's been opened before
+    let window = settings.window();
+    let window_size = window.map_or(WINDOW_SIZE, |window| Size::new(window.width, window.height));
+    let mut main_window = WindowDesc::new(|| {
+        let screens = YatzyState::matcher()
+            .menu(build_menu())
+            .starting(build_starting())
+            .lobby(build_lobby())
+            .editing_rules(build_rules_editor())
+            .in_game(build_in_game())
+            .finished(build_finished())
+            .reviewing(build_review())
+            .loading(build_load_screen())
+            .watching(build_replay())
+            .history(build_history())
+            .statistics(build_statistics())
+            .settings(build_settings())
+            .help(build_help())
+            .controller(RememberGeometry::new());
+        let layout = Flex::column()
+            .with_child(build_toasts())
+            .with_flex_child(screens.lens(AppState::screen), 1.0);
+        build_themed(layout)
+    })
+    .title(WINDOW_TITLE)
+    .window_size(window_size);
+    if let Some(window) = window {
+        main_window = main_window.set_position(Point::new(window.x, window.y));
+    }
+
+    // create the initial app state
+    let mut starting = StartingState {
+        players: Arc::new(Vec::new()),
+        presets: all_presets(),
+        rules: RuleSet::yatzy(),
+        forced_order: false,
+        teams: false,
+        seed: seed.map(|seed| seed.to_string()).unwrap_or_default(),
+        turn_time: String::new(),
+        grace_period: String::new(),
+        address: String::new(),
+        encrypted: false,
+        password: String::new(),
+        network_status: String::new(),
+        lan_games: Arc::new(Vec::new()),
+        by_file: false,
+        file_status: String::new(),
+        profiles: all_profiles(),
+        profiles_open: false,
+    };
+    starting.use_settings(&settings);
+    let initial_state = AppState {
+        screen: YatzyState::Menu(MenuState::new(starting)),
+        settings,
+        toasts: Arc::new(Vec::new()),
+    };
+
+    let launcher = AppLauncher::with_window(main_window);
+
+    // pass on what happens in network games
+    let (net_events, received) = channel::unbounded();
+    let sink = launcher.get_external_handle();
+    thread::spawn(move || {
+        for event in received {
+            if sink.submit_command(NET_EVENT, event, None).is_err() {
+                break;
+            }
+        }
+    });
+
+    // pass on what the smart dice on the table say
+    let (dice_events, received) = channel::unbounded();
+    let sink = launcher.get_external_handle();
+    thread::spawn(move || {
+        for event in received {
+            if sink.submit_command(DICE_EVENT, event, None).is_err() {
+                break;
+            }
+        }
+    });
+
+    // list the games hosted on the local network
+    let browser = Browser::start(net_events.clone())
+        .map_err(|e| eprintln!("warning: couldn't look for games on the network: {:#}", e))
+        .ok();
+
+    // start the application
+    let delegate = Delegate {
+        net_events,
+        dice_events,
+        connecting_dice: false,
+        lan_games: Arc::new(Vec::new()),
+        _browser: browser,
+        scorecard_window: None,
+    };
+    launcher.delegate(delegate).launch(initial_state)?;
+    Ok(())
+}
+
+/// The player's settings, or the defaults with a warning if they can't be read.
+fn load_settings() -> Settings {
+    let saved =
+        settings::default_path().map_or(Ok(Settings::default()), |path| Settings::load(&path));
+    saved.unwrap_or_else(|err| {
+        eprintln!("warning: couldn't load the settings: {:#}", err);
+        Settings::default()
+    })
+}
+
+/// The bots that can play in the window: the usual ones, and Twitch chat if a channel is set in
+/// the settings. Only the window waits for chat to vote, so it isn't one of the usual ones.
+fn window_bots() -> BotRegistry {
+    let mut registry = bot_registry();
+    if let Some(name) = load_settings().twitch_channel {
+        twitch::register(&mut registry, name);
+    }
+    registry
+}
+
+/// Sync the profiles and saved games in the background, if somewhere to sync them is set up.
+fn sync_in_background() {
+    let remote = match load_settings().sync {
+        Some(remote) => remote,
+        None => return,
+    };
+    thread::spawn(move || {
+        if let Err(e) = sync::sync_here(&remote) {
+            eprintln!("warning: couldn't sync: {:#}", e);
+        }
+    });
+}
+
+/// Set up the `Env` the way the settings have it.
+fn set_env(env: &mut Env, settings: &Settings) {
+    env.set(ANIMATION_SPEED, settings.speed());
+    env.set(SOUNDS, settings.sounds);
+    let compact_width = match settings.layout {
+        Layout::Automatic => COMPACT_WINDOW_WIDTH,
+        Layout::Wide => 0.0,
+        Layout::Compact => std::f64::INFINITY,
+    };
+    env.set(COMPACT_WIDTH, compact_width);
+    theme::apply(env, settings.theme, settings.colors);
+}
+
+/// How long an animation that normally takes `time` takes at the speed set in `env`.
+fn animation_time(time: Duration, env: &Env) -> Duration {
+    time.div_f64(env.get(ANIMATION_SPEED))
+}
+
+/// The time now, in seconds since the Unix epoch.
+fn now() -> u64 {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH);
+    now.map_or(0, |now| now.as_secs())
+}
+
+/// Everyone who has played on this computer.
+fn all_profiles() -> Arc<Vec<Profile>> {
+    let saved = profiles::default_path().map_or(Ok(Vec::new()), |path| profiles::load(&path));
+    match saved {
+        Ok(saved) => Arc::new(saved),
+        Err(err) => {
+            eprintln!("warning: couldn't load the profiles: {:#}", err);
+            Arc::new(Vec::new())
+        }
+    }
+}
+
+/// Find the `--seed <n>` (or `--seed=<n>`) option in the command line arguments.
+fn parse_seed(mut args: impl Iterator<Item = String>) -> Result<Option<u64>> {
+    let arg = match args.next() {
+        Some(arg) => arg,
+        None => return Ok(None),
+    };
+    let value = if arg == "--seed" {
+        args.next()
+            .ok_or_else(|| anyhow::anyhow!("--seed needs a value"))?
+    } else if arg.starts_with("--seed=") {
+        arg["--seed=".len()..].to_string()
+    } else {
+        anyhow::bail!("unrecognised argument {:?}", arg);
+    };
+    if let Some(arg) = args.next() {
+        anyhow::bail!("unrecognised argument {:?}", arg);
+    }
+    let seed = value
+        .parse()
+        .map_err(|_| anyhow::anyhow!("the seed must be a number, not {:?}", value))?;
+    Ok(Some(seed))
+}
+
+struct Delegate {
+    /// Where the threads of network games report what happens.
+    net_events: channel::Sender<NetEvent>,
+    /// Where the smart dice report what happens.
+    dice_events: channel::Sender<DiceEvent>,
+    /// Whether the smart dice are being looked for or have been found, so they're only looked
+    /// for once.
+    connecting_dice: bool,
+    /// The games found on the local network, kept here as the starting screen comes and goes.
+    lan_games: Arc<Vec<LanGame>>,
+    /// Looks for the games, if mDNS could be started.
+    _browser: Option<Browser>,
+    /// The scorecard's own window, while it's popped out.
+    scorecard_window: Option<WindowId>,
+}
+
+impl Delegate {
+    /// Let the game know whether its scorecard is in a window of its own, as a new game or one
+    /// that's been opened again doesn't.
+    fn sync_scorecard_window(&self, data: &mut AppState) {
+        if let YatzyState::InGame(state) = &mut data.screen {
+            state.scorecard_detached = self.scorecard_window.is_some();
+        }
+    }
+
+    /// Keep track of a game found on the local network, or one that has gone.
+    fn lan_event(&mut self, event: &NetEvent) {
+        let games = Arc::make_mut(&mut self.lan_games);
+        match event {
+            NetEvent::Found(game) => {
+                games.retain(|known| known.name != game.name);
+                games.push(game.clone());
+            }
+            NetEvent::Lost(name) => games.retain(|known| known.name != *name),
+            _ => (),
+        }
+    }
+
+    /// Handle a command, returning whether to pass it on.
+    fn dispatch(&mut self, cmd: &Command, data: &mut YatzyState) -> bool {
+        if let Some(mv) = cmd.get(PLAY_MOVE) {
+            if let YatzyState::InGame(data) = data {
+                data.play(*mv);
+            }
+            data.finish_game_if_complete();
+            false
+        } else if cmd.is(START_GAME) {
+            data.start_game();
+            false
+        } else if cmd.is(UNDO) {
+            if let YatzyState::InGame(data) = data {
+                data.undo();
+            }
+            false
+        } else if cmd.is(REDO) {
+            if let YatzyState::InGame(data) = data {
+                data.redo();
+            }
+            false
+        } else if cmd.is(TOGGLE_PAUSE) {
+            if let YatzyState::InGame(data) = data {
+                data.toggle_pause();
+            }
+            false
+        } else if let Some(idx) = cmd.get(HOLD_MATCHING) {
+            if let YatzyState::InGame(data) = data {
+                data.hold_matching(*idx);
+            }
+            false
+        } else if let Some(idx) = cmd.get(SHOW_ODDS) {
+            if let YatzyState::InGame(data) = data {
+                data.show_odds(*idx);
+            }
+            false
+        } else if cmd.is(STOP_ROLL) {
+            if let YatzyState::InGame(data) = data {
+                data.stop_roll();
+            }
+            false
+        } else if cmd.is(CONNECT_DICE) {
+            if !self.connecting_dice {
+                self.connecting_dice = true;
+                bluetooth::connect(self.dice_events.clone());
+            }
+            if let YatzyState::InGame(data) = data {
+                data.toast("Looking for smart dice, roll them once they're found");
+            }
+            false
+        } else if let Some(event) = cmd.get(DICE_EVENT) {
+            if let DiceEvent::Failed(_) = event {
+                self.connecting_dice = false;
+            }
+            if let YatzyState::InGame(data) = data {
+                data.dice_event(event.clone());
+            }
+            false
+        } else if cmd.is(NEW_GAME) {
+            data.new_game();
+            false
+        } else if cmd.is(REMATCH) {
+            data.rematch();
+            false
+        } else if cmd.is(EDIT_RULES) {
+            data.edit_rules();
+            false
+        } else if cmd.is(SAVE_RULES) {
+            data.save_rules();
+            false
+        } else if cmd.is(CANCEL_RULES) {
+            data.cancel_rules();
+            false
+        } else if cmd.is(REVIEW_GAME) {
+            data.review_game();
+            false
+        } else if cmd.is(CLOSE_REVIEW) {
+            data.close_review();
+            false
+        } else if cmd.is(OPEN_HISTORY) {
+            data.open_history();
+            false
+        } else if cmd.is(CLOSE_HISTORY) {
+            data.close_history();
+            false
+        } else if let Some(id) = cmd.get(WATCH_HISTORY_GAME) {
+            data.watch_history_game(*id);
+            false
+        } else if cmd.is(WATCH_PASTED_GAME) {
+            data.watch_pasted_game();
+            false
+        } else if cmd.is(WATCH_REPLAY) {
+            data.watch_game();
+            false
+        } else if cmd.is(CLOSE_REPLAY) {
+            data.close_replay();
+            false
+        } else if cmd.is(OPEN_MENU) {
+            data.open_menu();
+            false
+        } else if cmd.is(CONTINUE_GAME) {
+            data.continue_game();
+            false
+        } else if cmd.is(OPEN_STATISTICS) {
+            data.open_statistics();
+            false
+        } else if cmd.is(CLOSE_STATISTICS) {
+            data.close_statistics();
+            false
+        } else if cmd.is(OPEN_SETTINGS) {
+            data.open_settings();
+            false
+        } else if cmd.is(CLOSE_SETTINGS) {
+            data.close_settings();
+            false
+        } else if cmd.is(OPEN_HOW_TO_PLAY) {
+            data.open_how_to_play();
+            false
+        } else if cmd.is(OPEN_ABOUT) {
+            data.open_about();
+            false
+        } else if cmd.is(CLOSE_HELP) {
+            data.close_help();
+            false
+        } else if cmd.is(OPEN_SAVES) {
+            data.open_saves();
+            false
+        } else if cmd.is(CLOSE_SAVES) {
+            data.close_saves();
+            false
+        } else if let Some(name) = cmd.get(LOAD_SLOT) {
+            data.load_slot(name);
+            false
+        } else if let Some(name) = cmd.get(DELETE_SLOT) {
+            data.delete_slot(name);
+            false
+        } else if cmd.is(HOST_GAME) {
+            data.host(self.net_events.clone());
+            false
+        } else if cmd.is(JOIN_GAME) {
+            data.join(self.net_events.clone());
+            false
+        } else if let Some(address) = cmd.get(JOIN_LAN_GAME) {
+            if let YatzyState::Starting(state) = data {
+                state.address = address.clone();
+            }
+            data.join(self.net_events.clone());
+            false
+        } else if cmd.is(LEAVE_LOBBY) {
+            data.leave_lobby();
+            false
+        } else if let Some((connection, ban)) = cmd.get(KICK_PLAYER) {
+            data.kick(*connection, *ban);
+            false
+        } else if let Some(event) = cmd.get(NET_EVENT) {
+            self.lan_event(event);
+            data.net_event(event.clone(), self.net_events.clone());
+            false
+        } else if let Some(file) = cmd.get(commands::OPEN_FILE) {
+            let path = file.path();
+            if path.extension() == Some(OsStr::new(save::EXTENSION)) {
+                data.load_game(path);
+            } else if path.extension() == Some(OsStr::new(replay::EXTENSION))
+                || path.extension() == Some(OsStr::new(notation::EXTENSION))
+            {
+                data.watch_replay(path);
+            } else if path.extension() == Some(OsStr::new(export::CSV_EXTENSION)) {
+                data.import_paper_games(path);
+            } else {
+                data.open_game_file(path);
+            }
+            false
+        } else if let Some(Some(file)) = cmd.get(commands::SAVE_FILE) {
+            match data {
+                YatzyState::InGame(data) => data.save_to(file.path()),
+                YatzyState::Finished(data) => data.export_results(file.path()),
+                _ => (),
+            }
+            false
+        } else {
+            true
+        }
+    }
+}
+
+impl AppDelegate<AppState> for Delegate {
+    fn command(
+        &mut self,
+        ctx: &mut DelegateCtx,
+        _target: Target,
+        cmd: &Command,
+        data: &mut AppState,
+        _env: &Env,
+    ) -> bool {
+        // the settings are kept outside the screens, for the whole window to be drawn with
+        let pass_on = if cmd.is(SAVE_SETTINGS) {
+            data.save_settings();
+            false
+        } else if let Some(text) = cmd.get(SHOW_TOAST) {
+            Arc::make_mut(&mut data.toasts).push(text.clone());
+            false
+        } else if cmd.is(DETACH_SCORECARD) {
+            if self.scorecard_window.is_none() {
+                let window = WindowDesc::new(build_scorecard_window)
+                    .title(SCORECARD_TITLE)
+                    .window_size(SCORECARD_WINDOW_SIZE);
+                self.scorecard_window = Some(window.id);
+                ctx.new_window(window);
+            }
+            false
+        } else if cmd.is(ATTACH_SCORECARD) {
+            if let Some(id) = self.scorecard_window {
+                ctx.submit_command(commands::CLOSE_WINDOW, Target::Window(id));
+            }
+            false
+        } else if let Some(action) = cmd.get(RUN_PALETTE) {
+            if let YatzyState::InGame(state) = &mut data.screen {
+                if let Some(cmd) = state.run_palette(*action) {
+                    ctx.submit_command(cmd, None);
+                }
+            }
+            false
+        } else {
+            self.dispatch(cmd, &mut data.screen)
+        };
+        // the screens can't send commands themselves, so they leave their toasts to be sent on
+        for text in data.screen.take_toasts() {
+            ctx.submit_command(Command::new(SHOW_TOAST, text), None);
+        }
+        if let YatzyState::Starting(state) = &mut data.screen {
+            state.lan_games = self.lan_games.clone();
+        }
+        self.sync_scorecard_window(data);
+        pass_on
+    }
+
+    fn window_removed(
+        &mut self,
+        id: WindowId,
+        data: &mut AppState,
+        _env: &Env,
+        ctx: &mut DelegateCtx,
+    ) {
+        match self.scorecard_window {
+            Some(scorecard) if scorecard == id => self.scorecard_window = None,
+            // the scorecard can't be played on its own, so it goes with the main window
+            Some(scorecard) => {
+                ctx.submit_command(commands::CLOSE_WINDOW, Target::Window(scorecard))
+            }
+            None => (),
+        }
+        self.sync_scorecard_window(data);
+    }
+
+    fn event(
+        &mut self,
+        ctx: &mut DelegateCtx,
+        _window_id: WindowId,
+        event: Event,
+        data: &mut AppState,
+        _env: &Env,
+    ) -> Option<Event> {
+        if let Event::KeyDown(key) = &event {
+            // while the command palette is open, everything typed goes to it
+            if let YatzyState::InGame(state) = &mut data.screen {
+                if HotKey::new(SysMods::Cmd, "p").matches(key) && !state.paused {
+                    state.toggle_palette();
+                    return None;
+                } else if state.palette.open {
+                    if let Some(cmd) = state.palette_key(key) {
+                        ctx.submit_command(cmd, None);
+                    }
+                    return None;
+                }
+            }
+            let in_game = if let YatzyState::InGame(_) = data.screen {
+                true
+            } else {
+                false
+            };
+            // the shortcuts for moves are left to the game, after its text boxes
+            if in_game && key.key_code == KeyCode::Escape {
+                ctx.submit_command(TOGGLE_PAUSE, None);
+                return None;
+            }
+        }
+        Some(event)
+    }
+}
+
+/// A window's contents, in the colors of the theme from the settings.
+fn build_themed(layout: impl Widget<AppState> + 'static) -> impl Widget<AppState> {
+    // the theme is set below the window, so each screen paints its own background
+    EnvScope::new(
+        |env, data: &AppState| set_env(env, &data.settings),
+        layout.background(druid::theme::WINDOW_BACKGROUND_COLOR),
+    )
+}
+
+/// The scorecard's own window, for it to be put on another screen. It shows the game being
+/// played in the main window, and stands empty between games.
+fn build_scorecard_window() -> impl Widget<AppState> {
+    let screens = YatzyState::matcher()
+        .in_game(
+            build_scoreboard()
+                .controller(Pausable)
+                .controller(Shortcuts::new()),
+        )
+        .default(Label::new("The scorecard shows here during a game"));
+    let back_btn =
+        Button::new("Put the scorecard back").on_click(|ctx, _data: &mut AppState, _env| {
+            ctx.submit_command(ATTACH_SCORECARD, None);
+        });
+    let layout = Flex::column()
+        .with_child(back_btn)
+        .with_spacer(VERTICAL_WIDGET_SPACING)
+        .with_flex_child(Align::centered(screens.lens(AppState::screen)), 1.0)
+        .padding(CARD_PADDING);
+    build_themed(layout)
+}
+
+/// The toast being shown, if there is one, above whichever screen is open.
+fn build_toasts() -> impl Widget<AppState> {
+    Either::new(
+        |data: &AppState, _env| data.toasts.is_empty(),
+        SizedBox::empty(),
+        Label::new(|data: &AppState, _env: &Env| data.toasts.first().cloned().unwrap_or_default())
+            .padding(CARD_PADDING)
+            .background(theme::HIGHLIGHT_COLOR)
+            .rounded(CARD_PADDING),
+    )
+    .controller(ExpireToasts::new())
+}
+
+fn build_starting() -> impl Widget<StartingState> {
+    // everyone playing, taking turns in this order
+    let label = Label::new("Players:");
+    let seats = ViewSwitcher::new(
+        |data: &StartingState, _env| data.players.len(),
+        |count, _data, _env| {
+            let mut seats = Flex::column().cross_axis_alignment(CrossAxisAlignment::Start);
+            for idx in 0..*count {
+                seats.add_child(build_seat(idx));
+                seats.add_spacer(LABEL_SPACING);
+            }
+            Box::new(seats)
+        },
+    );
+    let add_player_btn =
+        Button::new("Add player").on_click(|_ctx, data: &mut StartingState, _env| {
+            data.add_player(Seat::person(""));
+        });
+    let add_computer_btn =
+        Button::new("Add computer").on_click(|_ctx, data: &mut StartingState, _env| {
+            let bot = window_bots().names().next().map(str::to_string);
+            data.add_player(Seat {
+                name: String::new(),
+                bot,
+                skin: skins::CLASSIC.name.to_string(),
+            });
+        });
+
+    // everyone who has played before, to pick instead of typing their name
+    let profile_list = ViewSwitcher::new(
+        |data: &StartingState, _env| data.profiles.clone(),
+        |profiles, _data, _env| {
+            let mut list = Flex::column();
+            for profile in profiles.iter() {
+                let text = format!(
+                    "{}: {} games, {} wins, average {:.0}, best {}",
+                    profile.name,
+                    profile.games,
+                    profile.wins,
+                    profile.average(),
+                    profile.best
+                );
+                let name = profile.name.clone();
+                list.add_child(Button::new(text).on_click(
+                    move |_ctx, data: &mut StartingState, _env| data.choose_profile(&name),
+                ));
+                list.add_spacer(LABEL_SPACING);
+            }
+            Box::new(list)
+        },
+    );
+    let profiles_btn = Button::new(|data: &StartingState, _env: &Env| {
+        if data.profiles_open {
+            "Profiles \u{25b4}".to_string()
+        } else {
+            "Profiles \u{25be}".to_string()
+        }
+    })
+    .on_click(|_ctx, data: &mut StartingState, _env| data.profiles_open = !data.profiles_open);
+    let profiles = Either::new(
+        |data: &StartingState, _env| data.profiles.is_empty(),
+        SizedBox::empty(),
+        Flex::column()
+            .with_child(profiles_btn)
+            .with_child(Either::new(
+                |data: &StartingState, _env| data.profiles_open,
+                profile_list,
+                SizedBox::empty(),
+            )),
+    );
+
+    // the variant to play, rebuilt when house rules are saved
+    let rules = ViewSwitcher::new(
+        |data: &StartingState, _env| data.presets.clone(),
+        |presets, _data, _env| {
+            let choices = presets
+                .iter()
+                .map(|rules| (rules.name.clone(), rules.clone()));
+            Box::new(RadioGroup::new(choices).lens(StartingState::rules))
+        },
+    );
+    let edit_rules_btn =
+        Button::new("House rules...").on_click(|ctx, _data: &mut StartingState, _env| {
+            ctx.submit_command(EDIT_RULES, None);
+        });
+    let forced_order =
+        Checkbox::new("Forced (fill categories in order)").lens(StartingState::forced_order);
+    // teams, when there are four players
+    let teams = Either::new(
+        |data: &StartingState, _env| data.players.len() == TEAMS.len(),
+        Checkbox::new("Teams (players 1 and 3 against 2 and 4)").lens(StartingState::teams),
+        SizedBox::empty(),
+    );
+
+    // an optional seed, to play the same dice as another game
+    let seed = Flex::row()
+        .with_child(Label::new("Seed:"))
+        .with_spacer(LABEL_SPACING)
+        .with_child(
+            TextBox::new()
+                .with_placeholder("random")
+                .fix_width(TEXT_BOX_WIDTH)
+                .lens(StartingState::seed),
+        )
+        .with_spacer(LABEL_SPACING)
+        .with_child(Label::new(|data: &StartingState, _env: &Env| {
+            if data.seed.trim().is_empty() || data.seed().is_some() {
+                String::new()
+            } else {
+                "(not a number, ignored)".to_string()
+            }
+        }));
+
+    // an optional time limit for each decision
+    let turn_time = Flex::row()
+        .with_child(Label::new("Seconds per decision:"))
+        .with_spacer(LABEL_SPACING)
+        .with_child(
+            TextBox::new()
+                .with_placeholder("no limit")
+                .fix_width(TEXT_BOX_WIDTH)
+                .lens(StartingState::turn_time),
+        );
+
+    // host a game for others to join, or join someone else's
+    let network = Flex::row()
+        .with_child(Label::new("Network:"))
+        .with_spacer(LABEL_SPACING)
+        .with_child(
+            TextBox::new()
+                .with_placeholder("port to host, or address to join")
+                .fix_width(TEXT_BOX_WIDTH * 2.0)
+                .lens(StartingState::address),
+        )
+        .with_spacer(LABEL_SPACING)
+        .with_child(Checkbox::new("Encrypt").lens(StartingState::encrypted))
+        .with_spacer(LABEL_SPACING)
+        .with_child(
+            Button::new("Host").on_click(|ctx, _data: &mut StartingState, _env| {
+                ctx.submit_command(HOST_GAME, None);
+            }),
+        )
+        .with_spacer(LABEL_SPACING)
+        .with_child(
+            Button::new("Join").on_click(|ctx, _data: &mut StartingState, _env| {
+                ctx.submit_command(JOIN_GAME, None);
+            }),
+        );
+    // how long to wait for players who drop out of a game being hosted
+    let grace_period = Flex::row()
+        .with_child(Label::new("Seconds to wait for dropped players:"))
+        .with_spacer(LABEL_SPACING)
+        .with_child(
+            TextBox::new()
+                .with_placeholder(protocol::DEFAULT_GRACE_PERIOD.to_string())
+                .fix_width(TEXT_BOX_WIDTH)
+                .lens(StartingState::grace_period),
+        );
+    // the password to join with, or for others to join the game being hosted with
+    let password = Flex::row()
+        .with_child(Label::new("Password:"))
+        .with_spacer(LABEL_SPACING)
+        .with_child(
+            TextBox::new()
+                .with_placeholder("none")
+                .fix_width(TEXT_BOX_WIDTH)
+                .lens(StartingState::password),
+        );
+    // a button to join each game found on the local network
+    let lan_games = List::new(|| {
+        Button::new(|game: &LanGame, _env: &Env| format!("Join {}", game.name)).on_click(
+            |ctx, game: &mut LanGame, _env| {
+                ctx.submit_command(Command::new(JOIN_LAN_GAME, game.address.clone()), None);
+            },
+        )
+    })
+    .lens(StartingState::lan_games);
+    let network_status = Label::new(|data: &StartingState, _env: &Env| data.network_status.clone())
+        .with_text_color(ERROR_COLOR);
+    // play by file, carry on a game someone sent or one saved earlier, or watch one again
+    let by_file = Flex::row()
+        .with_child(Checkbox::new("Play by file").lens(StartingState::by_file))
+        .with_spacer(LABEL_SPACING)
+        .with_child(Button::new("Open game file").on_click(
+            |ctx, _data: &mut StartingState, _env| {
+                let games = FileSpec::new("Yatzy game", &[correspondence::EXTENSION]);
+                let options = FileDialogOptions::new().allowed_types(vec![games]);
+                ctx.submit_command(Command::new(commands::SHOW_OPEN_PANEL, options), None);
+            },
+        ))
+        .with_spacer(LABEL_SPACING)
+        .with_child(
+            Button::new("Saved games").on_click(|ctx, _data: &mut StartingState, _env| {
+                ctx.submit_command(OPEN_SAVES, None);
+            }),
+        )
+        .with_spacer(LABEL_SPACING)
+        .with_child(Button::new("Watch a replay").on_click(
+            |ctx, _data: &mut StartingState, _env| {
+                let replays = FileSpec::new("Yatzy replay", &[replay::EXTENSION]);
+                let written = FileSpec::new("Game notation", &[notation::EXTENSION]);
+                let options = FileDialogOptions::new().allowed_types(vec![replays, written]);
+                ctx.submit_command(Command::new(commands::SHOW_OPEN_PANEL, options), None);
+            },
+        ))
+        .with_spacer(LABEL_SPACING)
+        .with_child(Button::new("Watch a pasted game").on_click(
+            |ctx, _data: &mut StartingState, _env| {
+                ctx.submit_command(WATCH_PASTED_GAME, None);
+            },
+        ))
+        .with_spacer(LABEL_SPACING)
+        .with_child(
+            Button::new("History").on_click(|ctx, _data: &mut StartingState, _env| {
+                ctx.submit_command(OPEN_HISTORY, None);
+            }),
+        );
+    let file_status = Label::new(|data: &StartingState, _env: &Env| data.file_status.clone())
+        .with_text_color(ERROR_COLOR);
+
+    let start_game_btn =
+        Button::new("Start game!").on_click(|ctx, _data: &mut StartingState, _env| {
+            ctx.submit_command(START_GAME, None);
+        });
+    let menu_btn = Button::new("Menu").on_click(|ctx, _data: &mut StartingState, _env| {
+        ctx.submit_command(OPEN_MENU, None);
+    });
+
+    // arrange the two widgets vertically, with some padding
+    let layout = Flex::column()
+        .with_child(
+            Flex::row()
+                .with_child(label)
+                .with_spacer(LABEL_SPACING)
+                .with_child(seats),
+        )
+        .with_spacer(LABEL_SPACING)
+        .with_child(
+            Flex::row()
+                .with_child(add_player_btn)
+                .with_spacer(LABEL_SPACING)
+                .with_child(add_computer_btn),
+        )
+        .with_spacer(LABEL_SPACING)
+        .with_child(profiles)
+        .with_spacer(LABEL_SPACING)
+        .with_child(teams)
+        .with_spacer(VERTICAL_WIDGET_SPACING)
+        .with_child(rules)
+        .with_spacer(LABEL_SPACING)
+        .with_child(edit_rules_btn)
+        .with_spacer(LABEL_SPACING)
+        .with_child(forced_order)
+        .with_spacer(VERTICAL_WIDGET_SPACING)
+        .with_child(seed)
+        .with_spacer(LABEL_SPACING)
+        .with_child(turn_time)
+        .with_spacer(VERTICAL_WIDGET_SPACING)
+        .with_child(network)
+        .with_spacer(LABEL_SPACING)
+        .with_child(grace_period)
+        .with_spacer(LABEL_SPACING)
+        .with_child(password)
+        .with_spacer(LABEL_SPACING)
+        .with_child(lan_games)
+        .with_spacer(LABEL_SPACING)
+        .with_child(network_status)
+        .with_spacer(VERTICAL_WIDGET_SPACING)
+        .with_child(by_file)
+        .with_spacer(LABEL_SPACING)
+        .with_child(file_status)
+        .with_spacer(VERTICAL_WIDGET_SPACING)
+        .with_child(
+            Flex::row()
+                .with_child(start_game_btn)
+                .with_spacer(LABEL_SPACING)
+                .with_child(menu_btn),
+        );
+
+    // center the two widgets in the available space
+    Align::centered(layout)
+}
+
+/// The row for a seat on the starting screen: its name, who plays in it, the skin on their dice,
+/// and buttons to move it up the turn order or take it out.
+fn build_seat(idx: usize) -> impl Widget<StartingState> {
+    let seat = druid::lens::Id.map(
+        move |data: &StartingState| data.players[idx].clone(),
+        move |data: &mut StartingState, seat: Seat| {
+            // the lens puts the seat back on every event, not only when it changes
+            if data.players[idx] != seat {
+                Arc::make_mut(&mut data.players)[idx] = seat;
+            }
+        },
+    );
+    let name = TextBox::new()
+        .with_placeholder("e.g. Joe Bloggs")
+        .fix_width(TEXT_BOX_WIDTH)
+        .lens(seat.then(Seat::name));
+    // pressed to go through a person and each of the bots
+    let bot_btn =
+        Button::new(
+            move |data: &StartingState, _env: &Env| match &data.players[idx].bot {
+                Some(bot) => format!("{} computer", bot),
+                None => "Person".to_string(),
+            },
+        )
+        .on_click(move |_ctx, data: &mut StartingState, _env| data.next_bot(idx));
+    // pressed to go through the skins for the seat's dice
+    let skin_btn = Button::new(move |data: &StartingState, _env: &Env| {
+        format!("{} dice", data.players[idx].skin)
+    })
+    .on_click(move |_ctx, data: &mut StartingState, _env| data.next_skin(idx));
+    let up_btn = Button::new("\u{25b4}")
+        .on_click(move |_ctx, data: &mut StartingState, _env| data.move_up(idx));
+    let down_btn = Button::new("\u{25be}")
+        .on_click(move |_ctx, data: &mut StartingState, _env| data.move_up(idx + 1));
+    let remove_btn = Button::new("Remove")
+        .on_click(move |_ctx, data: &mut StartingState, _env| data.remove_player(idx));
+
+    Flex::row()
+        .with_child(name)
+        .with_spacer(LABEL_SPACING)
+        .with_child(bot_btn)
+        .with_spacer(LABEL_SPACING)
+        .with_child(skin_btn)
+        .with_spacer(LABEL_SPACING)
+        .with_child(up_btn)
+        .with_spacer(LABEL_SPACING)
+        .with_child(down_btn)
+        .with_spacer(LABEL_SPACING)
+        .with_child(remove_btn)
+}
+
+fn build_rules_editor() -> impl Widget<RulesEditorState> {
+    // a labelled text box
+    fn field(
+        label: &str,
+        lens: impl Lens<RulesEditorState, String> + 'static,
+    ) -> impl Widget<RulesEditorState> {
+        Flex::row()
+            .with_child(Label::new(label).fix_width(TEXT_BOX_WIDTH))
+            .with_spacer(LABEL_SPACING)
+            .with_child(TextBox::new().fix_width(TEXT_BOX_WIDTH).lens(lens))
+    }
+
+    // a checkbox for each category, in two columns
+    let mut categories = Flex::row();
+    for half in Category::ALL.chunks((Category::ALL.len() + 1) / 2) {
+        let mut column = Flex::column();
+        for &category in half {
+            let included = RulesEditorState::rules.map(
+                move |rules: &RuleSet| rules.has_category(category),
+                move |rules: &mut RuleSet, included| rules.set_category(category, included),
+            );
+            column.add_child(
+                Checkbox::new(category.name())
+                    .lens(included)
+                    .fix_width(TEXT_BOX_WIDTH),
+            );
+        }
+        categories.add_child(column);
+    }
+
+    let error =
+        Label::new(|data: &RulesEditorState, _env: &Env| data.error.clone().unwrap_or_default())
+            .with_text_color(ERROR_COLOR);
+
+    let save_btn = Button::new("Save").on_click(|ctx, _data: &mut RulesEditorState, _env| {
+        ctx.submit_command(SAVE_RULES, None);
+    });
+    let cancel_btn = Button::new("Cancel").on_click(|ctx, _data: &mut RulesEditorState, _env| {
+        ctx.submit_command(CANCEL_RULES, None);
+    });
+
+    let layout = Flex::column()
+        .with_child(field("Name:", RulesEditorState::name))
+        .with_spacer(VERTICAL_WIDGET_SPACING)
+        .with_child(categories)
+        .with_spacer(VERTICAL_WIDGET_SPACING)
+        .with_child(field(
+            "Upper section bonus threshold:",
+            RulesEditorState::upper_bonus_threshold,
+        ))
+        .with_child(field("Upper section bonus:", RulesEditorState::upper_bonus))
+        .with_child(field("Rolls per turn:", RulesEditorState::rolls))
+        .with_spacer(LABEL_SPACING)
+        .with_child(error)
+        .with_spacer(VERTICAL_WIDGET_SPACING)
+        .with_child(
+            Flex::row()
+                .with_child(save_btn)
+                .with_spacer(LABEL_SPACING)
+                .with_child(cancel_btn),
+        );
+
+    Align::centered(layout)
+}
+
+fn build_in_game() -> impl Widget<InGameState> {
+    // the game takes the room the menus over it leave, in one column if that's narrow
+    let layout =
+        Compact::new(build_game_layout(false), build_game_layout(true)).controller(Pausable);
+
+    // the pause menu goes over the top of the game, which can't be played while it's open
+    let pause_menu = Either::new(
+        |data: &InGameState, _env| data.paused,
+        build_pause_menu(),
+        SizedBox::empty(),
+    );
+    let palette = Either::new(
+        |data: &InGameState, _env| data.palette.open,
+        build_palette().lens(InGameState::palette),
+        SizedBox::empty(),
+    );
+    let layout = Flex::column()
+        .with_child(pause_menu)
+        .with_child(palette)
+        .with_flex_child(layout, 1.0);
+
+    // center the widgets in the space over the status bar, landing the dice, counting down the
+    // turn timer, playing the computer's turns, asking the solver for hints and taking the keys
+    // for moves
+    Flex::column()
+        .with_flex_child(Align::centered(layout), 1.0)
+        .with_child(build_status_bar())
+        .controller(Shortcuts::new())
+        .controller(LandDice::new())
+        .controller(TurnTimer::new())
+        .controller(AutoPlay::new())
+        .controller(Advise::new())
+}
+
+/// A line along the bottom of the game saying what's being played, how far into it everyone is
+/// and how the network game is doing.
+fn build_status_bar() -> impl Widget<InGameState> {
+    Label::new(|data: &InGameState, _env: &Env| {
+        let game = &data.game;
+        let network = match data.session.as_deref() {
+            None => "Playing on this computer".to_string(),
+            Some(Session::Host(_)) => match data.joined.len() {
+                1 => "Hosting, 1 player joined".to_string(),
+                joined => format!("Hosting, {} players joined", joined),
+            },
+            Some(Session::Client(_)) => match data.connection {
+                Connection::Connected => "Connected to the host",
+                Connection::Reconnecting => "Reconnecting to the host",
+                Connection::Disconnected => "Disconnected from the host",
+            }
+            .to_string(),
+        };
+        format!(
+            "{} | Round {} of {} | Rolls left: {} | {} | {}",
+            game.rules().name,
+            game.round(),
+            game.rounds(),
+            game.rolls_left(),
+            date::elapsed(data.elapsed),
+            network
+        )
+    })
+    .padding(CARD_PADDING)
+}
+
+/// The game itself, with the panels for the chat and the rolls beside it. The compact layout is
+/// for small windows, keeping only what's needed to play in a column that scrolls, with the dice
+/// above the scorecard and the panels under them.
+fn build_game_layout(compact: bool) -> Box<dyn Widget<InGameState>> {
+    // whose turn it is and how far into it they are, so people know when to take the mouse
+    let player_name = Label::new(|data: &InGameState, _env: &Env| {
+        let player = data.current_player();
+        let whose = match data.session {
+            Some(_) if player.local && player.bot.is_none() => "Your turn".to_string(),
+            _ if data.players.len() > 1 => format!("{}'s turn", player.name),
+            _ => format!("Player: {}", player.name),
+        };
+        let game = &data.game;
+        match game.rolls() {
+            0 => format!("{}, ready to roll", whose),
+            rolls => format!("{}, roll {} of {}", whose, rolls, rolls + game.rolls_left()),
+        }
+    });
+    let totals = Label::new(|data: &InGameState, _env: &Env| team_totals(data));
+    let seed = Label::new(
+        |data: &InGameState, _env: &Env| match data.session.as_deref() {
+            Some(Session::Client(_)) => "Seed: kept by the host".to_string(),
+            _ => format!("Seed: {}", data.game.seed()),
+        },
+    );
+    // undo and redo, also available as Ctrl+Z and Ctrl+Y
+    let toolbar = Flex::row()
+        .with_child(
+            Button::new("Undo").on_click(|ctx, _data: &mut InGameState, _env| {
+                ctx.submit_command(UNDO, None);
+            }),
+        )
+        .with_spacer(LABEL_SPACING)
+        .with_child(
+            Button::new("Redo").on_click(|ctx, _data: &mut InGameState, _env| {
+                ctx.submit_command(REDO, None);
+            }),
+        )
+        .with_spacer(LABEL_SPACING)
+        .with_child(
+            TextBox::new()
+                .with_placeholder("save as")
+                .fix_width(TEXT_BOX_WIDTH)
+                .lens(InGameState::save_name),
+        )
+        .with_spacer(LABEL_SPACING)
+        .with_child(
+            Button::new("Save")
+                .on_click(|_ctx, data: &mut InGameState, _env| data.save_slot(false)),
+        )
+        .with_spacer(LABEL_SPACING)
+        .with_child(
+            Button::new("Save to file").on_click(|ctx, _data: &mut InGameState, _env| {
+                let saves = FileSpec::new("Saved game", &[save::EXTENSION]);
+                let options = FileDialogOptions::new().allowed_types(vec![saves]);
+                ctx.submit_command(Command::new(commands::SHOW_SAVE_PANEL, options), None);
+            }),
+        )
+        .with_spacer(LABEL_SPACING)
+        .with_child(Either::new(
+            |data: &InGameState, _env| data.rolling_on_table(),
+            Button::new("Roll on screen")
+                .on_click(|_ctx, data: &mut InGameState, _env| data.roll_on_screen()),
+            Button::new("Smart dice").on_click(|ctx, _data: &mut InGameState, _env| {
+                ctx.submit_command(CONNECT_DICE, None);
+            }),
+        ))
+        .with_spacer(LABEL_SPACING)
+        .with_child(Checkbox::new("Hints").lens(druid::lens::Id.map(
+            |data: &InGameState| data.hints,
+            |data: &mut InGameState, hints| data.set_hints(hints),
+        )));
+    let time_left = Label::new(|data: &InGameState, _env: &Env| match data.turn_time {
+        Some(_) => format!("Time left: {}s", data.time_left),
+        None => String::new(),
+    });
+    // in poker dice, the hand the dice make
+    let hand = Label::new(|data: &InGameState, _env: &Env| {
+        match (data.game.rules().faces, data.game.scores()) {
+            (Faces::Poker, Some(scores)) => PokerHand::of(&scores).name().to_string(),
+            _ => String::new(),
+        }
+    });
+    // the chances asked for from a die's menu
+    let odds = Label::new(|data: &InGameState, _env: &Env| data.odds.clone().unwrap_or_default());
+    // what a computer player is waiting for, like the time left for chat to vote
+    let bot_status =
+        Label::new(|data: &InGameState, _env: &Env| data.bot_status.clone().unwrap_or_default());
+    // why the last move wasn't allowed
+    let error = Label::new(|data: &InGameState, _env: &Env| data.error.clone().unwrap_or_default())
+        .with_text_color(ERROR_COLOR);
+
+    let dice = build_dice(true);
+    // rolling is a move like any other, so it can't be pressed in someone else's turn
+    let roll_label =
+        |data: &InGameState, _env: &Env| format!("Roll ({} left)", data.game.rolls_left());
+    let roll_btn = Either::new(
+        |data: &InGameState, _env| data.current_player().local && data.game.can_roll(),
+        Button::new(roll_label).on_click(|ctx, _data: &mut InGameState, _env| {
+            ctx.submit_command(Command::new(PLAY_MOVE, Move::Roll), None);
+        }),
+        Label::new(roll_label).with_text_color(DISABLED_COLOR),
+    );
+
+    let overwrite = build_overwrite();
+    // so are moves that look like a slip of the mouse, with the reason shown as the error
+    let confirm = Either::new(
+        |data: &InGameState, _env| data.confirming.is_some(),
+        Flex::row()
+            .with_child(
+                Button::new("Go ahead").on_click(|ctx, data: &mut InGameState, _env| {
+                    if let Some(mv) = data.confirm_move() {
+                        ctx.submit_command(Command::new(PLAY_MOVE, mv), None);
+                    }
+                }),
+            )
+            .with_spacer(LABEL_SPACING)
+            .with_child(
+                Button::new("Think again")
+                    .on_click(|_ctx, data: &mut InGameState, _env| data.cancel_move()),
+            ),
+        SizedBox::empty(),
+    );
+
+    // the scorecard can be popped out to be put on another screen
+    let scoreboard = Either::new(
+        |data: &InGameState, _env| data.scorecard_detached,
+        Flex::column()
+            .with_child(Label::new("The scorecard is in its own window"))
+            .with_spacer(LABEL_SPACING)
+            .with_child(Button::new("Put it back").on_click(
+                |ctx, _data: &mut InGameState, _env| {
+                    ctx.submit_command(ATTACH_SCORECARD, None);
+                },
+            )),
+        Flex::column()
+            .with_child(
+                Button::new("Pop out").on_click(|ctx, _data: &mut InGameState, _env| {
+                    ctx.submit_command(DETACH_SCORECARD, None);
+                }),
+            )
+            .with_spacer(LABEL_SPACING)
+            .with_child(build_scoreboard()),
+    );
+
+    // the chat sits beside the game, in network games
+    let chat = Either::new(
+        |data: &InGameState, _env| data.session.is_some(),
+        build_chat(),
+        SizedBox::empty(),
+    );
+
+    if compact {
+        let panels = Flex::row()
+            .with_child(chat)
+            .with_spacer(VERTICAL_WIDGET_SPACING)
+            .with_child(build_roll_log());
+        let layout = Flex::column()
+            .with_child(overwrite)
+            .with_child(player_name)
+            .with_child(time_left)
+            .with_spacer(LABEL_SPACING)
+            .with_child(dice)
+            .with_spacer(LABEL_SPACING)
+            .with_child(roll_btn)
+            .with_child(hand)
+            .with_child(odds)
+            .with_child(bot_status)
+            .with_child(error)
+            .with_child(confirm)
+            .with_spacer(LABEL_SPACING)
+            .with_child(scoreboard)
+            .with_spacer(VERTICAL_WIDGET_SPACING)
+            .with_child(panels);
+        return Box::new(Scroll::new(layout).vertical());
+    }
+
+    // arrange the widgets vertically, with some padding
+    let layout = Flex::column()
+        .with_child(toolbar)
+        .with_child(overwrite)
+        .with_spacer(VERTICAL_WIDGET_SPACING)
+        .with_child(build_player_cards())
+        .with_spacer(LABEL_SPACING)
+        .with_child(player_name)
+        .with_child(totals)
+        .with_child(seed)
+        .with_child(time_left)
+        .with_spacer(VERTICAL_WIDGET_SPACING)
+        .with_child(dice)
+        .with_spacer(LABEL_SPACING)
+        .with_child(roll_btn)
+        .with_spacer(LABEL_SPACING)
+        .with_child(hand)
+        .with_child(odds)
+        .with_child(bot_status)
+        .with_child(error)
+        .with_child(confirm)
+        .with_spacer(VERTICAL_WIDGET_SPACING)
+        .with_child(scoreboard);
+
+    // the game takes the room the panels leave it, for the dice and scorecard to fit themselves to
+    Box::new(
+        Flex::row()
+            .with_flex_child(layout, 1.0)
+            .with_spacer(VERTICAL_WIDGET_SPACING)
+            .with_child(chat)
+            .with_spacer(VERTICAL_WIDGET_SPACING)
+            .with_child(build_roll_log()),
+    )
+}
+
+/// Buttons to confirm saving over another game, or keep it, while that's waiting to be
+/// confirmed.
+fn build_overwrite() -> impl Widget<InGameState> {
+    Either::new(
+        |data: &InGameState, _env| data.overwriting,
+        Flex::row()
+            .with_child(
+                Button::new("Overwrite")
+                    .on_click(|_ctx, data: &mut InGameState, _env| data.save_slot(true)),
+            )
+            .with_spacer(LABEL_SPACING)
+            .with_child(
+                Button::new("Keep it")
+                    .on_click(|_ctx, data: &mut InGameState, _env| data.cancel_overwrite()),
+            ),
+        SizedBox::empty(),
+    )
+}
+
+/// What can be done with the game paused: carry on, save it in the slot named in the toolbar,
+/// change the settings or leave it for the main menu.
+fn build_pause_menu() -> impl Widget<InGameState> {
+    let resume_btn = Button::new("Resume").on_click(|ctx, _data: &mut InGameState, _env| {
+        ctx.submit_command(TOGGLE_PAUSE, None);
+    });
+    let save_btn =
+        Button::new("Save").on_click(|_ctx, data: &mut InGameState, _env| data.save_slot(false));
+    let settings_btn = build_leave_game_button("Settings", OPEN_SETTINGS);
+    let how_to_play_btn = build_leave_game_button("How to play", OPEN_HOW_TO_PLAY);
+    let about_btn = build_leave_game_button("About", OPEN_ABOUT);
+    let quit_btn = Button::new("Quit to menu").on_click(|ctx, _data: &mut InGameState, _env| {
+        ctx.submit_command(OPEN_MENU, None);
+    });
+    let note = Label::new(|data: &InGameState, _env: &Env| {
+        if data.session.is_some() {
+            "The game carries on for the others while you're away".to_string()
+        } else {
+            String::new()
+        }
+    });
+
+    Flex::column()
+        .with_child(Label::new("Paused"))
+        .with_spacer(LABEL_SPACING)
+        .with_child(
+            Flex::row()
+                .with_child(resume_btn)
+                .with_spacer(LABEL_SPACING)
+                .with_child(save_btn)
+                .with_spacer(LABEL_SPACING)
+                .with_child(settings_btn)
+                .with_spacer(LABEL_SPACING)
+                .with_child(how_to_play_btn)
+                .with_spacer(LABEL_SPACING)
+                .with_child(about_btn)
+                .with_spacer(LABEL_SPACING)
+                .with_child(quit_btn),
+        )
+        .with_child(build_overwrite())
+        .with_child(note)
+        .padding(CARD_PADDING)
+        .background(theme::HIGHLIGHT_COLOR)
+        .rounded(CARD_PADDING)
+}
+
+/// The command palette, listing what typing in it matches with the action Enter would pick lit
+/// up. Any of them can be clicked too.
+fn build_palette() -> impl Widget<Palette> {
+    let query = Label::new(|data: &Palette, _env: &Env| {
+        if data.query.is_empty() {
+            "Type to find an action".to_string()
+        } else {
+            format!("> {}", data.query)
+        }
+    });
+    let matches = Either::new(
+        |data: &Palette, _env| data.matches.is_empty(),
+        Label::new("Nothing matches").with_text_color(DISABLED_COLOR),
+        List::new(build_palette_entry).lens(Palette::matches),
+    );
+
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(query)
+        .with_spacer(LABEL_SPACING)
+        .with_child(matches)
+        .padding(CARD_PADDING)
+}
+
+/// An action in the command palette, lit up if Enter would pick it.
+fn build_palette_entry() -> impl Widget<PaletteEntry> {
+    Either::new(
+        |data: &PaletteEntry, _env| data.selected,
+        Label::new(|data: &PaletteEntry, _env: &Env| data.name.clone())
+            .padding(CARD_PADDING)
+            .background(theme::HIGHLIGHT_COLOR)
+            .rounded(CARD_PADDING),
+        Button::new(|data: &PaletteEntry, _env: &Env| data.name.clone()).on_click(
+            |ctx, data: &mut PaletteEntry, _env| {
+                ctx.submit_command(Command::new(RUN_PALETTE, data.action), None);
+            },
+        ),
+    )
+}
+
+/// The scorecard, with a tab for each player's sheet above it.
+fn build_scoreboard() -> impl Widget<InGameState> {
+    Flex::column()
+        .with_child(build_sheet_tabs())
+        .with_child(Scorecard::new())
+}
+
+/// A tab for each player's score sheet above the scorecard, when there's more than one, with the
+/// one being shown lit up. The sheet to place the dice in is the current player's, which is
+/// picked again at the start of each turn.
+fn build_sheet_tabs() -> impl Widget<InGameState> {
+    // rebuilt when the number of players changes, between games
+    ViewSwitcher::new(
+        |data: &InGameState, _env| data.players.len(),
+        |count, _data, _env| {
+            let mut tabs = Flex::row();
+            if *count < 2 {
+                return Box::new(tabs);
+            }
+            for idx in 0..*count {
+                if idx > 0 {
+                    tabs.add_spacer(LABEL_SPACING);
+                }
+                let name = move |data: &InGameState, _env: &Env| data.players[idx].name.clone();
+                tabs.add_child(Either::new(
+                    move |data: &InGameState, _env| data.shown_sheet == idx,
+                    Label::new(name)
+                        .padding(CARD_PADDING)
+                        .background(theme::HIGHLIGHT_COLOR)
+                        .rounded(CARD_PADDING),
+                    Button::new(name).on_click(move |_ctx, data: &mut InGameState, _env| {
+                        data.shown_sheet = idx;
+                    }),
+                ));
+            }
+            Box::new(tabs)
+        },
+    )
+}
+
+/// A button on the pause menu that opens another screen, taking the game off the screen until
+/// it's closed. A network game can't wait for that, so it can't be pressed in one.
+fn build_leave_game_button(text: &str, open: Selector<()>) -> impl Widget<InGameState> {
+    Either::new(
+        |data: &InGameState, _env| data.session.is_none(),
+        Button::new(text).on_click(move |ctx, _data: &mut InGameState, _env| {
+            ctx.submit_command(open, None);
+        }),
+        Label::new(text).with_text_color(DISABLED_COLOR),
+    )
+}
+
+/// The dice, in rows of up to `DICE_PER_ROW`, as many as the variant has. When they're
+/// `holdable`, clicking a die holds or releases it, and dragging it moves it in the tray.
+fn build_dice(holdable: bool) -> impl Widget<InGameState> {
+    // the game decides which dice are held, so clicking one is played as a move
+    let die_lens = move |place: usize| {
+        druid::lens::Id.map(
+            move |data: &InGameState| data.dice[place],
+            move |data: &mut InGameState, die: DieData| {
+                if !holdable {
+                    return;
+                }
+                if die.moved_by() != 0 {
+                    data.move_die(place, die.moved_by());
+                } else if die.is_held() != data.dice[place].is_held() {
+                    data.play(Move::ToggleHold(data.dice_order[place]));
+                }
+            },
+        )
+    };
+    // rebuild the tray when the number of dice changes, since it depends on the variant
+    ViewSwitcher::new(
+        |data: &InGameState, _env| data.dice.len(),
+        move |count, _data, _env| {
+            let mut tray = Flex::column();
+            for first in (0..*count).step_by(DICE_PER_ROW) {
+                if first > 0 {
+                    tray.add_spacer(LABEL_SPACING);
+                }
+                let mut row = Flex::row();
+                for place in first..(first + DICE_PER_ROW).min(*count) {
+                    if place > first {
+                        row.add_spacer(LABEL_SPACING);
+                    }
+                    // the dice share the width, growing and shrinking with the window
+                    let die = Die::new().lens(die_lens(place));
+                    if holdable {
+                        row.add_flex_child(die.controller(DieMenu::new(place)), 1.0);
+                    } else {
+                        row.add_flex_child(die, 1.0);
+                    }
+                }
+                tray.add_child(row);
+            }
+            Box::new(tray)
+        },
+    )
+}
+
+/// A card for each player with their total so far, when there's more than one, edged in their
+/// color, with the card of the player whose turn it is marked and lit up.
+fn build_player_cards() -> impl Widget<InGameState> {
+    // rebuilt when the number of players changes, between games
+    ViewSwitcher::new(
+        |data: &InGameState, _env| data.players.len(),
+        |count, _data, _env| {
+            let mut cards = Flex::row();
+            if *count < 2 {
+                return Box::new(cards);
+            }
+            for idx in 0..*count {
+                if idx > 0 {
+                    cards.add_spacer(LABEL_SPACING);
+                }
+                let card = move |marker: &'static str| {
+                    Label::new(move |data: &InGameState, _env: &Env| {
+                        let total = data.game.sheets()[idx].total();
+                        format!("{}{}: {}", marker, data.players[idx].name, total)
+                    })
+                    .padding(CARD_PADDING)
+                    // the same color as the border of the dice on the player's turns
+                    .border(theme::player_color(idx), 2.0)
+                };
+                cards.add_child(Either::new(
+                    move |data: &InGameState, _env| data.game.player() == idx,
+                    card("\u{25b6} ")
+                        .background(theme::HIGHLIGHT_COLOR)
+                        .rounded(CARD_PADDING),
+                    card(""),
+                ));
+            }
+            Box::new(cards)
+        },
+    )
+}
+
+/// Each team's total so far, in team games, marking the team whose turn it is.
+fn team_totals(data: &InGameState) -> String {
+    if !data.game.has_teams() {
+        return String::new();
+    }
+    let playing = data.game.team(data.game.player());
+    let teams = (0..data.game.team_count()).map(|team| {
+        let marker = if team == playing { "> " } else { "" };
+        let name = team_name(&data.game, &data.players, team);
+        format!("{}{}: {}", marker, name, data.game.team_total(team))
+    });
+    teams.collect::<Vec<_>>().join("   ")
+}
+
+/// The chat for network games, collapsed to a button counting the messages not yet read.
+fn build_chat() -> impl Widget<InGameState> {
+    let open_btn = Button::new(|data: &InGameState, _env: &Env| match data.unread_chat {
+        0 => "Chat".to_string(),
+        unread => format!("Chat ({})", unread),
+    })
+    .on_click(|_ctx, data: &mut InGameState, _env| data.set_chat_open(true));
+
+    let close_btn = Button::new("Hide chat")
+        .on_click(|_ctx, data: &mut InGameState, _env| data.set_chat_open(false));
+    let messages = Scroll::new(
+        List::new(|| Label::new(|line: &String, _env: &Env| line.clone())).lens(InGameState::chat),
+    )
+    .vertical()
+    .fix_size(TEXT_BOX_WIDTH * 1.5, 200.0);
+    let draft = TextBox::new()
+        .with_placeholder("Say something")
+        .fix_width(TEXT_BOX_WIDTH)
+        .lens(InGameState::chat_draft);
+    let send_btn =
+        Button::new("Send").on_click(|_ctx, data: &mut InGameState, _env| data.send_chat());
+    let panel = Flex::column()
+        .with_child(close_btn)
+        .with_spacer(LABEL_SPACING)
+        .with_child(messages)
+        .with_spacer(LABEL_SPACING)
+        .with_child(
+            Flex::row()
+                .with_child(draft)
+                .with_spacer(LABEL_SPACING)
+                .with_child(send_btn),
+        )
+        .with_spacer(VERTICAL_WIDGET_SPACING)
+        .with_child(build_kick_buttons().lens(InGameState::joined));
+
+    Either::new(|data: &InGameState, _env| data.chat_open, panel, open_btn)
+}
+
+/// The rolls made so far, to look back on what everyone rolled, or a button to show them.
+fn build_roll_log() -> impl Widget<InGameState> {
+    let open_btn = Button::new("Rolls")
+        .on_click(|_ctx, data: &mut InGameState, _env| data.set_rolls_open(true));
+    let close_btn = Button::new("Hide rolls")
+        .on_click(|_ctx, data: &mut InGameState, _env| data.set_rolls_open(false));
+    let rolls = Scroll::new(
+        List::new(|| Label::new(|line: &String, _env: &Env| line.clone()))
+            .lens(InGameState::roll_log),
+    )
+    .vertical()
+    .fix_size(TEXT_BOX_WIDTH * 1.5, 200.0);
+    let panel = Flex::column()
+        .with_child(close_btn)
+        .with_spacer(LABEL_SPACING)
+        .with_child(rolls);
+
+    Either::new(|data: &InGameState, _env| data.rolls_open, panel, open_btn)
+}
+
+/// Buttons to remove or ban each player who joined the game being hosted. There are none when
+/// joining someone else's game.
+fn build_kick_buttons() -> impl Widget<Arc<Vec<(ConnectionId, String)>>> {
+    ViewSwitcher::new(
+        |joined: &Arc<Vec<(ConnectionId, String)>>, _env| joined.clone(),
+        |joined, _data, _env| {
+            let mut rows = Flex::column();
+            for (connection, name) in joined.iter().cloned() {
+                let button = |text: &str, ban| {
+                    Button::new(text).on_click(move |ctx, _data, _env| {
+                        ctx.submit_command(Command::new(KICK_PLAYER, (connection, ban)), None);
+                    })
+                };
+                rows.add_child(
+                    Flex::row()
+                        .with_child(Label::new(name).fix_width(TEXT_BOX_WIDTH))
+                        .with_spacer(LABEL_SPACING)
+                        .with_child(button("Remove", false))
+                        .with_spacer(LABEL_SPACING)
+                        .with_child(button("Ban", true)),
+                );
+                rows.add_spacer(LABEL_SPACING);
+            }
+            Box::new(rows)
+        },
+    )
+}
+
+/// The players who joined a network game and are still connected, with their names, when
+/// hosting it.
+fn joined_names(session: &Session) -> Arc<Vec<(ConnectionId, String)>> {
+    let joined = match session {
+        Session::Host(host) => host.joined(),
+        Session::Client(_) => Vec::new(),
+    };
+    Arc::new(
+        joined
+            .into_iter()
+            .map(|(connection, player)| (connection, player.name))
+            .collect(),
+    )
+}
+
+/// The players on a team, like "Alice & Carol".
+fn team_name(game: &Game, players: &[Player], team: usize) -> String {
+    let names: Vec<_> = game
+        .team_members(team)
+        .map(|player| players[player].name.as_str())
+        .collect();
+    names.join(" & ")
+}
+
+fn build_finished() -> impl Widget<FinishedState> {
+    let winner = Label::new(|data: &FinishedState, _env: &Env| {
+        if data.game.has_teams() {
+            let team = data.winning_team();
+            return format!(
+                "{} win with {} points!",
+                team_name(&data.game, &data.players, team),
+                data.game.team_total(team)
+            );
+        }
+        let winner = data.winner();
+        format!(
+            "{} wins with {} points!",
+            data.players[winner].name,
+            data.sheets[winner].total()
+        )
+    });
+    // everyone's totals, when there was more than one player
+    let standings = Label::new(|data: &FinishedState, _env: &Env| {
+        if data.players.len() < 2 {
+            return String::new();
+        }
+        let totals = data.players.iter().zip(data.sheets.iter());
+        let mut standings: Vec<_> = totals
+            .map(|(player, sheet)| format!("{}: {}", player.name, sheet.total()))
+            .collect();
+        if data.game.has_teams() {
+            let teams = (0..data.game.team_count()).map(|team| {
+                let name = team_name(&data.game, &data.players, team);
+                format!("{}: {}", name, data.game.team_total(team))
+            });
+            standings = teams.chain(standings).collect();
+        }
+        standings.join("   ")
+    });
+    // the most Yatzys and the biggest score in a single slot
+    let highlights = Label::new(|data: &FinishedState, _env: &Env| {
+        let mut lines = Vec::new();
+        if let Some((player, count)) = data.highlights.most_yatzys() {
+            let times = match count {
+                1 => "once".to_string(),
+                2 => "twice".to_string(),
+                _ => format!("{} times", count),
+            };
+            lines.push(format!(
+                "Most Yatzys: {}, {}",
+                data.players[player].name, times
+            ));
+        }
+        if let Some((player, slot, points)) = data.highlights.biggest.filter(|b| b.2 > 0) {
+            lines.push(format!(
+                "Biggest score: {} points in {} by {}",
+                points,
+                slot_name(slot, &data.rules),
+                data.players[player].name
+            ));
+        }
+        lines.join("   ")
+    });
+    // everyone's score sheet, side by side
+    let sheets = ViewSwitcher::new(
+        |data: &FinishedState, _env| data.sheets.clone(),
+        |_sheets, data, _env| Box::new(build_final_sheets(data)),
+    );
+
+    let new_game_btn = Button::new("New game").on_click(|ctx, _data: &mut FinishedState, _env| {
+        ctx.submit_command(NEW_GAME, None);
+    });
+    // the same game again straight away, when everyone played on this computer
+    let rematch_btn = Either::new(
+        |data: &FinishedState, _env| data.players.iter().all(|player| player.local),
+        Button::new("Rematch").on_click(|ctx, _data: &mut FinishedState, _env| {
+            ctx.submit_command(REMATCH, None);
+        }),
+        SizedBox::empty(),
+    );
+    let menu_btn = Button::new("Menu").on_click(|ctx, _data: &mut FinishedState, _env| {
+        ctx.submit_command(OPEN_MENU, None);
+    });
+    let review_btn = Button::new("Review game").on_click(|ctx, _data: &mut FinishedState, _env| {
+        ctx.submit_command(REVIEW_GAME, None);
+    });
+    let watch_btn = Button::new("Watch replay").on_click(|ctx, _data: &mut FinishedState, _env| {
+        ctx.submit_command(WATCH_REPLAY, None);
+    });
+    // the score sheets as a spreadsheet, for people who keep their results
+    let export_btn =
+        Button::new("Export results").on_click(|ctx, _data: &mut FinishedState, _env| {
+            let results = FileSpec::new("CSV", &[export::CSV_EXTENSION]);
+            let options = FileDialogOptions::new().allowed_types(vec![results]);
+            ctx.submit_command(Command::new(commands::SHOW_SAVE_PANEL, options), None);
+        });
+    // a few lines of them, to paste into a chat
+    let copy_btn = Button::new("Copy result").on_click(|_ctx, data: &mut FinishedState, _env| {
+        data.copy_results();
+    });
+    // a picture of them, to share
+    let picture_btn =
+        Button::new("Save picture").on_click(|ctx, _data: &mut FinishedState, _env| {
+            let pictures = FileSpec::new("PNG image", &[snapshot::EXTENSION]);
+            let options = FileDialogOptions::new().allowed_types(vec![pictures]);
+            ctx.submit_command(Command::new(commands::SHOW_SAVE_PANEL, options), None);
+        });
+    let quit_btn = Button::new("Quit").on_click(|ctx, _data: &mut FinishedState, _env| {
+        ctx.submit_command(commands::QUIT_APP, None);
+    });
+
+    let layout = Flex::column()
+        .with_child(winner)
+        .with_child(standings)
+        .with_child(Label::new(|data: &FinishedState, _env: &Env| {
+            data.note.clone()
+        }))
+        .with_child(highlights)
+        .with_spacer(VERTICAL_WIDGET_SPACING)
+        .with_flex_child(Scroll::new(sheets), 1.0)
+        .with_spacer(VERTICAL_WIDGET_SPACING)
+        .with_child(
+            Flex::row()
+                .with_child(rematch_btn)
+                .with_spacer(LABEL_SPACING)
+                .with_child(new_game_btn)
+                .with_spacer(LABEL_SPACING)
+                .with_child(menu_btn)
+                .with_spacer(LABEL_SPACING)
+                .with_child(review_btn)
+                .with_spacer(LABEL_SPACING)
+                .with_child(watch_btn)
+                .with_spacer(LABEL_SPACING)
+                .with_child(export_btn)
+                .with_spacer(LABEL_SPACING)
+                .with_child(copy_btn)
+                .with_spacer(LABEL_SPACING)
+                .with_child(picture_btn)
+                .with_spacer(LABEL_SPACING)
+                .with_child(quit_btn),
+        );
+
+    Align::centered(layout)
+}
+
+/// Everyone's final score sheet in a column of their own, with the categories down the left.
+/// Variants with more than one column on a sheet have a column for each.
+fn build_final_sheets(data: &FinishedState) -> impl Widget<FinishedState> {
+    let rules = &data.rules;
+    let winner = data.winner();
+    let mut names = vec![String::new()];
+    if rules.columns > 1 {
+        names.push(String::new());
+    }
+    names.extend(
+        rules
+            .categories
+            .iter()
+            .map(|category| category.name().to_string()),
+    );
+    names.extend(vec!["Upper section".to_string(), "Bonus".to_string()]);
+    if rules.yatzy_bonus == YatzyBonus::American {
+        names.push("Yatzy bonus".to_string());
+    }
+    names.push("Total".to_string());
+
+    let mut table = Flex::row().with_child(text_column(names, RESULTS_NAME_WIDTH));
+    for (idx, (player, sheet)) in data.players.iter().zip(data.sheets.iter()).enumerate() {
+        for (column, card) in sheet.columns().iter().enumerate() {
+            // the player's name over their first column, marking the winner
+            let mut cells = vec![match (column, idx == winner) {
+                (0, true) => format!("\u{2605} {}", player.name),
+                (0, false) => player.name.clone(),
+                _ => String::new(),
+            }];
+            if rules.columns > 1 {
+                cells.push(format!("x{}", ScoreSheet::multiplier(column)));
+            }
+            let points = rules
+                .categories
+                .iter()
+                .map(|&category| match card.get(category) {
+                    Some(points) => points.to_string(),
+                    None => "-".to_string(),
+                });
+            cells.extend(points);
+            cells.push(card.upper_total().to_string());
+            cells.push(card.bonus().to_string());
+            if rules.yatzy_bonus == YatzyBonus::American {
+                cells.push(card.yatzy_bonus().to_string());
+            }
+            cells.push(card.total().to_string());
+            table.add_child(text_column(cells, RESULTS_COLUMN_WIDTH));
+        }
+    }
+    table
+}
+
+/// A column of fixed text, a line each.
+fn text_column<T: Data>(lines: Vec<String>, width: f64) -> impl Widget<T> {
+    let mut column = Flex::column().cross_axis_alignment(CrossAxisAlignment::Start);
+    for line in lines {
+        column.add_child(Label::new(line));
+    }
+    column.fix_width(width)
 }
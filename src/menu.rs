@@ -0,0 +1,85 @@
+//! The main menu, the first screen, leading to new games, saved games, statistics and settings.
+
+use druid::widget::{Align, Button, Either, Flex, Label};
+use druid::{commands, Data, Lens, Widget};
+
+use crate::core::save;
+use crate::{
+    StartingState, CONTINUE_GAME, DISABLED_COLOR, LABEL_SPACING, NEW_GAME, OPEN_ABOUT,
+    OPEN_HOW_TO_PLAY, OPEN_SETTINGS, OPEN_STATISTICS, VERTICAL_WIDGET_SPACING,
+};
+
+/// The first screen, leading to everything else.
+#[derive(Debug, Clone, Data, Lens)]
+pub(crate) struct MenuState {
+    /// The starting screen a new game is set up on.
+    pub(crate) starting: StartingState,
+    /// The slot of the game saved most recently, or `None` if there isn't one to continue.
+    pub(crate) last_save: Option<String>,
+}
+
+impl MenuState {
+    pub(crate) fn new(starting: StartingState) -> Self {
+        let slots = save::slots_dir().map_or(Ok(Vec::new()), |dir| save::list_slots(&dir));
+        let last_save = match slots {
+            Ok(slots) => slots.into_iter().next().map(|slot| slot.name),
+            Err(err) => {
+                eprintln!("warning: couldn't list the saved games: {:#}", err);
+                None
+            }
+        };
+        Self {
+            starting,
+            last_save,
+        }
+    }
+}
+
+pub(crate) fn build_menu() -> impl Widget<MenuState> {
+    let new_game_btn = Button::new("New Game").on_click(|ctx, _data: &mut MenuState, _env| {
+        ctx.submit_command(NEW_GAME, None);
+    });
+    // there's only a game to continue once one has been saved
+    let continue_btn = Either::new(
+        |data: &MenuState, _env| data.last_save.is_some(),
+        Button::new("Continue").on_click(|ctx, _data: &mut MenuState, _env| {
+            ctx.submit_command(CONTINUE_GAME, None);
+        }),
+        Label::new("Continue").with_text_color(DISABLED_COLOR),
+    );
+    let statistics_btn = Button::new("Statistics").on_click(|ctx, _data: &mut MenuState, _env| {
+        ctx.submit_command(OPEN_STATISTICS, None);
+    });
+    let settings_btn = Button::new("Settings").on_click(|ctx, _data: &mut MenuState, _env| {
+        ctx.submit_command(OPEN_SETTINGS, None);
+    });
+    let how_to_play_btn =
+        Button::new("How to play").on_click(|ctx, _data: &mut MenuState, _env| {
+            ctx.submit_command(OPEN_HOW_TO_PLAY, None);
+        });
+    let about_btn = Button::new("About").on_click(|ctx, _data: &mut MenuState, _env| {
+        ctx.submit_command(OPEN_ABOUT, None);
+    });
+    let quit_btn = Button::new("Quit").on_click(|ctx, _data: &mut MenuState, _env| {
+        ctx.submit_command(commands::QUIT_APP, None);
+    });
+
+    let layout = Flex::column()
+        .with_child(Label::new("Yatzy!"))
+        .with_spacer(VERTICAL_WIDGET_SPACING)
+        .with_child(new_game_btn)
+        .with_spacer(LABEL_SPACING)
+        .with_child(continue_btn)
+        .with_spacer(LABEL_SPACING)
+        .with_child(statistics_btn)
+        .with_spacer(LABEL_SPACING)
+        .with_child(settings_btn)
+        .with_spacer(LABEL_SPACING)
+        .with_child(how_to_play_btn)
+        .with_spacer(LABEL_SPACING)
+        .with_child(about_btn)
+        .with_spacer(LABEL_SPACING)
+        .with_child(quit_btn);
+
+    Align::centered(layout)
+}
@@ -0,0 +1,390 @@
+//! Joining a game hosted on another computer.
+//!
+//! If the connection drops once the game has started, the client keeps trying to rejoin for as
+//! long as the host keeps its seat, and then catches up from the host's events.
+//!
+//! If the host leaves, the players it named as successors take over in turn: each of the others
+//! tries the ones ahead of it, and the first successor nobody ahead of answers hosts the game
+//! itself, from its own copy of the events.
+
+use std::{
+    net::{SocketAddr, TcpStream},
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use crossbeam_channel::Sender;
+
+use super::protocol::{self, ClientMessage, MessageReader, ServerMessage, Succession};
+use super::{tls, websocket, NetEvent};
+use crate::core::moves::Move;
+
+/// How long to wait between attempts to rejoin.
+const RETRY_INTERVAL: Duration = Duration::from_secs(2);
+/// How long to try each host in turn, when the host has gone and someone else should take over.
+const TAKEOVER_WAIT: Duration = Duration::from_secs(10);
+
+/// Reads the next message from the host, or `None` once the connection is closed.
+type Reader = Box<dyn FnMut() -> Result<Option<ServerMessage>> + Send>;
+
+/// A connection to a host. Dropping it leaves the game.
+#[derive(Debug)]
+pub struct Client {
+    link: Arc<Mutex<Link>>,
+    /// The fingerprint of the host's certificate, if the game is encrypted.
+    fingerprint: Option<String>,
+}
+
+/// The connection as it is now, shared with the thread reading from it.
+#[derive(Debug)]
+struct Link {
+    outgoing: Sender<ClientMessage>,
+    /// Once the game has started, the token to rejoin with and how long the host keeps the seat.
+    rejoin: Option<(String, Duration)>,
+    /// This player's seat, once the game has started.
+    seat: Option<usize>,
+    /// Who takes over if the host leaves, as the host last said.
+    succession: Option<Succession>,
+    /// Whether the client has been dropped, so it shouldn't rejoin.
+    closed: bool,
+}
+
+/// Why the host stopped being listened to.
+enum Ended {
+    /// The host closed the connection on purpose, and why.
+    Refused(String),
+    /// The connection dropped, and why.
+    Lost(String),
+    /// The host left the game, for someone else to take over.
+    HostLeft,
+    /// The app doesn't want to hear any more.
+    Unheard,
+}
+
+impl Client {
+    /// Join the game hosted at `address` under the given name, and with the password if the host
+    /// asks for one, reporting what happens on `events`.
+    ///
+    /// An address like `ws://host:port` joins over WebSocket, such as a server started with
+    /// `--serve`, and one like `tls://host:port` joins a game that is encrypted. Anything else
+    /// is joined over TCP.
+    pub fn connect(
+        address: &str,
+        name: &str,
+        password: Option<&str>,
+        events: Sender<NetEvent>,
+    ) -> Result<Self> {
+        let (outgoing, read, fingerprint) = open(address)?;
+        let join = ClientMessage::Join {
+            name: name.into(),
+            password: password.map(String::from),
+        };
+        outgoing
+            .send(join)
+            .map_err(|_| anyhow!("the connection closed while joining the game"))?;
+        let link = Arc::new(Mutex::new(Link {
+            outgoing,
+            rejoin: None,
+            seat: None,
+            succession: None,
+            closed: false,
+        }));
+        let (address, shared) = (address.to_string(), link.clone());
+        let certificate = fingerprint.clone();
+        thread::spawn(move || run(&address, certificate, read, &shared, &events));
+        Ok(Self { link, fingerprint })
+    }
+
+    /// The fingerprint of the host's certificate, if the game is encrypted.
+    pub fn fingerprint(&self) -> Option<&str> {
+        self.fingerprint.as_deref()
+    }
+
+    /// Ask the host to make a move. What happens comes back as events.
+    pub fn send_move(&self, mv: Move) -> Result<()> {
+        self.send(ClientMessage::Move(mv), "sending the move to the host")
+    }
+
+    /// Tell the host whether this player is ready for the game to start.
+    pub fn set_ready(&self, ready: bool) -> Result<()> {
+        self.send(ClientMessage::Ready(ready), "telling the host you're ready")
+    }
+
+    /// Say something to everyone in the game. It comes back as an event once the host has
+    /// passed it on.
+    pub fn send_chat(&self, text: &str) -> Result<()> {
+        self.send(ClientMessage::Chat(text.into()), "sending the message")
+    }
+
+    /// Leave the game, closing the connection without trying to rejoin.
+    pub fn close(&self) {
+        let mut link = self.link.lock().unwrap();
+        link.closed = true;
+        // the connection closes once nothing can send on it
+        link.outgoing = crossbeam_channel::unbounded().0;
+    }
+
+    fn send(&self, message: ClientMessage, doing: &'static str) -> Result<()> {
+        self.link
+            .lock()
+            .unwrap()
+            .outgoing
+            .send(message)
+            .map_err(|_| anyhow!("the connection is closed"))
+            .context(doing)
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+/// Connect to the host and start the handshake, giving the channel to send on, the way to read
+/// and the fingerprint of the host's certificate if the game is encrypted.
+fn open(address: &str) -> Result<(Sender<ClientMessage>, Reader, Option<String>)> {
+    let (outgoing, read, fingerprint): (_, Reader, _) = if websocket::is_websocket(address) {
+        let (outgoing, incoming) = websocket::connect(address)?;
+        (outgoing, Box::new(move || Ok(incoming.recv().ok())), None)
+    } else if tls::is_tls(address) {
+        let (outgoing, incoming, fingerprint) = tls::connect(address)?;
+        let read = Box::new(move || Ok(incoming.recv().ok()));
+        (outgoing, read, Some(fingerprint))
+    } else {
+        let stream =
+            TcpStream::connect(address).with_context(|| format!("connecting to {}", address))?;
+        let mut reader = MessageReader::new(stream.try_clone()?);
+        let read = Box::new(move || reader.read());
+        (protocol::spawn_writer(stream), read, None)
+    };
+    let hello = ClientMessage::Hello {
+        version: protocol::PROTOCOL_VERSION,
+    };
+    outgoing
+        .send(hello)
+        .map_err(|_| anyhow!("the connection closed straight away"))?;
+    Ok((outgoing, read, fingerprint))
+}
+
+/// Pass on what the host sends until the connection closes for good, rejoining if it drops
+/// during the game. When rejoining an encrypted game, the host must have the certificate with
+/// the same `fingerprint`.
+fn run(
+    address: &str,
+    mut fingerprint: Option<String>,
+    mut read: Reader,
+    link: &Mutex<Link>,
+    events: &Sender<NetEvent>,
+) {
+    let mut address = address.to_string();
+    let reason = loop {
+        let (reason, host_left) = match listen(&mut read, link, events) {
+            Ended::Refused(reason) => break reason,
+            Ended::Lost(reason) => (reason, false),
+            Ended::HostLeft => ("the host left the game".to_string(), true),
+            Ended::Unheard => return,
+        };
+        let (rejoin, seat, succession) = {
+            let link = link.lock().unwrap();
+            if link.closed {
+                return;
+            }
+            (link.rejoin.clone(), link.seat, link.succession.clone())
+        };
+        let (token, grace_period) = match rejoin {
+            Some(rejoin) => rejoin,
+            None => break reason,
+        };
+        if events.send(NetEvent::Reconnecting(reason)).is_err() {
+            return;
+        }
+        let rejoined = match (succession, seat) {
+            (Some(succession), Some(seat)) => {
+                let host = if host_left {
+                    None
+                } else {
+                    Some((address.as_str(), fingerprint.as_deref()))
+                };
+                let takeover = Takeover {
+                    succession: &succession,
+                    seat,
+                    grace_period,
+                    encrypted: tls::is_tls(&address),
+                };
+                match migrate(host, &takeover, &token, link, events) {
+                    Ok(Some(rejoined)) => rejoined,
+                    // this player is hosting now
+                    Ok(None) => return,
+                    Err(e) => break format!("{:#}", e),
+                }
+            }
+            _ => match reconnect(&address, fingerprint.as_deref(), &token, grace_period, link) {
+                Ok((read, _)) => (address.clone(), read, fingerprint.clone()),
+                Err(e) => break format!("{:#}", e),
+            },
+        };
+        let (rejoined_at, reader, presented) = rejoined;
+        address = rejoined_at;
+        read = reader;
+        fingerprint = presented;
+    };
+    let _ = events.send(NetEvent::Disconnected(reason));
+}
+
+/// What a player needs to know to find whoever has taken over from the host.
+struct Takeover<'a> {
+    succession: &'a Succession,
+    seat: usize,
+    grace_period: Duration,
+    encrypted: bool,
+}
+
+/// Find out who is hosting the game now that the connection to the host has gone, trying the
+/// `host` first if it may still be there, and then the successors ahead of this player.
+///
+/// This gives the address the game was rejoined at, the way to read from it and its
+/// certificate's fingerprint, or `None` if nobody answered and this player has taken over.
+fn migrate(
+    host: Option<(&str, Option<&str>)>,
+    takeover: &Takeover,
+    token: &str,
+    link: &Mutex<Link>,
+    events: &Sender<NetEvent>,
+) -> Result<Option<(String, Reader, Option<String>)>> {
+    let scheme = if takeover.encrypted { "tls://" } else { "" };
+    // a successor has its own certificate, so there's no fingerprint to hold it to
+    let successors = takeover
+        .succession
+        .successors
+        .iter()
+        .take_while(|successor| successor.seat != takeover.seat)
+        .map(|successor| {
+            let address = SocketAddr::new(successor.ip, takeover.succession.port);
+            (format!("{}{}", scheme, address), None)
+        });
+    let candidates = host
+        .map(|(address, fingerprint)| (address.to_string(), fingerprint))
+        .into_iter()
+        .chain(successors);
+    for (address, fingerprint) in candidates {
+        if let Ok((read, presented)) = reconnect(&address, fingerprint, token, TAKEOVER_WAIT, link)
+        {
+            return Ok(Some((address, read, presented)));
+        }
+        if link.lock().unwrap().closed {
+            bail!("left the game");
+        }
+    }
+    let succeeding = takeover
+        .succession
+        .successors
+        .iter()
+        .any(|successor| successor.seat == takeover.seat);
+    if !succeeding {
+        bail!("nobody could take over hosting the game");
+    }
+    let promoted = NetEvent::Promoted {
+        succession: takeover.succession.clone(),
+        grace_period: takeover.grace_period.as_secs() as u32,
+        encrypted: takeover.encrypted,
+    };
+    let _ = events.send(promoted);
+    Ok(None)
+}
+
+/// Pass on what the host sends, read with `read`, until the connection closes.
+fn listen(read: &mut Reader, link: &Mutex<Link>, events: &Sender<NetEvent>) -> Ended {
+    loop {
+        let event = match read() {
+            Ok(Some(ServerMessage::Welcome { version })) => {
+                if protocol::negotiate(version) == Some(version) {
+                    continue;
+                }
+                return Ended::Refused(format!(
+                    "the host's copy of the game is too old to play with this one, it needs \
+                     updating (it speaks version {} of the protocol, this one needs at least {})",
+                    version,
+                    protocol::OLDEST_PROTOCOL_VERSION
+                ));
+            }
+            Ok(Some(ServerMessage::Lobby { players, variant })) => {
+                NetEvent::Lobby { players, variant }
+            }
+            Ok(Some(ServerMessage::Started {
+                rules,
+                players,
+                seat,
+                turn_time,
+                token,
+                grace_period,
+            })) => {
+                let grace_period = Duration::from_secs(grace_period.into());
+                let mut link = link.lock().unwrap();
+                link.rejoin = Some((token, grace_period));
+                link.seat = Some(seat);
+                drop(link);
+                NetEvent::Started {
+                    rules,
+                    players,
+                    seat,
+                    turn_time,
+                }
+            }
+            Ok(Some(ServerMessage::Resumed { events })) => NetEvent::Resumed(events),
+            Ok(Some(ServerMessage::Event(event))) => NetEvent::Event(event),
+            Ok(Some(ServerMessage::Chat { from, text })) => NetEvent::Chat { from, text },
+            Ok(Some(ServerMessage::Succession(succession))) => {
+                link.lock().unwrap().succession = Some(succession);
+                continue;
+            }
+            Ok(Some(ServerMessage::HostLeft)) => return Ended::HostLeft,
+            Ok(Some(ServerMessage::Refused(reason))) => return Ended::Refused(reason),
+            Ok(None) => return Ended::Lost("the host closed the connection".into()),
+            Err(e) => return Ended::Lost(format!("{:#}", e)),
+        };
+        // nothing more is heard once the game has been left
+        if link.lock().unwrap().closed || events.send(event).is_err() {
+            return Ended::Unheard;
+        }
+    }
+}
+
+/// Keep trying to take back the seat for up to `patience`, which is how long the host keeps it
+/// unless someone else may have taken over. If a `fingerprint` is given, the host must have the
+/// certificate with it. This gives the way to read from the host and its certificate's
+/// fingerprint.
+fn reconnect(
+    address: &str,
+    fingerprint: Option<&str>,
+    token: &str,
+    patience: Duration,
+    link: &Mutex<Link>,
+) -> Result<(Reader, Option<String>)> {
+    let give_up = Instant::now() + patience;
+    loop {
+        thread::sleep(RETRY_INTERVAL);
+        if link.lock().unwrap().closed {
+            bail!("left the game");
+        }
+        match open(address) {
+            Ok((_, _, presented))
+                if fingerprint.is_some() && presented.as_deref() != fingerprint =>
+            {
+                bail!("the host's certificate has changed, so it may not be the same host")
+            }
+            Ok((outgoing, read, presented)) => {
+                let rejoin = ClientMessage::Rejoin {
+                    token: token.into(),
+                };
+                let _ = outgoing.send(rejoin);
+                link.lock().unwrap().outgoing = outgoing;
+                return Ok((read, presented));
+            }
+            Err(e) if Instant::now() >= give_up => return Err(e.context("couldn't rejoin")),
+            Err(_) => (),
+        }
+    }
+}
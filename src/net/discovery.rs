@@ -0,0 +1,147 @@
+//! Finding games hosted on the local network with mDNS, so nobody has to type in addresses.
+//!
+//! A host advertises a `_yatzy._tcp` service named after whoever is hosting, and the starting
+//! screen lists the ones it finds. Encrypted games say so with a `tls` property.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    net::{IpAddr, Ipv4Addr, UdpSocket},
+    thread,
+};
+
+use anyhow::{anyhow, bail, Result};
+use crossbeam_channel::Sender;
+use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
+
+use super::NetEvent;
+
+/// The mDNS service type games are advertised under.
+const SERVICE_TYPE: &str = "_yatzy._tcp.local.";
+/// The property set on the services of encrypted games.
+const TLS_PROPERTY: &str = "tls";
+
+/// A game found on the local network.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LanGame {
+    /// Who is hosting it.
+    pub name: String,
+    /// The address to join it at.
+    pub address: String,
+}
+
+/// A game being advertised on the local network. Dropping it stops the advertising.
+pub struct Advertisement {
+    daemon: ServiceDaemon,
+    fullname: String,
+}
+
+impl Advertisement {
+    /// Advertise the game hosted on `port` under the given name, and whether it's `encrypted`.
+    pub fn start(name: &str, port: u16, encrypted: bool) -> Result<Self> {
+        let ip = local_ipv4()?;
+        let host_name = format!("yatzy-{}.local.", ip.to_string().replace('.', "-"));
+        let mut properties = HashMap::new();
+        if encrypted {
+            properties.insert(TLS_PROPERTY.to_string(), "1".to_string());
+        }
+        let info = ServiceInfo::new(
+            SERVICE_TYPE,
+            name,
+            &host_name,
+            &ip.to_string(),
+            port,
+            Some(properties),
+        )
+        .map_err(|e| anyhow!("advertising the game: {}", e))?;
+        let fullname = info.get_fullname().to_string();
+        let daemon = ServiceDaemon::new().map_err(|e| anyhow!("starting mDNS: {}", e))?;
+        daemon
+            .register(info)
+            .map_err(|e| anyhow!("advertising the game: {}", e))?;
+        Ok(Self { daemon, fullname })
+    }
+}
+
+impl fmt::Debug for Advertisement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Advertisement")
+            .field(&self.fullname)
+            .finish()
+    }
+}
+
+impl Drop for Advertisement {
+    fn drop(&mut self) {
+        let _ = self.daemon.unregister(&self.fullname);
+        let _ = self.daemon.shutdown();
+    }
+}
+
+/// Looks for games on the local network for as long as it is kept.
+pub struct Browser {
+    daemon: ServiceDaemon,
+}
+
+impl Browser {
+    /// Start looking, reporting the games found and lost on `events`.
+    pub fn start(events: Sender<NetEvent>) -> Result<Self> {
+        let daemon = ServiceDaemon::new().map_err(|e| anyhow!("starting mDNS: {}", e))?;
+        let found = daemon
+            .browse(SERVICE_TYPE)
+            .map_err(|e| anyhow!("looking for games: {}", e))?;
+        thread::spawn(move || {
+            while let Ok(event) = found.recv() {
+                let event = match event {
+                    ServiceEvent::ServiceResolved(info) => {
+                        let ip = match info.get_addresses().iter().next() {
+                            Some(ip) => *ip,
+                            None => continue,
+                        };
+                        let scheme = if info.get_properties().contains_key(TLS_PROPERTY) {
+                            "tls://"
+                        } else {
+                            ""
+                        };
+                        NetEvent::Found(LanGame {
+                            name: instance_name(info.get_fullname()).to_string(),
+                            address: format!("{}{}:{}", scheme, ip, info.get_port()),
+                        })
+                    }
+                    ServiceEvent::ServiceRemoved(_, fullname) => {
+                        NetEvent::Lost(instance_name(&fullname).to_string())
+                    }
+                    _ => continue,
+                };
+                if events.send(event).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Self { daemon })
+    }
+}
+
+impl Drop for Browser {
+    fn drop(&mut self) {
+        let _ = self.daemon.shutdown();
+    }
+}
+
+/// The name a game was advertised under, from the full service name.
+fn instance_name(fullname: &str) -> &str {
+    fullname
+        .strip_suffix(SERVICE_TYPE)
+        .map_or(fullname, |name| name.trim_end_matches('.'))
+}
+
+/// The address other computers on the network reach this one at. This asks which address
+/// would be used to reach a public one, but doesn't send anything.
+pub fn local_ipv4() -> Result<Ipv4Addr> {
+    let socket = UdpSocket::bind(("0.0.0.0", 0))?;
+    socket.connect(("8.8.8.8", 80))?;
+    match socket.local_addr()?.ip() {
+        IpAddr::V4(ip) => Ok(ip),
+        IpAddr::V6(_) => bail!("this computer has no IPv4 address to advertise"),
+    }
+}
@@ -0,0 +1,674 @@
+//! Hosting a network game: taking in the players who join, then passing their moves to the app
+//! and the game's events back to them.
+//!
+//! Every event sent is kept, so a player whose connection drops can rejoin and catch up. Players
+//! who joined over TCP keep a copy of the events too, so if the host leaves one of them can take
+//! over, see `Host::take_over`.
+//!
+//! The host can ask for a password to join, and turn players out with `Host::kick`. Both are
+//! checked here rather than in the app, so a player can't get around them with their own copy.
+
+use std::{
+    collections::{HashMap, HashSet},
+    net::{IpAddr, TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use crossbeam_channel::Sender;
+use rand::Rng;
+
+use super::protocol::{
+    self, ClientMessage, LobbyPlayer, MessageReader, PlayerInfo, ServerMessage, Succession,
+    Successor, SUCCESSION_VERSION,
+};
+use super::{
+    tls::{self, Identity},
+    websocket, NetEvent,
+};
+use crate::core::{event::GameEvent, rules::RuleSet};
+
+/// Identifies a connection to a player who joined, for as long as it's open.
+pub type ConnectionId = usize;
+
+/// How players connect to a host.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Transport {
+    /// Lines of JSON over plain TCP, as the app joins.
+    Tcp,
+    /// Lines of JSON over TLS, with the certificate from `Identity::load_or_create`.
+    Tls,
+    /// JSON text messages over WebSocket, for browsers and other programs.
+    WebSocket,
+}
+
+/// A game being hosted. Dropping it closes every connection and stops taking new players.
+#[derive(Debug)]
+pub struct Host {
+    port: u16,
+    identity: Option<Identity>,
+    shared: Arc<Mutex<Shared>>,
+    events: Sender<NetEvent>,
+}
+
+/// What the host's threads share.
+#[derive(Debug, Default)]
+struct Shared {
+    /// The players who have joined, in the order they joined.
+    connections: Vec<Connection>,
+    next_id: ConnectionId,
+    /// The name of the variant to be played, shown in the lobby.
+    variant: String,
+    /// What players have to give to join, if anything.
+    password: Option<String>,
+    /// The connections the host turned out, whose messages are no longer heard.
+    kicked: HashSet<ConnectionId>,
+    /// The addresses nobody can join from.
+    banned: HashSet<IpAddr>,
+    /// The port the game is hosted on.
+    port: u16,
+    /// Whether the players can take over if the host leaves. Browsers can't, so it isn't
+    /// offered over WebSocket.
+    succession: bool,
+    /// Each connection's index in the game's players, once the game has started.
+    seats: HashMap<ConnectionId, usize>,
+    /// The seat each token takes back, by the token's hash.
+    tokens: HashMap<String, usize>,
+    /// The seats being kept for players whose connection dropped, with the connection they had
+    /// and their name.
+    kept: HashMap<usize, (ConnectionId, String)>,
+    grace_period: Duration,
+    /// Every event sent in the game so far.
+    log: Vec<GameEvent>,
+    started: bool,
+    closed: bool,
+}
+
+#[derive(Debug)]
+struct Connection {
+    id: ConnectionId,
+    name: String,
+    ready: bool,
+    /// The version of the protocol agreed with the player.
+    version: u32,
+    /// Where the player connected from, if it's known.
+    ip: Option<IpAddr>,
+    /// For sending to the player. Dropping it closes the connection once everything sent has
+    /// been written.
+    outgoing: Sender<ServerMessage>,
+}
+
+impl Shared {
+    /// Send a message to every connection. A connection that fails is closed by its reader.
+    fn broadcast(&mut self, message: &ServerMessage) {
+        for connection in &self.connections {
+            let _ = connection.outgoing.send(message.clone());
+        }
+    }
+
+    fn send_lobby(&mut self) {
+        let players = self.connections.iter().map(Connection::player).collect();
+        let variant = self.variant.clone();
+        self.broadcast(&ServerMessage::Lobby { players, variant });
+    }
+
+    /// Tell everyone who can take over as host the order they would, once the game has
+    /// started.
+    fn send_succession(&self) {
+        if !self.succession || !self.started {
+            return;
+        }
+        let able = || {
+            self.connections
+                .iter()
+                .filter(|connection| connection.version >= SUCCESSION_VERSION)
+        };
+        let mut successors: Vec<_> = able()
+            .filter_map(|connection| {
+                Some(Successor {
+                    seat: *self.seats.get(&connection.id)?,
+                    ip: connection.ip?,
+                })
+            })
+            .collect();
+        successors.sort_by_key(|successor| successor.seat);
+        let tokens = self.tokens.iter().map(|(hash, seat)| (hash.clone(), *seat));
+        let message = ServerMessage::Succession(Succession {
+            port: self.port,
+            successors,
+            tokens: tokens.collect(),
+        });
+        for connection in able() {
+            let _ = connection.outgoing.send(message.clone());
+        }
+    }
+}
+
+impl Connection {
+    fn player(&self) -> LobbyPlayer {
+        LobbyPlayer {
+            name: self.name.clone(),
+            ready: self.ready,
+        }
+    }
+}
+
+impl Host {
+    /// Start listening for players connecting over `transport` on `port`, reporting what
+    /// happens on `events`. The lobby shows `variant` until `set_variant` changes it.
+    ///
+    /// A port of 0 picks any free port, see `port`.
+    pub fn start(
+        port: u16,
+        transport: Transport,
+        variant: &str,
+        events: Sender<NetEvent>,
+    ) -> Result<Self> {
+        let listener = TcpListener::bind(("0.0.0.0", port))
+            .with_context(|| format!("listening on port {}", port))?;
+        let port = listener.local_addr()?.port();
+        let identity = match transport {
+            Transport::Tls => Some(Identity::load_or_create().context("loading the certificate")?),
+            Transport::Tcp | Transport::WebSocket => None,
+        };
+        let shared = Arc::new(Mutex::new(Shared {
+            variant: variant.to_string(),
+            port,
+            succession: transport != Transport::WebSocket,
+            ..Shared::default()
+        }));
+        let (accepting, certificate, joining) = (shared.clone(), identity.clone(), events.clone());
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if accepting.lock().unwrap().closed {
+                    break;
+                }
+                if let Ok(stream) = stream {
+                    let (shared, events) = (accepting.clone(), joining.clone());
+                    let certificate = certificate.clone();
+                    thread::spawn(move || match (transport, certificate) {
+                        (Transport::Tls, Some(identity)) => {
+                            serve_tls(stream, &identity, shared, events)
+                        }
+                        (Transport::WebSocket, _) => serve_websocket(stream, shared, events),
+                        _ => serve_tcp(stream, shared, events),
+                    });
+                }
+            }
+        });
+        Ok(Self {
+            port,
+            identity,
+            shared,
+            events,
+        })
+    }
+
+    /// The port players join on.
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// The fingerprint of the certificate players see, if the game is encrypted.
+    pub fn fingerprint(&self) -> Option<&str> {
+        self.identity.as_ref().map(Identity::fingerprint)
+    }
+
+    /// The players who have joined and are still connected, in the order they joined.
+    pub fn joined(&self) -> Vec<(ConnectionId, LobbyPlayer)> {
+        let shared = self.shared.lock().unwrap();
+        shared
+            .connections
+            .iter()
+            .map(|connection| (connection.id, connection.player()))
+            .collect()
+    }
+
+    /// Show everyone in the lobby the variant that will be played.
+    pub fn set_variant(&self, variant: &str) {
+        let mut shared = self.shared.lock().unwrap();
+        shared.variant = variant.to_string();
+        shared.send_lobby();
+    }
+
+    /// Only let in players who give `password`, or anyone if it's `None`. Those who have already
+    /// joined stay.
+    pub fn set_password(&self, password: Option<&str>) {
+        self.shared.lock().unwrap().password = password.map(String::from);
+    }
+
+    /// Turn a player out of the lobby or the game. Once the game has started their seat isn't
+    /// kept for them, so they can't rejoin. If `ban` is set, nobody can join from the address the
+    /// player connected from either.
+    ///
+    /// This is reported as the player leaving.
+    pub fn kick(&self, connection: ConnectionId, ban: bool) {
+        let mut shared = self.shared.lock().unwrap();
+        let kicked = match shared.connections.iter().position(|c| c.id == connection) {
+            Some(idx) => shared.connections.remove(idx),
+            None => return,
+        };
+        let reason = if ban {
+            "the host banned you from the game"
+        } else {
+            "the host removed you from the game"
+        };
+        let _ = kicked.outgoing.send(ServerMessage::Refused(reason.into()));
+        shared.kicked.insert(connection);
+        if let Some(ip) = kicked.ip.filter(|_| ban) {
+            shared.banned.insert(ip);
+        }
+        if let Some(seat) = shared.seats.get(&connection).copied() {
+            shared.tokens.retain(|_, kept| *kept != seat);
+            shared.send_succession();
+        } else if !shared.started {
+            shared.send_lobby();
+        }
+        drop(shared);
+        let _ = self.events.send(NetEvent::Left {
+            connection,
+            name: kicked.name,
+        });
+    }
+
+    /// Start the game, telling everyone in `seats` which of `players` they are. Anyone else
+    /// who has joined is turned away, along with anyone who joins from now on.
+    ///
+    /// A player whose connection drops has `grace_period` seconds to rejoin before they leave.
+    pub fn start_game(
+        &self,
+        rules: &RuleSet,
+        players: &[PlayerInfo],
+        seats: HashMap<ConnectionId, usize>,
+        turn_time: Option<u32>,
+        grace_period: u32,
+    ) {
+        let mut shared = self.shared.lock().unwrap();
+        shared.started = true;
+        shared.grace_period = Duration::from_secs(grace_period.into());
+        let mut rng = rand::thread_rng();
+        let mut tokens = HashMap::new();
+        for connection in &shared.connections {
+            let message = match seats.get(&connection.id) {
+                Some(seat) => {
+                    let token = format!("{:032x}", rng.gen::<u128>());
+                    tokens.insert(protocol::token_hash(&token), *seat);
+                    ServerMessage::Started {
+                        rules: rules.clone(),
+                        players: players.to_vec(),
+                        seat: *seat,
+                        turn_time,
+                        token,
+                        grace_period,
+                    }
+                }
+                None => ServerMessage::Refused("the game started without you".into()),
+            };
+            let _ = connection.outgoing.send(message);
+        }
+        shared.tokens = tokens;
+        shared
+            .connections
+            .retain(|connection| seats.contains_key(&connection.id));
+        shared.seats = seats;
+        shared.send_succession();
+    }
+
+    /// Take over hosting a game that was hosted elsewhere, after its host left. `log` is every
+    /// event in the game so far.
+    ///
+    /// The players in `kept`, each seat with the player's name, have `grace_period` seconds to
+    /// rejoin with their tokens, which are checked against those in `succession`.
+    pub fn take_over(
+        transport: Transport,
+        succession: &Succession,
+        kept: Vec<(usize, String)>,
+        log: Vec<GameEvent>,
+        grace_period: u32,
+        events: Sender<NetEvent>,
+    ) -> Result<Self> {
+        let host = Self::start(succession.port, transport, "", events.clone())?;
+        let mut shared = host.shared.lock().unwrap();
+        shared.started = true;
+        shared.grace_period = Duration::from_secs(grace_period.into());
+        shared.log = log;
+        shared.tokens = succession.tokens.iter().cloned().collect();
+        for (seat, name) in kept {
+            let id = shared.next_id;
+            shared.next_id += 1;
+            shared.seats.insert(id, seat);
+            shared.kept.insert(seat, (id, name.clone()));
+            keep_seat(&host.shared, &events, seat, id, name, shared.grace_period);
+        }
+        drop(shared);
+        Ok(host)
+    }
+
+    /// The index in the game's players of whoever joined on a connection, once the game has
+    /// started.
+    pub fn seat(&self, connection: ConnectionId) -> Option<usize> {
+        self.shared.lock().unwrap().seats.get(&connection).copied()
+    }
+
+    /// Say something in the chat to everyone who joined.
+    pub fn chat(&self, from: &str, text: &str) {
+        self.shared.lock().unwrap().broadcast(&ServerMessage::Chat {
+            from: from.into(),
+            text: text.into(),
+        });
+    }
+
+    /// Send an event in the game to everyone who joined.
+    pub fn broadcast(&self, event: &GameEvent) {
+        let mut shared = self.shared.lock().unwrap();
+        shared.log.push(event.clone());
+        shared.broadcast(&ServerMessage::Event(event.clone()));
+    }
+}
+
+impl Drop for Host {
+    fn drop(&mut self) {
+        let mut shared = self.shared.lock().unwrap();
+        shared.closed = true;
+        for connection in &shared.connections {
+            // those who can take over should, the others have to stop
+            let message = if shared.started && connection.version >= SUCCESSION_VERSION {
+                ServerMessage::HostLeft
+            } else {
+                ServerMessage::Refused("the host closed the game".into())
+            };
+            let _ = connection.outgoing.send(message);
+        }
+        shared.connections.clear();
+        drop(shared);
+        // wake the listening thread so it sees that the game is closed
+        let _ = TcpStream::connect(("127.0.0.1", self.port));
+    }
+}
+
+fn serve_tcp(stream: TcpStream, shared: Arc<Mutex<Shared>>, events: Sender<NetEvent>) {
+    let outgoing = match stream.try_clone() {
+        Ok(writer) => protocol::spawn_writer(writer),
+        Err(_) => return,
+    };
+    let ip = stream.peer_addr().ok().map(|address| address.ip());
+    let mut reader = MessageReader::new(stream);
+    serve(
+        || reader.read().ok().flatten(),
+        outgoing,
+        ip,
+        shared,
+        events,
+    );
+}
+
+fn serve_websocket(stream: TcpStream, shared: Arc<Mutex<Shared>>, events: Sender<NetEvent>) {
+    let ip = stream.peer_addr().ok().map(|address| address.ip());
+    if let Ok((outgoing, incoming)) = websocket::accept(stream) {
+        serve(|| incoming.recv().ok(), outgoing, ip, shared, events);
+    }
+}
+
+fn serve_tls(
+    stream: TcpStream,
+    identity: &Identity,
+    shared: Arc<Mutex<Shared>>,
+    events: Sender<NetEvent>,
+) {
+    let ip = stream.peer_addr().ok().map(|address| address.ip());
+    if let Ok((outgoing, incoming)) = tls::accept(stream, identity) {
+        serve(|| incoming.recv().ok(), outgoing, ip, shared, events);
+    }
+}
+
+/// Read a player's messages with `read` until they leave, which it shows by returning `None`.
+/// `ip` is where they connected from.
+fn serve(
+    mut read: impl FnMut() -> Option<ClientMessage>,
+    outgoing: Sender<ServerMessage>,
+    ip: Option<IpAddr>,
+    shared: Arc<Mutex<Shared>>,
+    events: Sender<NetEvent>,
+) {
+    let banned = ip.filter(|ip| shared.lock().unwrap().banned.contains(ip));
+    if banned.is_some() {
+        let refusal = ServerMessage::Refused("the host has banned you from this game".into());
+        let _ = outgoing.send(refusal);
+        return;
+    }
+
+    // copies from before the handshake start straight away with `Join`
+    let version = match read() {
+        Some(ClientMessage::Hello { version }) => version,
+        Some(_) => 0,
+        None => return,
+    };
+    let version = match protocol::negotiate(version) {
+        Some(version) => {
+            let _ = outgoing.send(ServerMessage::Welcome { version });
+            version
+        }
+        None => {
+            let refusal = format!(
+                "your copy of the game is too old to join this one, it needs updating \
+                 (it speaks version {} of the protocol, the host needs at least {})",
+                version,
+                protocol::OLDEST_PROTOCOL_VERSION
+            );
+            let _ = outgoing.send(ServerMessage::Refused(refusal));
+            return;
+        }
+    };
+
+    let connection = |name: String, outgoing| Connection {
+        id: 0,
+        name,
+        ready: false,
+        version,
+        ip,
+        outgoing,
+    };
+    let joined = match read() {
+        Some(ClientMessage::Join { name, password }) => join(
+            connection(name, outgoing),
+            password.as_deref(),
+            &shared,
+            &events,
+        ),
+        Some(ClientMessage::Rejoin { token }) => rejoin(
+            &token,
+            connection(String::new(), outgoing),
+            &shared,
+            &events,
+        ),
+        _ => return,
+    };
+    let (id, name) = match joined {
+        Some(joined) => joined,
+        None => return,
+    };
+
+    while let Some(message) = read() {
+        if shared.lock().unwrap().kicked.contains(&id) {
+            break;
+        }
+        match message {
+            ClientMessage::Move(mv) => {
+                let seat = shared.lock().unwrap().seats.get(&id).copied();
+                if let Some(seat) = seat {
+                    let _ = events.send(NetEvent::Move { seat, mv });
+                }
+            }
+            ClientMessage::Ready(ready) => {
+                let mut shared = shared.lock().unwrap();
+                if shared.started {
+                    continue;
+                }
+                if let Some(connection) = shared.connections.iter_mut().find(|c| c.id == id) {
+                    connection.ready = ready;
+                }
+                shared.send_lobby();
+                drop(shared);
+                let _ = events.send(NetEvent::Ready {
+                    connection: id,
+                    ready,
+                });
+            }
+            ClientMessage::Chat(text) => {
+                shared.lock().unwrap().broadcast(&ServerMessage::Chat {
+                    from: name.clone(),
+                    text: text.clone(),
+                });
+                let _ = events.send(NetEvent::Chat {
+                    from: name.clone(),
+                    text,
+                });
+            }
+            ClientMessage::Hello { .. }
+            | ClientMessage::Join { .. }
+            | ClientMessage::Rejoin { .. } => (),
+        }
+    }
+
+    leave(id, name, &shared, &events);
+}
+
+/// Take a new player into the lobby, unless the game has started or they didn't give the
+/// password. The connection is given its id here.
+fn join(
+    mut connection: Connection,
+    password: Option<&str>,
+    shared: &Mutex<Shared>,
+    events: &Sender<NetEvent>,
+) -> Option<(ConnectionId, String)> {
+    let mut shared = shared.lock().unwrap();
+    let refusal = if shared.started || shared.closed {
+        Some("the game has already started")
+    } else if shared.password.is_none() || shared.password.as_deref() == password {
+        None
+    } else if password.is_none() {
+        Some("the game needs a password to join")
+    } else {
+        Some("the password is wrong")
+    };
+    if let Some(refusal) = refusal {
+        let _ = connection
+            .outgoing
+            .send(ServerMessage::Refused(refusal.into()));
+        return None;
+    }
+    let id = shared.next_id;
+    shared.next_id += 1;
+    let name = connection.name.clone();
+    connection.id = id;
+    shared.connections.push(connection);
+    shared.send_lobby();
+    drop(shared);
+    let _ = events.send(NetEvent::Joined {
+        connection: id,
+        name: name.clone(),
+    });
+    Some((id, name))
+}
+
+/// Give a player back the seat being kept for them, and send them the game so far. The
+/// connection is given its id and the player's name here.
+fn rejoin(
+    token: &str,
+    mut connection: Connection,
+    shared: &Mutex<Shared>,
+    events: &Sender<NetEvent>,
+) -> Option<(ConnectionId, String)> {
+    let mut shared = shared.lock().unwrap();
+    let seat = shared.tokens.get(&protocol::token_hash(token)).copied();
+    let kept = seat.and_then(|seat| Some((seat, shared.kept.remove(&seat)?)));
+    let (seat, (dropped, name)) = match kept {
+        Some(kept) => kept,
+        None => {
+            let refusal = ServerMessage::Refused("your seat is no longer being kept".into());
+            let _ = connection.outgoing.send(refusal);
+            return None;
+        }
+    };
+    let id = shared.next_id;
+    shared.next_id += 1;
+    shared.seats.remove(&dropped);
+    shared.seats.insert(id, seat);
+    let resumed = ServerMessage::Resumed {
+        events: shared.log.clone(),
+    };
+    let _ = connection.outgoing.send(resumed);
+    connection.id = id;
+    connection.name = name.clone();
+    connection.ready = true;
+    shared.connections.push(connection);
+    shared.send_succession();
+    drop(shared);
+    let _ = events.send(NetEvent::Rejoined {
+        seat,
+        name: name.clone(),
+    });
+    Some((id, name))
+}
+
+/// Tell the app a player has gone. Once the game has started, their seat is kept for the grace
+/// period first.
+fn leave(id: ConnectionId, name: String, shared: &Arc<Mutex<Shared>>, events: &Sender<NetEvent>) {
+    let mut locked = shared.lock().unwrap();
+    // the host already reported a player it turned out
+    if locked.kicked.contains(&id) {
+        return;
+    }
+    locked.connections.retain(|connection| connection.id != id);
+    let seat = locked.seats.get(&id).copied();
+    match seat {
+        Some(seat) if !locked.closed => {
+            locked.kept.insert(seat, (id, name.clone()));
+            locked.send_succession();
+            let grace_period = locked.grace_period;
+            drop(locked);
+            let _ = events.send(NetEvent::Dropped {
+                seat,
+                name: name.clone(),
+            });
+            keep_seat(shared, events, seat, id, name, grace_period);
+        }
+        _ => {
+            if !locked.started && !locked.closed {
+                locked.send_lobby();
+            }
+            drop(locked);
+            let _ = events.send(NetEvent::Left {
+                connection: id,
+                name,
+            });
+        }
+    }
+}
+
+/// Once `grace_period` is up, tell the app the player who had `seat` on connection `id` has
+/// left, unless they have rejoined.
+fn keep_seat(
+    shared: &Arc<Mutex<Shared>>,
+    events: &Sender<NetEvent>,
+    seat: usize,
+    id: ConnectionId,
+    name: String,
+    grace_period: Duration,
+) {
+    let (shared, events) = (shared.clone(), events.clone());
+    thread::spawn(move || {
+        thread::sleep(grace_period);
+        let mut shared = shared.lock().unwrap();
+        if shared.kept.get(&seat).map(|(kept, _)| *kept) == Some(id) {
+            shared.kept.remove(&seat);
+            drop(shared);
+            let _ = events.send(NetEvent::Left {
+                connection: id,
+                name,
+            });
+        }
+    });
+}
@@ -0,0 +1,186 @@
+//! How the headless server is doing, for Prometheus to scrape.
+//!
+//! `Metrics::serve` answers `GET /metrics` over plain HTTP on a port of its own, in the
+//! Prometheus text format, with how many players are in the game, whether it has started, and
+//! counts of the events, rejected moves and stand-in bots so far. Anything else gets a 404.
+
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+
+/// How long a scrape has to send its request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Counts kept by the server as the game goes on. They're only ever set from the server's own
+/// thread, and read from the one answering scrapes.
+#[derive(Debug)]
+pub struct Metrics {
+    started_at: Instant,
+    /// Everyone in the lobby, or in the game once it has started.
+    pub players: AtomicUsize,
+    pub game_started: AtomicBool,
+    /// The events sent to the players.
+    pub events: AtomicUsize,
+    /// Moves that weren't allowed, or were made out of turn.
+    pub rejected_moves: AtomicUsize,
+    /// Players who left, with a bot playing for them.
+    pub stand_ins: AtomicUsize,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self {
+            started_at: Instant::now(),
+            players: AtomicUsize::new(0),
+            game_started: AtomicBool::new(false),
+            events: AtomicUsize::new(0),
+            rejected_moves: AtomicUsize::new(0),
+            stand_ins: AtomicUsize::new(0),
+        }
+    }
+
+    /// The metrics in the Prometheus text format.
+    pub fn render(&self) -> String {
+        let get = |count: &AtomicUsize| count.load(Ordering::Relaxed);
+        let metrics = [
+            (
+                "yatzy_uptime_seconds",
+                "gauge",
+                "Seconds since the server started.",
+                self.started_at.elapsed().as_secs() as usize,
+            ),
+            (
+                "yatzy_players",
+                "gauge",
+                "Players in the lobby or the game.",
+                get(&self.players),
+            ),
+            (
+                "yatzy_game_started",
+                "gauge",
+                "Whether the game has started.",
+                self.game_started.load(Ordering::Relaxed) as usize,
+            ),
+            (
+                "yatzy_events_total",
+                "counter",
+                "Game events sent to the players.",
+                get(&self.events),
+            ),
+            (
+                "yatzy_rejected_moves_total",
+                "counter",
+                "Moves that weren't allowed or were out of turn.",
+                get(&self.rejected_moves),
+            ),
+            (
+                "yatzy_stand_ins_total",
+                "counter",
+                "Players who left with a bot playing for them.",
+                get(&self.stand_ins),
+            ),
+        ];
+        let mut text = String::new();
+        for (name, kind, help, value) in &metrics {
+            text.push_str(&format!(
+                "# HELP {} {}\n# TYPE {} {}\n{} {}\n",
+                name, help, name, kind, name, value
+            ));
+        }
+        text
+    }
+
+    /// Answer scrapes on `port` on a thread of its own, for as long as the program runs. This
+    /// gives the port, which is chosen by the system if `port` is 0.
+    pub fn serve(self: &Arc<Self>, port: u16) -> Result<u16> {
+        let listener = TcpListener::bind(("0.0.0.0", port))
+            .with_context(|| format!("listening for scrapes on port {}", port))?;
+        let port = listener.local_addr()?.port();
+        let metrics = self.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                // a scrape that goes wrong only loses that scrape
+                let _ = metrics.answer(stream);
+            }
+        });
+        Ok(port)
+    }
+
+    fn answer(&self, mut stream: TcpStream) -> Result<()> {
+        stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+        let mut reader = BufReader::new(&stream);
+        let mut request = String::new();
+        reader.read_line(&mut request)?;
+        // the headers don't matter, but are read so closing doesn't reset the connection
+        let mut header = String::new();
+        while reader.read_line(&mut header)? > 2 {
+            header.clear();
+        }
+        let path = request.split_whitespace().nth(1);
+        let response = match (request.starts_with("GET "), path) {
+            (true, Some("/metrics")) => {
+                let body = self.render();
+                format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\n\
+                     Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                )
+            }
+            _ => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                .to_string(),
+        };
+        stream.write_all(response.as_bytes())?;
+        Ok(())
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    #[test]
+    fn each_metric_has_its_help_and_type() {
+        let metrics = Metrics::new();
+        metrics.players.store(3, Ordering::Relaxed);
+        metrics.events.fetch_add(12, Ordering::Relaxed);
+        let text = metrics.render();
+        assert!(text.contains("# TYPE yatzy_players gauge\nyatzy_players 3\n"));
+        assert!(text.contains("# TYPE yatzy_events_total counter\nyatzy_events_total 12\n"));
+        assert!(text.contains("yatzy_game_started 0\n"));
+    }
+
+    #[test]
+    fn scrapes_are_answered_over_http() {
+        let metrics = Arc::new(Metrics::new());
+        let port = metrics.serve(0).unwrap();
+        let get = |path: &str| {
+            let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+            write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        };
+        let response = get("/metrics");
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("\r\n\r\n# HELP yatzy_uptime_seconds"));
+        assert!(response.ends_with("yatzy_stand_ins_total 0\n"));
+        assert!(get("/").starts_with("HTTP/1.1 404"));
+    }
+}
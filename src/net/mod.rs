@@ -0,0 +1,113 @@
+//! Network games: one copy of the game hosts, and others join it over TCP, encrypted with TLS if
+//! the host chooses. A headless server, started with `--serve`, hosts over WebSocket instead so
+//! browsers can join too.
+//!
+//! The host keeps the real `Game`. Players who joined send their moves to the host, which plays
+//! them if they're allowed and sends every resulting `GameEvent`, dice rolls included, to
+//! everyone, so each copy replays exactly the same game.
+//!
+//! Only the host rolls dice. A roll is asked for with `Move::Roll`, the host starts the dice
+//! rolling and then lands them with its own generator, and the values come to everyone in a
+//! `GameEvent::Rolled`. The seed is never sent, so nobody who joined can work out the dice
+//! before they land.
+//!
+//! Players who joined over TCP replay every event too, so if the host leaves, one of them can
+//! take over hosting from its own copy and the others rejoin it.
+//!
+//! Separately from games, `sync` keeps a copy of the profiles and saved games online, on a
+//! WebDAV server or in an S3-compatible store, and `webhook` lets people know how games are
+//! going.
+//!
+//! Nothing in here depends on druid. The host and client report what happens as `NetEvent`s on a
+//! channel, and the GUI passes them on to the app as commands.
+
+pub mod client;
+pub mod discovery;
+pub mod host;
+pub mod metrics;
+pub mod protocol;
+pub mod s3;
+pub mod server;
+pub mod sync;
+pub mod tls;
+pub mod twitch;
+pub mod webdav;
+pub mod webhook;
+pub mod websocket;
+
+pub use client::Client;
+pub use host::{ConnectionId, Host, Transport};
+
+use crate::core::{event::GameEvent, moves::Move, rules::RuleSet};
+use discovery::LanGame;
+use protocol::{LobbyPlayer, PlayerInfo, Succession};
+
+/// Something that happened on the network.
+#[derive(Debug, Clone)]
+pub enum NetEvent {
+    /// Hosting: someone joined the game.
+    Joined {
+        connection: ConnectionId,
+        name: String,
+    },
+    /// Hosting: someone left the game. Once it has started, this is when the time to rejoin
+    /// has run out.
+    Left {
+        connection: ConnectionId,
+        name: String,
+    },
+    /// Hosting: someone in the lobby said whether they're ready.
+    Ready {
+        connection: ConnectionId,
+        ready: bool,
+    },
+    /// Hosting: the connection to a player in the game dropped. Their seat is kept for them to
+    /// rejoin for a while, and then they leave.
+    Dropped { seat: usize, name: String },
+    /// Hosting: a player whose connection dropped has taken their seat back.
+    Rejoined { seat: usize, name: String },
+    /// Hosting: a player who joined made a move on their turn.
+    Move { seat: usize, mv: Move },
+    /// Joined: who is in the lobby, and the variant the host has chosen.
+    Lobby {
+        players: Vec<LobbyPlayer>,
+        variant: String,
+    },
+    /// Joined: the host started the game. `seat` is this player's index in `players`.
+    Started {
+        rules: RuleSet,
+        players: Vec<PlayerInfo>,
+        seat: usize,
+        turn_time: Option<u32>,
+    },
+    /// Joined: something happened in the game.
+    Event(GameEvent),
+    /// Joined: the connection to the host dropped during the game, and why. Rejoining is being
+    /// tried.
+    Reconnecting(String),
+    /// Joined: the game was rejoined, and these are all its events so far.
+    Resumed(Vec<GameEvent>),
+    /// Joined: the host left and nobody ahead of this player took over, so this player should
+    /// host the game from here with `Host::take_over`, keeping the seats for `grace_period`
+    /// seconds, and encrypted if the game was.
+    Promoted {
+        succession: Succession,
+        grace_period: u32,
+        encrypted: bool,
+    },
+    /// Someone said something in the chat. When hosting, this is only for those who joined.
+    Chat { from: String, text: String },
+    /// Joined: the connection to the host was closed, and why.
+    Disconnected(String),
+    /// A game was found on the local network.
+    Found(LanGame),
+    /// The game on the local network hosted under this name has gone.
+    Lost(String),
+}
+
+/// The network game this copy of the app is taking part in.
+#[derive(Debug)]
+pub enum Session {
+    Host(Host),
+    Client(Client),
+}
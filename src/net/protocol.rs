@@ -0,0 +1,264 @@
+//! The messages sent between the host and the players who join, as one JSON object per line
+//! over TCP or TLS, or one per text message over WebSocket.
+//!
+//! Every connection starts with a handshake: the player's copy sends `ClientMessage::Hello`
+//! with the newest version of these messages it speaks, and the host answers with
+//! `ServerMessage::Welcome` and the version they'll both use, or `Refused` if they have none in
+//! common. `Hello`, `Welcome` and `Refused` must never change, so that copies of any version
+//! can always tell each other why they can't play together.
+//!
+//! The host doesn't send anything a player's copy is too old to read: players who speak version 1
+//! can still join, they just can't take over if the host leaves.
+
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{IpAddr, Shutdown, TcpStream},
+    thread,
+    time::Duration,
+};
+
+use anyhow::{bail, Context, Result};
+use crossbeam_channel::Sender;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::core::{event::GameEvent, moves::Move, rules::RuleSet};
+
+/// The port games are hosted on when no other is given.
+pub const DEFAULT_PORT: u16 = 7777;
+/// The version of these messages. It goes up whenever they change in a way older copies can't
+/// read.
+pub const PROTOCOL_VERSION: u32 = 2;
+/// The oldest version this copy can still speak.
+pub const OLDEST_PROTOCOL_VERSION: u32 = 1;
+/// The first version in which a player can take over as host, with `ServerMessage::Succession`
+/// and `ServerMessage::HostLeft`.
+pub const SUCCESSION_VERSION: u32 = 2;
+/// How long a thread that both reads and writes a connection waits for a message to read before
+/// checking for messages to send.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(20);
+/// The longest line the other end may send before the connection is dropped. Every message in
+/// the protocol, even a whole game, fits with plenty to spare.
+pub const MAX_LINE: usize = 1 << 20;
+/// The seconds a host keeps a seat for a player whose connection dropped, when no other time is
+/// given.
+pub const DEFAULT_GRACE_PERIOD: u32 = 60;
+
+/// Someone taking part in a network game.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlayerInfo {
+    pub name: String,
+    /// The name of the bot playing for this player, or `None` for a person.
+    pub bot: Option<String>,
+}
+
+/// Someone waiting in the lobby for the host to start the game.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LobbyPlayer {
+    pub name: String,
+    /// Whether they have said they're ready to play.
+    pub ready: bool,
+}
+
+/// Who takes over hosting a game if the host leaves, and what they need to.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Succession {
+    /// The port the game is hosted on. Whoever takes over hosts it on the same one.
+    pub port: u16,
+    /// The players who can take over, in the order they would.
+    pub successors: Vec<Successor>,
+    /// The hash of every seat's token for rejoining, with the seat, so whoever takes over can
+    /// let the others back in without knowing their tokens. See `token_hash`.
+    pub tokens: Vec<(String, usize)>,
+}
+
+/// A player who can take over hosting the game.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Successor {
+    pub seat: usize,
+    /// The address the host reaches them at.
+    pub ip: IpAddr,
+}
+
+/// A message from a player who joined to the host.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ClientMessage {
+    /// The newest version of the protocol the player's copy speaks. This is always the first
+    /// message.
+    Hello { version: u32 },
+    /// Ask to join the game, with the password if the host asks for one. This or `Rejoin`
+    /// always follows the handshake.
+    Join {
+        name: String,
+        #[serde(default)]
+        password: Option<String>,
+    },
+    /// Take back a seat in a game that has started after the connection to the host dropped,
+    /// with the token sent when it started.
+    Rejoin { token: String },
+    /// Say whether the player is ready for the game to start.
+    Ready(bool),
+    /// Say something to everyone in the game.
+    Chat(String),
+    /// Make a move. The host ignores moves made out of turn.
+    Move(Move),
+}
+
+/// A message from the host to a player who joined.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ServerMessage {
+    /// The answer to `Hello`: the version of the protocol both ends will use.
+    Welcome { version: u32 },
+    /// Everyone in the lobby and the variant the host has chosen, sent whenever either changes.
+    Lobby {
+        players: Vec<LobbyPlayer>,
+        variant: String,
+    },
+    /// The game has started. `seat` is the receiver's index in `players`.
+    ///
+    /// If the connection drops, the seat is kept for `grace_period` seconds, in which the
+    /// receiver can take it back by rejoining with `token`.
+    ///
+    /// There's no seed: the host keeps it to itself, and the values of each roll come in
+    /// `Event(GameEvent::Rolled(..))` once the dice land.
+    Started {
+        rules: RuleSet,
+        players: Vec<PlayerInfo>,
+        seat: usize,
+        turn_time: Option<u32>,
+        token: String,
+        grace_period: u32,
+    },
+    /// The receiver has their seat back, and these are all the game's events so far.
+    Resumed { events: Vec<GameEvent> },
+    /// Who takes over if the host leaves, sent once the game has started and whenever it
+    /// changes.
+    Succession(Succession),
+    /// The host has left a game that has started, and is closing the connection. Whoever is
+    /// first in the succession takes over.
+    HostLeft,
+    /// Something happened in the game.
+    Event(GameEvent),
+    /// Someone in the game said something, the receiver included.
+    Chat { from: String, text: String },
+    /// The host won't have the receiver in the game, and is closing the connection.
+    Refused(String),
+}
+
+/// The version to speak with a copy whose newest is `theirs`, or `None` if this copy is too
+/// new to speak it.
+pub fn negotiate(theirs: u32) -> Option<u32> {
+    let version = theirs.min(PROTOCOL_VERSION);
+    if version >= OLDEST_PROTOCOL_VERSION {
+        Some(version)
+    } else {
+        None
+    }
+}
+
+/// The hash of a token for rejoining, which says nothing about the token itself.
+pub fn token_hash(token: &str) -> String {
+    let hash = ring::digest::digest(&ring::digest::SHA256, token.as_bytes());
+    hash.as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Add the default port to an address that doesn't give one.
+pub fn with_default_port(address: &str) -> String {
+    if address.contains(':') {
+        address.to_string()
+    } else {
+        format!("{}:{}", address, DEFAULT_PORT)
+    }
+}
+
+/// Send a message as a line of JSON.
+pub fn write_message<T: Serialize>(writer: &mut impl Write, message: &T) -> Result<()> {
+    let mut line = serde_json::to_string(message)?;
+    line.push('\n');
+    writer.write_all(line.as_bytes())?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Write the messages sent on the returned channel to `stream` on a thread of its own. Once
+/// every sender is dropped, the rest are written and the stream is shut down.
+pub fn spawn_writer<T: Serialize + Send + 'static>(mut stream: TcpStream) -> Sender<T> {
+    let (sender, outgoing) = crossbeam_channel::unbounded();
+    thread::spawn(move || {
+        for message in outgoing {
+            if write_message(&mut stream, &message).is_err() {
+                break;
+            }
+        }
+        let _ = stream.shutdown(Shutdown::Both);
+    });
+    sender
+}
+
+/// Reads messages sent with `write_message`.
+pub struct MessageReader<R> {
+    reader: BufReader<R>,
+    /// The line read so far, kept when reading fails partway through one.
+    line: Vec<u8>,
+}
+
+impl<R: Read> MessageReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader: BufReader::new(reader),
+            line: Vec::new(),
+        }
+    }
+
+    /// The next message, or `None` once the other end has closed the connection. Lines that
+    /// aren't a message in the protocol are skipped, and a line longer than `MAX_LINE` is an
+    /// error.
+    pub fn read<T: DeserializeOwned>(&mut self) -> Result<Option<T>> {
+        loop {
+            let room = (MAX_LINE + 1).saturating_sub(self.line.len()) as u64;
+            let read = Read::by_ref(&mut self.reader)
+                .take(room)
+                .read_until(b'\n', &mut self.line)
+                .context("reading a message")?;
+            if self.line.last() != Some(&b'\n') {
+                // a line that never ends would take all the memory there is
+                if self.line.len() > MAX_LINE {
+                    bail!("the other end sent a line longer than {} bytes", MAX_LINE);
+                }
+                if read == 0 {
+                    return Ok(None);
+                }
+                continue;
+            }
+            let line = std::mem::take(&mut self.line);
+            if let Ok(message) = serde_json::from_slice(&line) {
+                return Ok(Some(message));
+            }
+        }
+    }
+}
+
+/// Whether a read failed only because nothing arrived within `POLL_INTERVAL`.
+pub fn waited(e: &io::Error) -> bool {
+    e.kind() == io::ErrorKind::WouldBlock || e.kind() == io::ErrorKind::TimedOut
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines_that_arent_messages_are_skipped() {
+        let mut reader = MessageReader::new(&b"not a message\n\n{\"Ready\":"[..]);
+        assert_eq!(reader.read::<ClientMessage>().unwrap(), None);
+    }
+
+    #[test]
+    fn a_line_that_never_ends_is_an_error() {
+        let sent = vec![b'x'; MAX_LINE + 2];
+        let mut reader = MessageReader::new(&sent[..]);
+        assert!(reader.read::<ClientMessage>().is_err());
+    }
+}
@@ -0,0 +1,123 @@
+//! Syncing with a bucket in an S3-compatible store, such as Amazon S3, MinIO or Backblaze B2.
+//!
+//! Requests are signed with AWS Signature Version 4, and the bucket is named in the path rather
+//! than the host name, which every S3-compatible store understands.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use ring::{digest, hmac};
+
+use super::sync::{self, Remote, TIMEOUT};
+use crate::core::date;
+
+/// The headers that go into each request's signature.
+const SIGNED_HEADERS: &str = "host;x-amz-content-sha256;x-amz-date";
+
+/// A bucket in an S3-compatible store, which has to exist already.
+pub struct S3 {
+    /// Where the store is, without a `/` at the end.
+    endpoint: String,
+    /// The host name in `endpoint`, with its port if it gives one.
+    host: String,
+    region: String,
+    bucket: String,
+    prefix: String,
+    access_key: String,
+    secret_key: String,
+}
+
+impl S3 {
+    pub fn new(
+        endpoint: &str,
+        region: &str,
+        bucket: &str,
+        prefix: &str,
+        access_key: &str,
+        secret_key: &str,
+    ) -> Self {
+        let endpoint = endpoint.trim_end_matches('/');
+        let host = endpoint.splitn(2, "://").last().unwrap_or_default();
+        Self {
+            endpoint: endpoint.to_string(),
+            host: host.split('/').next().unwrap_or_default().to_string(),
+            region: region.to_string(),
+            bucket: bucket.to_string(),
+            prefix: prefix.to_string(),
+            access_key: access_key.to_string(),
+            secret_key: secret_key.to_string(),
+        }
+    }
+
+    /// A request for the file called `name`, signed for sending `payload`.
+    fn request(&self, method: &str, name: &str, payload: &[u8]) -> ureq::Request {
+        let path = format!(
+            "/{}/{}",
+            sync::encode(&self.bucket),
+            sync::encode(&format!("{}{}", self.prefix, name))
+        );
+        let now = SystemTime::now().duration_since(UNIX_EPOCH);
+        let time = date::timestamp(now.map_or(0, |now| now.as_secs()));
+        let day = &time[..8];
+        let payload_hash = hex(digest::digest(&digest::SHA256, payload).as_ref());
+
+        let canonical = format!(
+            "{}\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+            method, path, self.host, payload_hash, time, SIGNED_HEADERS, payload_hash
+        );
+        let scope = format!("{}/{}/s3/aws4_request", day, self.region);
+        let canonical_hash = digest::digest(&digest::SHA256, canonical.as_bytes());
+        let to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            time,
+            scope,
+            hex(canonical_hash.as_ref())
+        );
+        let mut key = sign(format!("AWS4{}", self.secret_key).as_bytes(), day);
+        for part in &[self.region.as_str(), "s3", "aws4_request"] {
+            key = sign(&key, part);
+        }
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.access_key,
+            scope,
+            SIGNED_HEADERS,
+            hex(&sign(&key, &to_sign))
+        );
+
+        let mut request = ureq::request(method, &format!("{}{}", self.endpoint, path));
+        request
+            .set("x-amz-content-sha256", &payload_hash)
+            .set("x-amz-date", &time)
+            .set("Authorization", &authorization)
+            .timeout(TIMEOUT);
+        request
+    }
+}
+
+impl Remote for S3 {
+    fn get(&self, name: &str) -> Result<Option<Vec<u8>>> {
+        let response = self.request("GET", name, &[]).call();
+        if response.status() == 404 {
+            return Ok(None);
+        }
+        let response = sync::check(response).with_context(|| format!("downloading {}", name))?;
+        sync::body(response).map(Some)
+    }
+
+    fn put(&self, name: &str, data: &[u8]) -> Result<()> {
+        let response = self.request("PUT", name, data).send_bytes(data);
+        sync::check(response).with_context(|| format!("uploading {}", name))?;
+        Ok(())
+    }
+}
+
+/// The HMAC-SHA256 of `data` with `key`.
+fn sign(key: &[u8], data: &str) -> Vec<u8> {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, key);
+    hmac::sign(&key, data.as_bytes()).as_ref().to_vec()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
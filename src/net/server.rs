@@ -0,0 +1,172 @@
+//! A game server with no window, which browsers and other programs join over WebSocket.
+//!
+//! The server hosts a single game: it waits in the lobby until enough players have joined and
+//! all of them are ready, plays the game through, and prints the results. Given a port for
+//! them, it also serves metrics for Prometheus to scrape there, as described in `metrics`, and
+//! it can send webhooks as the turns go round.
+//!
+//! The same game is written down as a gRPC service in `proto/yatzy.proto`, for clients in
+//! other languages, but no gRPC service is served: the server only speaks the native protocol
+//! over WebSocket.
+
+use std::{
+    collections::HashMap,
+    sync::{atomic::Ordering, Arc},
+};
+
+use anyhow::Result;
+use crossbeam_channel as channel;
+
+use super::host::{Host, Transport};
+use super::metrics::Metrics;
+use super::protocol::PlayerInfo;
+use super::webhook;
+use super::NetEvent;
+use crate::core::{
+    ai,
+    bot::{self, Bot, BotRegistry},
+    game::Game,
+    moves::{Move, MoveError},
+    rules::RuleSet,
+    score::GameRng,
+};
+
+/// The game to host and how.
+#[derive(Debug, Clone)]
+pub struct Config<'a> {
+    pub port: u16,
+    pub rules: RuleSet,
+    /// How many people have to join before the game can start.
+    pub players: usize,
+    /// What people have to give to join, if anything.
+    pub password: Option<&'a str>,
+    /// How many seconds someone who leaves during the game has to rejoin.
+    pub grace_period: u32,
+    /// Where to serve metrics, if anywhere.
+    pub metrics_port: Option<u16>,
+    /// Addresses to send webhooks to.
+    pub webhooks: Vec<String>,
+}
+
+/// Host a game as `config` has it. Anyone who leaves during the game and doesn't rejoin in time
+/// is replaced by the bot `ai::stand_in` names from `registry`.
+pub fn run(config: Config, registry: &BotRegistry) -> Result<()> {
+    let Config {
+        port,
+        rules,
+        players,
+        password,
+        grace_period,
+        metrics_port,
+        webhooks,
+    } = config;
+    let metrics = Arc::new(Metrics::new());
+    if let Some(port) = metrics_port {
+        let port = metrics.serve(port)?;
+        println!("serving metrics on http://0.0.0.0:{}/metrics", port);
+    }
+    let (sender, events) = channel::unbounded();
+    let host = Host::start(port, Transport::WebSocket, &rules.name, sender)?;
+    host.set_password(password);
+    println!(
+        "serving {} on ws://0.0.0.0:{}, waiting for {} players",
+        rules.name,
+        host.port(),
+        players
+    );
+
+    // wait in the lobby until everyone is ready
+    let joined = loop {
+        match events.recv()? {
+            NetEvent::Joined { name, .. } => println!("{} joined", name),
+            NetEvent::Left { name, .. } => println!("{} left", name),
+            NetEvent::Ready { .. } => (),
+            _ => continue,
+        }
+        let joined = host.joined();
+        metrics.players.store(joined.len(), Ordering::Relaxed);
+        if joined.len() >= players && joined.iter().all(|(_, player)| player.ready) {
+            break joined;
+        }
+    };
+
+    let info: Vec<PlayerInfo> = joined
+        .iter()
+        .map(|(_, player)| PlayerInfo {
+            name: player.name.clone(),
+            bot: None,
+        })
+        .collect();
+    let seats = joined
+        .iter()
+        .enumerate()
+        .map(|(seat, (connection, _))| (*connection, seat))
+        .collect();
+    let rng = GameRng::from_entropy();
+    host.start_game(&rules, &info, seats, None, grace_period);
+    println!("the game has started");
+    metrics.game_started.store(true, Ordering::Relaxed);
+
+    let mut game = Game::new(rules, rng, info.len());
+    let mut stand_ins: HashMap<usize, Box<dyn Bot>> = HashMap::new();
+    let names: Vec<String> = info.iter().map(|player| player.name.clone()).collect();
+    let mut published = 0;
+    webhook::send(&webhooks, webhook::notices(&game, &names, published));
+    while !game.is_complete() {
+        if let Some(bot) = stand_ins.get_mut(&game.player()) {
+            match bot::choose_move(&game, bot.as_mut()) {
+                Some(mv) => play(&mut game, mv).expect("bot moves are always allowed"),
+                None => break,
+            }
+        } else {
+            match events.recv()? {
+                // moves that aren't allowed are ignored, the player's copy checks them first
+                NetEvent::Move { seat, mv } => {
+                    let played = seat == game.player() && play(&mut game, mv).is_ok();
+                    if !played {
+                        metrics.rejected_moves.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+                NetEvent::Dropped { name, .. } => {
+                    println!("{} lost their connection, waiting for them", name)
+                }
+                NetEvent::Rejoined { name, .. } => println!("{} is back", name),
+                NetEvent::Left { connection, name } => {
+                    let stand_in = ai::stand_in(game.rules(), registry);
+                    if let (Some(seat), Some(bot)) =
+                        (host.seat(connection), registry.create(stand_in))
+                    {
+                        println!("{} left, the {} bot is playing for them", name, stand_in);
+                        stand_ins.insert(seat, bot);
+                        metrics.stand_ins.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+                _ => (),
+            }
+        }
+        for event in &game.events()[published..] {
+            host.broadcast(event);
+        }
+        let sent = game.events().len() - published;
+        metrics.events.fetch_add(sent, Ordering::Relaxed);
+        if sent > 0 {
+            webhook::send(&webhooks, webhook::notices(&game, &names, published));
+        }
+        published = game.events().len();
+    }
+
+    println!("final scores:");
+    for (player, sheet) in info.iter().zip(game.sheets()) {
+        println!("{:>5}  {}", sheet.total(), player.name);
+    }
+    Ok(())
+}
+
+/// Make a move, landing the dice straight away if it was a roll.
+fn play(game: &mut Game, mv: Move) -> Result<(), MoveError> {
+    game.play(mv)?;
+    if game.any_rolling() {
+        game.stop_roll();
+    }
+    Ok(())
+}
@@ -0,0 +1,232 @@
+//! Syncing the profiles and saved games with a copy kept online, to carry them between
+//! computers.
+//!
+//! Sync is off until a remote is set up in the `[sync]` table of the settings, either a folder
+//! on a WebDAV server or a bucket in an S3-compatible store. The remote keeps the profiles, each
+//! saved slot, and a list of the slots with how many events each game has had, so nothing has to
+//! be listed on the server.
+//!
+//! When both copies of something have changed, the one that has had more happen to it is kept:
+//! the profile with more games counted, or the saved game with more events. A tie keeps the copy
+//! on this computer. Deleting a slot only deletes it here, so it comes back at the next sync while
+//! the remote still has it.
+
+use std::{collections::BTreeMap, fmt, fs, io::Read, path::Path, time::Duration};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use super::{s3::S3, webdav::WebDav};
+use crate::core::{
+    profiles,
+    save::{self, SaveFile},
+    schema::{self, Schema},
+    settings::SyncSettings,
+};
+
+/// How long to wait for the server before giving up.
+pub(super) const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The names the profiles and the list of slots are kept under on the remote.
+const PROFILES: &str = "profiles.json";
+const SLOTS: &str = "slots.json";
+
+const SLOTS_SCHEMA: Schema = Schema {
+    name: "list of saved games",
+    migrations: &[schema::numbered],
+};
+
+/// Somewhere files can be kept online.
+pub trait Remote {
+    /// The file called `name`, or `None` if there isn't one.
+    fn get(&self, name: &str) -> Result<Option<Vec<u8>>>;
+
+    /// Save `data` as the file called `name`, replacing whatever was there.
+    fn put(&self, name: &str, data: &[u8]) -> Result<()>;
+}
+
+/// The remote set up in the settings.
+pub fn remote(settings: &SyncSettings) -> Box<dyn Remote> {
+    match settings {
+        SyncSettings::WebDav {
+            url,
+            user,
+            password,
+        } => Box::new(WebDav::new(url, user, password)),
+        SyncSettings::S3 {
+            endpoint,
+            region,
+            bucket,
+            prefix,
+            access_key,
+            secret_key,
+        } => Box::new(S3::new(
+            endpoint, region, bucket, prefix, access_key, secret_key,
+        )),
+    }
+}
+
+/// The slots on the remote, with how many events each game has had.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SlotList {
+    slots: BTreeMap<String, usize>,
+}
+
+/// What a sync changed on each side.
+#[derive(Debug, Default)]
+pub struct SyncReport {
+    pub uploaded: Vec<String>,
+    pub downloaded: Vec<String>,
+}
+
+impl fmt::Display for SyncReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.uploaded.is_empty() && self.downloaded.is_empty() {
+            return write!(f, "everything was already in sync");
+        }
+        let list = |names: &[String]| {
+            if names.is_empty() {
+                "nothing".to_string()
+            } else {
+                names.join(", ")
+            }
+        };
+        write!(
+            f,
+            "uploaded {}; downloaded {}",
+            list(&self.uploaded),
+            list(&self.downloaded)
+        )
+    }
+}
+
+/// Sync the profiles and saved slots on this computer with a remote.
+pub fn sync_here(settings: &SyncSettings) -> Result<SyncReport> {
+    let profiles = profiles::default_path().context("there's no config directory for profiles")?;
+    let slots = save::slots_dir().context("there's no config directory for saved games")?;
+    sync(&*remote(settings), &profiles, &slots)
+}
+
+/// Sync the profiles saved at `profiles_path` and the slots in `slots_dir` with the remote.
+pub fn sync(remote: &dyn Remote, profiles_path: &Path, slots_dir: &Path) -> Result<SyncReport> {
+    let mut report = SyncReport::default();
+    sync_profiles(remote, profiles_path, &mut report).context("syncing the profiles")?;
+    sync_slots(remote, slots_dir, &mut report).context("syncing the saved games")?;
+    Ok(report)
+}
+
+fn sync_profiles(remote: &dyn Remote, path: &Path, report: &mut SyncReport) -> Result<()> {
+    let ours = profiles::load(path)?;
+    let theirs = match remote.get(PROFILES)? {
+        Some(data) => profiles::parse(&String::from_utf8(data)?)?,
+        None => Vec::new(),
+    };
+    let merged = profiles::merge(&ours, &theirs);
+    if merged != ours {
+        profiles::save(path, &merged)?;
+        report.downloaded.push("profiles".into());
+    }
+    // the order of the profiles doesn't matter on the remote
+    let unchanged =
+        merged.len() == theirs.len() && merged.iter().all(|profile| theirs.contains(profile));
+    if !unchanged {
+        remote.put(PROFILES, profiles::to_json(&merged)?.as_bytes())?;
+        report.uploaded.push("profiles".into());
+    }
+    Ok(())
+}
+
+fn sync_slots(remote: &dyn Remote, dir: &Path, report: &mut SyncReport) -> Result<()> {
+    let mut list: SlotList = match remote.get(SLOTS)? {
+        Some(data) => SLOTS_SCHEMA.parse(&String::from_utf8(data)?)?,
+        None => SlotList::default(),
+    };
+    let mut ours = BTreeMap::new();
+    for slot in save::list_slots(dir)? {
+        let file = SaveFile::load(&save::slot_path(dir, &slot.name))?;
+        ours.insert(slot.name, file.game.events().len());
+    }
+
+    let mut listed = false;
+    for (name, &events) in &ours {
+        let newer = match list.slots.get(name) {
+            Some(&theirs) => events > theirs,
+            None => true,
+        };
+        if newer {
+            let path = save::slot_path(dir, name);
+            let data = fs::read(&path).with_context(|| format!("reading {}", path.display()))?;
+            remote.put(&slot_name(name), &data)?;
+            list.slots.insert(name.clone(), events);
+            listed = true;
+            report.uploaded.push(name.clone());
+        }
+    }
+    for (name, &events) in &list.slots {
+        let newer = match ours.get(name) {
+            Some(&ours) => events > ours,
+            None => true,
+        };
+        if newer {
+            let data = match remote.get(&slot_name(name))? {
+                Some(data) => String::from_utf8(data)?,
+                None => bail!("the remote lists {:?} but doesn't have it", name),
+            };
+            SaveFile::parse(&data)
+                .with_context(|| format!("reading {:?} from the remote", name))?;
+            fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+            let path = save::slot_path(dir, name);
+            fs::write(&path, data).with_context(|| format!("writing {}", path.display()))?;
+            report.downloaded.push(name.clone());
+        }
+    }
+    if listed {
+        let json = serde_json::to_string(&SLOTS_SCHEMA.write(&list)?)?;
+        remote.put(SLOTS, json.as_bytes())?;
+    }
+    Ok(())
+}
+
+/// The name a slot is kept under on the remote.
+fn slot_name(slot: &str) -> String {
+    format!("slot-{}.{}", slot, save::EXTENSION)
+}
+
+/// A name with everything but letters, digits, `/` and `-_.~` percent-encoded, to go in a URL.
+pub(super) fn encode(name: &str) -> String {
+    let mut encoded = String::new();
+    for byte in name.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// The response to a request, or an error if it didn't succeed.
+pub(super) fn check(response: ureq::Response) -> Result<ureq::Response> {
+    if let Some(e) = response.synthetic_error() {
+        bail!("{}", e);
+    }
+    if response.error() {
+        bail!(
+            "the server said {} {}",
+            response.status(),
+            response.status_text()
+        );
+    }
+    Ok(response)
+}
+
+/// Everything in the body of a response.
+pub(super) fn body(response: ureq::Response) -> Result<Vec<u8>> {
+    let mut data = Vec::new();
+    response
+        .into_reader()
+        .read_to_end(&mut data)
+        .context("downloading")?;
+    Ok(data)
+}
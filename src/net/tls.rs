@@ -0,0 +1,256 @@
+//! Encrypting games played over TCP with TLS, so they can be played over the internet.
+//!
+//! A host uses the certificate in `cert.pem` and `key.pem` in the user's config directory. If
+//! there isn't one, it makes one signed by itself and saves it there, so it stays the same from
+//! one game to the next. Nobody vouches for a certificate like that, so players who join accept
+//! whichever one the host has. Instead both ends show its fingerprint, and the players can
+//! compare them to be sure nobody is listening in between.
+//!
+//! Like a WebSocket, a TLS connection can't be read on one thread while it's written on another,
+//! so each gets a thread that owns it.
+
+use std::{
+    fmt,
+    fs::{self, File, OpenOptions},
+    io::{BufReader, Read, Write},
+    net::{Shutdown, TcpStream},
+    path::{Path, PathBuf},
+    sync::Arc,
+    thread,
+};
+
+use anyhow::{anyhow, Context, Result};
+use crossbeam_channel::{Receiver, Sender, TryRecvError};
+use rustls::{
+    internal::pemfile, Certificate, ClientConfig, ClientSession, NoClientAuth, RootCertStore,
+    ServerCertVerified, ServerCertVerifier, ServerConfig, ServerSession, Session, StreamOwned,
+    TLSError,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use webpki::DNSNameRef;
+
+use super::protocol::{self, ClientMessage, ServerMessage, MAX_LINE, POLL_INTERVAL};
+
+/// The name certificates are made out to. Games are joined by address, so it isn't checked.
+const SERVER_NAME: &str = "yatzy";
+
+/// Whether an address to join is an encrypted one, `tls://host[:port]`.
+pub fn is_tls(address: &str) -> bool {
+    address.starts_with("tls://")
+}
+
+/// Where a host's certificate and its private key are kept, in the user's config directory.
+pub fn default_paths() -> Option<(PathBuf, PathBuf)> {
+    let dir = dirs::config_dir()?.join("yatzy");
+    Some((dir.join("cert.pem"), dir.join("key.pem")))
+}
+
+/// The certificate a host proves itself with.
+#[derive(Clone)]
+pub struct Identity {
+    config: Arc<ServerConfig>,
+    fingerprint: String,
+}
+
+impl Identity {
+    /// Load the certificate at the default paths, making one first if there isn't one.
+    pub fn load_or_create() -> Result<Self> {
+        let (cert, key) = default_paths()
+            .ok_or_else(|| anyhow!("there's no config directory to keep the certificate in"))?;
+        if !cert.exists() && !key.exists() {
+            create(&cert, &key)?;
+        }
+        Self::load(&cert, &key)
+    }
+
+    /// Load the certificate in `cert` and its PKCS #8 private key in `key`, both PEM files.
+    pub fn load(cert: &Path, key: &Path) -> Result<Self> {
+        let certs = pemfile::certs(&mut open(cert)?)
+            .map_err(|_| anyhow!("{} isn't a PEM certificate", cert.display()))?;
+        let private_key = pemfile::pkcs8_private_keys(&mut open(key)?)
+            .ok()
+            .and_then(|keys| keys.into_iter().next())
+            .ok_or_else(|| anyhow!("{} has no PKCS #8 private key", key.display()))?;
+        let fingerprint = certs
+            .first()
+            .map(fingerprint)
+            .ok_or_else(|| anyhow!("{} has no certificate in it", cert.display()))?;
+        let mut config = ServerConfig::new(NoClientAuth::new());
+        config
+            .set_single_cert(certs, private_key)
+            .map_err(|e| anyhow!("using {}: {}", cert.display(), e))?;
+        Ok(Self {
+            config: Arc::new(config),
+            fingerprint,
+        })
+    }
+
+    /// The fingerprint of the certificate, for players to compare.
+    pub fn fingerprint(&self) -> &str {
+        &self.fingerprint
+    }
+}
+
+impl fmt::Debug for Identity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Identity").field(&self.fingerprint).finish()
+    }
+}
+
+/// Finish the TLS handshake with a player who connected to a host.
+pub fn accept(
+    stream: TcpStream,
+    identity: &Identity,
+) -> Result<(Sender<ServerMessage>, Receiver<ClientMessage>)> {
+    let session = ServerSession::new(&identity.config);
+    spawn(StreamOwned::new(session, stream))
+}
+
+/// Connect to the host at `tls://host[:port]`, on the default port if none is given. Also gives
+/// the fingerprint of the host's certificate.
+pub fn connect(address: &str) -> Result<(Sender<ClientMessage>, Receiver<ServerMessage>, String)> {
+    let host = protocol::with_default_port(address.trim_start_matches("tls://"));
+    let stream = TcpStream::connect(&host).with_context(|| format!("connecting to {}", address))?;
+    let mut config = ClientConfig::new();
+    config
+        .dangerous()
+        .set_certificate_verifier(Arc::new(AnyCertificate));
+    let name = DNSNameRef::try_from_ascii_str(SERVER_NAME).expect("the server name is valid");
+    let mut tls = StreamOwned::new(ClientSession::new(&Arc::new(config), name), stream);
+    while tls.sess.is_handshaking() {
+        tls.sess
+            .complete_io(&mut tls.sock)
+            .with_context(|| format!("setting up encryption with {}", address))?;
+    }
+    let fingerprint = tls
+        .sess
+        .get_peer_certificates()
+        .and_then(|certs| certs.first().map(fingerprint))
+        .ok_or_else(|| anyhow!("{} didn't send a certificate", address))?;
+    let (outgoing, incoming) = spawn(tls)?;
+    Ok((outgoing, incoming, fingerprint))
+}
+
+/// Accepts whatever certificate the host has, see the module docs.
+struct AnyCertificate;
+
+impl ServerCertVerifier for AnyCertificate {
+    fn verify_server_cert(
+        &self,
+        _roots: &RootCertStore,
+        _presented_certs: &[Certificate],
+        _dns_name: DNSNameRef,
+        _ocsp_response: &[u8],
+    ) -> Result<ServerCertVerified, TLSError> {
+        Ok(ServerCertVerified::assertion())
+    }
+}
+
+/// Make a certificate signed by itself, and save it.
+fn create(cert: &Path, key: &Path) -> Result<()> {
+    let generated = rcgen::generate_simple_self_signed(vec![SERVER_NAME.to_string()])
+        .map_err(|e| anyhow!("making a certificate: {}", e))?;
+    let pem = generated
+        .serialize_pem()
+        .map_err(|e| anyhow!("making a certificate: {}", e))?;
+    for path in &[cert, key] {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+        }
+    }
+    fs::write(cert, pem).with_context(|| format!("writing {}", cert.display()))?;
+    write_private(key, &generated.serialize_private_key_pem())
+        .with_context(|| format!("writing {}", key.display()))
+}
+
+/// Write a file only the user can read, on platforms where files have owners.
+fn write_private(path: &Path, contents: &str) -> Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)?.write_all(contents.as_bytes())?;
+    Ok(())
+}
+
+fn open(path: &Path) -> Result<BufReader<File>> {
+    let file = File::open(path).with_context(|| format!("reading {}", path.display()))?;
+    Ok(BufReader::new(file))
+}
+
+/// The SHA-256 hash of a certificate, as pairs of hex digits. All of it is shown, since a
+/// shorter part is easier to make another certificate match.
+fn fingerprint(cert: &Certificate) -> String {
+    let hash = ring::digest::digest(&ring::digest::SHA256, &cert.0);
+    let pairs: Vec<_> = hash
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02X}", byte))
+        .collect();
+    pairs.join(":")
+}
+
+/// Start the thread that owns the connection, giving the channels to send and receive on. The
+/// connection is closed once every sender is dropped, and the receiver ends once it is closed.
+fn spawn<S, In, Out>(tls: StreamOwned<S, TcpStream>) -> Result<(Sender<Out>, Receiver<In>)>
+where
+    S: Session + 'static,
+    In: DeserializeOwned + Send + 'static,
+    Out: Serialize + Send + 'static,
+{
+    tls.sock.set_read_timeout(Some(POLL_INTERVAL))?;
+    let (sender, outgoing) = crossbeam_channel::unbounded();
+    let (incoming, receiver) = crossbeam_channel::unbounded();
+    thread::spawn(move || pump(tls, outgoing, incoming));
+    Ok((sender, receiver))
+}
+
+fn pump<S: Session, In: DeserializeOwned, Out: Serialize>(
+    mut tls: StreamOwned<S, TcpStream>,
+    outgoing: Receiver<Out>,
+    incoming: Sender<In>,
+) {
+    let mut received = Vec::new();
+    let mut buffer = [0; 4096];
+    loop {
+        loop {
+            match outgoing.try_recv() {
+                Ok(message) => {
+                    if protocol::write_message(&mut tls, &message).is_err() {
+                        return;
+                    }
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    tls.sess.send_close_notify();
+                    let _ = tls.flush();
+                    let _ = tls.sock.shutdown(Shutdown::Both);
+                    return;
+                }
+            }
+        }
+        match tls.read(&mut buffer) {
+            Ok(0) => return,
+            Ok(read) => received.extend_from_slice(&buffer[..read]),
+            Err(e) if protocol::waited(&e) => continue,
+            Err(_) => return,
+        }
+        while let Some(end) = received.iter().position(|&byte| byte == b'\n') {
+            let line: Vec<u8> = received.drain(..=end).collect();
+            // anything that isn't a message in the protocol is ignored
+            if let Ok(message) = serde_json::from_slice(&line) {
+                if incoming.send(message).is_err() {
+                    return;
+                }
+            }
+        }
+        // a line that never ends would take all the memory there is
+        if received.len() > MAX_LINE {
+            let _ = tls.sock.shutdown(Shutdown::Both);
+            return;
+        }
+    }
+}
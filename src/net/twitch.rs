@@ -0,0 +1,360 @@
+//! Twitch chat plays: the viewers of a channel vote in its chat on what to hold and where to
+//! score, and `ChatPlayer` plays whatever most of them voted for.
+//!
+//! The chat is read over IRC without logging in, so nothing is posted to it. The votes are
+//!
+//! - `!keep 1 3 5` to hold those dice, counting from the left, and roll the others, or `!keep`
+//!   on its own to roll them all,
+//! - `!score full house`, or as much of a category's name as tells it apart, to stop rolling and
+//!   score there.
+//!
+//! Each vote is open for `VOTE_TIME`, and only a viewer's last vote counts. If nobody votes, the
+//! `Hard` bot decides instead.
+
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::TcpStream,
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use crossbeam_channel as channel;
+
+use crate::core::{
+    ai::Hard,
+    bot::{Bot, BotRegistry},
+    event::GameEvent,
+    fuzzy,
+    game::Game,
+    scorecard::Slot,
+};
+
+/// What the chat player is called among the bots.
+pub const NAME: &str = "Twitch chat";
+
+/// How long each vote is open.
+pub const VOTE_TIME: Duration = Duration::from_secs(20);
+
+/// Where Twitch chat is served over IRC.
+const SERVER: (&str, u16) = ("irc.chat.twitch.tv", 6667);
+
+/// Twitch lets anyone read chat with a nickname like this, without logging in.
+const ANONYMOUS_NICK: &str = "justinfan31415";
+
+/// Something said in chat.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChatMessage {
+    pub user: String,
+    pub text: String,
+}
+
+/// Add the chat of `name`, a Twitch channel, to the bots. A chat that can't be read is warned
+/// about, and `Hard` plays instead.
+pub fn register(registry: &mut BotRegistry, name: String) {
+    registry.register(NAME, move || match join(&name) {
+        Ok(chat) => Box::new(ChatPlayer::new(chat, VOTE_TIME)),
+        Err(e) => {
+            eprintln!("warning: couldn't read the chat of {}: {:#}", name, e);
+            Box::new(Hard)
+        }
+    });
+}
+
+/// Read the chat of the Twitch channel `name` on a thread of its own, passing on everything
+/// said in it until the connection drops.
+pub fn join(name: &str) -> Result<channel::Receiver<ChatMessage>> {
+    let mut stream = TcpStream::connect(SERVER).context("connecting to Twitch chat")?;
+    let name = name.trim_start_matches('#').to_lowercase();
+    write!(stream, "NICK {}\r\nJOIN #{}\r\n", ANONYMOUS_NICK, name)?;
+    let reader = BufReader::new(stream.try_clone()?);
+    let (sender, chat) = channel::unbounded();
+    thread::spawn(move || {
+        for line in reader.lines() {
+            let sent = match line.map(|line| parse_line(&line)) {
+                Ok(Some(Line::Ping(server))) => write!(stream, "PONG :{}\r\n", server).is_ok(),
+                Ok(Some(Line::Said(message))) => sender.send(message).is_ok(),
+                Ok(None) => true,
+                Err(_) => false,
+            };
+            if !sent {
+                break;
+            }
+        }
+    });
+    Ok(chat)
+}
+
+/// A line from the IRC server that matters here.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Line {
+    /// The server checking the connection is still there, which has to be answered.
+    Ping(String),
+    Said(ChatMessage),
+}
+
+/// Read a line from the IRC server, like `:alice!alice@alice.tmi.twitch.tv PRIVMSG #cats :hi`.
+fn parse_line(line: &str) -> Option<Line> {
+    if let Some(server) = line.strip_prefix("PING :") {
+        return Some(Line::Ping(server.to_string()));
+    }
+    let (source, rest) = line.strip_prefix(':')?.split_once(' ')?;
+    let (_, text) = rest.strip_prefix("PRIVMSG ")?.split_once(" :")?;
+    let user = source.split('!').next()?;
+    Some(Line::Said(ChatMessage {
+        user: user.to_string(),
+        text: text.to_string(),
+    }))
+}
+
+/// What a viewer voted for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Vote {
+    /// Hold these dice and roll the others.
+    Keep(Vec<bool>),
+    /// Stop rolling and score in this slot.
+    Score(Slot),
+}
+
+impl Vote {
+    /// The vote in a chat message, if it's one that can be played in `game` now.
+    fn parse(text: &str, game: &Game) -> Option<Self> {
+        let text = text.trim();
+        if let Some(dice) = command(text, "!keep") {
+            if !game.can_roll() {
+                return None;
+            }
+            let mut holds = vec![false; game.dice().len()];
+            for c in dice.chars().filter(|c| c.is_alphanumeric()) {
+                let idx = c.to_digit(10)? as usize;
+                *holds.get_mut(idx.checked_sub(1)?)? = true;
+            }
+            return Some(Vote::Keep(holds));
+        }
+        let name = command(text, "!score")?.trim();
+        if name.is_empty() {
+            return None;
+        }
+        let open: Vec<Slot> = game.slots().filter(|slot| game.can_score(*slot)).collect();
+        let names: Vec<&str> = open.iter().map(|slot| slot.category.name()).collect();
+        let best = *fuzzy::rank(name, &names).first()?;
+        Some(Vote::Score(open[best]))
+    }
+}
+
+/// What comes after `name` at the start of `text`, if it's that command.
+fn command<'a>(text: &'a str, name: &str) -> Option<&'a str> {
+    let start = text.get(..name.len())?;
+    let rest = &text[name.len()..];
+    let whole_word = rest.chars().next().is_none_or(char::is_whitespace);
+    if start.eq_ignore_ascii_case(name) && whole_word {
+        Some(rest)
+    } else {
+        None
+    }
+}
+
+/// Plays what a Twitch channel's chat votes for, see the module's description.
+pub struct ChatPlayer {
+    chat: channel::Receiver<ChatMessage>,
+    vote_time: Duration,
+    /// The decision being voted on, as how many events there had been when it came up not
+    /// counting holds, and when the vote closes.
+    voting: Option<(usize, Instant)>,
+    /// Each viewer's vote, in the order they first voted.
+    votes: Vec<(String, Vote)>,
+    /// Decides when nobody votes.
+    fallback: Hard,
+}
+
+impl ChatPlayer {
+    /// Play what's voted for in `chat`, with each vote open for `vote_time`.
+    pub fn new(chat: channel::Receiver<ChatMessage>, vote_time: Duration) -> Self {
+        Self {
+            chat,
+            vote_time,
+            voting: None,
+            votes: Vec::new(),
+            fallback: Hard,
+        }
+    }
+
+    /// The vote with the most votes, the first of them to be voted for if there's a tie.
+    fn winner(&self) -> Option<&Vote> {
+        let mut counts: Vec<(&Vote, usize)> = Vec::new();
+        for (_, vote) in &self.votes {
+            match counts.iter_mut().find(|(counted, _)| *counted == vote) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((vote, 1)),
+            }
+        }
+        // the last of the most voted for is the first in reverse
+        counts
+            .into_iter()
+            .rev()
+            .max_by_key(|(_, count)| *count)
+            .map(|(vote, _)| vote)
+    }
+}
+
+impl Bot for ChatPlayer {
+    fn choose_holds(&mut self, game: &Game) -> Vec<bool> {
+        match self.winner() {
+            Some(Vote::Keep(holds)) => holds.clone(),
+            Some(Vote::Score(_)) => vec![true; game.dice().len()],
+            None => self.fallback.choose_holds(game),
+        }
+    }
+
+    fn choose_category(&mut self, game: &Game) -> Slot {
+        match self.winner() {
+            Some(Vote::Score(slot)) => *slot,
+            _ => self.fallback.choose_category(game),
+        }
+    }
+
+    fn ready(&mut self, game: &Game) -> bool {
+        let decision = game
+            .events()
+            .iter()
+            .filter(|event| !matches!(event, GameEvent::HoldToggled(_)))
+            .count();
+        let now = Instant::now();
+        let closes = match self.voting {
+            Some((voting, closes)) if voting == decision => closes,
+            _ => {
+                // anything said before the vote opened isn't a vote in it
+                self.chat.try_iter().for_each(drop);
+                self.votes.clear();
+                let closes = now + self.vote_time;
+                self.voting = Some((decision, closes));
+                closes
+            }
+        };
+        if now >= closes {
+            return true;
+        }
+        for message in self.chat.try_iter() {
+            if let Some(vote) = Vote::parse(&message.text, game) {
+                match self
+                    .votes
+                    .iter_mut()
+                    .find(|(user, _)| *user == message.user)
+                {
+                    Some((_, voted)) => *voted = vote,
+                    None => self.votes.push((message.user, vote)),
+                }
+            }
+        }
+        false
+    }
+
+    fn status(&self) -> Option<String> {
+        let (_, closes) = self.voting?;
+        let left = closes.checked_duration_since(Instant::now())?;
+        Some(format!(
+            "Chat is voting, {}s left: !keep 1 3 5 to roll the others, or !score and a category",
+            left.as_secs() + 1
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{
+        moves::Move,
+        rules::RuleSet,
+        score::{GameRng, Score},
+        scorecard::Category,
+    };
+
+    fn said(user: &str, text: &str) -> ChatMessage {
+        ChatMessage {
+            user: user.to_string(),
+            text: text.to_string(),
+        }
+    }
+
+    /// A game where the first roll has landed on `faces`.
+    fn rolled(faces: [u8; 5]) -> Game {
+        let mut game = Game::new(RuleSet::yatzy(), GameRng::from_seed(5), 1);
+        game.play(Move::Roll).unwrap();
+        game.land(faces.iter().map(|&face| Score(face)).collect());
+        game
+    }
+
+    #[test]
+    fn chat_is_read_from_irc_lines() {
+        assert_eq!(
+            parse_line(":alice!alice@alice.tmi.twitch.tv PRIVMSG #cats :!keep 1 2"),
+            Some(Line::Said(said("alice", "!keep 1 2")))
+        );
+        assert_eq!(
+            parse_line("PING :tmi.twitch.tv"),
+            Some(Line::Ping("tmi.twitch.tv".to_string()))
+        );
+        assert_eq!(
+            parse_line(":tmi.twitch.tv 001 justinfan31415 :Welcome"),
+            None
+        );
+    }
+
+    #[test]
+    fn votes_are_read_from_what_was_said() {
+        let game = rolled([2, 2, 3, 5, 2]);
+        let keep = |holds: [bool; 5]| Some(Vote::Keep(holds.to_vec()));
+        assert_eq!(
+            Vote::parse("!keep 1 2 5", &game),
+            keep([true, true, false, false, true])
+        );
+        assert_eq!(
+            Vote::parse("!KEEP 35", &game),
+            keep([false, false, true, false, true])
+        );
+        assert_eq!(Vote::parse("!keep", &game), keep([false; 5]));
+        assert_eq!(Vote::parse("!keep 6", &game), None);
+        assert_eq!(Vote::parse("!keeping", &game), None);
+        assert_eq!(
+            Vote::parse("!score full", &game),
+            Some(Vote::Score(Slot::new(Category::FullHouse)))
+        );
+        assert_eq!(Vote::parse("!score", &game), None);
+        assert_eq!(Vote::parse("nice roll", &game), None);
+    }
+
+    #[test]
+    fn the_most_voted_for_is_played() {
+        let game = rolled([6, 6, 6, 1, 2]);
+        let (sender, chat) = channel::unbounded();
+        let mut player = ChatPlayer::new(chat, Duration::from_secs(60));
+        sender.send(said("early", "!keep")).unwrap();
+        assert!(!player.ready(&game));
+        assert!(player.status().is_some());
+        for (user, text) in &[
+            ("alice", "!keep 123"),
+            ("bob", "!score sixes"),
+            ("carol", "!keep 123"),
+            ("bob", "!keep 1 2 3"),
+            ("dave", "!score chance"),
+        ] {
+            sender.send(said(user, text)).unwrap();
+        }
+        assert!(!player.ready(&game));
+        assert_eq!(player.choose_holds(&game), [true, true, true, false, false]);
+
+        // a vote that's closed keeps its result
+        player.voting = Some((player.voting.unwrap().0, Instant::now()));
+        assert!(player.ready(&game));
+        assert_eq!(player.choose_holds(&game), [true, true, true, false, false]);
+    }
+
+    #[test]
+    fn nobody_voting_leaves_it_to_the_fallback() {
+        let game = rolled([6, 6, 6, 6, 6]);
+        let (_sender, chat) = channel::unbounded();
+        let mut player = ChatPlayer::new(chat, Duration::from_secs(0));
+        assert!(player.ready(&game));
+        assert_eq!(player.choose_holds(&game), Hard.choose_holds(&game));
+    }
+}
@@ -0,0 +1,45 @@
+//! Syncing with a folder on a WebDAV server, such as Nextcloud or ownCloud.
+
+use anyhow::{Context, Result};
+
+use super::sync::{self, Remote, TIMEOUT};
+
+/// A folder on a WebDAV server, which has to exist already.
+pub struct WebDav {
+    url: String,
+    user: String,
+    password: String,
+}
+
+impl WebDav {
+    pub fn new(url: &str, user: &str, password: &str) -> Self {
+        Self {
+            url: url.trim_end_matches('/').to_string(),
+            user: user.to_string(),
+            password: password.to_string(),
+        }
+    }
+
+    fn request(&self, method: &str, name: &str) -> ureq::Request {
+        let mut request = ureq::request(method, &format!("{}/{}", self.url, sync::encode(name)));
+        request.auth(&self.user, &self.password).timeout(TIMEOUT);
+        request
+    }
+}
+
+impl Remote for WebDav {
+    fn get(&self, name: &str) -> Result<Option<Vec<u8>>> {
+        let response = self.request("GET", name).call();
+        if response.status() == 404 {
+            return Ok(None);
+        }
+        let response = sync::check(response).with_context(|| format!("downloading {}", name))?;
+        sync::body(response).map(Some)
+    }
+
+    fn put(&self, name: &str, data: &[u8]) -> Result<()> {
+        let response = self.request("PUT", name).send_bytes(data);
+        sync::check(response).with_context(|| format!("uploading {}", name))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,185 @@
+//! Webhooks: addresses that are sent a little JSON when a game starts, when it's someone's turn
+//! and when it's over, so a chat channel or something at home can let people know.
+//!
+//! Each notice is POSTed to every address as an object with an `event` of `started`, `turn` or
+//! `finished`, for example
+//!
+//! ```text
+//! {"event":"turn","variant":"Yatzy","player":"Bob","round":3}
+//! ```
+//!
+//! The notices come from the game's events, so whatever plays the game only has to say which
+//! events are new since it last sent them.
+
+use std::thread;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use super::sync::{self, TIMEOUT};
+use crate::core::{event::GameEvent, game::Game};
+
+/// Something to let people know about.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "lowercase")]
+pub enum Notice {
+    /// A game between these players, in turn order, has begun.
+    Started {
+        variant: String,
+        players: Vec<String>,
+    },
+    /// It's this player's turn, in the given round counting from 1.
+    Turn {
+        variant: String,
+        player: String,
+        round: usize,
+    },
+    /// The game is over, with everyone's totals in turn order.
+    Finished { variant: String, totals: Vec<Total> },
+}
+
+/// A player's total at the end of a game.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Total {
+    pub player: String,
+    pub total: u16,
+}
+
+/// What to let people know about the events of `game` from `from` on, given the players' names
+/// in turn order. Starting from the first event, the game starting is one of them.
+pub fn notices(game: &Game, names: &[String], from: usize) -> Vec<Notice> {
+    let variant = &game.rules().name;
+    let mut notices = Vec::new();
+    if from == 0 {
+        notices.push(Notice::Started {
+            variant: variant.clone(),
+            players: names.to_vec(),
+        });
+    }
+    let ended = |events: &[GameEvent]| {
+        events
+            .iter()
+            .filter(|event| matches!(event, GameEvent::Scored(_) | GameEvent::Scratched(_)))
+            .count()
+    };
+    let (before, after) = game.events().split_at(from.min(game.events().len()));
+    let played = ended(before);
+    for turns in played + 1..=played + ended(after) {
+        if turns == game.rounds() * game.players() {
+            notices.push(Notice::Finished {
+                variant: variant.clone(),
+                totals: names
+                    .iter()
+                    .zip(game.sheets())
+                    .map(|(name, sheet)| Total {
+                        player: name.clone(),
+                        total: sheet.total(),
+                    })
+                    .collect(),
+            });
+        } else {
+            notices.push(Notice::Turn {
+                variant: variant.clone(),
+                player: names[turns % game.players()].clone(),
+                round: turns / game.players() + 1,
+            });
+        }
+    }
+    notices
+}
+
+/// Send each notice to every one of `urls`, in the background. Webhooks that can't be reached
+/// are only warned about, since the game carries on without them.
+pub fn send(urls: &[String], notices: Vec<Notice>) {
+    if urls.is_empty() || notices.is_empty() {
+        return;
+    }
+    let urls = urls.to_vec();
+    thread::spawn(move || {
+        for notice in &notices {
+            for url in &urls {
+                if let Err(e) = post(url, notice) {
+                    eprintln!("warning: couldn't send a webhook: {:#}", e);
+                }
+            }
+        }
+    });
+}
+
+fn post(url: &str, notice: &Notice) -> Result<()> {
+    let json = serde_json::to_string(notice)?;
+    let response = ureq::request("POST", url)
+        .set("Content-Type", "application/json")
+        .timeout(TIMEOUT)
+        .send_bytes(json.as_bytes());
+    sync::check(response).with_context(|| format!("posting to {}", url))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{
+        moves::Move,
+        rules::RuleSet,
+        score::{GameRng, Score},
+        scorecard::{Category, Slot},
+    };
+
+    fn names() -> Vec<String> {
+        vec!["Alice".to_string(), "Bob".to_string()]
+    }
+
+    /// Play a turn that scores five sixes in `category`.
+    fn turn(game: &mut Game, category: Category) {
+        game.play(Move::Roll).unwrap();
+        game.apply(GameEvent::Rolled(vec![Score(6); 5]));
+        game.play(Move::Score(Slot::new(category))).unwrap();
+    }
+
+    #[test]
+    fn each_turn_says_who_is_next() {
+        let mut game = Game::new(RuleSet::yatzy(), GameRng::from_seed(3), 2);
+        assert_eq!(
+            notices(&game, &names(), 0),
+            vec![Notice::Started {
+                variant: "Yatzy".to_string(),
+                players: names(),
+            }]
+        );
+        turn(&mut game, Category::Sixes);
+        let published = game.events().len();
+        turn(&mut game, Category::Sixes);
+        let turn = |player: &str, round| Notice::Turn {
+            variant: "Yatzy".to_string(),
+            player: player.to_string(),
+            round,
+        };
+        assert_eq!(notices(&game, &names(), published), vec![turn("Alice", 2)]);
+        assert_eq!(
+            notices(&game, &names(), 0)[1..],
+            [turn("Bob", 1), turn("Alice", 2)]
+        );
+    }
+
+    #[test]
+    fn the_last_turn_gives_the_totals() {
+        let mut rules = RuleSet::yatzy();
+        rules.categories = vec![Category::Sixes];
+        let mut game = Game::new(rules, GameRng::from_seed(3), 2);
+        turn(&mut game, Category::Sixes);
+        let published = game.events().len();
+        turn(&mut game, Category::Sixes);
+        let total = |player: &str| Total {
+            player: player.to_string(),
+            total: game.sheets()[0].total(),
+        };
+        assert_eq!(
+            notices(&game, &names(), published),
+            vec![Notice::Finished {
+                variant: "Yatzy".to_string(),
+                totals: vec![total("Alice"), total("Bob")],
+            }]
+        );
+    }
+}
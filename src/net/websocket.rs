@@ -0,0 +1,94 @@
+//! Carrying the protocol's messages over WebSocket, one JSON text message each.
+//!
+//! A WebSocket can't be read on one thread while it's written on another, so each connection gets
+//! a thread that owns the socket, checking for messages to send between short waits for messages
+//! to read.
+
+use std::{net::TcpStream, thread};
+
+use anyhow::{anyhow, Context, Result};
+use crossbeam_channel::{Receiver, Sender, TryRecvError};
+use serde::{de::DeserializeOwned, Serialize};
+use tungstenite::{Message, WebSocket};
+
+use super::protocol::{self, ClientMessage, ServerMessage, DEFAULT_PORT, POLL_INTERVAL};
+
+/// Whether an address to join is a WebSocket one, `ws://host[:port]`.
+pub fn is_websocket(address: &str) -> bool {
+    address.starts_with("ws://")
+}
+
+/// Finish the WebSocket handshake with a player who connected to a host.
+pub fn accept(stream: TcpStream) -> Result<(Sender<ServerMessage>, Receiver<ClientMessage>)> {
+    let socket = tungstenite::accept(stream).map_err(|e| anyhow!("{}", e))?;
+    spawn(socket)
+}
+
+/// Connect to the host at `ws://host[:port]`, on the default port if none is given.
+pub fn connect(address: &str) -> Result<(Sender<ClientMessage>, Receiver<ServerMessage>)> {
+    let host = address.trim_start_matches("ws://").trim_end_matches('/');
+    let host = if host.contains(':') {
+        host.to_string()
+    } else {
+        format!("{}:{}", host, DEFAULT_PORT)
+    };
+    let stream = TcpStream::connect(&host).with_context(|| format!("connecting to {}", address))?;
+    let (socket, _) = tungstenite::client(format!("ws://{}/", host).as_str(), stream)
+        .map_err(|e| anyhow!("connecting to {}: {}", address, e))?;
+    spawn(socket)
+}
+
+/// Start the thread that owns the socket, giving the channels to send and receive on. The
+/// socket is closed once every sender is dropped, and the receiver ends once it is closed.
+fn spawn<In, Out>(socket: WebSocket<TcpStream>) -> Result<(Sender<Out>, Receiver<In>)>
+where
+    In: DeserializeOwned + Send + 'static,
+    Out: Serialize + Send + 'static,
+{
+    socket.get_ref().set_read_timeout(Some(POLL_INTERVAL))?;
+    let (sender, outgoing) = crossbeam_channel::unbounded();
+    let (incoming, receiver) = crossbeam_channel::unbounded();
+    thread::spawn(move || pump(socket, outgoing, incoming));
+    Ok((sender, receiver))
+}
+
+fn pump<In: DeserializeOwned, Out: Serialize>(
+    mut socket: WebSocket<TcpStream>,
+    outgoing: Receiver<Out>,
+    incoming: Sender<In>,
+) {
+    loop {
+        loop {
+            let message = match outgoing.try_recv() {
+                Ok(message) => message,
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    let _ = socket.close(None);
+                    let _ = socket.write_pending();
+                    return;
+                }
+            };
+            let text = match serde_json::to_string(&message) {
+                Ok(text) => text,
+                Err(_) => continue,
+            };
+            if socket.write_message(Message::Text(text)).is_err() {
+                return;
+            }
+        }
+        match socket.read_message() {
+            Ok(Message::Text(text)) => {
+                // anything that isn't a message in the protocol is ignored
+                if let Ok(message) = serde_json::from_str(&text) {
+                    if incoming.send(message).is_err() {
+                        return;
+                    }
+                }
+            }
+            // pings are answered by tungstenite itself
+            Ok(_) => (),
+            Err(tungstenite::Error::Io(e)) if protocol::waited(&e) => (),
+            Err(_) => return,
+        }
+    }
+}
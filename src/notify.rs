@@ -0,0 +1,23 @@
+//! Desktop notifications, so someone playing over the network knows when it's their turn while
+//! they're looking at something else.
+
+use std::thread;
+
+use notify_rust::Notification;
+
+/// Let `name` know it's their turn in a game of `variant`. Showing it is left to a thread of its
+/// own, since it can wait on the desktop, and a notification that can't be shown only gets a
+/// warning, as the window says whose turn it is too.
+pub fn your_turn(name: &str, variant: &str) {
+    let body = format!("It's {}'s turn at {}", name, variant);
+    thread::spawn(move || {
+        let shown = Notification::new()
+            .appname("Yatzy")
+            .summary("Your turn")
+            .body(&body)
+            .show();
+        if let Err(e) = shown {
+            eprintln!("warning: couldn't show a notification: {}", e);
+        }
+    });
+}
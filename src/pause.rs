@@ -0,0 +1,35 @@
+//! Keeps the game from being played while it's paused, leaving the pause menu to be used.
+
+use druid::widget::Controller;
+use druid::{Env, Event, EventCtx, Widget};
+
+use crate::InGameState;
+
+/// Holds back the mouse and keyboard from the game while the pause menu is open. Everything else
+/// still gets through, so the dice and timers can see the game is paused and stop themselves.
+pub struct Pausable;
+
+impl<W: Widget<InGameState>> Controller<InGameState, W> for Pausable {
+    fn event(
+        &mut self,
+        child: &mut W,
+        ctx: &mut EventCtx,
+        event: &Event,
+        data: &mut InGameState,
+        env: &Env,
+    ) {
+        let input = match event {
+            Event::MouseDown(_)
+            | Event::MouseUp(_)
+            | Event::MouseMove(_)
+            | Event::Wheel(_)
+            | Event::KeyDown(_)
+            | Event::KeyUp(_)
+            | Event::Paste(_) => true,
+            _ => false,
+        };
+        if !(input && data.paused) {
+            child.event(ctx, event, data, env)
+        }
+    }
+}
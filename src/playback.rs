@@ -0,0 +1,56 @@
+//! Plays a replay through on its own, an event at a time.
+
+use druid::widget::Controller;
+use druid::{Env, Event, EventCtx, LifeCycle, LifeCycleCtx, TimerToken, Widget};
+use std::time::Duration;
+
+use crate::{animation_time, replay_view::ReplayState};
+
+/// How long each event is shown for, long enough to see the dice roll.
+const STEP_TIME: Duration = Duration::from_millis(700);
+
+/// Steps `ReplayState` forward while it is playing.
+pub struct Playback {
+    timer: Option<TimerToken>,
+}
+
+impl Playback {
+    pub fn new() -> Self {
+        Self { timer: None }
+    }
+}
+
+impl<W: Widget<ReplayState>> Controller<ReplayState, W> for Playback {
+    fn event(
+        &mut self,
+        child: &mut W,
+        ctx: &mut EventCtx,
+        event: &Event,
+        data: &mut ReplayState,
+        env: &Env,
+    ) {
+        match event {
+            Event::Timer(tok) if self.timer == Some(*tok) => {
+                self.timer = Some(ctx.request_timer(animation_time(STEP_TIME, env)));
+                if data.playing {
+                    data.step();
+                }
+            }
+            _ => child.event(ctx, event, data, env),
+        }
+    }
+
+    fn lifecycle(
+        &mut self,
+        child: &mut W,
+        ctx: &mut LifeCycleCtx,
+        event: &LifeCycle,
+        data: &ReplayState,
+        env: &Env,
+    ) {
+        if let LifeCycle::WidgetAdded = event {
+            self.timer = Some(ctx.request_timer(animation_time(STEP_TIME, env)));
+        }
+        child.lifecycle(ctx, event, data, env)
+    }
+}
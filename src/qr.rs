@@ -0,0 +1,67 @@
+//! A QR code of the address to join a hosted game at, for scanning with a phone.
+
+use druid::{
+    BoxConstraints, Color, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx, PaintCtx,
+    Point, Rect, RenderContext, Size, UpdateCtx, Widget,
+};
+use qrcode::QrCode;
+
+/// The side of each module, the smallest square of the code.
+const MODULE_SIZE: f64 = 4.0;
+/// The modules of white left around the code, which scanners need to find it.
+const QUIET_ZONE: usize = 4;
+
+/// Draws a QR code of some text, black on white whatever the theme, so it scans.
+pub struct QrImage {
+    /// The modules on a side.
+    width: usize,
+    /// Whether each module is dark, a row at a time.
+    dark: Vec<bool>,
+}
+
+impl QrImage {
+    /// A code for `text`, or `None` if it is too long to fit in one.
+    pub fn new(text: &str) -> Option<Self> {
+        let code = QrCode::new(text.as_bytes()).ok()?;
+        let dark = code
+            .to_colors()
+            .into_iter()
+            .map(|color| color == qrcode::Color::Dark)
+            .collect();
+        Some(Self {
+            width: code.width(),
+            dark,
+        })
+    }
+
+    /// The side of the code, quiet zone included.
+    fn side(&self) -> f64 {
+        (self.width + 2 * QUIET_ZONE) as f64 * MODULE_SIZE
+    }
+}
+
+impl<T> Widget<T> for QrImage {
+    fn event(&mut self, _ctx: &mut EventCtx, _event: &Event, _data: &mut T, _env: &Env) {}
+
+    fn lifecycle(&mut self, _ctx: &mut LifeCycleCtx, _event: &LifeCycle, _data: &T, _env: &Env) {}
+
+    fn update(&mut self, _ctx: &mut UpdateCtx, _old_data: &T, _data: &T, _env: &Env) {}
+
+    fn layout(&mut self, _ctx: &mut LayoutCtx, bc: &BoxConstraints, _data: &T, _env: &Env) -> Size {
+        bc.constrain(Size::new(self.side(), self.side()))
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, _data: &T, _env: &Env) {
+        let side = self.side();
+        ctx.fill(
+            Rect::from_origin_size(Point::ORIGIN, (side, side)),
+            &Color::WHITE,
+        );
+        for (i, _) in self.dark.iter().enumerate().filter(|(_, &dark)| dark) {
+            let x = (i % self.width + QUIET_ZONE) as f64 * MODULE_SIZE;
+            let y = (i / self.width + QUIET_ZONE) as f64 * MODULE_SIZE;
+            let module = Rect::from_origin_size((x, y), (MODULE_SIZE, MODULE_SIZE));
+            ctx.fill(module, &Color::BLACK);
+        }
+    }
+}
@@ -0,0 +1,224 @@
+//! Watching a replay, an event at a time, like a game being played.
+
+use druid::widget::{Align, Button, Flex, Label, Slider};
+use druid::{Data, Env, Lens, LensExt, Widget, WidgetExt};
+use std::sync::Arc;
+
+use crate::core::{event::GameEvent, game::Game, replay::Replay, scorecard::Slot, skins};
+use crate::playback::Playback;
+use crate::scorecard::Scorecard;
+use crate::{
+    build_dice, build_player_cards, slot_name, team_totals, InGameState, Player, YatzyState,
+    CLOSE_REPLAY, ERROR_COLOR, LABEL_SPACING, TEXT_BOX_WIDTH, VERTICAL_WIDGET_SPACING,
+};
+
+/// A replay being watched, an event at a time.
+#[derive(Debug, Clone, Data, Lens)]
+pub(crate) struct ReplayState {
+    pub(crate) replay: Arc<Replay>,
+    /// The game as it was after the first `position` events, shown like a game being played.
+    pub(crate) view: InGameState,
+    pub(crate) position: usize,
+    /// What happened in the last event shown.
+    pub(crate) last: String,
+    /// Whether the events are being played through on their own.
+    pub(crate) playing: bool,
+    /// The screen to go back to.
+    pub(crate) back: Arc<YatzyState>,
+    /// Which totals the replay gives that its game doesn't add up to, if any.
+    pub(crate) tampered: String,
+}
+
+impl ReplayState {
+    /// Watch a replay from the start, going back to `back` afterwards.
+    pub(crate) fn new(replay: Replay, back: YatzyState) -> Self {
+        let players = replay
+            .players
+            .iter()
+            .map(|name| Player {
+                name: name.clone(),
+                bot: None,
+                local: false,
+                skin: skins::CLASSIC.name.to_string(),
+            })
+            .collect();
+        let view = InGameState::from_game(players, replay.game_at(0), None);
+        let wrong: Vec<_> = replay
+            .wrong_totals()
+            .iter()
+            .map(|wrong| {
+                format!(
+                    "{} is given {} points but scored {}",
+                    replay.players[wrong.player], wrong.claimed, wrong.actual
+                )
+            })
+            .collect();
+        let tampered = if wrong.is_empty() {
+            String::new()
+        } else {
+            format!(
+                "This replay has been changed since the game was played: {}.",
+                wrong.join(", ")
+            )
+        };
+        Self {
+            replay: Arc::new(replay),
+            view,
+            position: 0,
+            last: String::new(),
+            playing: false,
+            back: Arc::new(back),
+            tampered,
+        }
+    }
+
+    /// How many events there are to watch.
+    pub(crate) fn len(&self) -> usize {
+        self.replay.events.len()
+    }
+
+    /// Jump to just after the first `position` events.
+    pub(crate) fn seek(&mut self, position: usize) {
+        let position = position.min(self.len());
+        // rebuild up to the event before, so the last one can be described
+        self.position = position.saturating_sub(1);
+        self.view.game = self.replay.game_at(self.position);
+        self.last = String::new();
+        if position > 0 {
+            self.step();
+        } else {
+            self.view.sync_dice();
+        }
+    }
+
+    /// Show the next event happening, stopping at the end.
+    pub(crate) fn step(&mut self) {
+        if let Some(event) = self.replay.events.get(self.position) {
+            let player = self.view.game.player();
+            self.view.game.apply(event.clone());
+            self.last = format!(
+                "{} {}",
+                self.view.players[player].name,
+                describe_event(event, &self.view.game, player)
+            );
+            self.position += 1;
+        }
+        if self.position == self.len() {
+            self.playing = false;
+        }
+        self.view.sync_dice();
+    }
+
+    /// Start or stop playing the events through, from the start if they all have been.
+    pub(crate) fn toggle_play(&mut self) {
+        if !self.playing && self.position == self.len() {
+            self.seek(0);
+        }
+        self.playing = !self.playing;
+    }
+}
+
+/// What a player did in an event, for example "scored 25 in Full House", given the game just
+/// after it.
+fn describe_event(event: &GameEvent, game: &Game, player: usize) -> String {
+    let slot_name = |slot: &Slot| slot_name(*slot, game.rules());
+    match event {
+        GameEvent::RollStarted => "rolls".to_string(),
+        GameEvent::Rolled(values) => {
+            let values: Vec<_> = values.iter().map(|value| value.0.to_string()).collect();
+            format!("rolled {}", values.join(" "))
+        }
+        GameEvent::HoldToggled(idx) if game.dice()[*idx].held => format!("held die {}", idx + 1),
+        GameEvent::HoldToggled(idx) => format!("released die {}", idx + 1),
+        GameEvent::Scored(slot) => match game.sheets()[player].get(*slot) {
+            Some(points) => format!("scored {} in {}", points, slot_name(slot)),
+            None => format!("scored {}", slot_name(slot)),
+        },
+        GameEvent::Scratched(slot) => format!("scratched {}", slot_name(slot)),
+    }
+}
+
+pub(crate) fn build_replay() -> impl Widget<ReplayState> {
+    let last = Label::new(|data: &ReplayState, _env: &Env| {
+        if data.position == 0 {
+            format!("The start of the game, {} events to watch", data.len())
+        } else {
+            format!("Event {} of {}: {}", data.position, data.len(), data.last)
+        }
+    });
+    let player_name = Label::new(|data: &ReplayState, _env: &Env| {
+        if data.view.game.is_complete() {
+            "The game is over".to_string()
+        } else {
+            format!("{}'s turn", data.view.current_player().name)
+        }
+    });
+    let totals =
+        Label::new(|data: &InGameState, _env: &Env| team_totals(data)).lens(ReplayState::view);
+
+    let controls = Flex::row()
+        .with_child(Button::new("|<").on_click(|_ctx, data: &mut ReplayState, _env| data.seek(0)))
+        .with_spacer(LABEL_SPACING)
+        .with_child(
+            Button::new("<").on_click(|_ctx, data: &mut ReplayState, _env| {
+                data.seek(data.position.saturating_sub(1))
+            }),
+        )
+        .with_spacer(LABEL_SPACING)
+        .with_child(
+            Button::new(|data: &ReplayState, _env: &Env| {
+                if data.playing { "Pause" } else { "Play" }.to_string()
+            })
+            .on_click(|_ctx, data: &mut ReplayState, _env| data.toggle_play()),
+        )
+        .with_spacer(LABEL_SPACING)
+        .with_child(Button::new(">").on_click(|_ctx, data: &mut ReplayState, _env| data.step()))
+        .with_spacer(LABEL_SPACING)
+        .with_child(
+            Button::new(">|").on_click(|_ctx, data: &mut ReplayState, _env| data.seek(data.len())),
+        );
+    // the whole game from start to end, dragged to jump to any event
+    let timeline = Slider::new()
+        .lens(druid::lens::Id.map(
+            |data: &ReplayState| {
+                if data.len() == 0 {
+                    0.0
+                } else {
+                    data.position as f64 / data.len() as f64
+                }
+            },
+            |data: &mut ReplayState, fraction: f64| {
+                let position = (fraction * data.len() as f64).round() as usize;
+                // the lens puts the value back on every event, not only when it changes
+                if position != data.position {
+                    data.seek(position);
+                }
+            },
+        ))
+        .fix_width(2.0 * TEXT_BOX_WIDTH);
+    let back_btn = Button::new("Back").on_click(|ctx, _data: &mut ReplayState, _env| {
+        ctx.submit_command(CLOSE_REPLAY, None);
+    });
+    let tampered = Label::new(|data: &ReplayState, _env: &Env| data.tampered.clone())
+        .with_text_color(ERROR_COLOR);
+
+    let layout = Flex::column()
+        .with_child(tampered)
+        .with_child(last)
+        .with_child(build_player_cards().lens(ReplayState::view))
+        .with_child(player_name)
+        .with_child(totals)
+        .with_spacer(VERTICAL_WIDGET_SPACING)
+        .with_child(build_dice(false).lens(ReplayState::view))
+        .with_spacer(VERTICAL_WIDGET_SPACING)
+        .with_child(Scorecard::read_only().lens(ReplayState::view))
+        .with_spacer(VERTICAL_WIDGET_SPACING)
+        .with_child(controls)
+        .with_spacer(LABEL_SPACING)
+        .with_child(timeline)
+        .with_spacer(VERTICAL_WIDGET_SPACING)
+        .with_child(back_btn);
+
+    // center the widgets in the available space, playing the events through when asked
+    Align::centered(layout).controller(Playback::new())
+}
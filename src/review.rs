@@ -0,0 +1,92 @@
+//! The review of a finished game, showing where each player lost points.
+
+use druid::widget::{Align, Button, Flex, Label, List, Scroll};
+use druid::{Data, Env, Lens, Widget, WidgetExt};
+use std::sync::Arc;
+
+use crate::core::analysis::{self, Rating};
+use crate::{FinishedState, CLOSE_REVIEW, VERTICAL_WIDGET_SPACING};
+
+/// The review of a finished game, with the results to go back to.
+#[derive(Debug, Clone, Data, Lens)]
+pub(crate) struct ReviewState {
+    pub(crate) finished: FinishedState,
+    /// Where the values come from, and how far to trust them.
+    pub(crate) basis: String,
+    /// How well each player played, one line each.
+    pub(crate) summaries: Arc<Vec<String>>,
+    /// Every decision that wasn't the best, in the order they were made.
+    pub(crate) lines: Arc<Vec<String>>,
+}
+
+impl ReviewState {
+    /// Analyse the finished game. This can take a few seconds without a strategy table.
+    pub(crate) fn new(finished: &FinishedState) -> Self {
+        let analysis = analysis::analyse(&finished.game);
+        let basis = if analysis.whole_game {
+            "Points are expected final scores with optimal play."
+        } else {
+            "Points are only looked ahead to the end of each turn. \
+             Run `yatzy generate-strategy` for a full review of Yatzy games."
+        };
+        let summaries = finished
+            .players
+            .iter()
+            .enumerate()
+            .map(|(idx, player)| {
+                format!(
+                    "{}: {:.1} points lost, {} inaccuracies, {} mistakes, {} blunders",
+                    player.name,
+                    analysis.loss(idx),
+                    analysis.count(idx, Rating::Inaccuracy),
+                    analysis.count(idx, Rating::Mistake),
+                    analysis.count(idx, Rating::Blunder),
+                )
+            })
+            .collect();
+        let lines = analysis
+            .decisions
+            .iter()
+            .filter(|decision| decision.rating() != Rating::Best)
+            .map(|decision| {
+                format!(
+                    "Turn {}, {}: {}",
+                    decision.turn,
+                    finished.players[decision.player].name,
+                    decision.describe(&finished.rules)
+                )
+            })
+            .collect();
+        Self {
+            finished: finished.clone(),
+            basis: basis.to_string(),
+            summaries: Arc::new(summaries),
+            lines: Arc::new(lines),
+        }
+    }
+}
+
+pub(crate) fn build_review() -> impl Widget<ReviewState> {
+    let basis = Label::new(|data: &ReviewState, _env: &Env| data.basis.clone());
+    let summaries = List::new(|| Label::new(|line: &String, _env: &Env| line.clone()))
+        .lens(ReviewState::summaries);
+    // the decisions that cost points, which can be most of the game
+    let lines = Scroll::new(
+        List::new(|| Label::new(|line: &String, _env: &Env| line.clone())).lens(ReviewState::lines),
+    )
+    .vertical();
+    let back_btn = Button::new("Back").on_click(|ctx, _data: &mut ReviewState, _env| {
+        ctx.submit_command(CLOSE_REVIEW, None);
+    });
+
+    let layout = Flex::column()
+        .with_child(basis)
+        .with_spacer(VERTICAL_WIDGET_SPACING)
+        .with_child(summaries)
+        .with_spacer(VERTICAL_WIDGET_SPACING)
+        .with_flex_child(lines, 1.0)
+        .with_spacer(VERTICAL_WIDGET_SPACING)
+        .with_child(back_btn);
+
+    Align::centered(layout)
+}
@@ -0,0 +1,65 @@
+//! Lands the dice a moment after they start rolling, so the roll can be seen.
+
+use druid::widget::Controller;
+use druid::{Env, Event, EventCtx, TimerToken, UpdateCtx, Widget};
+use std::time::Duration;
+
+use crate::{animation_time, InGameState, STOP_ROLL};
+
+/// How long the dice roll for before landing.
+const ROLL_TIME: Duration = Duration::from_millis(1_000);
+
+/// Sends `STOP_ROLL` once the dice have rolled for a while, however they were rolled. In a network
+/// game only the host's dice land, and the others are sent where they landed. Dice rolled on the
+/// table land when the smart dice do instead.
+pub struct LandDice {
+    timer: Option<TimerToken>,
+}
+
+impl LandDice {
+    pub fn new() -> Self {
+        Self { timer: None }
+    }
+}
+
+impl<W: Widget<InGameState>> Controller<InGameState, W> for LandDice {
+    fn event(
+        &mut self,
+        child: &mut W,
+        ctx: &mut EventCtx,
+        event: &Event,
+        data: &mut InGameState,
+        env: &Env,
+    ) {
+        match event {
+            Event::Timer(tok) if self.timer == Some(*tok) => {
+                self.timer = None;
+                // a paused game rolls again from the start once it carries on, and dice that
+                // went on to be rolled on the table land when those do
+                if !data.is_paused() && !data.rolling_on_table() {
+                    ctx.submit_command(STOP_ROLL, None);
+                }
+            }
+            _ => child.event(ctx, event, data, env),
+        }
+    }
+
+    fn update(
+        &mut self,
+        child: &mut W,
+        ctx: &mut UpdateCtx,
+        old_data: &InGameState,
+        data: &InGameState,
+        env: &Env,
+    ) {
+        // a timer from before a pause may never come, if the settings were opened meanwhile
+        if old_data.is_paused() && !data.is_paused() {
+            self.timer = None;
+        }
+        let on_screen = !data.is_paused() && !data.rolling_on_table();
+        if self.timer.is_none() && data.game.any_rolling() && on_screen {
+            self.timer = Some(ctx.request_timer(animation_time(ROLL_TIME, env)));
+        }
+        child.update(ctx, old_data, data, env)
+    }
+}
@@ -0,0 +1,110 @@
+//! The list of games saved in slots, to carry one on.
+
+use druid::widget::{Align, Button, Flex, Label, List, Scroll};
+use druid::{commands, Command, Data, Env, FileDialogOptions, FileSpec, Lens, Widget, WidgetExt};
+use std::sync::Arc;
+
+use crate::core::save::{self, SlotInfo};
+use crate::{
+    YatzyState, CLOSE_SAVES, DELETE_SLOT, ERROR_COLOR, LABEL_SPACING, LOAD_SLOT,
+    VERTICAL_WIDGET_SPACING,
+};
+
+/// The games saved in slots, to carry one on.
+#[derive(Debug, Clone, Data, Lens)]
+pub(crate) struct LoadState {
+    /// The screen to go back to.
+    pub(crate) back: Arc<YatzyState>,
+    /// The saved games, most recent first.
+    pub(crate) slots: Arc<Vec<SlotInfo>>,
+    /// Why the games couldn't be listed, or one couldn't be loaded or deleted.
+    pub(crate) status: String,
+}
+
+impl LoadState {
+    pub(crate) fn new(back: Arc<YatzyState>) -> Self {
+        let mut state = Self {
+            back,
+            slots: Arc::new(Vec::new()),
+            status: String::new(),
+        };
+        state.refresh();
+        state
+    }
+
+    /// List the games saved now.
+    pub(crate) fn refresh(&mut self) {
+        let slots = save::slots_dir()
+            .ok_or_else(|| anyhow::anyhow!("there's no config directory for saves"))
+            .and_then(|dir| save::list_slots(&dir));
+        match slots {
+            Ok(slots) => {
+                self.status = if slots.is_empty() {
+                    "No games have been saved yet".into()
+                } else {
+                    String::new()
+                };
+                self.slots = Arc::new(slots);
+            }
+            Err(e) => self.status = format!("Couldn't list the saved games: {:#}", e),
+        }
+    }
+}
+
+pub(crate) fn build_load_screen() -> impl Widget<LoadState> {
+    // a line for each saved game, with buttons to load or delete it
+    let slots = List::new(|| {
+        let description = Label::new(|slot: &SlotInfo, _env: &Env| {
+            format!(
+                "{}: {} playing {}, {} of {} categories filled, saved {}",
+                slot.name,
+                slot.players.join(", "),
+                slot.variant,
+                slot.filled,
+                slot.categories,
+                slot.date()
+            )
+        });
+        Flex::row()
+            .with_child(description)
+            .with_spacer(LABEL_SPACING)
+            .with_child(
+                Button::new("Load").on_click(|ctx, slot: &mut SlotInfo, _env| {
+                    ctx.submit_command(Command::new(LOAD_SLOT, slot.name.clone()), None);
+                }),
+            )
+            .with_spacer(LABEL_SPACING)
+            .with_child(
+                Button::new("Delete").on_click(|ctx, slot: &mut SlotInfo, _env| {
+                    ctx.submit_command(Command::new(DELETE_SLOT, slot.name.clone()), None);
+                }),
+            )
+    })
+    .lens(LoadState::slots);
+    let status =
+        Label::new(|data: &LoadState, _env: &Env| data.status.clone()).with_text_color(ERROR_COLOR);
+    // games saved to a file of the player's choosing aren't listed
+    let from_file_btn =
+        Button::new("Load from a file").on_click(|ctx, _data: &mut LoadState, _env| {
+            let saves = FileSpec::new("Saved game", &[save::EXTENSION]);
+            let options = FileDialogOptions::new().allowed_types(vec![saves]);
+            ctx.submit_command(Command::new(commands::SHOW_OPEN_PANEL, options), None);
+        });
+    let back_btn = Button::new("Back").on_click(|ctx, _data: &mut LoadState, _env| {
+        ctx.submit_command(CLOSE_SAVES, None);
+    });
+
+    let layout = Flex::column()
+        .with_flex_child(Scroll::new(slots).vertical(), 1.0)
+        .with_spacer(VERTICAL_WIDGET_SPACING)
+        .with_child(status)
+        .with_spacer(VERTICAL_WIDGET_SPACING)
+        .with_child(
+            Flex::row()
+                .with_child(from_file_btn)
+                .with_spacer(LABEL_SPACING)
+                .with_child(back_btn),
+        );
+
+    Align::centered(layout)
+}
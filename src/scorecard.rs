@@ -0,0 +1,401 @@
+//! A scorecard widget, showing a player's score sheet and placing the dice in it.
+
+use druid::piet::{FontBuilder, Text, TextLayoutBuilder};
+use druid::{
+    theme, Affine, BoxConstraints, Color, Command, Env, Event, EventCtx, LayoutCtx, LifeCycle,
+    LifeCycleCtx, MouseButton, PaintCtx, Point, Rect, RenderContext, Size, UpdateCtx, Widget,
+};
+
+use crate::core::{
+    moves::Move,
+    rules::YatzyBonus,
+    scorecard::{Category, ScoreSheet, Slot},
+};
+use crate::theme::{ACCENT_COLOR, HOT_COLOR};
+use crate::{InGameState, PLAY_MOVE};
+
+const ROW_HEIGHT: f64 = 22.0;
+/// The width of the column naming the categories.
+const NAME_WIDTH: f64 = 160.0;
+/// The width of the points in each slot.
+const POINTS_WIDTH: f64 = 60.0;
+/// The width of the hint in each slot, when hints are on.
+const HINT_WIDTH: f64 = 48.0;
+/// The width of the teammates' points in each slot, in team games.
+const TEAMMATES_WIDTH: f64 = 64.0;
+const PADDING: f64 = 6.0;
+/// The most the scorecard is shrunk to fit a narrow window, beyond which it's cut off instead.
+const MIN_SCALE: f64 = 0.6;
+
+/// A row of the scorecard.
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Row {
+    /// The numbers above the columns, in variants with more than one.
+    Multipliers,
+    Category(Category),
+    /// The upper section's points so far.
+    UpperTotal,
+    /// The upper section bonus, or how many more points it needs.
+    Bonus,
+    YatzyBonus,
+    /// The total of each column, and of the whole sheet.
+    Total,
+}
+
+/// Where everything goes, which depends on the variant and what's being shown.
+struct Grid {
+    rows: Vec<Row>,
+    columns: usize,
+    cell_width: f64,
+    hints: bool,
+    teams: bool,
+    /// How much the whole grid is shrunk to fit the width it has, where 1 is full size. The
+    /// rest of the grid is measured at full size.
+    scale: f64,
+}
+
+impl Grid {
+    /// The grid for the game, shrunk if need be to fit in `max_width`.
+    fn new(data: &InGameState, max_width: f64) -> Self {
+        let rules = data.game.rules();
+        let mut rows = Vec::new();
+        if rules.columns > 1 {
+            rows.push(Row::Multipliers);
+        }
+        let last_upper = rules.categories.iter().rposition(|cat| cat.is_upper());
+        for (idx, &category) in rules.categories.iter().enumerate() {
+            rows.push(Row::Category(category));
+            // the upper section's totals go after its last category
+            if Some(idx) == last_upper {
+                rows.push(Row::UpperTotal);
+                rows.push(Row::Bonus);
+            }
+        }
+        if rules.yatzy_bonus == YatzyBonus::American {
+            rows.push(Row::YatzyBonus);
+        }
+        rows.push(Row::Total);
+
+        let hints = data.hints;
+        let teams = data.game.has_teams();
+        let mut cell_width = POINTS_WIDTH;
+        if hints {
+            cell_width += HINT_WIDTH;
+        }
+        if teams {
+            cell_width += TEAMMATES_WIDTH;
+        }
+        let width = NAME_WIDTH + rules.columns as f64 * cell_width;
+        let scale = if max_width < width {
+            (max_width / width).max(MIN_SCALE)
+        } else {
+            1.0
+        };
+        Self {
+            rows,
+            columns: rules.columns,
+            cell_width,
+            hints,
+            teams,
+            scale,
+        }
+    }
+
+    /// The size of the grid at full size.
+    fn size(&self) -> Size {
+        Size::new(
+            NAME_WIDTH + self.columns as f64 * self.cell_width,
+            self.rows.len() as f64 * ROW_HEIGHT,
+        )
+    }
+
+    /// The size of the grid as it's shown.
+    fn scaled_size(&self) -> Size {
+        self.size() * self.scale
+    }
+
+    fn cell(&self, row: usize, column: usize) -> Rect {
+        let (x, y) = (
+            NAME_WIDTH + column as f64 * self.cell_width,
+            row as f64 * ROW_HEIGHT,
+        );
+        Rect::new(x, y, x + self.cell_width, y + ROW_HEIGHT)
+    }
+
+    /// The slot at a point where the grid is shown, if there's one there.
+    fn slot_at(&self, pos: Point) -> Option<Slot> {
+        let pos = Point::new(pos.x / self.scale, pos.y / self.scale);
+        if pos.x < NAME_WIDTH || pos.y < 0.0 {
+            return None;
+        }
+        let column = ((pos.x - NAME_WIDTH) / self.cell_width) as usize;
+        let row = (pos.y / ROW_HEIGHT) as usize;
+        match self.rows.get(row) {
+            Some(Row::Category(category)) if column < self.columns => {
+                Some(Slot::in_column(column, *category))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The score sheet of the player picked in `InGameState::shown_sheet`, with a row for each
+/// category and the totals under them.
+///
+/// When it's the current player's sheet, clicking an open slot places the dice in it, and
+/// right-clicking scratches it, asking first if that looks like a slip. The slot under the mouse
+/// shows what the dice would score there, dimmed. In a narrow window the whole sheet is shrunk
+/// to fit, as far as it can still be read.
+pub struct Scorecard {
+    /// Whether the dice can be placed from here, rather than only watched.
+    playable: bool,
+    /// The open slot under the mouse.
+    hot: Option<Slot>,
+    /// The width there was for the sheet when it was last laid out.
+    max_width: f64,
+}
+
+impl Scorecard {
+    pub fn new() -> Self {
+        Self {
+            playable: true,
+            hot: None,
+            max_width: std::f64::INFINITY,
+        }
+    }
+
+    /// A scorecard that only shows the points, for watching a game.
+    pub fn read_only() -> Self {
+        Self {
+            playable: false,
+            hot: None,
+            max_width: std::f64::INFINITY,
+        }
+    }
+
+    /// The slot under the mouse if the dice could go in it.
+    fn open_slot_at(&self, pos: Point, data: &InGameState) -> Option<Slot> {
+        if !self.playable || !data.showing_current_sheet() {
+            return None;
+        }
+        Grid::new(data, self.max_width)
+            .slot_at(pos)
+            .filter(|slot| data.game.sheet().get(*slot).is_none())
+    }
+}
+
+impl Widget<InGameState> for Scorecard {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut InGameState, _env: &Env) {
+        match event {
+            Event::MouseMove(mouse) => {
+                let hot = self.open_slot_at(mouse.pos, data);
+                if hot != self.hot {
+                    self.hot = hot;
+                    ctx.request_paint();
+                }
+            }
+            Event::MouseDown(mouse) => {
+                let slot = match self.open_slot_at(mouse.pos, data) {
+                    Some(slot) => slot,
+                    None => return,
+                };
+                let mv = match mouse.button {
+                    MouseButton::Left => Move::Score(slot),
+                    MouseButton::Right => Move::Scratch(slot),
+                    _ => return,
+                };
+                // moves that look like a slip wait to be confirmed
+                if !data.doubt(mv) {
+                    ctx.submit_command(Command::new(PLAY_MOVE, mv), None);
+                }
+                ctx.set_handled();
+            }
+            _ => (),
+        }
+    }
+
+    fn lifecycle(
+        &mut self,
+        ctx: &mut LifeCycleCtx,
+        event: &LifeCycle,
+        _data: &InGameState,
+        _env: &Env,
+    ) {
+        if let LifeCycle::HotChanged(false) = event {
+            self.hot = None;
+            ctx.request_paint();
+        }
+    }
+
+    fn update(
+        &mut self,
+        ctx: &mut UpdateCtx,
+        old_data: &InGameState,
+        data: &InGameState,
+        _env: &Env,
+    ) {
+        let resized = old_data.game.rules() != data.game.rules()
+            || old_data.hints != data.hints
+            || old_data.game.has_teams() != data.game.has_teams();
+        if resized {
+            ctx.request_layout();
+        }
+        // the slot under the mouse may have been filled, or another sheet picked
+        let filled = self
+            .hot
+            .map_or(false, |slot| data.game.sheet().get(slot).is_some());
+        if filled || !data.showing_current_sheet() {
+            self.hot = None;
+        }
+        ctx.request_paint();
+    }
+
+    fn layout(
+        &mut self,
+        _ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &InGameState,
+        _env: &Env,
+    ) -> Size {
+        self.max_width = bc.max().width;
+        bc.constrain(Grid::new(data, self.max_width).scaled_size())
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, data: &InGameState, env: &Env) {
+        let grid = Grid::new(data, self.max_width);
+        // everything is drawn at full size, and shrunk along with the grid
+        ctx.transform(Affine::scale(grid.scale));
+        let game = &data.game;
+        let rules = game.rules();
+        let player = data.shown_sheet;
+        let sheet = &game.sheets()[player];
+        let scores = game.scores();
+        let font = ctx
+            .text()
+            .new_font_by_name(env.get(theme::FONT_NAME), env.get(theme::TEXT_SIZE_NORMAL))
+            .build()
+            .unwrap();
+        let text_color = env.get(theme::LABEL_COLOR);
+        let dim_color = env.get(theme::PLACEHOLDER_COLOR);
+        let hint_color = env.get(ACCENT_COLOR);
+        let line_color = env.get(theme::BORDER_DARK);
+
+        // text is drawn from its baseline
+        let baseline = (ROW_HEIGHT + env.get(theme::TEXT_SIZE_NORMAL)) / 2.0 - 2.0;
+        let write = |ctx: &mut PaintCtx, text: &str, x: f64, top: f64, color: &Color| {
+            let layout = ctx
+                .text()
+                .new_text_layout(&font, text, std::f64::INFINITY)
+                .build()
+                .unwrap();
+            ctx.draw_text(&layout, Point::new(x, top + baseline), color);
+        };
+
+        for (idx, row) in grid.rows.iter().enumerate() {
+            let top = idx as f64 * ROW_HEIGHT;
+            let name = match row {
+                Row::Multipliers => String::new(),
+                Row::Category(category) => category.name().to_string(),
+                Row::UpperTotal => "Upper section".to_string(),
+                Row::Bonus => "Bonus".to_string(),
+                Row::YatzyBonus => "Yatzy bonus".to_string(),
+                Row::Total if grid.columns > 1 => format!("Total: {}", sheet.total()),
+                Row::Total => "Total".to_string(),
+            };
+            write(ctx, &name, PADDING, top, &text_color);
+
+            for column in 0..grid.columns {
+                let cell = grid.cell(idx, column);
+                let card = match sheet.column(column) {
+                    Some(card) => card,
+                    None => continue,
+                };
+                let (mut points, mut color) = match row {
+                    Row::Multipliers => {
+                        (format!("x{}", ScoreSheet::multiplier(column)), &dim_color)
+                    }
+                    Row::Category(category) => match card.get(*category) {
+                        Some(points) => (points.to_string(), &text_color),
+                        // in Forced Yatzy, mark the only category that can be filled
+                        None if rules.forced_order
+                            && card.next_category(rules) == Some(*category) =>
+                        {
+                            (">".to_string(), &dim_color)
+                        }
+                        None => (String::new(), &text_color),
+                    },
+                    Row::UpperTotal => (card.upper_total().to_string(), &text_color),
+                    // until it's earned, how far off the bonus is
+                    Row::Bonus => match card.points_to_bonus(rules) {
+                        0 => (card.bonus().to_string(), &text_color),
+                        short => (format!("{} more", short), &dim_color),
+                    },
+                    Row::YatzyBonus => (card.yatzy_bonus().to_string(), &text_color),
+                    Row::Total => (card.total().to_string(), &text_color),
+                };
+
+                let slot = match row {
+                    Row::Category(category) => Some(Slot::in_column(column, *category)),
+                    _ => None,
+                };
+                if slot.is_some() && slot == self.hot {
+                    ctx.fill(cell, &env.get(HOT_COLOR));
+                    // what the dice would score here, to compare before placing them
+                    if let (Some(slot), Some(dice)) = (slot, &scores) {
+                        if game.can_score(slot) {
+                            points = sheet.points(slot, dice, rules).to_string();
+                            color = &dim_color;
+                        }
+                    }
+                }
+                let mut x = cell.x0 + PADDING;
+                write(ctx, &points, x, top, color);
+                x += POINTS_WIDTH;
+
+                let slot = match slot {
+                    Some(slot) => slot,
+                    None => continue,
+                };
+                // how good this slot would be for the final hand, on the sheet it would go in
+                if grid.hints {
+                    let values = data.placement_values.as_ref();
+                    let value = values.and_then(|values| values.iter().find(|(s, _)| *s == slot));
+                    match value {
+                        Some((_, value)) if data.showing_current_sheet() => {
+                            write(ctx, &format!("{:+.1}", value), x, top, &hint_color)
+                        }
+                        _ => (),
+                    }
+                    x += HINT_WIDTH;
+                }
+                // in a team game, what this player's teammates have in this slot
+                if grid.teams {
+                    let scores: Vec<_> = game
+                        .team_members(game.team(player))
+                        .filter(|&teammate| teammate != player)
+                        .map(|teammate| {
+                            game.sheets()[teammate]
+                                .get(slot)
+                                .map_or("-".into(), |points| points.to_string())
+                        })
+                        .collect();
+                    write(ctx, &format!("({})", scores.join(", ")), x, top, &dim_color);
+                }
+            }
+
+            // a line under each row, and a heavier one above the totals
+            let width = grid.size().width;
+            let bottom = top + ROW_HEIGHT;
+            ctx.fill(Rect::new(0.0, bottom - 1.0, width, bottom), &line_color);
+            if let Some(Row::UpperTotal) | Some(Row::Total) = grid.rows.get(idx + 1) {
+                ctx.fill(Rect::new(0.0, bottom - 2.0, width, bottom), &line_color);
+            }
+        }
+        // lines between the columns
+        let height = grid.size().height;
+        for column in 0..grid.columns {
+            let x = grid.cell(0, column).x0;
+            ctx.fill(Rect::new(x, 0.0, x + 1.0, height), &line_color);
+        }
+    }
+}
@@ -0,0 +1,211 @@
+//! The settings screen, where the saved settings are changed.
+
+use druid::widget::{
+    Align, Button, Checkbox, Flex, Label, List, RadioGroup, Scroll, Slider, TextBox, ViewSwitcher,
+};
+use druid::{Data, Env, Lens, LensExt, Widget, WidgetExt};
+use std::sync::Arc;
+
+use crate::core::settings::{ColorPalette, Layout, Settings, Theme};
+use crate::{
+    all_presets, load_settings, YatzyState, CLOSE_SETTINGS, ERROR_COLOR, LABEL_SPACING,
+    MAX_PLAYERS, SAVE_SETTINGS, TEXT_BOX_WIDTH, VERTICAL_WIDGET_SPACING,
+};
+
+/// The settings being changed, saved only when the player says so.
+#[derive(Debug, Clone, Data, Lens)]
+pub(crate) struct SettingsState {
+    /// The screen to go back to.
+    pub(crate) back: Arc<YatzyState>,
+    /// The settings as they were opened, including those that aren't changed here.
+    pub(crate) saved: Settings,
+    pub(crate) theme: Theme,
+    pub(crate) colors: ColorPalette,
+    pub(crate) layout: Layout,
+    pub(crate) sounds: bool,
+    pub(crate) animation_speed: f64,
+    pub(crate) default_variant: String,
+    /// The names filled in on the starting screen, one for each player.
+    pub(crate) player_names: Arc<Vec<String>>,
+    /// The names of the variants to choose the default from.
+    pub(crate) variants: Arc<Vec<String>>,
+    /// Why the settings couldn't be saved.
+    pub(crate) status: String,
+}
+
+impl SettingsState {
+    /// Start changing the settings, as they're saved now.
+    pub(crate) fn new(back: Arc<YatzyState>) -> Self {
+        let saved = load_settings();
+        let mut player_names = saved.player_names.clone();
+        player_names.truncate(MAX_PLAYERS);
+        Self {
+            back,
+            theme: saved.theme,
+            colors: saved.colors,
+            layout: saved.layout,
+            sounds: saved.sounds,
+            animation_speed: saved.speed(),
+            default_variant: saved.default_variant.clone(),
+            player_names: Arc::new(player_names),
+            variants: Arc::new(
+                all_presets()
+                    .iter()
+                    .map(|rules| rules.name.clone())
+                    .collect(),
+            ),
+            status: String::new(),
+            saved,
+        }
+    }
+
+    /// The settings with the changes made.
+    pub(crate) fn settings(&self) -> Settings {
+        Settings {
+            theme: self.theme,
+            colors: self.colors,
+            layout: self.layout,
+            sounds: self.sounds,
+            animation_speed: self.animation_speed,
+            default_variant: self.default_variant.clone(),
+            player_names: self
+                .player_names
+                .iter()
+                .map(|name| name.trim().to_string())
+                .filter(|name| !name.is_empty())
+                .collect(),
+            ..self.saved.clone()
+        }
+    }
+
+    pub(crate) fn add_player(&mut self) {
+        if self.player_names.len() < MAX_PLAYERS {
+            Arc::make_mut(&mut self.player_names).push(String::new());
+        }
+    }
+
+    pub(crate) fn remove_player(&mut self) {
+        Arc::make_mut(&mut self.player_names).pop();
+    }
+}
+
+pub(crate) fn build_settings() -> impl Widget<SettingsState> {
+    let theme = Flex::row()
+        .with_child(Label::new("Theme:"))
+        .with_spacer(LABEL_SPACING)
+        .with_child(
+            RadioGroup::new(vec![("Light", Theme::Light), ("Dark", Theme::Dark)])
+                .lens(SettingsState::theme),
+        );
+    // held dice have a thicker border too, so they don't only stand out by their color
+    let colors = Flex::row()
+        .with_child(Label::new("Colors:"))
+        .with_spacer(LABEL_SPACING)
+        .with_child(
+            RadioGroup::new(vec![
+                ("Standard", ColorPalette::Standard),
+                ("Deuteranopia", ColorPalette::Deuteranopia),
+                ("Protanopia", ColorPalette::Protanopia),
+                ("Tritanopia", ColorPalette::Tritanopia),
+            ])
+            .lens(SettingsState::colors),
+        );
+    let layout = Flex::row()
+        .with_child(Label::new("Layout:"))
+        .with_spacer(LABEL_SPACING)
+        .with_child(
+            RadioGroup::new(vec![
+                ("Automatic", Layout::Automatic),
+                ("Wide", Layout::Wide),
+                ("Compact", Layout::Compact),
+            ])
+            .lens(SettingsState::layout),
+        );
+    let sounds = Checkbox::new("Play sounds").lens(SettingsState::sounds);
+
+    // from a quarter of the normal speed to four times it, with normal speed in the middle
+    let speed = Flex::row()
+        .with_child(Label::new(|data: &SettingsState, _env: &Env| {
+            format!("Animation speed: {:.2}x", data.animation_speed)
+        }))
+        .with_spacer(LABEL_SPACING)
+        .with_child(Slider::new().lens(druid::lens::Id.map(
+            |data: &SettingsState| (data.animation_speed * 4.0).log(16.0),
+            |data: &mut SettingsState, fraction: f64| {
+                let speed = 16f64.powf(fraction) / 4.0;
+                // the lens puts the value back on every event, not only when it changes
+                if (speed - data.animation_speed).abs() > 1e-9 {
+                    data.animation_speed = speed;
+                }
+            },
+        )));
+
+    // the variant chosen when a game is set up
+    let variant = ViewSwitcher::new(
+        |data: &SettingsState, _env| data.variants.clone(),
+        |variants, _data, _env| {
+            let choices = variants.iter().map(|name| (name.clone(), name.clone()));
+            Box::new(RadioGroup::new(choices).lens(SettingsState::default_variant))
+        },
+    );
+
+    // the names filled in when a game is set up
+    let names = List::new(|| {
+        TextBox::new()
+            .with_placeholder("e.g. Joe Bloggs")
+            .fix_width(TEXT_BOX_WIDTH)
+    })
+    .lens(SettingsState::player_names);
+    let add_player_btn =
+        Button::new("Add player").on_click(|_ctx, data: &mut SettingsState, _env| {
+            data.add_player();
+        });
+    let remove_player_btn =
+        Button::new("Remove player").on_click(|_ctx, data: &mut SettingsState, _env| {
+            data.remove_player();
+        });
+
+    let status = Label::new(|data: &SettingsState, _env: &Env| data.status.clone())
+        .with_text_color(ERROR_COLOR);
+    let save_btn = Button::new("Save").on_click(|ctx, _data: &mut SettingsState, _env| {
+        ctx.submit_command(SAVE_SETTINGS, None);
+    });
+    let cancel_btn = Button::new("Cancel").on_click(|ctx, _data: &mut SettingsState, _env| {
+        ctx.submit_command(CLOSE_SETTINGS, None);
+    });
+
+    let layout = Flex::column()
+        .with_child(theme)
+        .with_spacer(LABEL_SPACING)
+        .with_child(colors)
+        .with_spacer(LABEL_SPACING)
+        .with_child(layout)
+        .with_spacer(LABEL_SPACING)
+        .with_child(sounds)
+        .with_spacer(LABEL_SPACING)
+        .with_child(speed)
+        .with_spacer(VERTICAL_WIDGET_SPACING)
+        .with_child(Label::new("Variant:"))
+        .with_child(variant)
+        .with_spacer(VERTICAL_WIDGET_SPACING)
+        .with_child(Label::new("Players:"))
+        .with_child(names)
+        .with_spacer(LABEL_SPACING)
+        .with_child(
+            Flex::row()
+                .with_child(add_player_btn)
+                .with_spacer(LABEL_SPACING)
+                .with_child(remove_player_btn),
+        )
+        .with_spacer(VERTICAL_WIDGET_SPACING)
+        .with_child(status)
+        .with_spacer(VERTICAL_WIDGET_SPACING)
+        .with_child(
+            Flex::row()
+                .with_child(save_btn)
+                .with_spacer(LABEL_SPACING)
+                .with_child(cancel_btn),
+        );
+
+    Align::centered(Scroll::new(layout).vertical())
+}
@@ -0,0 +1,96 @@
+//! The keyboard shortcuts for playing, which only take the keys nothing else in the game used.
+
+use druid::widget::Controller;
+use druid::{
+    Command, Env, Event, EventCtx, HotKey, KeyCode, LifeCycle, LifeCycleCtx, SysMods, TimerToken,
+    Widget,
+};
+use std::time::Duration;
+
+use crate::core::moves::Move;
+use crate::{InGameState, PLAY_MOVE, REDO, UNDO};
+
+/// Plays moves, and undoes and redoes them, from the keyboard.
+///
+/// Keys only reach this while the game or something in it has the focus, so it takes the focus
+/// when the game is shown and whenever the game is clicked. A text box that is clicked takes the
+/// focus from it, and keeps the keys typed in it, undo and redo included.
+pub struct Shortcuts {
+    /// A timer to take the focus once the game is shown, which can only be done from an event.
+    focus_timer: Option<TimerToken>,
+}
+
+impl Shortcuts {
+    pub fn new() -> Self {
+        Self { focus_timer: None }
+    }
+}
+
+impl<W: Widget<InGameState>> Controller<InGameState, W> for Shortcuts {
+    fn event(
+        &mut self,
+        child: &mut W,
+        ctx: &mut EventCtx,
+        event: &Event,
+        data: &mut InGameState,
+        env: &Env,
+    ) {
+        match event {
+            Event::Timer(tok) if self.focus_timer == Some(*tok) => {
+                self.focus_timer = None;
+                ctx.request_focus();
+            }
+            Event::MouseDown(_) => {
+                ctx.request_focus();
+                child.event(ctx, event, data, env);
+            }
+            Event::KeyDown(key) => {
+                child.event(ctx, event, data, env);
+                if ctx.is_handled() || !ctx.is_focused() {
+                    return;
+                }
+                if HotKey::new(SysMods::Cmd, "z").matches(key) {
+                    ctx.submit_command(UNDO, None);
+                    ctx.set_handled();
+                    return;
+                } else if HotKey::new(SysMods::Cmd, "y").matches(key) {
+                    ctx.submit_command(REDO, None);
+                    ctx.set_handled();
+                    return;
+                }
+                // the keys are for typing while the chat is open, and for the menu while paused
+                if let (false, false, Some(mv)) = (data.paused, data.chat_open, data.shortcut(key))
+                {
+                    let mv = match mv {
+                        Move::Roll if key.key_code != KeyCode::Space => {
+                            // Enter goes ahead with a move waiting to be confirmed
+                            data.confirm_move().or(Some(mv))
+                        }
+                        Move::Score(_) | Move::Scratch(_) if data.doubt(mv) => None,
+                        _ => Some(mv),
+                    };
+                    if let Some(mv) = mv {
+                        ctx.submit_command(Command::new(PLAY_MOVE, mv), None);
+                    }
+                    ctx.set_handled();
+                }
+            }
+            _ => child.event(ctx, event, data, env),
+        }
+    }
+
+    fn lifecycle(
+        &mut self,
+        child: &mut W,
+        ctx: &mut LifeCycleCtx,
+        event: &LifeCycle,
+        data: &InGameState,
+        env: &Env,
+    ) {
+        if let LifeCycle::WidgetAdded = event {
+            ctx.register_for_focus();
+            self.focus_timer = Some(ctx.request_timer(Duration::from_millis(0)));
+        }
+        child.lifecycle(ctx, event, data, env)
+    }
+}
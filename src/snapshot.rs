@@ -0,0 +1,133 @@
+//! Pictures of the results of a game, to share in chat apps.
+//!
+//! Widgets only paint into a window, so the score sheets are drawn straight onto an offscreen
+//! bitmap, laid out like the CSV export, and encoded as a PNG. The sizes and colors are fixed
+//! rather than taken from the window or the theme, so every picture of a game looks the same,
+//! however the window was when it was taken.
+
+use std::{fs::File, io::BufWriter, path::Path};
+
+use anyhow::{anyhow, Context, Result};
+use druid::piet::{self, Device, FontBuilder, ImageFormat, Text, TextLayoutBuilder};
+use druid::{Color, Point, Rect, RenderContext};
+
+use crate::core::{export, game::Game};
+
+/// The extension of the pictures.
+pub const EXTENSION: &str = "png";
+
+/// The font the results are written in. There's no `Env` offscreen to take the theme's from.
+const FONT_NAME: &str = "sans-serif";
+const FONT_SIZE: f64 = 14.0;
+const ROW_HEIGHT: f64 = 22.0;
+/// The width of the column naming the categories.
+const HEADING_WIDTH: f64 = 160.0;
+/// The width of each player's column.
+const COLUMN_WIDTH: f64 = 90.0;
+const PADDING: f64 = 12.0;
+/// How many pixels a picture has for each point it's laid out in, so it stays sharp when chat
+/// apps show it on high resolution screens.
+const SCALE: f64 = 2.0;
+/// The background of every other row, to make them easier to follow across.
+const STRIPE_COLOR: Color = Color::rgb8(0xee, 0xee, 0xee);
+
+/// Draw the score sheets of a game between the named players, and save the picture at `path`.
+pub fn save_png(path: &Path, game: &Game, players: &[String]) -> Result<()> {
+    let title = title(game, players);
+    let table = export::table(game, players);
+    let columns = table.first().map_or(1, Vec::len);
+    let width = 2.0 * PADDING + HEADING_WIDTH + (columns - 1) as f64 * COLUMN_WIDTH;
+    let height = 2.0 * PADDING + (table.len() + 1) as f64 * ROW_HEIGHT;
+    let (width, height) = (
+        (width * SCALE).ceil() as usize,
+        (height * SCALE).ceil() as usize,
+    );
+
+    let mut device = Device::new().map_err(drawing)?;
+    let mut target = device
+        .bitmap_target(width, height, SCALE)
+        .map_err(drawing)?;
+    {
+        let mut rc = target.render_context();
+        draw(&mut rc, &title, &table, width as f64 / SCALE)?;
+        rc.finish().map_err(drawing)?;
+    }
+    // the background is opaque, so the premultiplied pixels are the same as straight ones
+    let pixels = target
+        .into_raw_pixels(ImageFormat::RgbaPremul)
+        .map_err(drawing)?;
+
+    let file = File::create(path).with_context(|| format!("creating {}", path.display()))?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), width as u32, height as u32);
+    encoder.set_color(png::ColorType::RGBA);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder
+        .write_header()
+        .with_context(|| format!("writing {}", path.display()))?;
+    writer
+        .write_image_data(&pixels)
+        .with_context(|| format!("writing {}", path.display()))
+}
+
+/// The line over the score sheets: the variant and who won.
+fn title(game: &Game, players: &[String]) -> String {
+    let totals = game.sheets().iter().map(|sheet| sheet.total());
+    // the first player wins a tie, as on the results screen
+    let winner =
+        totals.enumerate().fold(
+            None,
+            |best: Option<(usize, u16)>, (player, total)| match best {
+                Some((_, most)) if most >= total => best,
+                _ => Some((player, total)),
+            },
+        );
+    match winner {
+        Some((player, total)) if !game.has_teams() => format!(
+            "{}: {} wins with {} points",
+            game.rules().name,
+            players[player],
+            total
+        ),
+        _ => game.rules().name.clone(),
+    }
+}
+
+/// Draw the title and then the table a row at a time, `width` wide.
+fn draw(rc: &mut impl RenderContext, title: &str, table: &[Vec<String>], width: f64) -> Result<()> {
+    rc.clear(Color::WHITE);
+    let font = rc
+        .text()
+        .new_font_by_name(FONT_NAME, FONT_SIZE)
+        .build()
+        .map_err(drawing)?;
+    let text_b = rc.solid_brush(Color::BLACK);
+    let stripe_b = rc.solid_brush(STRIPE_COLOR);
+    let rows = std::iter::once(vec![title.to_string()]).chain(table.iter().cloned());
+    for (row, cells) in rows.enumerate() {
+        let top = PADDING + row as f64 * ROW_HEIGHT;
+        // every other row of values is striped, starting with the first under the headings
+        if row > 1 && row % 2 == 0 {
+            let stripe = Rect::new(PADDING, top, width - PADDING, top + ROW_HEIGHT);
+            rc.fill(stripe, &stripe_b);
+        }
+        for (column, cell) in cells.iter().enumerate() {
+            let left = match column {
+                0 => PADDING,
+                _ => PADDING + HEADING_WIDTH + (column - 1) as f64 * COLUMN_WIDTH,
+            };
+            let layout = rc
+                .text()
+                .new_text_layout(&font, cell, std::f64::INFINITY)
+                .build()
+                .map_err(drawing)?;
+            // text is drawn from its baseline
+            let origin = Point::new(left, top + (ROW_HEIGHT + FONT_SIZE) / 2.0 - 2.0);
+            rc.draw_text(&layout, origin, &text_b);
+        }
+    }
+    Ok(())
+}
+
+fn drawing(e: piet::Error) -> anyhow::Error {
+    anyhow!("drawing the results: {}", e)
+}
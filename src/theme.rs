@@ -0,0 +1,105 @@
+//! The colors of the themes in the settings, set in the `Env` of every widget.
+//!
+//! Besides druid's own keys, like `WINDOW_BACKGROUND_COLOR` for the background, the game is drawn
+//! with the keys here, so it changes with the theme as soon as it's picked.
+
+use druid::{theme, Color, Env, Key};
+
+use crate::core::settings::{ColorPalette, Theme};
+use crate::die;
+
+/// The body of a die.
+pub const DIE_COLOR: Key<Color> = Key::new("yatzy.theme.die");
+/// The pips on a die, or the card rank on a poker die.
+pub const PIP_COLOR: Key<Color> = Key::new("yatzy.theme.pips");
+/// The pips on a die that isn't bright, such as one a hint says to roll again.
+pub const DIM_PIP_COLOR: Key<Color> = Key::new("yatzy.theme.dim-pips");
+/// What stands out from everything else, like the border of a held die and the hints.
+pub const ACCENT_COLOR: Key<Color> = Key::new("yatzy.theme.accent");
+/// The background of whatever is picked out, like the card of the player whose turn it is.
+pub const HIGHLIGHT_COLOR: Key<Color> = Key::new("yatzy.theme.highlight");
+/// The background of the slot on the scorecard under the mouse.
+pub const HOT_COLOR: Key<Color> = Key::new("yatzy.theme.hot");
+
+/// The color of each seat in a game, from the palette of Okabe and Ito so they can be told apart
+/// with any kind of color blindness. Seats after the last start again from the first.
+const PLAYER_COLORS: [Color; 7] = [
+    Color::rgb8(0x00, 0x72, 0xb2),
+    Color::rgb8(0xe6, 0x9f, 0x00),
+    Color::rgb8(0x00, 0x9e, 0x73),
+    Color::rgb8(0xcc, 0x79, 0xa7),
+    Color::rgb8(0x56, 0xb4, 0xe9),
+    Color::rgb8(0xd5, 0x5e, 0x00),
+    Color::rgb8(0xf0, 0xe4, 0x42),
+];
+
+/// The color of the player in the seat at `idx`, worn by their dice and their card.
+pub fn player_color(idx: usize) -> Color {
+    PLAYER_COLORS[idx % PLAYER_COLORS.len()].clone()
+}
+
+/// Set the colors of a theme, with the accent and highlight from a palette. The rest of the dark
+/// theme is druid's own, so it's already set.
+pub fn apply(env: &mut Env, chosen: Theme, colors: ColorPalette) {
+    apply_theme(env, chosen);
+    apply_palette(env, colors);
+}
+
+/// Set the accent and highlight for the kinds of color blindness, from the palette of Okabe and
+/// Ito, which can be told apart with any of them. The standard ones are set with the theme.
+fn apply_palette(env: &mut Env, colors: ColorPalette) {
+    let (accent, highlight) = match colors {
+        ColorPalette::Standard => return,
+        // orange against blue, leaving out the reds and greens that look alike
+        ColorPalette::Deuteranopia | ColorPalette::Protanopia => (
+            Color::rgb8(0xe6, 0x9f, 0x00),
+            Color::rgba8(0x00, 0x72, 0xb2, 0x60),
+        ),
+        // vermilion against bluish green, leaving out the blues and yellows that look alike
+        ColorPalette::Tritanopia => (
+            Color::rgb8(0xd5, 0x5e, 0x00),
+            Color::rgba8(0x00, 0x9e, 0x73, 0x60),
+        ),
+    };
+    env.set(ACCENT_COLOR, accent);
+    env.set(HIGHLIGHT_COLOR, highlight);
+}
+
+fn apply_theme(env: &mut Env, chosen: Theme) {
+    env.set(die::CORNER_RADIUS, 6.0);
+    env.set(die::BORDER_WIDTH, 4.0);
+    env.set(ACCENT_COLOR, Color::rgb8(0xff, 0xc0, 0x00));
+    env.set(HIGHLIGHT_COLOR, Color::rgba8(0x80, 0xc0, 0xff, 0x60));
+    if chosen == Theme::Dark {
+        env.set(DIE_COLOR, Color::BLACK);
+        env.set(PIP_COLOR, Color::WHITE);
+        env.set(DIM_PIP_COLOR, Color::rgb8(0x60, 0x60, 0x60));
+        env.set(die::BORDER_COLOR, Color::WHITE);
+        env.set(HOT_COLOR, Color::rgba8(0xff, 0xff, 0xff, 0x30));
+        return;
+    }
+    env.set(DIE_COLOR, Color::WHITE);
+    env.set(PIP_COLOR, Color::BLACK);
+    env.set(DIM_PIP_COLOR, Color::rgb8(0xb0, 0xb0, 0xb0));
+    env.set(die::BORDER_COLOR, Color::rgb8(0x40, 0x40, 0x40));
+    env.set(HOT_COLOR, Color::rgba8(0x00, 0x00, 0x00, 0x18));
+    // a darker accent, to stand out against the light background
+    env.set(ACCENT_COLOR, Color::rgb8(0xe0, 0x90, 0x00));
+
+    env.set(
+        theme::WINDOW_BACKGROUND_COLOR,
+        Color::rgb8(0xf2, 0xf2, 0xf2),
+    );
+    env.set(theme::LABEL_COLOR, Color::rgb8(0x20, 0x20, 0x20));
+    env.set(theme::PLACEHOLDER_COLOR, Color::rgb8(0x90, 0x90, 0x90));
+    env.set(theme::BUTTON_LIGHT, Color::rgb8(0xff, 0xff, 0xff));
+    env.set(theme::BUTTON_DARK, Color::rgb8(0xdc, 0xdc, 0xdc));
+    env.set(theme::BACKGROUND_LIGHT, Color::rgb8(0xff, 0xff, 0xff));
+    env.set(theme::BACKGROUND_DARK, Color::rgb8(0xe6, 0xe6, 0xe6));
+    env.set(theme::FOREGROUND_LIGHT, Color::rgb8(0x40, 0x40, 0x40));
+    env.set(theme::FOREGROUND_DARK, Color::rgb8(0x20, 0x20, 0x20));
+    env.set(theme::BORDER_LIGHT, Color::rgb8(0xb0, 0xb0, 0xb0));
+    env.set(theme::BORDER_DARK, Color::rgb8(0xc8, 0xc8, 0xc8));
+    env.set(theme::SELECTION_COLOR, Color::rgb8(0xa6, 0xcc, 0xff));
+    env.set(theme::CURSOR_COLOR, Color::BLACK);
+}
@@ -0,0 +1,81 @@
+//! A countdown for each decision in a turn, making the move for the player when it runs out.
+
+use druid::widget::Controller;
+use druid::{
+    Command, Env, Event, EventCtx, LifeCycle, LifeCycleCtx, TimerToken, UpdateCtx, Widget,
+};
+use std::time::Duration;
+
+use crate::{now, InGameState, PLAY_MOVE};
+
+const TICK: Duration = Duration::from_secs(1);
+
+/// Counts down `InGameState::time_left` once a second while a turn timer is set, stopping while
+/// the game is paused. The ticks also keep `InGameState::elapsed` up to date for the status bar.
+pub struct TurnTimer {
+    timer: Option<TimerToken>,
+}
+
+impl TurnTimer {
+    pub fn new() -> Self {
+        Self { timer: None }
+    }
+}
+
+impl<W: Widget<InGameState>> Controller<InGameState, W> for TurnTimer {
+    fn event(
+        &mut self,
+        child: &mut W,
+        ctx: &mut EventCtx,
+        event: &Event,
+        data: &mut InGameState,
+        env: &Env,
+    ) {
+        match event {
+            Event::Timer(tok) if self.timer == Some(*tok) => {
+                self.timer = Some(ctx.request_timer(TICK));
+                data.elapsed = now().saturating_sub(data.started_at);
+                if data.turn_time.is_none() || data.game.any_rolling() || data.is_paused() {
+                    return;
+                }
+                data.time_left = data.time_left.saturating_sub(1);
+                // in a network game, each player's own computer makes the move for them
+                if data.time_left == 0 && data.current_player().local {
+                    if let Some(mv) = data.game.timeout_move() {
+                        ctx.submit_command(Command::new(PLAY_MOVE, mv), None);
+                    }
+                }
+            }
+            _ => child.event(ctx, event, data, env),
+        }
+    }
+
+    fn lifecycle(
+        &mut self,
+        child: &mut W,
+        ctx: &mut LifeCycleCtx,
+        event: &LifeCycle,
+        data: &InGameState,
+        env: &Env,
+    ) {
+        if let LifeCycle::WidgetAdded = event {
+            self.timer = Some(ctx.request_timer(TICK));
+        }
+        child.lifecycle(ctx, event, data, env)
+    }
+
+    fn update(
+        &mut self,
+        child: &mut W,
+        ctx: &mut UpdateCtx,
+        old_data: &InGameState,
+        data: &InGameState,
+        env: &Env,
+    ) {
+        // the ticks stop coming while the settings are open, so start them again after a pause
+        if old_data.is_paused() && !data.is_paused() {
+            self.timer = Some(ctx.request_timer(TICK));
+        }
+        child.update(ctx, old_data, data, env)
+    }
+}
@@ -0,0 +1,57 @@
+//! Takes each toast away once it's had time to be read, for the next in the queue to be shown.
+
+use druid::widget::Controller;
+use druid::{Env, Event, EventCtx, TimerToken, UpdateCtx, Widget};
+use std::{sync::Arc, time::Duration};
+
+use crate::AppState;
+
+/// How long each toast is shown for. It's for reading, so the animation speed doesn't change it.
+const TOAST_TIME: Duration = Duration::from_millis(3_000);
+
+/// Shows the toasts in `AppState::toasts` one at a time, the first in the queue for a few seconds
+/// before the next.
+pub struct ExpireToasts {
+    timer: Option<TimerToken>,
+}
+
+impl ExpireToasts {
+    pub fn new() -> Self {
+        Self { timer: None }
+    }
+}
+
+impl<W: Widget<AppState>> Controller<AppState, W> for ExpireToasts {
+    fn event(
+        &mut self,
+        child: &mut W,
+        ctx: &mut EventCtx,
+        event: &Event,
+        data: &mut AppState,
+        env: &Env,
+    ) {
+        match event {
+            Event::Timer(tok) if self.timer == Some(*tok) => {
+                self.timer = None;
+                if !data.toasts.is_empty() {
+                    Arc::make_mut(&mut data.toasts).remove(0);
+                }
+            }
+            _ => child.event(ctx, event, data, env),
+        }
+    }
+
+    fn update(
+        &mut self,
+        child: &mut W,
+        ctx: &mut UpdateCtx,
+        old_data: &AppState,
+        data: &AppState,
+        env: &Env,
+    ) {
+        if self.timer.is_none() && !data.toasts.is_empty() {
+            self.timer = Some(ctx.request_timer(TOAST_TIME));
+        }
+        child.update(ctx, old_data, data, env)
+    }
+}